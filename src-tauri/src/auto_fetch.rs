@@ -0,0 +1,173 @@
+use crate::{config, credentials, git, perf};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::sync::Notify;
+
+const AUTO_FETCH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Event emitted after every successful background fetch with an `AutoFetchUpdate`.
+pub const AUTO_FETCH_SYNC_EVENT: &str = "auto-fetch-sync-status";
+/// Event emitted (in addition to the sync event) when the upstream gained commits.
+pub const AUTO_FETCH_NEW_COMMITS_EVENT: &str = "auto-fetch-new-commits";
+
+// Repositories the frontend currently has open; only these are fetched.
+static OPEN_REPOS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+// Wakes the scheduler when `auto_fetch` or `default_remote` change.
+static SETTINGS_CHANGED: Notify = Notify::const_new();
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AutoFetchUpdate {
+    pub path: String,
+    pub remote: String,
+    pub status: git::SyncStatus,
+    pub new_commits: usize,
+}
+
+pub fn register_repository(path: &str) -> Result<(), String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("E_AUTO_FETCH_INVALID: repository path is required".to_string());
+    }
+    lock_open_repos()
+        .get_or_insert_with(HashSet::new)
+        .insert(path.to_string());
+    Ok(())
+}
+
+pub fn unregister_repository(path: &str) {
+    if let Some(paths) = lock_open_repos().as_mut() {
+        paths.remove(path.trim());
+    }
+}
+
+/// Called by `config::write_settings`. Enabling auto-fetch or switching the
+/// default remote fetches right away and restarts the interval.
+pub fn settings_changed(fields: &[String]) {
+    if fields
+        .iter()
+        .any(|field| field == "auto_fetch" || field == "default_remote")
+    {
+        SETTINGS_CHANGED.notify_one();
+    }
+}
+
+/// Spawn the background loop that fetches open repositories while
+/// `AppConfig.auto_fetch` is enabled. Called once from `setup`.
+pub fn start_scheduler(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(AUTO_FETCH_INTERVAL) => {}
+                _ = SETTINGS_CHANGED.notified() => {}
+            }
+
+            let settings = match config::read_settings(&app) {
+                Ok(settings) => settings,
+                Err(error) => {
+                    crate::runtime::append_runtime_log(&format!(
+                        "auto-fetch failed to load settings: {}",
+                        error
+                    ));
+                    continue;
+                }
+            };
+            if !settings.auto_fetch {
+                continue;
+            }
+
+            for path in open_repositories() {
+                let app = app.clone();
+                let remote = settings.default_remote.clone();
+                let _ = tauri::async_runtime::spawn_blocking(move || {
+                    fetch_repository(&app, &path, &remote)
+                })
+                .await;
+            }
+        }
+    });
+}
+
+fn fetch_repository(app: &tauri::AppHandle, path: &str, remote: &str) {
+    let Ok(_operation) = crate::shutdown::track("auto_fetch", path) else {
+        return;
+    };
+    let (username, password) = stored_credential(app, path, remote).unwrap_or_default();
+    let result = perf::time("auto_fetch", Duration::ZERO, || {
+        git::fetch_with_sync_status(path, remote, &username, &password)
+    });
+    match result {
+        Ok(update) => {
+            let update = AutoFetchUpdate {
+                path: path.to_string(),
+                remote: remote.to_string(),
+                status: update.status,
+                new_commits: update.new_commits,
+            };
+            let _ = app.emit(AUTO_FETCH_SYNC_EVENT, &update);
+            if update.new_commits > 0 {
+                crate::runtime::append_runtime_log(&format!(
+                    "auto-fetch repo={} remote={} new_commits={}",
+                    path, remote, update.new_commits
+                ));
+                let _ = app.emit(AUTO_FETCH_NEW_COMMITS_EVENT, &update);
+            }
+        }
+        Err(error) => crate::runtime::append_runtime_log(&format!(
+            "auto-fetch repo={} remote={} failed: {}",
+            path, remote, error
+        )),
+    }
+}
+
+/// Keychain credential for the remote's host, if one was saved in the app.
+fn stored_credential(app: &tauri::AppHandle, path: &str, remote: &str) -> Option<(String, String)> {
+    let url = git::list_remotes(path)
+        .ok()?
+        .into_iter()
+        .find(|info| info.name == remote)?
+        .url?;
+    let host = git::parse_remote_host(&url)?;
+    credentials::find_credential(app, &host).ok().flatten()
+}
+
+fn open_repositories() -> Vec<String> {
+    let mut paths: Vec<String> = lock_open_repos()
+        .as_ref()
+        .map(|paths| paths.iter().cloned().collect())
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+fn lock_open_repos() -> std::sync::MutexGuard<'static, Option<HashSet<String>>> {
+    OPEN_REPOS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_and_unregister_open_repositories() {
+        let path = format!("/tmp/gitlite-auto-fetch-test-{}", uuid::Uuid::new_v4());
+        assert!(register_repository("  ")
+            .unwrap_err()
+            .starts_with("E_AUTO_FETCH_INVALID"));
+
+        register_repository(&path).unwrap();
+        register_repository(&format!(" {} ", path)).unwrap();
+        assert_eq!(
+            open_repositories()
+                .iter()
+                .filter(|open| **open == path)
+                .count(),
+            1
+        );
+
+        unregister_repository(&path);
+        assert!(!open_repositories().contains(&path));
+    }
+}
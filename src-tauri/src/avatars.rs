@@ -0,0 +1,291 @@
+use crate::github_auth;
+use crate::github_client::{self, GitHubRateLimit};
+use crate::json_store::{unix_now, JsonStore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+const AVATAR_CACHE_FILENAME: &str = "avatar_cache.json";
+const CACHE_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+/// Searches that found no account, or failed, are retried after an hour.
+const NEGATIVE_TTL_SECS: i64 = 60 * 60;
+const SEARCH_API_BASE: &str = "https://api.github.com";
+/// GitHub user searches one call may start; the search API allows 30 a
+/// minute, and a long history must not spend it all on avatars.
+const MAX_SEARCHES_PER_CALL: usize = 20;
+const NOREPLY_DOMAIN: &str = "@users.noreply.github.com";
+const MAX_EMAILS: usize = 500;
+/// GitHub lookups in flight at once.
+const MAX_CONCURRENT_LOOKUPS: usize = 4;
+
+// Serializes read-modify-write cycles on the cache file.
+static CACHE_STORE: JsonStore<BTreeMap<String, CachedAvatar>> =
+    JsonStore::new(AVATAR_CACHE_FILENAME, "E_AVATAR_CACHE_STORE");
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AuthorAvatar {
+    pub email: String,
+    pub url: String,
+    pub source: String, // "github" | "gravatar"
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct CachedAvatar {
+    url: String,
+    source: String,
+    resolved_at: i64,
+    /// Whether a GitHub search was possible when this was resolved; Gravatar
+    /// fallbacks from signed-out lookups are retried once a token exists.
+    searched: bool,
+}
+
+/// Resolve avatar URLs for commit author emails, in input order without
+/// duplicates. Cached entries are reused for a week, failed or empty
+/// searches for an hour. Emails beyond the search budget get a Gravatar
+/// fallback that is not cached, so a later call picks them up.
+pub async fn get_author_avatars(
+    app: &tauri::AppHandle,
+    emails: &[String],
+) -> Result<Vec<AuthorAvatar>, String> {
+    let emails = normalize_emails(emails);
+    let cache_path = CACHE_STORE.path(app)?;
+    let token = github_auth::load_token_from_keychain(None).ok().flatten();
+    let now = unix_now();
+
+    let cached = {
+        let _guard = CACHE_STORE.lock();
+        read_cache(&cache_path)
+    };
+
+    // Stale or missing entries are resolved concurrently, a few at a time.
+    let mut entries: Vec<Option<CachedAvatar>> = Vec::with_capacity(emails.len());
+    let mut lookups = tokio::task::JoinSet::new();
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_LOOKUPS));
+    let mut searches = search_budget(&github_client::rate_limits(SEARCH_API_BASE), now);
+    for (index, email) in emails.iter().enumerate() {
+        let entry = cached
+            .get(email)
+            .filter(|entry| is_fresh(entry, now, token.is_some()));
+        let searches_github = token.is_some() && noreply_avatar_url(email).is_none();
+        if entry.is_none() && (!searches_github || searches > 0) {
+            if searches_github {
+                searches -= 1;
+            }
+            let (email, token, permits) = (email.clone(), token.clone(), permits.clone());
+            lookups.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (index, resolve(&email, token.as_deref(), now).await)
+            });
+        }
+        entries.push(entry.cloned());
+    }
+    let mut resolved = BTreeMap::new();
+    while let Some(joined) = lookups.join_next().await {
+        // A panicked lookup leaves its email to the uncached fallback below.
+        let Ok((index, entry)) = joined else {
+            continue;
+        };
+        resolved.insert(emails[index].clone(), entry.clone());
+        entries[index] = Some(entry);
+    }
+
+    let avatars = emails
+        .into_iter()
+        .zip(entries)
+        .map(|(email, entry)| {
+            let entry = entry.unwrap_or_else(|| fallback(&email, now, false));
+            AuthorAvatar {
+                email,
+                url: entry.url,
+                source: entry.source,
+            }
+        })
+        .collect();
+
+    if !resolved.is_empty() {
+        let _guard = CACHE_STORE.lock();
+        let mut cache = read_cache(&cache_path);
+        cache.retain(|_, entry| now - entry.resolved_at < ttl(entry));
+        cache.extend(resolved);
+        CACHE_STORE.write(&cache_path, &cache)?;
+    }
+    Ok(avatars)
+}
+
+/// The avatar for `email`. A failed GitHub search (rate limit, network)
+/// falls back to Gravatar like a search without a match.
+async fn resolve(email: &str, token: Option<&str>, now: i64) -> CachedAvatar {
+    let github = match (noreply_avatar_url(email), token) {
+        (Some(url), _) => Ok(Some(url)),
+        (None, Some(token)) => github_auth::search_avatar_by_email(token, email).await,
+        (None, None) => Ok(None),
+    };
+    match github {
+        Ok(Some(url)) => CachedAvatar {
+            url,
+            source: "github".to_string(),
+            resolved_at: now,
+            searched: true,
+        },
+        Ok(None) | Err(_) => fallback(email, now, token.is_some()),
+    }
+}
+
+/// Searches this call may start: `MAX_SEARCHES_PER_CALL`, or less when
+/// GitHub last reported fewer remaining in the current window.
+fn search_budget(limits: &[GitHubRateLimit], now: i64) -> usize {
+    limits
+        .iter()
+        .find(|limit| limit.resource == "search" && limit.reset_at > now)
+        .map_or(MAX_SEARCHES_PER_CALL, |limit| {
+            MAX_SEARCHES_PER_CALL.min(limit.remaining as usize)
+        })
+}
+
+fn fallback(email: &str, now: i64, searched: bool) -> CachedAvatar {
+    CachedAvatar {
+        url: gravatar_url(email),
+        source: "gravatar".to_string(),
+        resolved_at: now,
+        searched,
+    }
+}
+
+fn is_fresh(entry: &CachedAvatar, now: i64, can_search: bool) -> bool {
+    now - entry.resolved_at < ttl(entry) && (entry.searched || !can_search)
+}
+
+fn ttl(entry: &CachedAvatar) -> i64 {
+    if entry.searched && entry.source != "github" {
+        NEGATIVE_TTL_SECS
+    } else {
+        CACHE_TTL_SECS
+    }
+}
+
+/// `123+login@users.noreply.github.com` or `login@users.noreply.github.com`.
+fn noreply_avatar_url(email: &str) -> Option<String> {
+    let local = email.strip_suffix(NOREPLY_DOMAIN)?;
+    match local.split_once('+') {
+        Some((id, _)) if !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) => Some(format!(
+            "https://avatars.githubusercontent.com/u/{}?v=4",
+            id
+        )),
+        Some((_, login)) if !login.is_empty() => Some(format!("https://github.com/{}.png", login)),
+        None if !local.is_empty() => Some(format!("https://github.com/{}.png", local)),
+        _ => None,
+    }
+}
+
+fn gravatar_url(email: &str) -> String {
+    let hash: String = Sha256::digest(email.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("https://www.gravatar.com/avatar/{}?d=identicon", hash)
+}
+
+fn normalize_emails(emails: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    emails
+        .iter()
+        .map(|email| email.trim().to_lowercase())
+        .filter(|email| email.contains('@') && seen.insert(email.clone()))
+        .take(MAX_EMAILS)
+        .collect()
+}
+
+fn read_cache(cache_path: &Path) -> BTreeMap<String, CachedAvatar> {
+    // A corrupt cache is only a cache; start over rather than fail.
+    CACHE_STORE.read(cache_path).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn resolves_noreply_and_gravatar_urls() {
+        assert_eq!(
+            noreply_avatar_url("583231+octocat@users.noreply.github.com").as_deref(),
+            Some("https://avatars.githubusercontent.com/u/583231?v=4")
+        );
+        assert_eq!(
+            noreply_avatar_url("octocat@users.noreply.github.com").as_deref(),
+            Some("https://github.com/octocat.png")
+        );
+        assert_eq!(noreply_avatar_url("octocat@example.com"), None);
+
+        assert_eq!(
+            gravatar_url("test@example.com"),
+            "https://www.gravatar.com/avatar/973dfe463ec85785f5f95af5ba3906eedb2d931c24e69824a89ea65dba4e813b?d=identicon"
+        );
+        assert_eq!(
+            normalize_emails(&[
+                " Jane@Example.com ".to_string(),
+                "jane@example.com".to_string(),
+                "not-an-email".to_string(),
+            ]),
+            vec!["jane@example.com".to_string()]
+        );
+    }
+
+    #[test]
+    fn cache_entries_expire_and_signed_out_fallbacks_are_retried() {
+        let dir = std::env::temp_dir().join(format!("gitlite-avatars-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let cache_path = dir.join(AVATAR_CACHE_FILENAME);
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let entry = runtime.block_on(resolve("jane@example.com", None, 1000));
+        assert_eq!(entry.source, "gravatar");
+        assert!(!entry.searched);
+
+        let mut cache = BTreeMap::new();
+        cache.insert("jane@example.com".to_string(), entry.clone());
+        CACHE_STORE.write(&cache_path, &cache).unwrap();
+        let stored = read_cache(&cache_path);
+        assert_eq!(stored.get("jane@example.com"), Some(&entry));
+
+        assert!(is_fresh(&entry, 1000 + CACHE_TTL_SECS - 1, false));
+        assert!(!is_fresh(&entry, 1000 + CACHE_TTL_SECS, false));
+        assert!(!is_fresh(&entry, 1001, true));
+
+        let not_found = fallback("jane@example.com", 1000, true);
+        assert!(is_fresh(&not_found, 1000 + NEGATIVE_TTL_SECS - 1, true));
+        assert!(!is_fresh(&not_found, 1000 + NEGATIVE_TTL_SECS, true));
+
+        fs::write(&cache_path, "not json").unwrap();
+        assert!(read_cache(&cache_path).is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn searches_are_capped_by_the_remaining_quota() {
+        let search = |remaining, reset_at| GitHubRateLimit {
+            host: "api.github.com".to_string(),
+            resource: "search".to_string(),
+            limit: 30,
+            remaining,
+            used: 30 - remaining,
+            reset_at,
+        };
+        assert_eq!(search_budget(&[], 1000), MAX_SEARCHES_PER_CALL);
+        assert_eq!(search_budget(&[search(3, 1060)], 1000), 3);
+        assert_eq!(search_budget(&[search(0, 1060)], 1000), 0);
+        assert_eq!(
+            search_budget(&[search(29, 1060)], 1000),
+            MAX_SEARCHES_PER_CALL
+        );
+        // the window has reset since GitHub reported it
+        assert_eq!(
+            search_budget(&[search(0, 990)], 1000),
+            MAX_SEARCHES_PER_CALL
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use crate::credentials::{KeychainCodes, KeychainItem};
+use crate::git::parse_remote_host;
+use reqwest::header::{ACCEPT, USER_AGENT};
+use serde::{Deserialize, Serialize};
+
+const BITBUCKET_HOST: &str = "bitbucket.org";
+const USER_URL: &str = "https://api.bitbucket.org/2.0/user";
+const APP_USER_AGENT: &str = "GitLite/0.1.0";
+const KEYCHAIN_SERVICE: &str = "com.gitlite.app.bitbucket";
+const KEYCHAIN_CODES: KeychainCodes = KeychainCodes {
+    init: "E_BITBUCKET_KEYCHAIN_INIT",
+    read: "E_BITBUCKET_KEYCHAIN_READ",
+    write: "E_BITBUCKET_KEYCHAIN_WRITE",
+    delete: "E_BITBUCKET_KEYCHAIN_DELETE",
+};
+
+/// Which account an app password belongs to. The password itself stays in
+/// the keychain.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BitbucketAccount {
+    pub workspace: String,
+    pub username: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct BitbucketUser {
+    pub username: String,
+    pub display_name: String,
+    pub avatar_url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredAppPassword {
+    username: String,
+    app_password: String,
+}
+
+#[derive(Deserialize)]
+struct BitbucketUserResponse {
+    username: String,
+    display_name: Option<String>,
+    links: Option<UserLinks>,
+}
+
+#[derive(Deserialize)]
+struct UserLinks {
+    avatar: Option<Link>,
+}
+
+#[derive(Deserialize)]
+struct Link {
+    href: String,
+}
+
+/// Store the app password used for remotes under `workspace`.
+pub fn save_app_password(
+    workspace: &str,
+    username: &str,
+    app_password: &str,
+) -> Result<BitbucketAccount, String> {
+    let workspace = normalize_workspace(workspace)?;
+    let username = username.trim();
+    if username.is_empty() {
+        return Err("E_BITBUCKET_USERNAME_EMPTY: username is required".to_string());
+    }
+    if app_password.trim().is_empty() {
+        return Err("E_BITBUCKET_APP_PASSWORD_EMPTY: app password is required".to_string());
+    }
+
+    let stored = StoredAppPassword {
+        username: username.to_string(),
+        app_password: app_password.trim().to_string(),
+    };
+    let secret = serde_json::to_string(&stored)
+        .map_err(|error| format!("E_BITBUCKET_KEYCHAIN_WRITE: {}", error))?;
+    keychain_item(&workspace)?.save(&secret)?;
+    Ok(BitbucketAccount {
+        workspace,
+        username: stored.username,
+    })
+}
+
+pub fn load_account(workspace: &str) -> Result<Option<BitbucketAccount>, String> {
+    let workspace = normalize_workspace(workspace)?;
+    Ok(
+        load_app_password(&workspace)?.map(|stored| BitbucketAccount {
+            workspace,
+            username: stored.username,
+        }),
+    )
+}
+
+pub fn delete_app_password(workspace: &str) -> Result<(), String> {
+    let workspace = normalize_workspace(workspace)?;
+    keychain_item(&workspace)?.delete()
+}
+
+/// Saved `(username, app_password)` for a bitbucket.org remote URL, for git
+/// credential callbacks. Other hosts and lookup failures yield `None`.
+pub fn find_for_url(url: &str) -> Option<(String, String)> {
+    let workspace = workspace_from_url(url)?;
+    let stored = load_app_password(&workspace).ok().flatten()?;
+    Some((stored.username, stored.app_password))
+}
+
+/// Profile of the account an app password belongs to.
+pub async fn fetch_user(username: &str, app_password: &str) -> Result<BitbucketUser, String> {
+    let username = username.trim();
+    if username.is_empty() {
+        return Err("E_BITBUCKET_USERNAME_EMPTY: username is required".to_string());
+    }
+    if app_password.trim().is_empty() {
+        return Err("E_BITBUCKET_APP_PASSWORD_EMPTY: app password is required".to_string());
+    }
+
+    let response = reqwest::Client::new()
+        .get(USER_URL)
+        .header(ACCEPT, "application/json")
+        .header(USER_AGENT, APP_USER_AGENT)
+        .basic_auth(username, Some(app_password.trim()))
+        .send()
+        .await
+        .map_err(|error| format!("E_BITBUCKET_USER_FETCH: {}", error))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unable to read response body".to_string());
+        return Err(format!(
+            "E_BITBUCKET_USER_FETCH: Bitbucket returned {} ({})",
+            status.as_u16(),
+            body
+        ));
+    }
+
+    let payload: BitbucketUserResponse = response
+        .json()
+        .await
+        .map_err(|error| format!("E_BITBUCKET_USER_PARSE: {}", error))?;
+
+    Ok(BitbucketUser {
+        display_name: payload
+            .display_name
+            .unwrap_or_else(|| payload.username.clone()),
+        avatar_url: payload
+            .links
+            .and_then(|links| links.avatar)
+            .map(|avatar| avatar.href)
+            .unwrap_or_default(),
+        username: payload.username,
+    })
+}
+
+/// `team` for `https://user@bitbucket.org/team/repo.git` or
+/// `git@bitbucket.org:team/repo.git`.
+fn workspace_from_url(url: &str) -> Option<String> {
+    if parse_remote_host(url)? != BITBUCKET_HOST {
+        return None;
+    }
+    let path = match url.trim().split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?.1,
+        None => url.trim().split_once(':')?.1,
+    };
+    normalize_workspace(path.split('/').next()?).ok()
+}
+
+fn load_app_password(workspace: &str) -> Result<Option<StoredAppPassword>, String> {
+    keychain_item(workspace)?
+        .load()?
+        .map(|secret| serde_json::from_str(&secret))
+        .transpose()
+        .map_err(|error| format!("E_BITBUCKET_KEYCHAIN_READ: {}", error))
+}
+
+fn keychain_item(workspace: &str) -> Result<KeychainItem, String> {
+    KeychainItem::new(KEYCHAIN_SERVICE, workspace, &KEYCHAIN_CODES)
+}
+
+fn normalize_workspace(workspace: &str) -> Result<String, String> {
+    let normalized = workspace.trim().trim_matches('/').to_ascii_lowercase();
+    if normalized.is_empty() || normalized.contains('/') {
+        return Err(format!(
+            "E_BITBUCKET_WORKSPACE_INVALID: '{}' is not a workspace id",
+            workspace
+        ));
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_is_read_from_bitbucket_remotes_only() {
+        assert_eq!(
+            workspace_from_url("https://octo@bitbucket.org/Team/app.git").as_deref(),
+            Some("team")
+        );
+        assert_eq!(
+            workspace_from_url("git@bitbucket.org:team/app.git").as_deref(),
+            Some("team")
+        );
+        assert_eq!(workspace_from_url("https://bitbucket.org/"), None);
+        assert_eq!(workspace_from_url("https://github.com/team/app.git"), None);
+        assert!(normalize_workspace(" a/b ")
+            .unwrap_err()
+            .starts_with("E_BITBUCKET_WORKSPACE_INVALID"));
+    }
+}
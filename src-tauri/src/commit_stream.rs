@@ -0,0 +1,215 @@
+use crate::git::{self, Commit, CommitQuery, RevwalkGuards};
+use crate::perf;
+use git2::Repository;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::Emitter;
+
+/// Event emitted for every batch of walked commits (payload `CommitBatch`).
+pub const COMMIT_BATCH_EVENT: &str = "commit-stream://batch";
+/// Event emitted once when a stream ends (payload `CommitStreamDone`).
+pub const COMMIT_STREAM_DONE_EVENT: &str = "commit-stream://done";
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CommitBatch {
+    pub stream_id: String,
+    pub commits: Vec<Commit>,
+    /// Commits delivered so far, including this batch.
+    pub loaded: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CommitStreamDone {
+    pub stream_id: String,
+    pub total: usize,
+    pub cancelled: bool,
+    pub error: Option<String>,
+}
+
+/// Running history walks kept in Tauri managed state so they can be cancelled.
+#[derive(Clone, Default)]
+pub struct CommitStreams {
+    streams: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+}
+
+impl CommitStreams {
+    /// Start walking history on a blocking thread and return the stream id
+    /// immediately. Batches arrive as `commit-stream://batch` events. The
+    /// query's continuation token is ignored.
+    pub fn start(
+        &self,
+        app: &tauri::AppHandle,
+        path: String,
+        query: CommitQuery,
+        batch_size: Option<usize>,
+    ) -> String {
+        let app = app.clone();
+        self.start_with(path, query, batch_size, move |event| {
+            let _ = match event {
+                StreamEvent::Batch(batch) => app.emit(COMMIT_BATCH_EVENT, batch),
+                StreamEvent::Done(done) => app.emit(COMMIT_STREAM_DONE_EVENT, done),
+            };
+        })
+    }
+
+    /// Stop a running stream after its current batch.
+    pub fn cancel(&self, stream_id: &str) -> Result<(), String> {
+        let streams = self.lock_streams();
+        let cancel = streams
+            .get(stream_id)
+            .ok_or_else(|| format!("E_COMMIT_STREAM_NOT_FOUND: {}", stream_id))?;
+        cancel.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn start_with(
+        &self,
+        path: String,
+        query: CommitQuery,
+        batch_size: Option<usize>,
+        emit: impl Fn(StreamEvent) + Send + 'static,
+    ) -> String {
+        let stream_id = uuid::Uuid::new_v4().to_string();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.lock_streams()
+            .insert(stream_id.clone(), cancel.clone());
+
+        let streams = self.clone();
+        let id = stream_id.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut loaded = 0;
+            let result = perf::time("stream_commits", Duration::ZERO, || {
+                Repository::open(&path)
+                    .map_err(|e| format!("Failed to open repository: {}", e))
+                    .and_then(|repo| {
+                        let query = CommitQuery {
+                            continuation: None,
+                            ..query
+                        };
+                        // Streams run on their own thread and are cancellable,
+                        // so the per-request revwalk guards don't apply.
+                        git::walk_commits(
+                            &repo,
+                            &query,
+                            &RevwalkGuards::unlimited(),
+                            batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+                            |commits| {
+                                if cancel.load(Ordering::SeqCst) {
+                                    return false;
+                                }
+                                loaded += commits.len();
+                                emit(StreamEvent::Batch(CommitBatch {
+                                    stream_id: id.clone(),
+                                    commits,
+                                    loaded,
+                                }));
+                                !cancel.load(Ordering::SeqCst)
+                            },
+                        )
+                    })
+            });
+
+            streams.lock_streams().remove(&id);
+            emit(StreamEvent::Done(CommitStreamDone {
+                stream_id: id,
+                total: loaded,
+                cancelled: cancel.load(Ordering::SeqCst),
+                error: result.err(),
+            }));
+        });
+
+        stream_id
+    }
+
+    fn lock_streams(&self) -> MutexGuard<'_, HashMap<String, Arc<AtomicBool>>> {
+        self.streams.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+enum StreamEvent {
+    Batch(CommitBatch),
+    Done(CommitStreamDone),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+    use std::sync::mpsc;
+
+    fn run_git(args: &[&str], cwd: &Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn streams_batches_then_done() {
+        let dir = std::env::temp_dir().join(format!(
+            "gitlite-commit-stream-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        run_git(&["init"], &dir);
+        run_git(&["config", "user.name", "Test User"], &dir);
+        run_git(&["config", "user.email", "test@example.com"], &dir);
+        for i in 0..5 {
+            run_git(
+                &["commit", "--allow-empty", "-m", &format!("Commit {}", i)],
+                &dir,
+            );
+        }
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let streams = CommitStreams::default();
+        let (sender, receiver) = mpsc::channel();
+        let id = runtime.block_on(async {
+            streams.start_with(
+                dir.to_string_lossy().into_owned(),
+                CommitQuery {
+                    limit: usize::MAX,
+                    ..Default::default()
+                },
+                Some(2),
+                move |event| {
+                    let _ = sender.send(event);
+                },
+            )
+        });
+
+        let mut batches = Vec::new();
+        let done = loop {
+            match receiver
+                .recv_timeout(std::time::Duration::from_secs(10))
+                .unwrap()
+            {
+                StreamEvent::Batch(batch) => batches.push(batch),
+                StreamEvent::Done(done) => break done,
+            }
+        };
+        assert_eq!(
+            batches.iter().map(|b| b.loaded).collect::<Vec<_>>(),
+            vec![2, 4, 5]
+        );
+        assert!(batches.iter().all(|b| b.stream_id == id));
+        assert_eq!(done.total, 5);
+        assert!(!done.cancelled);
+        assert!(done.error.is_none());
+        assert!(streams
+            .cancel(&id)
+            .unwrap_err()
+            .starts_with("E_COMMIT_STREAM_NOT_FOUND"));
+
+        drop(runtime);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
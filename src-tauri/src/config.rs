@@ -1,14 +1,33 @@
+use crate::error::{ErrorCode, FieldError, GitLiteError};
+use crate::git;
+use crate::git_pool;
 use git2::Repository;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::fs;
-use std::path::PathBuf;
-use tauri::Manager;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
 
 const THEME_FILENAME: &str = "theme.txt";
 const SETTINGS_FILENAME: &str = "settings.json";
+const THEMES: &[&str] = &["system", "light", "dark"];
+const LANGUAGES: &[&str] = &["en", "ko", "ja", "zh", "es", "fr", "de", "pt"];
+const UPDATE_CHANNELS: &[&str] = &["stable", "beta"];
+const EDITORS: &[&str] = &["vscode", "sublime", "custom"];
+
+/// Event emitted with a `SettingsChanged` payload whenever settings are saved
+/// with different values.
+pub const SETTINGS_CHANGED_EVENT: &str = "settings://changed";
+
+/// Serializes every write of settings.json, so read-modify-write updates
+/// cannot drop each other's changes.
+static SETTINGS_LOCK: Mutex<()> = Mutex::new(());
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
 pub struct AppConfig {
     pub theme: String,                  // "system" | "light" | "dark"
     pub git_user_name: Option<String>,  // Git user.name
@@ -24,6 +43,18 @@ pub struct AppConfig {
     pub language: Option<String>,       // UI language (en, ko, ja, zh, es, fr, de, pt)
     pub update_channel: String,         // update channel (stable | beta)
     pub auto_update_check: bool,        // check update on launch
+    pub revwalk_max_millis: u64,        // history walk time budget per request (default 2000)
+    pub revwalk_max_commits: usize,     // commits visited per history request (default 100000)
+    pub use_mailmap: bool,              // map authors through .mailmap (default true)
+    pub git_worker_threads: usize,      // concurrent git operations (default 4, applies on restart)
+    pub feature_flags: HashMap<String, bool>, // overrides for gated subsystems (see features.rs)
+    pub diff_max_files: usize,          // files with hunks per commit diff (default 1000)
+    pub diff_max_lines_per_file: usize, // diff lines per file (default 5000)
+    pub diff_max_blob_bytes: u64,       // larger files are listed without hunks (default 5 MiB)
+    pub ca_bundle_path: Option<String>, // imported CA certificates (PEM) for HTTPS remotes
+    pub editor: String,                 // external editor (vscode | sublime | custom)
+    pub editor_command: Option<String>, // custom editor template, e.g. "idea --line {line} {file}"
+    pub ssh_identity: Option<String>,   // SHA256 fingerprint of the SSH identity to try first
 }
 
 impl Default for AppConfig {
@@ -43,10 +74,155 @@ impl Default for AppConfig {
             language: None,
             update_channel: "stable".to_string(),
             auto_update_check: true,
+            revwalk_max_millis: 2000,
+            revwalk_max_commits: 100_000,
+            use_mailmap: true,
+            git_worker_threads: git_pool::DEFAULT_WORKERS,
+            feature_flags: HashMap::new(),
+            diff_max_files: 1000,
+            diff_max_lines_per_file: 5000,
+            diff_max_blob_bytes: 5 * 1024 * 1024,
+            ca_bundle_path: None,
+            editor: "vscode".to_string(),
+            editor_command: None,
+            ssh_identity: None,
         }
     }
 }
 
+impl AppConfig {
+    pub fn revwalk_guards(&self) -> git::RevwalkGuards {
+        git::RevwalkGuards {
+            max_duration: Duration::from_millis(self.revwalk_max_millis.max(1)),
+            max_objects: self.revwalk_max_commits.max(1),
+        }
+    }
+
+    /// Field-level problems, empty when the settings can be saved.
+    pub fn validate(&self) -> Vec<FieldError> {
+        self.clone().repair()
+    }
+
+    /// Clamp out-of-range numbers and reset unknown choices to their
+    /// defaults, so a hand-edited or outdated settings file still loads.
+    /// Returns what was wrong.
+    pub fn repair(&mut self) -> Vec<FieldError> {
+        let defaults = AppConfig::default();
+        let mut errors = Vec::new();
+        clamp(&mut errors, "font_size", &mut self.font_size, 8, 32);
+        clamp(&mut errors, "tab_size", &mut self.tab_size, 1, 16);
+        clamp(
+            &mut errors,
+            "diff_context_lines",
+            &mut self.diff_context_lines,
+            0,
+            100,
+        );
+        clamp(
+            &mut errors,
+            "max_recent_repos",
+            &mut self.max_recent_repos,
+            1,
+            100,
+        );
+        clamp(
+            &mut errors,
+            "git_worker_threads",
+            &mut self.git_worker_threads,
+            1,
+            git_pool::MAX_WORKERS,
+        );
+
+        choose(
+            &mut errors,
+            "theme",
+            &mut self.theme,
+            THEMES,
+            &defaults.theme,
+        );
+        if let Some(mut language) = self.language.take() {
+            // An unknown language falls back to following the system.
+            let known = errors.len();
+            choose(&mut errors, "language", &mut language, LANGUAGES, "");
+            self.language = (errors.len() == known).then_some(language);
+        }
+        choose(
+            &mut errors,
+            "update_channel",
+            &mut self.update_channel,
+            UPDATE_CHANNELS,
+            &defaults.update_channel,
+        );
+        choose(
+            &mut errors,
+            "editor",
+            &mut self.editor,
+            EDITORS,
+            &defaults.editor,
+        );
+        let has_command = self
+            .editor_command
+            .as_ref()
+            .is_some_and(|command| command.contains("{file}"));
+        if self.editor == "custom" && !has_command {
+            errors.push(FieldError {
+                field: "editor_command".to_string(),
+                message: "a template with {file} is required for a custom editor".to_string(),
+            });
+            self.editor = defaults.editor;
+        }
+        errors
+    }
+
+    pub fn diff_limits(&self) -> git::DiffLimits {
+        git::DiffLimits {
+            max_files: self.diff_max_files.max(1),
+            max_lines_per_file: self.diff_max_lines_per_file.max(1),
+            max_blob_bytes: self.diff_max_blob_bytes.max(1),
+            context_lines: self.diff_context_lines,
+        }
+    }
+}
+
+fn clamp<T: PartialOrd + Copy + Display>(
+    errors: &mut Vec<FieldError>,
+    field: &str,
+    value: &mut T,
+    min: T,
+    max: T,
+) {
+    if *value < min || *value > max {
+        errors.push(FieldError {
+            field: field.to_string(),
+            message: format!("{} is outside {}..={}", value, min, max),
+        });
+        *value = if *value < min { min } else { max };
+    }
+}
+
+fn choose(
+    errors: &mut Vec<FieldError>,
+    field: &str,
+    value: &mut String,
+    allowed: &[&str],
+    default: &str,
+) {
+    if !allowed.contains(&value.as_str()) {
+        errors.push(FieldError {
+            field: field.to_string(),
+            message: format!("'{}' is not one of {}", value, allowed.join(", ")),
+        });
+        *value = default.to_string();
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SettingsChanged {
+    /// Names of the `AppConfig` fields whose values changed.
+    pub fields: Vec<String>,
+    pub settings: AppConfig,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GitUserConfig {
     pub name: Option<String>,
@@ -65,64 +241,72 @@ fn get_settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     Ok(app_data_dir.join(SETTINGS_FILENAME))
 }
 
-pub fn get_theme_file_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let app_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-
-    fs::create_dir_all(&app_dir)
-        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
-
-    Ok(app_dir.join(THEME_FILENAME))
-}
-
-pub fn load_theme_from_disk(theme_path: &PathBuf) -> Result<String, String> {
-    match fs::read_to_string(theme_path) {
-        Ok(content) => {
-            let theme = content.trim();
-            if theme.is_empty() {
-                Ok("system".to_string())
-            } else {
-                Ok(theme.to_string())
-            }
-        }
-        Err(_) => Ok("system".to_string()),
+/// Move the theme from the legacy `theme.txt` into settings.json, which is
+/// the only place it is stored now. Called once from `setup`.
+pub fn migrate_theme_file(app: &tauri::AppHandle) -> Result<(), String> {
+    let theme_path = get_settings_path(app)?.with_file_name(THEME_FILENAME);
+    if !theme_path.exists() {
+        return Ok(());
+    }
+    if let Some(theme) = read_legacy_theme(&theme_path) {
+        update_settings(app, |settings| {
+            settings.theme = theme;
+            Ok(())
+        })?;
     }
+    fs::remove_file(&theme_path).map_err(|e| format!("Failed to remove {}: {}", THEME_FILENAME, e))
 }
 
-pub fn save_theme_to_disk(theme_path: &PathBuf, theme: &str) -> Result<(), String> {
-    fs::write(theme_path, theme).map_err(|e| format!("Failed to write theme: {}", e))
+/// Theme saved in `theme.txt`, if it names a known theme.
+fn read_legacy_theme(theme_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(theme_path).ok()?;
+    let theme = content.trim();
+    THEMES.contains(&theme).then(|| theme.to_string())
 }
 
+/// Kept for older frontends; reads `AppConfig.theme`.
 #[tauri::command]
-pub async fn load_theme(app: tauri::AppHandle) -> Result<String, String> {
-    let theme_path = get_theme_file_path(&app)?;
-    load_theme_from_disk(&theme_path)
+pub async fn load_theme(app: tauri::AppHandle) -> Result<String, GitLiteError> {
+    Ok(read_settings(&app)?.theme)
 }
 
+/// Kept for older frontends; updates `AppConfig.theme`.
 #[tauri::command]
-pub async fn save_theme(theme: String, app: tauri::AppHandle) -> Result<(), String> {
-    let valid_themes = ["system", "light", "dark"];
-    if !valid_themes.contains(&theme.as_str()) {
-        return Err(format!(
-            "Invalid theme: {}. Must be one of: system, light, dark",
-            theme
-        ));
+pub async fn save_theme(theme: String, app: tauri::AppHandle) -> Result<(), GitLiteError> {
+    if !THEMES.contains(&theme.as_str()) {
+        return Err(GitLiteError::new(
+            ErrorCode::SettingsInvalid,
+            format!(
+                "Invalid theme: {}. Must be one of: system, light, dark",
+                theme
+            ),
+        )
+        .with_fields(vec![FieldError {
+            field: "theme".to_string(),
+            message: format!("'{}' is not one of {}", theme, THEMES.join(", ")),
+        }]));
     }
 
-    let theme_path = get_theme_file_path(&app)?;
-    save_theme_to_disk(&theme_path, &theme)
+    update_settings(&app, |settings| {
+        settings.theme = theme;
+        Ok(())
+    })
+    .map_err(GitLiteError::from)
 }
 
 #[tauri::command]
-pub async fn load_settings(app: tauri::AppHandle) -> Result<AppConfig, String> {
-    let settings_path = get_settings_path(&app)?;
+pub async fn load_settings(app: tauri::AppHandle) -> Result<AppConfig, GitLiteError> {
+    read_settings(&app).map_err(GitLiteError::from)
+}
+
+pub fn read_settings(app: &tauri::AppHandle) -> Result<AppConfig, String> {
+    let settings_path = get_settings_path(app)?;
 
     match fs::read_to_string(&settings_path) {
         Ok(content) => {
-            let config: AppConfig = serde_json::from_str(&content)
+            let mut config: AppConfig = serde_json::from_str(&content)
                 .map_err(|e| format!("Failed to parse settings: {}", e))?;
+            config.repair();
             Ok(config)
         }
         Err(_) => Ok(AppConfig::default()),
@@ -130,17 +314,83 @@ pub async fn load_settings(app: tauri::AppHandle) -> Result<AppConfig, String> {
 }
 
 #[tauri::command]
-pub async fn save_settings(config: AppConfig, app: tauri::AppHandle) -> Result<(), String> {
-    let settings_path = get_settings_path(&app)?;
+pub async fn save_settings(config: AppConfig, app: tauri::AppHandle) -> Result<(), GitLiteError> {
+    let errors = config.validate();
+    if !errors.is_empty() {
+        return Err(GitLiteError::new(
+            ErrorCode::SettingsInvalid,
+            format!("{} setting(s) are invalid; nothing was saved", errors.len()),
+        )
+        .with_fields(errors));
+    }
+    write_settings(&app, &config).map_err(GitLiteError::from)
+}
+
+/// Read the settings, apply `change` and save the result, all under the
+/// settings lock. Nothing is written when `change` fails.
+pub fn update_settings<T>(
+    app: &tauri::AppHandle,
+    change: impl FnOnce(&mut AppConfig) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = lock();
+    let mut settings = read_settings(app)?;
+    let result = change(&mut settings)?;
+    save(app, &settings)?;
+    Ok(result)
+}
 
-    let json = serde_json::to_string_pretty(&config)
+/// Persist `config`, then notify the frontend and backend consumers (the
+/// auto-fetch scheduler) about the fields that changed.
+pub fn write_settings(app: &tauri::AppHandle, config: &AppConfig) -> Result<(), String> {
+    let _guard = lock();
+    save(app, config)
+}
+
+fn lock() -> MutexGuard<'static, ()> {
+    SETTINGS_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn save(app: &tauri::AppHandle, config: &AppConfig) -> Result<(), String> {
+    let settings_path = get_settings_path(app)?;
+    let previous = read_settings(app).unwrap_or_default();
+
+    let json = serde_json::to_string_pretty(config)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
-    fs::write(&settings_path, json).map_err(|e| format!("Failed to write settings: {}", e))
+    fs::write(&settings_path, json).map_err(|e| format!("Failed to write settings: {}", e))?;
+
+    let fields = changed_fields(&previous, config);
+    if !fields.is_empty() {
+        crate::auto_fetch::settings_changed(&fields);
+        if fields.iter().any(|field| field == "ssh_identity") {
+            git::prefer_ssh_identity(config.ssh_identity.clone());
+        }
+        let _ = app.emit(
+            SETTINGS_CHANGED_EVENT,
+            SettingsChanged {
+                fields,
+                settings: config.clone(),
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Top-level `AppConfig` fields that differ between `old` and `new`.
+fn changed_fields(old: &AppConfig, new: &AppConfig) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(field, value)| old.get(field.as_str()) != Some(value))
+        .map(|(field, _)| field.clone())
+        .collect()
 }
 
 #[tauri::command]
-pub fn get_git_config(path: String) -> Result<GitUserConfig, String> {
+pub fn get_git_config(path: String) -> Result<GitUserConfig, GitLiteError> {
     let repo = Repository::open(&path).map_err(|e| e.message().to_string())?;
     let config = repo.config().map_err(|e| e.message().to_string())?;
 
@@ -151,7 +401,7 @@ pub fn get_git_config(path: String) -> Result<GitUserConfig, String> {
 }
 
 #[tauri::command]
-pub fn set_git_config(path: String, name: String, email: String) -> Result<(), String> {
+pub fn set_git_config(path: String, name: String, email: String) -> Result<(), GitLiteError> {
     let repo = Repository::open(&path).map_err(|e| e.message().to_string())?;
     let mut config = repo.config().map_err(|e| e.message().to_string())?;
 
@@ -167,7 +417,7 @@ pub fn set_git_config(path: String, name: String, email: String) -> Result<(), S
 
 #[cfg(test)]
 mod tests {
-    use super::{get_git_config, set_git_config};
+    use super::{changed_fields, get_git_config, read_legacy_theme, set_git_config, AppConfig};
     use git2::Repository;
     use std::fs;
     use std::path::PathBuf;
@@ -205,4 +455,84 @@ mod tests {
 
         fs::remove_dir_all(repo_dir).expect("failed to clean temp repo");
     }
+
+    #[test]
+    fn changed_fields_lists_only_differing_settings() {
+        let old = AppConfig::default();
+        assert!(changed_fields(&old, &old.clone()).is_empty());
+
+        let mut new = old.clone();
+        new.auto_fetch = true;
+        new.default_remote = "upstream".to_string();
+        new.feature_flags.insert("mirror".to_string(), false);
+        let mut fields = changed_fields(&old, &new);
+        fields.sort();
+        assert_eq!(fields, ["auto_fetch", "default_remote", "feature_flags"]);
+    }
+
+    #[test]
+    fn validate_reports_each_invalid_field() {
+        assert!(AppConfig::default().validate().is_empty());
+
+        let config = AppConfig {
+            font_size: 200,
+            tab_size: 0,
+            git_worker_threads: 64,
+            theme: "solarized".to_string(),
+            language: Some("xx".to_string()),
+            update_channel: "nightly".to_string(),
+            ..AppConfig::default()
+        };
+        let errors = config.validate();
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "font_size",
+                "tab_size",
+                "git_worker_threads",
+                "theme",
+                "language",
+                "update_channel"
+            ]
+        );
+    }
+
+    #[test]
+    fn repair_clamps_numbers_and_resets_unknown_choices() {
+        let mut config = AppConfig {
+            font_size: 200,
+            tab_size: 0,
+            language: Some("xx".to_string()),
+            editor: "custom".to_string(),
+            editor_command: Some("idea".to_string()),
+            ..AppConfig::default()
+        };
+        let fixed: Vec<String> = config
+            .repair()
+            .into_iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(
+            fixed,
+            ["font_size", "tab_size", "language", "editor_command"]
+        );
+        assert_eq!(config.font_size, 32);
+        assert_eq!(config.tab_size, 1);
+        assert_eq!(config.language, None);
+        assert_eq!(config.editor, "vscode");
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn legacy_theme_file_is_read_only_when_valid() {
+        let dir = create_temp_repo();
+        let theme_path = dir.join("theme.txt");
+        assert_eq!(read_legacy_theme(&theme_path), None);
+        fs::write(&theme_path, "dark\n").unwrap();
+        assert_eq!(read_legacy_theme(&theme_path).as_deref(), Some("dark"));
+        fs::write(&theme_path, "neon").unwrap();
+        assert_eq!(read_legacy_theme(&theme_path), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
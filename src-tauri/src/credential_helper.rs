@@ -0,0 +1,417 @@
+use crate::credentials;
+use git2::Config;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::process::{Command, Stdio};
+
+/// Argument that switches the gitlite binary into `git credential` helper mode,
+/// e.g. `credential.helper = !'/path/to/gitlite' credential-helper`.
+pub const HELPER_ARG: &str = "credential-helper";
+const HELPER_CONFIG_KEY: &str = "credential.helper";
+const HELPER_MATCH_REGEX: &str = "[Gg]it[Ll]ite.*credential-helper";
+
+#[derive(Serialize)]
+pub struct CredentialHelperStatus {
+    pub helper_command: Option<String>,
+    pub enabled: bool,
+    pub configured_helpers: Vec<String>,
+}
+
+/// Entry point for `gitlite credential-helper <get|store|erase>`. Returns the
+/// process exit code.
+pub fn run(action: &str) -> i32 {
+    let stdin = io::stdin();
+    let mut input = String::new();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else { break };
+        if line.is_empty() {
+            break;
+        }
+        input.push_str(&line);
+        input.push('\n');
+    }
+
+    match handle_request(action, &parse_request(&input)) {
+        Ok(Some(output)) => {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(output.as_bytes());
+            let _ = stdout.flush();
+            0
+        }
+        Ok(None) => 0,
+        Err(error) => {
+            eprintln!("gitlite credential-helper: {}", error);
+            1
+        }
+    }
+}
+
+pub fn get_status() -> Result<CredentialHelperStatus, String> {
+    let config = Config::open_default().map_err(|e| {
+        format!(
+            "E_CREDENTIAL_HELPER_CONFIG: Failed to read git config: {}",
+            e
+        )
+    })?;
+    let configured_helpers = configured_helpers(&config);
+    let enabled = configured_helpers
+        .iter()
+        .any(|helper| is_gitlite_helper(helper));
+
+    Ok(CredentialHelperStatus {
+        helper_command: helper_command().ok(),
+        enabled,
+        configured_helpers,
+    })
+}
+
+/// Add or remove gitlite from the user's global `credential.helper` list.
+pub fn set_enabled(enabled: bool) -> Result<CredentialHelperStatus, String> {
+    let mut config = open_global_config()?;
+
+    if enabled {
+        let command = helper_command()?;
+        config
+            .set_multivar(HELPER_CONFIG_KEY, HELPER_MATCH_REGEX, &command)
+            .map_err(|e| format!("E_CREDENTIAL_HELPER_CONFIG: {}", e))?;
+    } else {
+        match config.remove_multivar(HELPER_CONFIG_KEY, HELPER_MATCH_REGEX) {
+            Ok(()) => {}
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {}
+            Err(e) => return Err(format!("E_CREDENTIAL_HELPER_CONFIG: {}", e)),
+        }
+    }
+
+    get_status()
+}
+
+/// Forward a credential write made inside the app to the other helpers
+/// configured in git config, so command-line tooling sees the same change.
+/// `action` is the `git credential` subcommand, `approve` or `reject`.
+pub fn notify_external_helpers(action: &str, host: &str, username: &str, password: &str) {
+    let Ok(config) = Config::open_default() else {
+        return;
+    };
+
+    let mut request = format!("protocol=https\nhost={}\nusername={}\n", host, username);
+    if !password.is_empty() {
+        request.push_str(&format!("password={}\n", password));
+    }
+    request.push('\n');
+
+    for helper in configured_helpers(&config) {
+        if is_gitlite_helper(&helper) {
+            continue;
+        }
+        if let Err(error) = invoke_helper(&helper, action, &request) {
+            crate::runtime::append_runtime_log(&format!(
+                "credential helper '{}' {} failed: {}",
+                helper, action, error
+            ));
+        }
+    }
+}
+
+/// What one configured helper did with credentials passed to
+/// `store_credentials`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HelperStoreResult {
+    pub helper: String,
+    pub stored: bool,
+    pub error: Option<String>,
+}
+
+/// `git credential approve` for credentials that just worked: hand them to
+/// every configured `credential.helper` so command-line git can reuse them.
+/// GitLite's own helper entry is skipped; `save_credential` covers it.
+pub fn store_credentials(
+    url: &str,
+    username: &str,
+    password: &str,
+) -> Result<Vec<HelperStoreResult>, String> {
+    let config = Config::open_default().map_err(|e| {
+        format!(
+            "E_CREDENTIAL_HELPER_CONFIG: Failed to read git config: {}",
+            e
+        )
+    })?;
+    store_credentials_in(&config, url, username, password)
+}
+
+fn store_credentials_in(
+    config: &Config,
+    url: &str,
+    username: &str,
+    password: &str,
+) -> Result<Vec<HelperStoreResult>, String> {
+    let request = store_request(url, username, password)?;
+    let helpers: Vec<String> = configured_helpers(config)
+        .into_iter()
+        .filter(|helper| !is_gitlite_helper(helper))
+        .collect();
+    if helpers.is_empty() {
+        return Err("E_CREDENTIAL_HELPER_NONE: no credential.helper is configured".to_string());
+    }
+
+    Ok(helpers
+        .into_iter()
+        .map(|helper| {
+            let result = invoke_helper(&helper, "approve", &request);
+            HelperStoreResult {
+                helper,
+                stored: result.is_ok(),
+                error: result.err(),
+            }
+        })
+        .collect())
+}
+
+fn store_request(url: &str, username: &str, password: &str) -> Result<String, String> {
+    let (protocol, rest) = url.trim().split_once("://").unwrap_or(("", ""));
+    let protocol = protocol.to_ascii_lowercase();
+    if protocol != "https" && protocol != "http" {
+        return Err(format!(
+            "E_CREDENTIAL_URL_INVALID: '{}' is not an http(s) URL",
+            url
+        ));
+    }
+    let host = credentials::normalize_host(rest)?;
+    if username.trim().is_empty() {
+        return Err("E_CREDENTIAL_USERNAME_EMPTY: username is required".to_string());
+    }
+    if password.is_empty() {
+        return Err("E_CREDENTIAL_SECRET_EMPTY: password is required".to_string());
+    }
+    // A line break would let a value inject extra fields such as `host=`
+    // into the helper protocol (CVE-2020-5260).
+    for (value, code, field) in [
+        (host.as_str(), "E_CREDENTIAL_HOST_INVALID", "host"),
+        (username, "E_CREDENTIAL_USERNAME_INVALID", "username"),
+        (password, "E_CREDENTIAL_SECRET_INVALID", "password"),
+    ] {
+        if value.contains(['\n', '\r', '\0']) {
+            return Err(format!(
+                "{}: {} cannot contain newlines or NUL bytes",
+                code, field
+            ));
+        }
+    }
+    Ok(format!(
+        "protocol={}\nhost={}\nusername={}\npassword={}\n\n",
+        protocol,
+        host,
+        username.trim(),
+        password
+    ))
+}
+
+fn handle_request(
+    action: &str,
+    request: &HashMap<String, String>,
+) -> Result<Option<String>, String> {
+    let protocol = request.get("protocol").map(String::as_str).unwrap_or("");
+    if protocol != "https" && protocol != "http" {
+        return Ok(None);
+    }
+    let Some(host) = request.get("host") else {
+        return Ok(None);
+    };
+    let username = request.get("username").map(String::as_str);
+    let index_path = credentials::standalone_index_path()?;
+
+    match action {
+        "get" => Ok(
+            credentials::find_credential_at(&index_path, host, username)?
+                .map(|(username, password)| format_response(&username, &password)),
+        ),
+        "store" => {
+            let (Some(username), Some(password)) = (username, request.get("password")) else {
+                return Ok(None);
+            };
+            credentials::save_credential_at(&index_path, host, username, password)?;
+            Ok(None)
+        }
+        "erase" => {
+            if let Some(username) = username {
+                credentials::delete_credential_at(&index_path, host, username)?;
+            }
+            Ok(None)
+        }
+        // Unknown actions must be ignored per the helper protocol.
+        _ => Ok(None),
+    }
+}
+
+fn parse_request(input: &str) -> HashMap<String, String> {
+    input
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .collect()
+}
+
+fn format_response(username: &str, password: &str) -> String {
+    format!("username={}\npassword={}\n", username, password)
+}
+
+pub(crate) fn configured_helpers(config: &Config) -> Vec<String> {
+    let mut helpers = Vec::new();
+    if let Ok(entries) = config.multivar(HELPER_CONFIG_KEY, None) {
+        let _ = entries.for_each(|entry| match entry.value() {
+            // An empty value resets the helper list, matching git's behavior.
+            Some("") | None => helpers.clear(),
+            Some(value) => helpers.push(value.to_string()),
+        });
+    }
+    helpers
+}
+
+fn is_gitlite_helper(helper: &str) -> bool {
+    helper.contains(HELPER_ARG) && helper.to_ascii_lowercase().contains("gitlite")
+}
+
+fn helper_command() -> Result<String, String> {
+    let exe = std::env::current_exe().map_err(|e| {
+        format!(
+            "E_CREDENTIAL_HELPER_PATH: Failed to resolve executable: {}",
+            e
+        )
+    })?;
+    Ok(format!(
+        "!{} {}",
+        shell_quote(&exe.to_string_lossy()),
+        HELPER_ARG
+    ))
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn open_global_config() -> Result<Config, String> {
+    let path = Config::find_global()
+        .ok()
+        .or_else(|| dirs::home_dir().map(|home| home.join(".gitconfig")))
+        .ok_or_else(|| {
+            "E_CREDENTIAL_HELPER_CONFIG: Failed to locate global git config".to_string()
+        })?;
+    Config::open(&path).map_err(|e| format!("E_CREDENTIAL_HELPER_CONFIG: {}", e))
+}
+
+/// Run `git credential <approve|reject>` with `helper` as the only
+/// configured helper, so git resolves it the way it always does (`!` shell
+/// snippets, absolute paths, `git credential-<name>`) on every platform.
+fn invoke_helper(helper: &str, action: &str, request: &str) -> Result<(), String> {
+    let mut child = Command::new("git")
+        // An empty value clears the helpers inherited from git config.
+        .args(["-c", "credential.helper=", "-c"])
+        .arg(format!("{}={}", HELPER_CONFIG_KEY, helper))
+        .args(["credential", action])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(request.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("exited with {} ({})", output.status, stderr.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn parse_request_reads_key_value_lines() {
+        let request = parse_request("protocol=https\nhost=github.com\nusername=octo=cat\n");
+        assert_eq!(request.get("protocol").unwrap(), "https");
+        assert_eq!(request.get("host").unwrap(), "github.com");
+        assert_eq!(request.get("username").unwrap(), "octo=cat");
+    }
+
+    #[test]
+    fn non_http_requests_are_ignored() {
+        let request = parse_request("protocol=ssh\nhost=github.com\n");
+        assert_eq!(handle_request("get", &request).unwrap(), None);
+    }
+
+    #[test]
+    fn store_credentials_feeds_configured_helpers() {
+        let dir = std::env::temp_dir().join(format!("gitlite-helper-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let captured = dir.join("captured");
+        let mut config = Config::open(&dir.join("gitconfig")).unwrap();
+        config
+            .set_str(
+                HELPER_CONFIG_KEY,
+                &format!("!f() {{ cat > '{}'; }}; f", captured.display()),
+            )
+            .unwrap();
+
+        let results = store_credentials_in(
+            &config,
+            "https://octo@GitHub.com/org/repo.git",
+            "octo",
+            "s3cret",
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].stored, "{:?}", results[0].error);
+        assert_eq!(
+            fs::read_to_string(&captured).unwrap(),
+            "protocol=https\nhost=github.com\nusername=octo\npassword=s3cret\n"
+        );
+
+        assert!(store_request("ssh://github.com/org/repo", "octo", "x")
+            .unwrap_err()
+            .starts_with("E_CREDENTIAL_URL_INVALID"));
+        config.set_str(HELPER_CONFIG_KEY, "").unwrap();
+        assert!(
+            store_credentials_in(&config, "https://github.com", "octo", "x")
+                .unwrap_err()
+                .starts_with("E_CREDENTIAL_HELPER_NONE")
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn store_request_rejects_injected_fields() {
+        assert!(
+            store_request("https://github.com\nhost=evil.example", "octo", "x")
+                .unwrap_err()
+                .starts_with("E_CREDENTIAL_HOST_INVALID")
+        );
+        assert!(store_request("https://github.com", "octo\nhost=evil", "x")
+            .unwrap_err()
+            .starts_with("E_CREDENTIAL_USERNAME_INVALID"));
+        for password in ["x\nhost=evil.example", "x\r", "x\0"] {
+            assert!(store_request("https://github.com", "octo", password)
+                .unwrap_err()
+                .starts_with("E_CREDENTIAL_SECRET_INVALID"));
+        }
+    }
+
+    #[test]
+    fn detects_gitlite_helper_entries() {
+        assert!(is_gitlite_helper(
+            "!'/Applications/GitLite.app/Contents/MacOS/gitlite' credential-helper"
+        ));
+        assert!(!is_gitlite_helper("osxkeychain"));
+        assert_eq!(shell_quote("/opt/it's"), "'/opt/it'\\''s'");
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::Emitter;
+
+/// How long an operation waits for the user to answer a prompt.
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+static PROMPTER: Prompter<CredentialRequest, CredentialAnswer> = Prompter::new();
+
+type Emit<R> = Box<dyn Fn(&R) + Send + Sync>;
+
+/// Bridges a synchronous libgit2 callback to the frontend: the callback
+/// emits a request and blocks until `respond` is called for its id. Nothing
+/// is emitted until the frontend says it listens, so operations without a
+/// dialog to answer them fail at once instead of waiting out the timeout.
+pub(crate) struct Prompter<R, A> {
+    emit: Mutex<Option<Emit<R>>>,
+    listening: AtomicBool,
+    pending: Mutex<BTreeMap<String, Sender<Option<A>>>>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CredentialRequest {
+    pub id: String,
+    pub url: String,
+    /// Username from the URL or the command, if one is known.
+    pub username: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct CredentialAnswer {
+    pub username: String,
+    pub password: String,
+}
+
+impl<R, A> Prompter<R, A> {
+    pub(crate) const fn new() -> Self {
+        Prompter {
+            emit: Mutex::new(None),
+            listening: AtomicBool::new(false),
+            pending: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub(crate) fn install(&self, emit: Emit<R>) {
+        *lock(&self.emit) = Some(emit);
+    }
+
+    /// Whether a frontend listener answers requests.
+    pub(crate) fn listen(&self, enabled: bool) {
+        self.listening.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Emit the request built from a fresh id and wait up to `timeout` for
+    /// its answer. `None` when cancelled, timed out, or nobody listens.
+    pub(crate) fn request(&self, build: impl FnOnce(String) -> R, timeout: Duration) -> Option<A> {
+        if !self.listening.load(Ordering::SeqCst) {
+            return None;
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        let (sender, receiver) = mpsc::channel();
+        lock(&self.pending).insert(id.clone(), sender);
+        let request = build(id.clone());
+
+        let emitted = match lock(&self.emit).as_ref() {
+            Some(emit) => {
+                emit(&request);
+                true
+            }
+            None => false,
+        };
+        let answer = if emitted {
+            receiver.recv_timeout(timeout).ok().flatten()
+        } else {
+            None
+        };
+        lock(&self.pending).remove(&id);
+        answer
+    }
+
+    /// Deliver `answer` to the request `id`; false when it already timed out
+    /// or was answered.
+    pub(crate) fn respond(&self, id: &str, answer: Option<A>) -> bool {
+        match lock(&self.pending).remove(id) {
+            Some(sender) => {
+                // The waiting side may have just timed out; nothing left to do then.
+                let _ = sender.send(answer);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Send prompts to the frontend as `credentials://request` events.
+pub fn install(app: tauri::AppHandle) {
+    PROMPTER.install(Box::new(move |request| {
+        let _ = app.emit("credentials://request", request);
+    }));
+}
+
+/// Start or stop prompting; the frontend enables this once its
+/// `credentials://request` listener is registered.
+pub fn listen(enabled: bool) {
+    PROMPTER.listen(enabled);
+}
+
+/// Ask the user for credentials for `url`, blocking until they answer,
+/// cancel, or `PROMPT_TIMEOUT` passes. `None` without a frontend attached.
+pub fn request(url: &str, username: Option<&str>) -> Option<CredentialAnswer> {
+    PROMPTER.request(
+        |id| CredentialRequest {
+            id,
+            url: url.to_string(),
+            username: username.map(str::to_string),
+        },
+        PROMPT_TIMEOUT,
+    )
+}
+
+/// Answer a pending `credentials://request`; `None` cancels it.
+pub fn respond(id: &str, answer: Option<CredentialAnswer>) -> Result<(), String> {
+    if PROMPTER.respond(id, answer) {
+        Ok(())
+    } else {
+        Err(format!(
+            "E_CREDENTIAL_PROMPT_NOT_FOUND: prompt '{}' already timed out or was answered",
+            id
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn ask(
+        prompter: &Prompter<CredentialRequest, CredentialAnswer>,
+        username: Option<&str>,
+        timeout: Duration,
+    ) -> Option<CredentialAnswer> {
+        prompter.request(
+            |id| CredentialRequest {
+                id,
+                url: "https://example.com".to_string(),
+                username: username.map(str::to_string),
+            },
+            timeout,
+        )
+    }
+
+    #[test]
+    fn prompts_block_until_answered_or_timed_out() {
+        let prompter = Arc::new(Prompter::new());
+        assert_eq!(
+            ask(&prompter, None, Duration::ZERO),
+            None,
+            "no frontend attached"
+        );
+
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        prompter.install(Box::new(move |request| {
+            lock(&sender).send(request.clone()).unwrap();
+        }));
+        assert_eq!(
+            ask(&prompter, None, Duration::from_secs(5)),
+            None,
+            "no listener registered"
+        );
+        prompter.listen(true);
+        let answering = Arc::clone(&prompter);
+        let frontend = thread::spawn(move || {
+            let request: CredentialRequest = receiver.recv().unwrap();
+            assert_eq!(request.username.as_deref(), Some("octo"));
+            let answer = CredentialAnswer {
+                username: "octo".to_string(),
+                password: "s3cret".to_string(),
+            };
+            assert!(answering.respond(&request.id, Some(answer)));
+            receiver
+        });
+        let answer = ask(&prompter, Some("octo"), Duration::from_secs(5)).unwrap();
+        assert_eq!(answer.password, "s3cret");
+
+        let receiver = frontend.join().unwrap();
+        assert_eq!(ask(&prompter, None, Duration::from_millis(10)), None);
+        let timed_out = receiver.recv().unwrap();
+        assert!(!prompter.respond(&timed_out.id, None));
+    }
+}
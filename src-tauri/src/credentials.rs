@@ -0,0 +1,358 @@
+use crate::json_store::unix_now;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+const APP_IDENTIFIER: &str = "com.gitlite.app";
+const KEYCHAIN_SERVICE_PREFIX: &str = "com.gitlite.app.credential";
+const CREDENTIALS_INDEX_FILENAME: &str = "credentials.json";
+
+/// Metadata for a credential stored in the OS keychain. The secret itself is
+/// never part of this struct.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CredentialEntry {
+    pub host: String,
+    pub username: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+pub fn list_credentials(app: &tauri::AppHandle) -> Result<Vec<CredentialEntry>, String> {
+    let index_path = get_index_path(app)?;
+    read_index(&index_path)
+}
+
+/// Create or replace the secret for `host` + `username`.
+pub fn save_credential(
+    app: &tauri::AppHandle,
+    host: &str,
+    username: &str,
+    secret: &str,
+) -> Result<CredentialEntry, String> {
+    let index_path = get_index_path(app)?;
+    let entry = save_credential_at(&index_path, host, username, secret)?;
+    crate::credential_helper::notify_external_helpers(
+        "approve",
+        &entry.host,
+        &entry.username,
+        secret,
+    );
+    Ok(entry)
+}
+
+pub fn delete_credential(app: &tauri::AppHandle, host: &str, username: &str) -> Result<(), String> {
+    let index_path = get_index_path(app)?;
+    delete_credential_at(&index_path, host, username)?;
+    if let (Ok(host), Ok(username)) = (normalize_host(host), normalize_username(username)) {
+        crate::credential_helper::notify_external_helpers("reject", &host, &username, "");
+    }
+    Ok(())
+}
+
+/// Stored `(username, secret)` for `host`, newest entry first.
+pub fn find_credential(
+    app: &tauri::AppHandle,
+    host: &str,
+) -> Result<Option<(String, String)>, String> {
+    find_credential_at(&get_index_path(app)?, host, None)
+}
+
+pub fn save_credential_at(
+    index_path: &Path,
+    host: &str,
+    username: &str,
+    secret: &str,
+) -> Result<CredentialEntry, String> {
+    let host = normalize_host(host)?;
+    let username = normalize_username(username)?;
+    if secret.trim().is_empty() {
+        return Err("E_CREDENTIAL_SECRET_EMPTY: secret is required".to_string());
+    }
+
+    keychain_item(&host, &username)?.save(secret)?;
+
+    let mut entries = read_index(index_path)?;
+    let entry = upsert_entry(&mut entries, &host, &username, unix_now());
+    write_index(index_path, &entries)?;
+    Ok(entry)
+}
+
+pub fn delete_credential_at(index_path: &Path, host: &str, username: &str) -> Result<(), String> {
+    let host = normalize_host(host)?;
+    let username = normalize_username(username)?;
+
+    keychain_item(&host, &username)?.delete()?;
+
+    let mut entries = read_index(index_path)?;
+    entries.retain(|entry| !(entry.host == host && entry.username == username));
+    write_index(index_path, &entries)
+}
+
+/// Look up the stored secret for `host`, optionally restricted to `username`.
+/// When no username is given the most recently updated entry wins.
+pub fn find_credential_at(
+    index_path: &Path,
+    host: &str,
+    username: Option<&str>,
+) -> Result<Option<(String, String)>, String> {
+    let host = normalize_host(host)?;
+    let username = username.map(str::trim).filter(|value| !value.is_empty());
+
+    let mut candidates: Vec<CredentialEntry> = read_index(index_path)?
+        .into_iter()
+        .filter(|entry| entry.host == host)
+        .filter(|entry| username.is_none_or(|name| entry.username == name))
+        .collect();
+    candidates.sort_by_key(|entry| std::cmp::Reverse(entry.updated_at));
+
+    for entry in candidates {
+        if let Some(secret) = load_secret(&entry.host, &entry.username)? {
+            return Ok(Some((entry.username, secret)));
+        }
+    }
+    Ok(None)
+}
+
+/// Saved `(username, secret)` for the host of a remote URL, for git
+/// credential callbacks, which run without an app handle. Lookup failures
+/// count as nothing saved so the caller falls through to its next source.
+pub fn find_for_url(url: &str, username: Option<&str>) -> Option<(String, String)> {
+    let index_path = standalone_index_path().ok()?;
+    find_credential_at(&index_path, url, username)
+        .ok()
+        .flatten()
+}
+
+pub fn load_secret(host: &str, username: &str) -> Result<Option<String>, String> {
+    keychain_item(host, username)?.load()
+}
+
+/// The error codes a `KeychainItem` reports, one set per module.
+pub struct KeychainCodes {
+    pub init: &'static str,
+    pub read: &'static str,
+    pub write: &'static str,
+    pub delete: &'static str,
+}
+
+const KEYCHAIN_CODES: KeychainCodes = KeychainCodes {
+    init: "E_CREDENTIAL_KEYCHAIN_INIT",
+    read: "E_CREDENTIAL_KEYCHAIN_READ",
+    write: "E_CREDENTIAL_KEYCHAIN_WRITE",
+    delete: "E_CREDENTIAL_KEYCHAIN_DELETE",
+};
+
+/// One secret in the OS keychain. Every module that stores secrets goes
+/// through this and reports failures under its own `KeychainCodes`.
+pub struct KeychainItem {
+    entry: keyring::Entry,
+    codes: &'static KeychainCodes,
+}
+
+impl KeychainItem {
+    pub fn new(
+        service: &str,
+        account: &str,
+        codes: &'static KeychainCodes,
+    ) -> Result<Self, String> {
+        let entry = keyring::Entry::new(service, account)
+            .map_err(|error| format!("{}: {}", codes.init, error))?;
+        Ok(KeychainItem { entry, codes })
+    }
+
+    /// The stored secret; `None` when nothing, or an empty string, is stored.
+    pub fn load(&self) -> Result<Option<String>, String> {
+        match self.entry.get_password() {
+            Ok(secret) if !secret.is_empty() => Ok(Some(secret)),
+            Ok(_) | Err(keyring::Error::NoEntry) => Ok(None),
+            Err(error) => Err(format!("{}: {}", self.codes.read, error)),
+        }
+    }
+
+    pub fn save(&self, secret: &str) -> Result<(), String> {
+        self.entry
+            .set_password(secret)
+            .map_err(|error| format!("{}: {}", self.codes.write, error))
+    }
+
+    /// Deleting an item that does not exist succeeds.
+    pub fn delete(&self) -> Result<(), String> {
+        match self.entry.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(error) => Err(format!("{}: {}", self.codes.delete, error)),
+        }
+    }
+}
+
+/// Index location used outside the Tauri runtime (e.g. git credential helper
+/// mode). Mirrors Tauri's `app_data_dir` resolution.
+pub fn standalone_index_path() -> Result<PathBuf, String> {
+    let app_data_dir = dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| "E_CREDENTIAL_INDEX_DIR: Failed to resolve app data dir".to_string())?;
+
+    fs::create_dir_all(&app_data_dir).map_err(|error| {
+        format!(
+            "E_CREDENTIAL_INDEX_DIR: Failed to create app data dir: {}",
+            error
+        )
+    })?;
+
+    Ok(app_data_dir.join(CREDENTIALS_INDEX_FILENAME))
+}
+
+/// Reduce user input such as `https://GitHub.com/org/repo.git` to `github.com`.
+pub fn normalize_host(host: &str) -> Result<String, String> {
+    let trimmed = host.trim();
+    let without_scheme = trimmed
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(trimmed);
+    let authority = without_scheme.split('/').next().unwrap_or("");
+    let authority = authority
+        .rsplit_once('@')
+        .map(|(_, rest)| rest)
+        .unwrap_or(authority);
+    let normalized = authority.to_ascii_lowercase();
+
+    if normalized.is_empty() {
+        return Err("E_CREDENTIAL_HOST_EMPTY: host is required".to_string());
+    }
+    Ok(normalized)
+}
+
+fn normalize_username(username: &str) -> Result<String, String> {
+    let trimmed = username.trim();
+    if trimmed.is_empty() {
+        return Err("E_CREDENTIAL_USERNAME_EMPTY: username is required".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+fn upsert_entry(
+    entries: &mut Vec<CredentialEntry>,
+    host: &str,
+    username: &str,
+    now: i64,
+) -> CredentialEntry {
+    if let Some(existing) = entries
+        .iter_mut()
+        .find(|entry| entry.host == host && entry.username == username)
+    {
+        existing.updated_at = now;
+        return existing.clone();
+    }
+
+    let entry = CredentialEntry {
+        host: host.to_string(),
+        username: username.to_string(),
+        created_at: now,
+        updated_at: now,
+    };
+    entries.push(entry.clone());
+    entries.sort_by(|a, b| (&a.host, &a.username).cmp(&(&b.host, &b.username)));
+    entry
+}
+
+fn keychain_item(host: &str, username: &str) -> Result<KeychainItem, String> {
+    let service = format!("{}:{}", KEYCHAIN_SERVICE_PREFIX, host);
+    KeychainItem::new(&service, username, &KEYCHAIN_CODES)
+}
+
+fn get_index_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|error| {
+        format!(
+            "E_CREDENTIAL_INDEX_DIR: Failed to resolve app data dir: {}",
+            error
+        )
+    })?;
+
+    fs::create_dir_all(&app_data_dir).map_err(|error| {
+        format!(
+            "E_CREDENTIAL_INDEX_DIR: Failed to create app data dir: {}",
+            error
+        )
+    })?;
+
+    Ok(app_data_dir.join(CREDENTIALS_INDEX_FILENAME))
+}
+
+fn read_index(index_path: &Path) -> Result<Vec<CredentialEntry>, String> {
+    match fs::read_to_string(index_path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|error| format!("E_CREDENTIAL_INDEX_READ: {}", error)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(format!("E_CREDENTIAL_INDEX_READ: {}", error)),
+    }
+}
+
+fn write_index(index_path: &Path, entries: &[CredentialEntry]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(entries)
+        .map_err(|error| format!("E_CREDENTIAL_INDEX_WRITE: {}", error))?;
+    fs::write(index_path, json).map_err(|error| format!("E_CREDENTIAL_INDEX_WRITE: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_host_strips_scheme_path_and_userinfo() {
+        assert_eq!(
+            normalize_host("https://user@GitHub.com/org/repo.git").unwrap(),
+            "github.com"
+        );
+        assert_eq!(
+            normalize_host("git.example.com:8443").unwrap(),
+            "git.example.com:8443"
+        );
+        assert!(normalize_host("  ")
+            .unwrap_err()
+            .contains("E_CREDENTIAL_HOST_EMPTY"));
+    }
+
+    #[test]
+    fn keychain_items_report_errors_under_their_codes() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let item = KeychainItem::new("com.gitlite.test", "account", &KEYCHAIN_CODES).unwrap();
+        assert_eq!(item.load().unwrap(), None);
+        item.save("secret").unwrap();
+        assert_eq!(item.load().unwrap().as_deref(), Some("secret"));
+        item.delete().unwrap();
+        item.delete().unwrap();
+        assert_eq!(item.load().unwrap(), None);
+
+        item.entry
+            .get_credential()
+            .downcast_ref::<keyring::mock::MockCredential>()
+            .unwrap()
+            .set_error(keyring::Error::PlatformFailure("locked".into()));
+        assert!(item
+            .load()
+            .unwrap_err()
+            .starts_with("E_CREDENTIAL_KEYCHAIN_READ"));
+    }
+
+    #[test]
+    fn index_roundtrip_keeps_created_at_on_update() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-credentials-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let index_path = dir.join(CREDENTIALS_INDEX_FILENAME);
+
+        let mut entries = read_index(&index_path).unwrap();
+        assert!(entries.is_empty());
+
+        upsert_entry(&mut entries, "github.com", "octocat", 100);
+        let updated = upsert_entry(&mut entries, "github.com", "octocat", 200);
+        assert_eq!(updated.created_at, 100);
+        assert_eq!(updated.updated_at, 200);
+
+        write_index(&index_path, &entries).unwrap();
+        let reloaded = read_index(&index_path).unwrap();
+        assert_eq!(reloaded, entries);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
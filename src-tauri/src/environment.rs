@@ -0,0 +1,128 @@
+use crate::{credential_helper, git};
+use git2::Config;
+use serde::Serialize;
+use std::process::Command;
+
+/// What the machine provides to gitlite, to explain why auth or signing
+/// features are unavailable.
+#[derive(Serialize, Clone, Debug)]
+pub struct EnvironmentReport {
+    pub libgit2_version: String,
+    /// Optional libgit2 features compiled in: `https`, `ssh`, `threads`.
+    pub libgit2_features: Vec<String>,
+    pub git: ToolStatus,
+    pub ssh_agent: SshAgentStatus,
+    /// `credential.helper` values from the global and system git config.
+    pub credential_helpers: Vec<String>,
+    /// Program from `gpg.program`, else `gpg`.
+    pub gpg: ToolStatus,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ToolStatus {
+    pub program: String,
+    pub available: bool,
+    /// First line of `<program> --version`.
+    pub version: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SshAgentStatus {
+    pub available: bool,
+    /// Identities loaded in the agent.
+    pub identities: Option<usize>,
+    pub error: Option<String>,
+}
+
+pub fn check_environment() -> EnvironmentReport {
+    let version = git2::Version::get();
+    let (major, minor, rev) = version.libgit2_version();
+    let libgit2_features = [
+        ("https", version.https()),
+        ("ssh", version.ssh()),
+        ("threads", version.threads()),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(feature, _)| feature.to_string())
+    .collect();
+
+    let config = Config::open_default().ok();
+    let gpg_program = config
+        .as_ref()
+        .and_then(|config| config.get_string("gpg.program").ok())
+        .filter(|program| !program.trim().is_empty())
+        .unwrap_or_else(|| "gpg".to_string());
+
+    let ssh_agent = match git::list_ssh_agent_keys() {
+        Ok(keys) => SshAgentStatus {
+            available: true,
+            identities: Some(keys.len()),
+            error: None,
+        },
+        Err(error) => SshAgentStatus {
+            available: false,
+            identities: None,
+            error: Some(error),
+        },
+    };
+
+    EnvironmentReport {
+        libgit2_version: format!("{}.{}.{}", major, minor, rev),
+        libgit2_features,
+        git: tool_status("git"),
+        ssh_agent,
+        credential_helpers: config
+            .as_ref()
+            .map(credential_helper::configured_helpers)
+            .unwrap_or_default(),
+        gpg: tool_status(&gpg_program),
+    }
+}
+
+fn tool_status(program: &str) -> ToolStatus {
+    let (version, error) = match Command::new(program).arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            (
+                stdout.lines().next().map(|line| line.trim().to_string()),
+                None,
+            )
+        }
+        Ok(output) => (
+            None,
+            Some(format!(
+                "`{} --version` exited with {}",
+                program, output.status
+            )),
+        ),
+        Err(error) => (None, Some(error.to_string())),
+    };
+    ToolStatus {
+        program: program.to_string(),
+        available: version.is_some(),
+        version,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_status_reports_version_or_error() {
+        let git = tool_status("git");
+        assert!(git.available, "{:?}", git);
+        assert!(git.version.unwrap().starts_with("git version"));
+
+        let missing = tool_status("gitlite-no-such-tool");
+        assert!(!missing.available);
+        assert_eq!(missing.version, None);
+        assert!(missing.error.is_some());
+
+        let report = check_environment();
+        assert!(report.libgit2_version.starts_with("1."));
+    }
+}
@@ -0,0 +1,501 @@
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+macro_rules! error_codes {
+    ($($variant:ident => $code:literal,)*) => {
+        /// Every `E_*` code a command can fail with. Unprefixed failures
+        /// (mostly libgit2 messages) map to `Internal`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ErrorCode {
+            $($variant,)*
+            Internal,
+        }
+
+        impl ErrorCode {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $code,)*
+                    ErrorCode::Internal => "E_INTERNAL",
+                }
+            }
+
+            pub fn from_code(code: &str) -> Option<ErrorCode> {
+                match code {
+                    $($code => Some(ErrorCode::$variant),)*
+                    "E_INTERNAL" => Some(ErrorCode::Internal),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    AbortAutostashConflict => "E_ABORT_AUTOSTASH_CONFLICT",
+    AbortFailed => "E_ABORT_FAILED",
+    AbortNoOperation => "E_ABORT_NO_OPERATION",
+    AbortUnsupported => "E_ABORT_UNSUPPORTED",
+    AmendFailed => "E_AMEND_FAILED",
+    AmendSignature => "E_AMEND_SIGNATURE",
+    ArchiveBadFormat => "E_ARCHIVE_BAD_FORMAT",
+    ArchiveDestEmpty => "E_ARCHIVE_DEST_EMPTY",
+    ArchivePath => "E_ARCHIVE_PATH",
+    ArchiveRefNotFound => "E_ARCHIVE_REF_NOT_FOUND",
+    ArchiveTooLarge => "E_ARCHIVE_TOO_LARGE",
+    ArchiveWrite => "E_ARCHIVE_WRITE",
+    AutoFetchInvalid => "E_AUTO_FETCH_INVALID",
+    AvatarCacheStore => "E_AVATAR_CACHE_STORE",
+    Bare => "E_BARE",
+    BitbucketAppPasswordEmpty => "E_BITBUCKET_APP_PASSWORD_EMPTY",
+    BitbucketKeychainDelete => "E_BITBUCKET_KEYCHAIN_DELETE",
+    BitbucketKeychainInit => "E_BITBUCKET_KEYCHAIN_INIT",
+    BitbucketKeychainRead => "E_BITBUCKET_KEYCHAIN_READ",
+    BitbucketKeychainWrite => "E_BITBUCKET_KEYCHAIN_WRITE",
+    BitbucketUsernameEmpty => "E_BITBUCKET_USERNAME_EMPTY",
+    BitbucketUserFetch => "E_BITBUCKET_USER_FETCH",
+    BitbucketUserParse => "E_BITBUCKET_USER_PARSE",
+    BitbucketWorkspaceInvalid => "E_BITBUCKET_WORKSPACE_INVALID",
+    BranchBadHash => "E_BRANCH_BAD_HASH",
+    BranchCommitNotFound => "E_BRANCH_COMMIT_NOT_FOUND",
+    BranchCreateFailed => "E_BRANCH_CREATE_FAILED",
+    BranchDeleteCurrent => "E_BRANCH_DELETE_CURRENT",
+    BranchEmpty => "E_BRANCH_EMPTY",
+    CheckoutBadHash => "E_CHECKOUT_BAD_HASH",
+    CheckoutCommitNotFound => "E_CHECKOUT_COMMIT_NOT_FOUND",
+    CheckoutDetachedFailed => "E_CHECKOUT_DETACHED_FAILED",
+    CheckoutFailed => "E_CHECKOUT_FAILED",
+    CherrypickBadHash => "E_CHERRYPICK_BAD_HASH",
+    CherrypickCheckout => "E_CHERRYPICK_CHECKOUT",
+    CherrypickCleanup => "E_CHERRYPICK_CLEANUP",
+    CherrypickCommit => "E_CHERRYPICK_COMMIT",
+    CherrypickCommitNotFound => "E_CHERRYPICK_COMMIT_NOT_FOUND",
+    CherrypickConflict => "E_CHERRYPICK_CONFLICT",
+    CherrypickConflictCleanup => "E_CHERRYPICK_CONFLICT_CLEANUP",
+    CherrypickFailed => "E_CHERRYPICK_FAILED",
+    CherrypickHead => "E_CHERRYPICK_HEAD",
+    CherrypickHeadCommit => "E_CHERRYPICK_HEAD_COMMIT",
+    CherrypickIndex => "E_CHERRYPICK_INDEX",
+    CherrypickMergeCommit => "E_CHERRYPICK_MERGE_COMMIT",
+    CherrypickSignature => "E_CHERRYPICK_SIGNATURE",
+    CherrypickTree => "E_CHERRYPICK_TREE",
+    CherrypickWriteTree => "E_CHERRYPICK_WRITE_TREE",
+    CloneDestExists => "E_CLONE_DEST_EXISTS",
+    CloneFailed => "E_CLONE_FAILED",
+    CloneUrlEmpty => "E_CLONE_URL_EMPTY",
+    CommitAuthorInvalid => "E_COMMIT_AUTHOR_INVALID",
+    CommitContinuationInvalid => "E_COMMIT_CONTINUATION_INVALID",
+    CommitEmptyMessage => "E_COMMIT_EMPTY_MESSAGE",
+    CommitNoStaged => "E_COMMIT_NO_STAGED",
+    CommitStatsLimit => "E_COMMIT_STATS_LIMIT",
+    CommitStreamNotFound => "E_COMMIT_STREAM_NOT_FOUND",
+    ContainsBadHash => "E_CONTAINS_BAD_HASH",
+    ContainsCommitNotFound => "E_CONTAINS_COMMIT_NOT_FOUND",
+    CredentialHelperConfig => "E_CREDENTIAL_HELPER_CONFIG",
+    CredentialHelperNone => "E_CREDENTIAL_HELPER_NONE",
+    CredentialHelperPath => "E_CREDENTIAL_HELPER_PATH",
+    CredentialHostEmpty => "E_CREDENTIAL_HOST_EMPTY",
+    CredentialHostInvalid => "E_CREDENTIAL_HOST_INVALID",
+    CredentialIndexDir => "E_CREDENTIAL_INDEX_DIR",
+    CredentialIndexRead => "E_CREDENTIAL_INDEX_READ",
+    CredentialIndexWrite => "E_CREDENTIAL_INDEX_WRITE",
+    CredentialKeychainDelete => "E_CREDENTIAL_KEYCHAIN_DELETE",
+    CredentialKeychainInit => "E_CREDENTIAL_KEYCHAIN_INIT",
+    CredentialKeychainRead => "E_CREDENTIAL_KEYCHAIN_READ",
+    CredentialKeychainWrite => "E_CREDENTIAL_KEYCHAIN_WRITE",
+    CredentialPromptNotFound => "E_CREDENTIAL_PROMPT_NOT_FOUND",
+    CredentialSecretEmpty => "E_CREDENTIAL_SECRET_EMPTY",
+    CredentialSecretInvalid => "E_CREDENTIAL_SECRET_INVALID",
+    CredentialUrlInvalid => "E_CREDENTIAL_URL_INVALID",
+    CredentialUsernameEmpty => "E_CREDENTIAL_USERNAME_EMPTY",
+    CredentialUsernameInvalid => "E_CREDENTIAL_USERNAME_INVALID",
+    DiffFileNotFound => "E_DIFF_FILE_NOT_FOUND",
+    DiffSpec => "E_DIFF_SPEC",
+    EditorLaunch => "E_EDITOR_LAUNCH",
+    EditorNotConfigured => "E_EDITOR_NOT_CONFIGURED",
+    EditorPath => "E_EDITOR_PATH",
+    FeatureDisabled => "E_FEATURE_DISABLED",
+    FeatureFlagUnknown => "E_FEATURE_FLAG_UNKNOWN",
+    FetchScopeInvalid => "E_FETCH_SCOPE_INVALID",
+    ForkUpstreamExists => "E_FORK_UPSTREAM_EXISTS",
+    ForkUrlEmpty => "E_FORK_URL_EMPTY",
+    GithubClientIdMissing => "E_GITHUB_CLIENT_ID_MISSING",
+    GithubForkFailed => "E_GITHUB_FORK_FAILED",
+    GithubForkInvalid => "E_GITHUB_FORK_INVALID",
+    GithubHostInvalid => "E_GITHUB_HOST_INVALID",
+    GithubKeychainDelete => "E_GITHUB_KEYCHAIN_DELETE",
+    GithubKeychainInit => "E_GITHUB_KEYCHAIN_INIT",
+    GithubKeychainRead => "E_GITHUB_KEYCHAIN_READ",
+    GithubKeychainWrite => "E_GITHUB_KEYCHAIN_WRITE",
+    GithubOauthDeviceCodeEmpty => "E_GITHUB_OAUTH_DEVICE_CODE_EMPTY",
+    GithubOauthNetwork => "E_GITHUB_OAUTH_NETWORK",
+    GithubOauthParse => "E_GITHUB_OAUTH_PARSE",
+    GithubOauthPollError => "E_GITHUB_OAUTH_POLL_ERROR",
+    GithubOauthPollFailed => "E_GITHUB_OAUTH_POLL_FAILED",
+    GithubOauthPollInvalid => "E_GITHUB_OAUTH_POLL_INVALID",
+    GithubOauthStartFailed => "E_GITHUB_OAUTH_START_FAILED",
+    GithubPrCreate => "E_GITHUB_PR_CREATE",
+    GithubPrHeadNotFound => "E_GITHUB_PR_HEAD_NOT_FOUND",
+    GithubPrHeadNotPushed => "E_GITHUB_PR_HEAD_NOT_PUSHED",
+    GithubPrInvalid => "E_GITHUB_PR_INVALID",
+    GithubPullsFetch => "E_GITHUB_PULLS_FETCH",
+    GithubPullsParse => "E_GITHUB_PULLS_PARSE",
+    GithubReleaseCreate => "E_GITHUB_RELEASE_CREATE",
+    GithubReleaseInvalid => "E_GITHUB_RELEASE_INVALID",
+    GithubReleaseParse => "E_GITHUB_RELEASE_PARSE",
+    GithubReleaseTagNotFound => "E_GITHUB_RELEASE_TAG_NOT_FOUND",
+    GithubRemoteUnsupported => "E_GITHUB_REMOTE_UNSUPPORTED",
+    GithubReposAffiliation => "E_GITHUB_REPOS_AFFILIATION",
+    GithubReposFetch => "E_GITHUB_REPOS_FETCH",
+    GithubReposParse => "E_GITHUB_REPOS_PARSE",
+    GithubStatusFetch => "E_GITHUB_STATUS_FETCH",
+    GithubStatusRefNotFound => "E_GITHUB_STATUS_REF_NOT_FOUND",
+    GithubTokenEmpty => "E_GITHUB_TOKEN_EMPTY",
+    GithubUserFetch => "E_GITHUB_USER_FETCH",
+    GithubUserParse => "E_GITHUB_USER_PARSE",
+    GitlabClientIdMissing => "E_GITLAB_CLIENT_ID_MISSING",
+    GitlabHostInvalid => "E_GITLAB_HOST_INVALID",
+    GitlabKeychainDelete => "E_GITLAB_KEYCHAIN_DELETE",
+    GitlabKeychainInit => "E_GITLAB_KEYCHAIN_INIT",
+    GitlabKeychainRead => "E_GITLAB_KEYCHAIN_READ",
+    GitlabKeychainWrite => "E_GITLAB_KEYCHAIN_WRITE",
+    GitlabOauthDeviceCodeEmpty => "E_GITLAB_OAUTH_DEVICE_CODE_EMPTY",
+    GitlabOauthNetwork => "E_GITLAB_OAUTH_NETWORK",
+    GitlabOauthParse => "E_GITLAB_OAUTH_PARSE",
+    GitlabOauthPollError => "E_GITLAB_OAUTH_POLL_ERROR",
+    GitlabOauthPollFailed => "E_GITLAB_OAUTH_POLL_FAILED",
+    GitlabOauthPollInvalid => "E_GITLAB_OAUTH_POLL_INVALID",
+    GitlabOauthRefreshFailed => "E_GITLAB_OAUTH_REFRESH_FAILED",
+    GitlabOauthStartFailed => "E_GITLAB_OAUTH_START_FAILED",
+    GitlabTokenEmpty => "E_GITLAB_TOKEN_EMPTY",
+    GitlabUserFetch => "E_GITLAB_USER_FETCH",
+    GitlabUserParse => "E_GITLAB_USER_PARSE",
+    GitWorker => "E_GIT_WORKER",
+    HeadDetached => "E_HEAD_DETACHED",
+    HeadInvalid => "E_HEAD_INVALID",
+    HeadUnborn => "E_HEAD_UNBORN",
+    HostKeyPromptNotFound => "E_HOST_KEY_PROMPT_NOT_FOUND",
+    IndexBackupFailed => "E_INDEX_BACKUP_FAILED",
+    IndexBackupNotFound => "E_INDEX_BACKUP_NOT_FOUND",
+    IndexFlagNotTracked => "E_INDEX_FLAG_NOT_TRACKED",
+    IndexFlagUnknown => "E_INDEX_FLAG_UNKNOWN",
+    IndexFlagWrite => "E_INDEX_FLAG_WRITE",
+    InitBranchInvalid => "E_INIT_BRANCH_INVALID",
+    InitHasCommits => "E_INIT_HAS_COMMITS",
+    InitLicenseUnknown => "E_INIT_LICENSE_UNKNOWN",
+    InitTemplateUnknown => "E_INIT_TEMPLATE_UNKNOWN",
+    InitWriteFailed => "E_INIT_WRITE_FAILED",
+    JobFailed => "E_JOB_FAILED",
+    JobFinished => "E_JOB_FINISHED",
+    JobNotFound => "E_JOB_NOT_FOUND",
+    LockHeld => "E_LOCK_HELD",
+    LockIdentity => "E_LOCK_IDENTITY",
+    LockInvalidPath => "E_LOCK_INVALID_PATH",
+    LockNotFound => "E_LOCK_NOT_FOUND",
+    LockNotOwner => "E_LOCK_NOT_OWNER",
+    LockSync => "E_LOCK_SYNC",
+    MaintenanceBadAction => "E_MAINTENANCE_BAD_ACTION",
+    MaintenanceFailed => "E_MAINTENANCE_FAILED",
+    MergeToolFailed => "E_MERGE_TOOL_FAILED",
+    MergeToolNoConflict => "E_MERGE_TOOL_NO_CONFLICT",
+    MergeToolNotConfigured => "E_MERGE_TOOL_NOT_CONFIGURED",
+    MergeToolUnresolved => "E_MERGE_TOOL_UNRESOLVED",
+    MirrorAuth => "E_MIRROR_AUTH",
+    MirrorFetch => "E_MIRROR_FETCH",
+    MirrorJobInvalid => "E_MIRROR_JOB_INVALID",
+    MirrorJobNotFound => "E_MIRROR_JOB_NOT_FOUND",
+    MirrorJobRunning => "E_MIRROR_JOB_RUNNING",
+    MirrorJobStore => "E_MIRROR_JOB_STORE",
+    MirrorNetwork => "E_MIRROR_NETWORK",
+    MirrorPush => "E_MIRROR_PUSH",
+    MirrorRemoteNotFound => "E_MIRROR_REMOTE_NOT_FOUND",
+    MirrorSameRemote => "E_MIRROR_SAME_REMOTE",
+    MoveConflicted => "E_MOVE_CONFLICTED",
+    MoveDestinationExists => "E_MOVE_DESTINATION_EXISTS",
+    MoveInvalidPath => "E_MOVE_INVALID_PATH",
+    MoveNotTracked => "E_MOVE_NOT_TRACKED",
+    MoveSourceMissing => "E_MOVE_SOURCE_MISSING",
+    OwnershipDirNotFound => "E_OWNERSHIP_DIR_NOT_FOUND",
+    PatchApplyFailed => "E_PATCH_APPLY_FAILED",
+    PatchEmpty => "E_PATCH_EMPTY",
+    PatchParse => "E_PATCH_PARSE",
+    PathspecEmpty => "E_PATHSPEC_EMPTY",
+    PermalinkFile => "E_PERMALINK_FILE",
+    PermalinkLine => "E_PERMALINK_LINE",
+    PermalinkNotPushed => "E_PERMALINK_NOT_PUSHED",
+    PolicyInvalid => "E_POLICY_INVALID",
+    PolicyPresetNotFound => "E_POLICY_PRESET_NOT_FOUND",
+    PolicyPresetRead => "E_POLICY_PRESET_READ",
+    PolicyPresetStore => "E_POLICY_PRESET_STORE",
+    PolicyViolation => "E_POLICY_VIOLATION",
+    PrBranchDiverged => "E_PR_BRANCH_DIVERGED",
+    PrNotFound => "E_PR_NOT_FOUND",
+    PrNumberInvalid => "E_PR_NUMBER_INVALID",
+    PullAuth => "E_PULL_AUTH",
+    PullAutostashConflict => "E_PULL_AUTOSTASH_CONFLICT",
+    PullConflict => "E_PULL_CONFLICT",
+    PullDetached => "E_PULL_DETACHED",
+    PullDirty => "E_PULL_DIRTY",
+    PullNetwork => "E_PULL_NETWORK",
+    PullNonFf => "E_PULL_NON_FF",
+    PullStrategyInvalid => "E_PULL_STRATEGY_INVALID",
+    PushAuth => "E_PUSH_AUTH",
+    PushNetwork => "E_PUSH_NETWORK",
+    PushNonFf => "E_PUSH_NON_FF",
+    PushRejected => "E_PUSH_REJECTED",
+    RecentRepoInvalid => "E_RECENT_REPO_INVALID",
+    RecentRepoNotFound => "E_RECENT_REPO_NOT_FOUND",
+    RecentRepoStore => "E_RECENT_REPO_STORE",
+    RefspecInvalid => "E_REFSPEC_INVALID",
+    RefspecNotFound => "E_REFSPEC_NOT_FOUND",
+    RemoteNotFound => "E_REMOTE_NOT_FOUND",
+    RepoNotesInvalid => "E_REPO_NOTES_INVALID",
+    RepoNotesStore => "E_REPO_NOTES_STORE",
+    RepoNotesTooLarge => "E_REPO_NOTES_TOO_LARGE",
+    RepoPickerPath => "E_REPO_PICKER_PATH",
+    ResetBadHash => "E_RESET_BAD_HASH",
+    ResetBadMode => "E_RESET_BAD_MODE",
+    ResetCommitNotFound => "E_RESET_COMMIT_NOT_FOUND",
+    ResetFailed => "E_RESET_FAILED",
+    RevealFailed => "E_REVEAL_FAILED",
+    RevealPath => "E_REVEAL_PATH",
+    RevertBadHash => "E_REVERT_BAD_HASH",
+    RevertCheckout => "E_REVERT_CHECKOUT",
+    RevertCleanup => "E_REVERT_CLEANUP",
+    RevertCommit => "E_REVERT_COMMIT",
+    RevertCommitNotFound => "E_REVERT_COMMIT_NOT_FOUND",
+    RevertConflict => "E_REVERT_CONFLICT",
+    RevertConflictCleanup => "E_REVERT_CONFLICT_CLEANUP",
+    RevertFailed => "E_REVERT_FAILED",
+    RevertHead => "E_REVERT_HEAD",
+    RevertHeadCommit => "E_REVERT_HEAD_COMMIT",
+    RevertIndex => "E_REVERT_INDEX",
+    RevertMergeCommit => "E_REVERT_MERGE_COMMIT",
+    RevertSignature => "E_REVERT_SIGNATURE",
+    RevertTree => "E_REVERT_TREE",
+    RevertWriteTree => "E_REVERT_WRITE_TREE",
+    RewordBadHash => "E_REWORD_BAD_HASH",
+    RewordCommitNotFound => "E_REWORD_COMMIT_NOT_FOUND",
+    RewordEmptyMessage => "E_REWORD_EMPTY_MESSAGE",
+    RewordFailed => "E_REWORD_FAILED",
+    RewordNotOnBranch => "E_REWORD_NOT_ON_BRANCH",
+    RewordSignature => "E_REWORD_SIGNATURE",
+    RuntimeLogDir => "E_RUNTIME_LOG_DIR",
+    RuntimeLogRead => "E_RUNTIME_LOG_READ",
+    RuntimeLogWrite => "E_RUNTIME_LOG_WRITE",
+    ScanBaseDir => "E_SCAN_BASE_DIR",
+    SelfTestSetup => "E_SELF_TEST_SETUP",
+    SettingsInvalid => "E_SETTINGS_INVALID",
+    ShuttingDown => "E_SHUTTING_DOWN",
+    SshAgentProtocol => "E_SSH_AGENT_PROTOCOL",
+    SshAgentUnavailable => "E_SSH_AGENT_UNAVAILABLE",
+    SshHostKeyMismatch => "E_SSH_HOST_KEY_MISMATCH",
+    SshHostKeyRevoked => "E_SSH_HOST_KEY_REVOKED",
+    SshHostKeySave => "E_SSH_HOST_KEY_SAVE",
+    SshHostKeyUnknown => "E_SSH_HOST_KEY_UNKNOWN",
+    SshKeyNotFound => "E_SSH_KEY_NOT_FOUND",
+    SshPassphraseEmpty => "E_SSH_PASSPHRASE_EMPTY",
+    SshPassphraseKeychainDelete => "E_SSH_PASSPHRASE_KEYCHAIN_DELETE",
+    SshPassphraseKeychainInit => "E_SSH_PASSPHRASE_KEYCHAIN_INIT",
+    SshPassphraseKeychainRead => "E_SSH_PASSPHRASE_KEYCHAIN_READ",
+    SshPassphraseKeychainWrite => "E_SSH_PASSPHRASE_KEYCHAIN_WRITE",
+    StashApplyConflict => "E_STASH_APPLY_CONFLICT",
+    StashEmpty => "E_STASH_EMPTY",
+    StashInvalidIndex => "E_STASH_INVALID_INDEX",
+    StatusUntrackedMode => "E_STATUS_UNTRACKED_MODE",
+    SubmoduleUpdateFailed => "E_SUBMODULE_UPDATE_FAILED",
+    TlsCaInvalid => "E_TLS_CA_INVALID",
+    TlsCaUnsupported => "E_TLS_CA_UNSUPPORTED",
+    TlsCaWrite => "E_TLS_CA_WRITE",
+    TlsCertificate => "E_TLS_CERTIFICATE",
+    TransferCancelled => "E_TRANSFER_CANCELLED",
+    WebUrlArgs => "E_WEB_URL_ARGS",
+    WebUrlNoRemote => "E_WEB_URL_NO_REMOTE",
+    WebUrlRevision => "E_WEB_URL_REVISION",
+    WebUrlUnsupported => "E_WEB_URL_UNSUPPORTED",
+    WorkspaceDestEmpty => "E_WORKSPACE_DEST_EMPTY",
+    WorkspaceManifestInvalid => "E_WORKSPACE_MANIFEST_INVALID",
+    WorkspaceManifestRead => "E_WORKSPACE_MANIFEST_READ",
+    WorkspaceManifestWrite => "E_WORKSPACE_MANIFEST_WRITE",
+    WorkspaceNoRemote => "E_WORKSPACE_NO_REMOTE",
+    WorkspaceTarget => "E_WORKSPACE_TARGET",
+}
+
+impl ErrorCode {
+    /// Suggested next step shown under the message, for codes where one exists.
+    pub fn hint(self) -> Option<&'static str> {
+        let hint = match self {
+            ErrorCode::Bare => "Open a repository with a working tree.",
+            ErrorCode::HeadDetached | ErrorCode::PullDetached => "Check out a branch first.",
+            ErrorCode::HeadUnborn => "Create the first commit first.",
+            ErrorCode::PullDirty => "Commit or stash your changes, or pull with autostash.",
+            ErrorCode::PullAutostashConflict => "Apply the latest stash and resolve the conflicts.",
+            ErrorCode::AbortAutostashConflict => {
+                "The rebase was aborted. Apply the latest stash and resolve the conflicts."
+            }
+            ErrorCode::PullConflict => "Pull with another strategy or merge the branches by hand.",
+            ErrorCode::PullNonFf | ErrorCode::PushNonFf => {
+                "Pull and integrate the remote changes, then try again."
+            }
+            ErrorCode::PullAuth | ErrorCode::PushAuth | ErrorCode::MirrorAuth => {
+                "Check the saved credentials for this remote."
+            }
+            ErrorCode::PullNetwork | ErrorCode::PushNetwork | ErrorCode::MirrorNetwork => {
+                "Check your network connection and the remote URL."
+            }
+            ErrorCode::CommitNoStaged => "Stage at least one file before committing.",
+            ErrorCode::StashApplyConflict
+            | ErrorCode::CherrypickConflict
+            | ErrorCode::RevertConflict => "Resolve the conflicts, then continue.",
+            ErrorCode::LockHeld => "Ask the lock owner to release the file.",
+            ErrorCode::PolicyViolation => "Fix the listed rules or commit with the policy skipped.",
+            ErrorCode::GithubClientIdMissing => "Enter a GitHub OAuth Client ID in Settings.",
+            ErrorCode::GitlabOauthRefreshFailed => "Sign in to GitLab again.",
+            ErrorCode::MergeToolNotConfigured => "Set merge.tool in your git config.",
+            ErrorCode::SettingsInvalid => "Correct the listed settings and save again.",
+            ErrorCode::FeatureDisabled => "Turn the feature on under experimental features.",
+            _ => return None,
+        };
+        Some(hint)
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Error returned by every command, serialized as
+/// `{ code, message, details, hint, fields }`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GitLiteError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Option<String>,
+    pub hint: Option<&'static str>,
+    /// Per-field problems of a rejected input, e.g. settings.
+    pub fields: Vec<FieldError>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl GitLiteError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        GitLiteError {
+            code,
+            message: message.into(),
+            details: None,
+            hint: code.hint(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    pub fn with_fields(mut self, fields: Vec<FieldError>) -> Self {
+        self.fields = fields;
+        self
+    }
+}
+
+/// Lift the `E_*: message` strings produced by the git and store modules.
+impl From<String> for GitLiteError {
+    fn from(error: String) -> Self {
+        match error.split_once(':') {
+            Some((prefix, message)) => match ErrorCode::from_code(prefix.trim()) {
+                Some(code) => GitLiteError::new(code, message.trim()),
+                None => GitLiteError::new(ErrorCode::Internal, error),
+            },
+            None => match ErrorCode::from_code(error.trim()) {
+                Some(code) => GitLiteError::new(code, ""),
+                None => GitLiteError::new(ErrorCode::Internal, error),
+            },
+        }
+    }
+}
+
+impl From<&str> for GitLiteError {
+    fn from(error: &str) -> Self {
+        GitLiteError::from(error.to_string())
+    }
+}
+
+impl fmt::Display for GitLiteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.message.is_empty() {
+            write!(f, "{}", self.code.as_str())
+        } else {
+            write!(f, "{}: {}", self.code.as_str(), self.message)
+        }
+    }
+}
+
+impl std::error::Error for GitLiteError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn from_string_splits_code_message_and_hint() {
+        let error = GitLiteError::from("E_PULL_DIRTY: working tree has changes".to_string());
+        assert_eq!(error.code, ErrorCode::PullDirty);
+        assert_eq!(error.message, "working tree has changes");
+        assert!(error.hint.is_some());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap()["code"],
+            serde_json::json!("E_PULL_DIRTY")
+        );
+
+        let internal = GitLiteError::from("Failed to open repository: missing".to_string());
+        assert_eq!(internal.code, ErrorCode::Internal);
+        assert_eq!(internal.message, "Failed to open repository: missing");
+        assert_eq!(
+            internal.to_string(),
+            "E_INTERNAL: Failed to open repository: missing"
+        );
+    }
+
+    #[test]
+    fn every_error_code_in_the_backend_has_a_variant() {
+        // Codes must appear as literals to be found here, so none may be
+        // assembled at runtime.
+        fn visit(dir: &Path, pattern: &regex::Regex, codes: &mut Vec<String>) {
+            let built = regex::Regex::new(r#""E_\{"#).unwrap();
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    visit(&path, pattern, codes);
+                } else if path.extension().is_some_and(|ext| ext == "rs") {
+                    let source = fs::read_to_string(&path).unwrap();
+                    assert!(
+                        !built.is_match(&source),
+                        "{} formats an error code",
+                        path.display()
+                    );
+                    codes.extend(pattern.find_iter(&source).map(|m| m.as_str().to_string()));
+                }
+            }
+        }
+
+        let pattern = regex::Regex::new(r"\bE_[A-Z0-9_]*[A-Z0-9]\b").unwrap();
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let this_file = manifest_dir.join(Path::new(file!()));
+        let mut codes = Vec::new();
+        visit(this_file.parent().unwrap(), &pattern, &mut codes);
+        let missing: Vec<&String> = codes
+            .iter()
+            .filter(|code| ErrorCode::from_code(code).is_none())
+            .collect();
+        assert!(missing.is_empty(), "codes without a variant: {:?}", missing);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::config::{self, AppConfig};
+use serde::Serialize;
+
+/// Subsystems that ship behind a switch. Modules check
+/// `AppConfig::feature_enabled` before doing anything on their behalf.
+const FEATURES: &[FeatureDefinition] = &[FeatureDefinition {
+    name: "rebase_engine",
+    description: "Reword older commits by replaying the commits above them",
+    default: true,
+}];
+
+struct FeatureDefinition {
+    name: &'static str,
+    description: &'static str,
+    default: bool,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FeatureFlag {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+    pub default: bool,
+}
+
+impl AppConfig {
+    /// Whether `name` is on: the user's override if any, else the built-in
+    /// default. Unknown names are always off.
+    pub fn feature_enabled(&self, name: &str) -> bool {
+        FEATURES
+            .iter()
+            .find(|feature| feature.name == name)
+            .map(|feature| {
+                self.feature_flags
+                    .get(name)
+                    .copied()
+                    .unwrap_or(feature.default)
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Fail with `E_FEATURE_DISABLED` unless `name` is on; gated commands call
+/// this before any work.
+pub fn require(config: &AppConfig, name: &str) -> Result<(), String> {
+    if config.feature_enabled(name) {
+        Ok(())
+    } else {
+        Err(format!(
+            "E_FEATURE_DISABLED: the '{}' feature is turned off",
+            name
+        ))
+    }
+}
+
+pub fn feature_flags(config: &AppConfig) -> Vec<FeatureFlag> {
+    FEATURES
+        .iter()
+        .map(|feature| FeatureFlag {
+            name: feature.name.to_string(),
+            description: feature.description.to_string(),
+            enabled: config.feature_enabled(feature.name),
+            default: feature.default,
+        })
+        .collect()
+}
+
+pub fn get_feature_flags(app: &tauri::AppHandle) -> Result<Vec<FeatureFlag>, String> {
+    Ok(feature_flags(&config::read_settings(app)?))
+}
+
+/// Override one flag in the settings file. Setting a flag back to its
+/// default drops the override so future default changes apply.
+pub fn set_feature_flag(
+    app: &tauri::AppHandle,
+    name: &str,
+    enabled: bool,
+) -> Result<Vec<FeatureFlag>, String> {
+    config::update_settings(app, |config| {
+        apply_override(config, name, enabled)?;
+        Ok(feature_flags(config))
+    })
+}
+
+fn apply_override(config: &mut AppConfig, name: &str, enabled: bool) -> Result<(), String> {
+    let feature = FEATURES
+        .iter()
+        .find(|feature| feature.name == name)
+        .ok_or_else(|| format!("E_FEATURE_FLAG_UNKNOWN: {}", name))?;
+    if enabled == feature.default {
+        config.feature_flags.remove(name);
+    } else {
+        config.feature_flags.insert(name.to_string(), enabled);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_apply_on_top_of_defaults() {
+        let mut config = AppConfig::default();
+        assert!(feature_flags(&config).iter().all(|flag| flag.enabled));
+        assert!(require(&config, "rebase_engine").is_ok());
+
+        apply_override(&mut config, "rebase_engine", false).unwrap();
+        assert!(!config.feature_enabled("rebase_engine"));
+        assert!(require(&config, "rebase_engine")
+            .unwrap_err()
+            .starts_with("E_FEATURE_DISABLED"));
+
+        apply_override(&mut config, "rebase_engine", true).unwrap();
+        assert!(config.feature_flags.is_empty());
+
+        assert!(apply_override(&mut config, "nope", true)
+            .unwrap_err()
+            .starts_with("E_FEATURE_FLAG_UNKNOWN"));
+        config.feature_flags.insert("nope".to_string(), true);
+        assert!(!config.feature_enabled("nope"));
+    }
+}
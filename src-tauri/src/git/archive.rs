@@ -0,0 +1,567 @@
+use flate2::write::{DeflateEncoder, GzEncoder};
+use flate2::{Compression, Crc};
+use git2::{ObjectType, Repository, TreeWalkMode, TreeWalkResult};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const TAR_BLOCK_SIZE: usize = 512;
+const FILEMODE_EXECUTABLE: i32 = 0o100755;
+const FILEMODE_LINK: i32 = 0o120000;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ArchiveExport {
+    pub dest: String,
+    pub format: String,
+    pub commit_hash: String,
+    pub file_count: usize,
+    pub size_bytes: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    fn parse(format: &str) -> Result<Self, String> {
+        match format.trim().to_ascii_lowercase().as_str() {
+            "zip" => Ok(ArchiveFormat::Zip),
+            "tar" => Ok(ArchiveFormat::Tar),
+            "tar.gz" | "tgz" => Ok(ArchiveFormat::TarGz),
+            other => Err(format!(
+                "E_ARCHIVE_BAD_FORMAT: unsupported archive format '{}' (expected zip, tar or tar.gz)",
+                other
+            )),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+}
+
+struct ArchiveEntry {
+    path: String,
+    mode: i32,
+    data: Vec<u8>,
+}
+
+/// Export the tree of a commit, branch or tag as a zip or tar archive.
+pub fn export_archive(
+    path: &str,
+    reference: &str,
+    format: &str,
+    dest: &str,
+    prefix: Option<&str>,
+) -> Result<ArchiveExport, String> {
+    let format = ArchiveFormat::parse(format)?;
+    if dest.trim().is_empty() {
+        return Err("E_ARCHIVE_DEST_EMPTY: destination path is required".to_string());
+    }
+
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let reference = if reference.trim().is_empty() {
+        "HEAD"
+    } else {
+        reference.trim()
+    };
+    let commit = repo
+        .revparse_single(reference)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| {
+            format!(
+                "E_ARCHIVE_REF_NOT_FOUND: failed to resolve '{}': {}",
+                reference, e
+            )
+        })?;
+    let tree = commit
+        .tree()
+        .map_err(|e| format!("Failed to get tree: {}", e))?;
+
+    let prefix = normalize_prefix(prefix.unwrap_or(""));
+    let mut entries = Vec::new();
+    let mut walk_error: Option<String> = None;
+
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+        let Some(name) = entry.name() else {
+            walk_error = Some(format!(
+                "E_ARCHIVE_PATH: invalid UTF-8 path under '{}'",
+                root
+            ));
+            return TreeWalkResult::Abort;
+        };
+        match repo.find_blob(entry.id()) {
+            Ok(blob) => {
+                entries.push(ArchiveEntry {
+                    path: format!("{}{}{}", prefix, root, name),
+                    mode: entry.filemode(),
+                    data: blob.content().to_vec(),
+                });
+                TreeWalkResult::Ok
+            }
+            Err(e) => {
+                walk_error = Some(format!("Failed to read blob '{}{}': {}", root, name, e));
+                TreeWalkResult::Abort
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to walk tree: {}", e))?;
+
+    if let Some(error) = walk_error {
+        return Err(error);
+    }
+
+    let mtime = commit.time().seconds().max(0) as u64;
+    let file_count = entries.len();
+
+    if let Err(error) = write_archive(Path::new(dest), format, &entries, mtime) {
+        let _ = fs::remove_file(dest);
+        return Err(error);
+    }
+
+    let size_bytes = fs::metadata(dest)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+
+    Ok(ArchiveExport {
+        dest: dest.to_string(),
+        format: format.as_str().to_string(),
+        commit_hash: commit.id().to_string(),
+        file_count,
+        size_bytes,
+    })
+}
+
+fn normalize_prefix(prefix: &str) -> String {
+    let trimmed = prefix.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", trimmed)
+    }
+}
+
+fn write_archive(
+    dest: &Path,
+    format: ArchiveFormat,
+    entries: &[ArchiveEntry],
+    mtime: u64,
+) -> Result<(), String> {
+    let file = File::create(dest).map_err(|e| {
+        format!(
+            "E_ARCHIVE_WRITE: failed to create '{}': {}",
+            dest.display(),
+            e
+        )
+    })?;
+    let writer = BufWriter::new(file);
+
+    match format {
+        ArchiveFormat::Tar => {
+            let mut writer = writer;
+            write_tar(&mut writer, entries, mtime)?;
+            writer.flush().map_err(archive_io_error)
+        }
+        ArchiveFormat::TarGz => {
+            let mut encoder = GzEncoder::new(writer, Compression::default());
+            write_tar(&mut encoder, entries, mtime)?;
+            encoder
+                .finish()
+                .and_then(|mut inner| inner.flush())
+                .map_err(archive_io_error)
+        }
+        ArchiveFormat::Zip => {
+            let mut writer = writer;
+            write_zip(&mut writer, entries, mtime)?;
+            writer.flush().map_err(archive_io_error)
+        }
+    }
+}
+
+fn archive_io_error(error: std::io::Error) -> String {
+    format!("E_ARCHIVE_WRITE: {}", error)
+}
+
+fn write_tar<W: Write>(writer: &mut W, entries: &[ArchiveEntry], mtime: u64) -> Result<(), String> {
+    for entry in entries {
+        let is_link = entry.mode == FILEMODE_LINK;
+        let mode = if entry.mode == FILEMODE_EXECUTABLE || is_link {
+            0o755
+        } else {
+            0o644
+        };
+        let link_target = if is_link {
+            String::from_utf8_lossy(&entry.data).into_owned()
+        } else {
+            String::new()
+        };
+        let size = if is_link { 0 } else { entry.data.len() as u64 };
+
+        if entry.path.len() > 100 || link_target.len() > 100 {
+            let mut records = String::new();
+            if entry.path.len() > 100 {
+                records.push_str(&pax_record("path", &entry.path));
+            }
+            if link_target.len() > 100 {
+                records.push_str(&pax_record("linkpath", &link_target));
+            }
+            let pax_header = tar_header(
+                "././@PaxHeader",
+                0o644,
+                records.len() as u64,
+                mtime,
+                b'x',
+                "",
+            );
+            writer.write_all(&pax_header).map_err(archive_io_error)?;
+            write_tar_data(writer, records.as_bytes())?;
+        }
+
+        let header = tar_header(
+            truncate_utf8(&entry.path, 100),
+            mode,
+            size,
+            mtime,
+            if is_link { b'2' } else { b'0' },
+            truncate_utf8(&link_target, 100),
+        );
+        writer.write_all(&header).map_err(archive_io_error)?;
+        if !is_link {
+            write_tar_data(writer, &entry.data)?;
+        }
+    }
+
+    writer
+        .write_all(&[0u8; TAR_BLOCK_SIZE * 2])
+        .map_err(archive_io_error)
+}
+
+fn write_tar_data<W: Write>(writer: &mut W, data: &[u8]) -> Result<(), String> {
+    writer.write_all(data).map_err(archive_io_error)?;
+    let padding = (TAR_BLOCK_SIZE - data.len() % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE;
+    writer
+        .write_all(&vec![0u8; padding])
+        .map_err(archive_io_error)
+}
+
+fn tar_header(
+    name: &str,
+    mode: u32,
+    size: u64,
+    mtime: u64,
+    typeflag: u8,
+    linkname: &str,
+) -> [u8; TAR_BLOCK_SIZE] {
+    let mut header = [0u8; TAR_BLOCK_SIZE];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], mode as u64);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size);
+    write_octal(&mut header[136..148], mtime);
+    header[148..156].fill(b' ');
+    header[156] = typeflag;
+    header[157..157 + linkname.len()].copy_from_slice(linkname.as_bytes());
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    let checksum: u32 = header.iter().map(|byte| *byte as u32).sum();
+    let checksum = format!("{:06o}\0 ", checksum);
+    header[148..156].copy_from_slice(checksum.as_bytes());
+    header
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    let width = field.len() - 1;
+    let digits = format!("{:0width$o}", value, width = width);
+    let digits = &digits.as_bytes()[digits.len().saturating_sub(width)..];
+    field[..width].copy_from_slice(digits);
+    field[width] = 0;
+}
+
+fn pax_record(key: &str, value: &str) -> String {
+    // The length prefix counts itself, so grow it until the total is stable.
+    let body_len = key.len() + value.len() + 3;
+    let mut total = body_len + 1;
+    while total != body_len + total.to_string().len() {
+        total = body_len + total.to_string().len();
+    }
+    format!("{} {}={}\n", total, key, value)
+}
+
+fn truncate_utf8(value: &str, max_len: usize) -> &str {
+    if value.len() <= max_len {
+        return value;
+    }
+    let mut end = max_len;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
+
+fn write_zip<W: Write>(writer: &mut W, entries: &[ArchiveEntry], mtime: u64) -> Result<(), String> {
+    if entries.len() > u16::MAX as usize {
+        return Err(format!(
+            "E_ARCHIVE_TOO_LARGE: zip archives support at most {} files",
+            u16::MAX
+        ));
+    }
+
+    let (dos_time, dos_date) = dos_datetime(mtime);
+    let mut central_directory = Vec::new();
+    let mut offset: u64 = 0;
+
+    for entry in entries {
+        let mut crc = Crc::new();
+        crc.update(&entry.data);
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&entry.data).map_err(archive_io_error)?;
+        let compressed = encoder.finish().map_err(archive_io_error)?;
+
+        if entry.data.len() as u64 > u32::MAX as u64 || offset > u32::MAX as u64 {
+            return Err(format!(
+                "E_ARCHIVE_TOO_LARGE: '{}' exceeds the zip size limit",
+                entry.path
+            ));
+        }
+
+        let name = entry.path.as_bytes();
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        local.extend_from_slice(&20u16.to_le_bytes());
+        local.extend_from_slice(&0x0800u16.to_le_bytes());
+        local.extend_from_slice(&8u16.to_le_bytes());
+        local.extend_from_slice(&dos_time.to_le_bytes());
+        local.extend_from_slice(&dos_date.to_le_bytes());
+        local.extend_from_slice(&crc.sum().to_le_bytes());
+        local.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        local.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+        local.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        local.extend_from_slice(&0u16.to_le_bytes());
+        local.extend_from_slice(name);
+
+        let unix_mode: u32 = match entry.mode {
+            FILEMODE_LINK => 0o120777,
+            FILEMODE_EXECUTABLE => 0o100755,
+            _ => 0o100644,
+        };
+
+        central_directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central_directory.extend_from_slice(&((3u16 << 8) | 20).to_le_bytes());
+        central_directory.extend_from_slice(&20u16.to_le_bytes());
+        central_directory.extend_from_slice(&0x0800u16.to_le_bytes());
+        central_directory.extend_from_slice(&8u16.to_le_bytes());
+        central_directory.extend_from_slice(&dos_time.to_le_bytes());
+        central_directory.extend_from_slice(&dos_date.to_le_bytes());
+        central_directory.extend_from_slice(&crc.sum().to_le_bytes());
+        central_directory.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        central_directory.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+        central_directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        central_directory.extend_from_slice(&[0u8; 8]);
+        central_directory.extend_from_slice(&(unix_mode << 16).to_le_bytes());
+        central_directory.extend_from_slice(&(offset as u32).to_le_bytes());
+        central_directory.extend_from_slice(name);
+
+        writer.write_all(&local).map_err(archive_io_error)?;
+        writer.write_all(&compressed).map_err(archive_io_error)?;
+        offset += (local.len() + compressed.len()) as u64;
+    }
+
+    if offset > u32::MAX as u64 {
+        return Err("E_ARCHIVE_TOO_LARGE: archive exceeds the zip size limit".to_string());
+    }
+
+    let mut end = Vec::with_capacity(22);
+    end.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    end.extend_from_slice(&[0u8; 4]);
+    end.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    end.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    end.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+    end.extend_from_slice(&(offset as u32).to_le_bytes());
+    end.extend_from_slice(&0u16.to_le_bytes());
+
+    writer
+        .write_all(&central_directory)
+        .map_err(archive_io_error)?;
+    writer.write_all(&end).map_err(archive_io_error)
+}
+
+fn dos_datetime(unix_seconds: u64) -> (u16, u16) {
+    let days = (unix_seconds / 86_400) as i64;
+    let seconds_of_day = unix_seconds % 86_400;
+
+    // Civil-from-days conversion (proleptic Gregorian, UTC).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+
+    let hour = (seconds_of_day / 3_600) as u16;
+    let minute = ((seconds_of_day % 3_600) / 60) as u16;
+    let second = (seconds_of_day % 60) as u16;
+    let time = (hour << 11) | (minute << 5) | (second / 2);
+    let date = (((year - 1980) as u16) << 9) | ((month as u16) << 5) | day as u16;
+    (time, date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &PathBuf) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    fn create_test_repo() -> PathBuf {
+        let test_dir =
+            std::env::temp_dir().join(format!("gitlite-archive-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(test_dir.join("src")).unwrap();
+
+        run_git(&["init"], &test_dir);
+        run_git(&["config", "user.name", "Test User"], &test_dir);
+        run_git(&["config", "user.email", "test@example.com"], &test_dir);
+
+        fs::write(test_dir.join("README.md"), "readme\n").unwrap();
+        fs::write(test_dir.join("src").join("main.rs"), "fn main() {}\n").unwrap();
+        run_git(&["add", "."], &test_dir);
+        run_git(&["commit", "-m", "Initial commit"], &test_dir);
+        run_git(&["tag", "v1.0.0"], &test_dir);
+
+        test_dir
+    }
+
+    fn list_archive(tool: &str, args: &[&str], archive: &Path) -> String {
+        let output = Command::new(tool).args(args).arg(archive).output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    /// Walk the local file headers and inflate the entry named `name`.
+    fn read_zip_entry(bytes: &[u8], name: &str) -> Option<Vec<u8>> {
+        let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize;
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let mut at = 0;
+        while at + 30 <= bytes.len() && u32_at(at) == 0x0403_4b50 {
+            let compressed_len = u32_at(at + 18) as usize;
+            let name_len = u16_at(at + 26);
+            let data_start = at + 30 + name_len + u16_at(at + 28);
+            let data = &bytes[data_start..data_start + compressed_len];
+            if &bytes[at + 30..at + 30 + name_len] == name.as_bytes() {
+                let mut inflated = Vec::new();
+                flate2::read::DeflateDecoder::new(data)
+                    .read_to_end(&mut inflated)
+                    .unwrap();
+                assert_eq!(inflated.len(), u32_at(at + 22) as usize);
+                return Some(inflated);
+            }
+            at = data_start + compressed_len;
+        }
+        None
+    }
+
+    #[test]
+    fn test_export_archive_tar_with_prefix() {
+        let repo = create_test_repo();
+        let dest = repo.join("export.tar");
+
+        let result = export_archive(
+            repo.to_str().unwrap(),
+            "v1.0.0",
+            "tar",
+            dest.to_str().unwrap(),
+            Some("release-1.0"),
+        )
+        .unwrap();
+        assert_eq!(result.file_count, 2);
+        assert_eq!(result.format, "tar");
+
+        let listing = list_archive("tar", &["-tf"], &dest);
+        assert!(listing.contains("release-1.0/README.md"));
+        assert!(listing.contains("release-1.0/src/main.rs"));
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+
+    #[test]
+    fn test_export_archive_zip() {
+        let repo = create_test_repo();
+        let dest = repo.join("export.zip");
+
+        let result = export_archive(
+            repo.to_str().unwrap(),
+            "HEAD",
+            "zip",
+            dest.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.file_count, 2);
+        assert_eq!(result.format, "zip");
+
+        let bytes = fs::read(&dest).unwrap();
+        assert_eq!(
+            read_zip_entry(&bytes, "src/main.rs").as_deref(),
+            Some(&b"fn main() {}\n"[..])
+        );
+        assert_eq!(
+            read_zip_entry(&bytes, "README.md").as_deref(),
+            Some(&b"readme\n"[..])
+        );
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+
+    #[test]
+    fn test_export_archive_rejects_unknown_format() {
+        let repo = create_test_repo();
+        let dest = repo.join("export.rar");
+
+        let result = export_archive(
+            repo.to_str().unwrap(),
+            "HEAD",
+            "rar",
+            dest.to_str().unwrap(),
+            None,
+        );
+        assert!(result.unwrap_err().contains("E_ARCHIVE_BAD_FORMAT"));
+        assert!(!dest.exists());
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+}
@@ -1,5 +1,7 @@
-use git2::{BranchType, Repository};
+use super::repository::{ensure_worktree, report_checkout_progress, CheckoutProgress};
+use git2::{BranchType, Oid, Repository, Sort};
 use serde::Serialize;
+use std::collections::HashSet;
 
 #[derive(Serialize, Debug, Clone)]
 pub struct Branch {
@@ -11,7 +13,10 @@ pub struct Branch {
 
 pub fn get_branches(path: &str) -> Result<Vec<Branch>, String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    get_branches_in(&repo)
+}
 
+pub fn get_branches_in(repo: &Repository) -> Result<Vec<Branch>, String> {
     let head_ref = repo
         .head()
         .map(|h| h.shorthand().map(String::from))
@@ -58,6 +63,94 @@ pub fn get_branches(path: &str) -> Result<Vec<Branch>, String> {
     Ok(branches)
 }
 
+/// Local and remote-tracking branches whose history includes `commit_hash`.
+pub fn branches_containing(path: &str, commit_hash: &str) -> Result<Vec<Branch>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let oid = contained_commit(&repo, commit_hash)?;
+    let tip = |branch: &Branch| {
+        branch
+            .target_hash
+            .as_deref()
+            .and_then(|h| Oid::from_str(h).ok())
+    };
+    let branches = get_branches_in(&repo)?;
+    let tips: Vec<Oid> = branches.iter().filter_map(tip).collect();
+    let containing = descendants(&repo, &tips, oid)?;
+    Ok(branches
+        .into_iter()
+        .filter(|branch| tip(branch).is_some_and(|tip| containing.contains(&tip)))
+        .collect())
+}
+
+/// Tags, sorted by name, whose commit history includes `commit_hash`.
+pub fn tags_containing(path: &str, commit_hash: &str) -> Result<Vec<String>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let oid = contained_commit(&repo, commit_hash)?;
+    let references = repo
+        .references_glob("refs/tags/*")
+        .map_err(|e| format!("Failed to list tags: {}", e))?;
+    let mut tagged = Vec::new();
+    for reference in references.flatten() {
+        // Tags of trees or blobs have no history.
+        let (Some(name), Ok(commit)) = (reference.shorthand(), reference.peel_to_commit()) else {
+            continue;
+        };
+        tagged.push((name.to_string(), commit.id()));
+    }
+    let tips: Vec<Oid> = tagged.iter().map(|(_, tip)| *tip).collect();
+    let containing = descendants(&repo, &tips, oid)?;
+    let mut tags: Vec<String> = tagged
+        .into_iter()
+        .filter(|(_, tip)| containing.contains(tip))
+        .map(|(name, _)| name)
+        .collect();
+    tags.sort();
+    Ok(tags)
+}
+
+fn contained_commit(repo: &Repository, commit_hash: &str) -> Result<Oid, String> {
+    let oid = Oid::from_str(commit_hash).map_err(|e| {
+        format!(
+            "E_CONTAINS_BAD_HASH: invalid commit hash '{}': {}",
+            commit_hash, e
+        )
+    })?;
+    repo.find_commit(oid)
+        .map(|commit| commit.id())
+        .map_err(|e| format!("E_CONTAINS_COMMIT_NOT_FOUND: {}", e))
+}
+
+/// `commit` and every commit reachable from `tips` that has it in its
+/// history, found in one walk however many tips there are. Ancestors of
+/// `commit` are hidden, so the walk covers only the history above it.
+fn descendants(repo: &Repository, tips: &[Oid], commit: Oid) -> Result<HashSet<Oid>, String> {
+    let walk_error = |e: git2::Error| format!("Failed to walk history: {}", e);
+    let mut revwalk = repo.revwalk().map_err(walk_error)?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(walk_error)?;
+    for tip in tips {
+        revwalk.push(*tip).map_err(walk_error)?;
+    }
+    for parent in repo.find_commit(commit).map_err(walk_error)?.parent_ids() {
+        revwalk.hide(parent).map_err(walk_error)?;
+    }
+
+    // Reverse topological order visits parents first.
+    let mut containing = HashSet::from([commit]);
+    for oid in revwalk {
+        let oid = oid.map_err(walk_error)?;
+        let found = repo.find_commit(oid).map_err(walk_error)?;
+        if found
+            .parent_ids()
+            .any(|parent| containing.contains(&parent))
+        {
+            containing.insert(oid);
+        }
+    }
+    Ok(containing)
+}
+
 pub fn create_branch(path: &str, name: &str) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
@@ -99,8 +192,13 @@ pub fn delete_branch(path: &str, name: &str) -> Result<(), String> {
     Ok(())
 }
 
-pub fn checkout_branch(path: &str, name: &str) -> Result<(), String> {
+pub fn checkout_branch(
+    path: &str,
+    name: &str,
+    on_progress: impl FnMut(&CheckoutProgress),
+) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Checkout")?;
 
     let obj = repo
         .revparse_single(&format!("refs/heads/{}", name))
@@ -108,6 +206,7 @@ pub fn checkout_branch(path: &str, name: &str) -> Result<(), String> {
 
     let mut checkout = git2::build::CheckoutBuilder::new();
     checkout.safe();
+    report_checkout_progress(&mut checkout, on_progress);
 
     repo.checkout_tree(&obj, Some(&mut checkout))
         .map_err(|e| format!("Failed to checkout tree: {}", e))?;
@@ -164,6 +263,15 @@ mod tests {
         test_dir
     }
 
+    fn run_git(args: &[&str], cwd: &Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
     fn current_branch_name(repo_path: &Path) -> String {
         get_branches(repo_path.to_str().unwrap())
             .unwrap()
@@ -217,6 +325,65 @@ mod tests {
         fs::remove_dir_all(test_repo).unwrap();
     }
 
+    #[test]
+    fn test_branches_and_tags_containing_commit() {
+        let test_repo = create_test_repo();
+        let path = test_repo.to_str().unwrap();
+        let base = current_branch_name(&test_repo);
+        let initial = get_branches(path).unwrap()[0].target_hash.clone().unwrap();
+        run_git(&["tag", "-a", "v1.0.0", "-m", "First release"], &test_repo);
+        run_git(&["checkout", "-b", "release/1.1"], &test_repo);
+        fs::write(test_repo.join("fix.txt"), "fix").unwrap();
+        run_git(&["add", "."], &test_repo);
+        run_git(&["commit", "-m", "Fix"], &test_repo);
+        run_git(&["tag", "v1.1.0"], &test_repo);
+        let fix = get_branches(path)
+            .unwrap()
+            .into_iter()
+            .find(|b| b.name == "release/1.1")
+            .and_then(|b| b.target_hash)
+            .unwrap();
+
+        let names = |hash: &str| -> Vec<String> {
+            let mut names: Vec<String> = branches_containing(path, hash)
+                .unwrap()
+                .into_iter()
+                .map(|b| b.name)
+                .collect();
+            names.sort();
+            names
+        };
+        let mut both = vec![base.clone(), "release/1.1".to_string()];
+        both.sort();
+        assert_eq!(names(&initial), both);
+        assert_eq!(names(&fix), ["release/1.1"]);
+        assert_eq!(
+            tags_containing(path, &initial).unwrap(),
+            ["v1.0.0", "v1.1.0"]
+        );
+        assert_eq!(tags_containing(path, &fix).unwrap(), ["v1.1.0"]);
+        assert!(tags_containing(path, "nothex")
+            .unwrap_err()
+            .starts_with("E_CONTAINS_BAD_HASH"));
+        assert!(branches_containing(path, &"0".repeat(40))
+            .unwrap_err()
+            .starts_with("E_CONTAINS_COMMIT_NOT_FOUND"));
+
+        // A merge commit contains the history of both of its parents.
+        run_git(&["checkout", &base], &test_repo);
+        fs::write(test_repo.join("other.txt"), "other").unwrap();
+        run_git(&["add", "."], &test_repo);
+        run_git(&["commit", "-m", "Other"], &test_repo);
+        run_git(&["checkout", "-b", "merged"], &test_repo);
+        run_git(
+            &["merge", "--no-ff", "-m", "Merge", "release/1.1"],
+            &test_repo,
+        );
+        assert_eq!(names(&fix), ["merged", "release/1.1"]);
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
     #[test]
     fn test_get_branches_invalid_path() {
         let result = get_branches("/nonexistent/path");
@@ -297,7 +464,7 @@ mod tests {
             .output()
             .unwrap();
 
-        let result = checkout_branch(test_repo.to_str().unwrap(), "feature-checkout");
+        let result = checkout_branch(test_repo.to_str().unwrap(), "feature-checkout", |_| {});
         assert!(result.is_ok());
 
         let branches = get_branches(test_repo.to_str().unwrap()).unwrap();
@@ -308,6 +475,58 @@ mod tests {
         fs::remove_dir_all(test_repo).unwrap();
     }
 
+    #[test]
+    fn test_checkout_branch_reports_progress() {
+        let test_repo = create_test_repo();
+        let default_branch = current_branch_name(&test_repo);
+        run_git(&["checkout", "-b", "feature-progress"], &test_repo);
+        for index in 0..5 {
+            fs::write(test_repo.join(format!("file-{}.txt", index)), "content").unwrap();
+        }
+        run_git(&["add", "."], &test_repo);
+        run_git(&["commit", "-m", "Add files"], &test_repo);
+        run_git(&["checkout", &default_branch], &test_repo);
+
+        let mut updates = Vec::new();
+        checkout_branch(
+            test_repo.to_str().unwrap(),
+            "feature-progress",
+            |progress| updates.push(progress.clone()),
+        )
+        .unwrap();
+
+        let last = updates.last().expect("checkout should report progress");
+        assert!(last.total >= 5);
+        assert_eq!(last.completed, last.total);
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_bare_repository_lists_branches_but_rejects_checkout() {
+        let test_repo = create_test_repo();
+        let bare_dir = test_repo.with_extension("bare");
+        Command::new("git")
+            .args([
+                "clone",
+                "--bare",
+                test_repo.to_str().unwrap(),
+                bare_dir.to_str().unwrap(),
+            ])
+            .output()
+            .unwrap();
+
+        let branches = get_branches(bare_dir.to_str().unwrap()).unwrap();
+        let current = branches.iter().find(|b| b.is_current).unwrap();
+        assert!(create_branch(bare_dir.to_str().unwrap(), "mirror-only").is_ok());
+
+        let result = checkout_branch(bare_dir.to_str().unwrap(), &current.name, |_| {});
+        assert!(result.unwrap_err().starts_with("E_BARE"));
+
+        fs::remove_dir_all(test_repo).unwrap();
+        fs::remove_dir_all(bare_dir).unwrap();
+    }
+
     #[test]
     fn test_checkout_with_uncommitted_changes_allowed_if_non_conflicting() {
         let test_repo = create_test_repo();
@@ -327,7 +546,7 @@ mod tests {
 
         fs::write(test_repo.join("test.txt"), "modified content").unwrap();
 
-        let result = checkout_branch(test_repo.to_str().unwrap(), "feature-dirty");
+        let result = checkout_branch(test_repo.to_str().unwrap(), "feature-dirty", |_| {});
         assert!(result.is_ok());
 
         fs::remove_dir_all(test_repo).unwrap();
@@ -0,0 +1,271 @@
+use super::mirror::saved_credential_callbacks;
+use super::remote::{TransferProgress, E_TRANSFER_CANCELLED};
+use super::repository::{report_checkout_progress, CheckoutProgress};
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{Config, ErrorCode, FetchOptions, Repository, SubmoduleUpdateOptions};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// Where a recursive clone is among the submodules found so far. `total`
+/// grows as nested submodules are discovered.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct SubmoduleProgress {
+    /// Submodule being updated, relative to the clone root; empty once all
+    /// are done.
+    pub path: String,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Clone `url` into `dest`, checking out `branch` when given (otherwise the
+/// remote's default branch), then initialize and update every submodule,
+/// nested ones included, when `recurse_submodules` is set. `on_progress`
+/// receives transfer updates summed over the clone and its submodules and
+/// returns `false` to cancel; `on_checkout` follows the initial checkout.
+pub fn clone_repository(
+    url: &str,
+    dest: &Path,
+    branch: Option<&str>,
+    recurse_submodules: bool,
+    mut on_progress: impl FnMut(&TransferProgress) -> bool,
+    on_checkout: impl FnMut(&CheckoutProgress),
+    on_submodule: impl FnMut(&SubmoduleProgress),
+) -> Result<(), String> {
+    if url.trim().is_empty() {
+        return Err("E_CLONE_URL_EMPTY: repository URL is required".to_string());
+    }
+    if dest.exists()
+        && dest
+            .read_dir()
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(true)
+    {
+        return Err(format!(
+            "E_CLONE_DEST_EXISTS: {} already exists and is not empty",
+            dest.display()
+        ));
+    }
+
+    let config = Config::open_default().map_err(|e| format!("Failed to read git config: {}", e))?;
+    let mut transferred = TransferProgress::default();
+    {
+        let mut callbacks = saved_credential_callbacks(&config, url, true);
+        callbacks.transfer_progress(|stats| {
+            transferred = TransferProgress {
+                received_objects: stats.received_objects(),
+                total_objects: stats.total_objects(),
+                received_bytes: stats.received_bytes(),
+            };
+            on_progress(&transferred)
+        });
+
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(callbacks);
+
+        let mut checkout = CheckoutBuilder::new();
+        report_checkout_progress(&mut checkout, on_checkout);
+
+        let mut builder = RepoBuilder::new();
+        builder.fetch_options(fetch_options);
+        builder.with_checkout(checkout);
+        if let Some(branch) = branch.map(str::trim).filter(|value| !value.is_empty()) {
+            builder.branch(branch);
+        }
+
+        builder
+            .clone(url.trim(), dest)
+            .map_err(|e| match e.code() {
+                ErrorCode::User => format!("{}: Clone cancelled", E_TRANSFER_CANCELLED),
+                _ => format!("E_CLONE_FAILED: {}", e),
+            })?;
+    }
+
+    if recurse_submodules {
+        update_submodules(dest, &config, transferred, on_progress, on_submodule)?;
+    }
+    Ok(())
+}
+
+/// Breadth-first `git submodule update --init --recursive`, reporting
+/// transfer progress on top of `transferred`.
+fn update_submodules(
+    root: &Path,
+    config: &Config,
+    mut transferred: TransferProgress,
+    mut on_progress: impl FnMut(&TransferProgress) -> bool,
+    mut on_submodule: impl FnMut(&SubmoduleProgress),
+) -> Result<(), String> {
+    let mut progress = SubmoduleProgress::default();
+    let mut pending = VecDeque::from([PathBuf::new()]);
+    while let Some(prefix) = pending.pop_front() {
+        let repo = Repository::open(root.join(&prefix))
+            .map_err(|e| format!("Failed to open repository: {}", e))?;
+        let submodules = repo
+            .submodules()
+            .map_err(|e| format!("E_SUBMODULE_UPDATE_FAILED: {}", e))?;
+        progress.total += submodules.len();
+
+        for mut submodule in submodules {
+            let path = prefix.join(submodule.path());
+            progress.path = path.to_string_lossy().replace('\\', "/");
+            on_submodule(&progress);
+
+            let base = transferred.clone();
+            let mut current = TransferProgress::default();
+            let result = {
+                let mut callbacks =
+                    saved_credential_callbacks(config, submodule.url().unwrap_or_default(), true);
+                callbacks.transfer_progress(|stats| {
+                    current = TransferProgress {
+                        received_objects: stats.received_objects(),
+                        total_objects: stats.total_objects(),
+                        received_bytes: stats.received_bytes(),
+                    };
+                    on_progress(&TransferProgress {
+                        received_objects: base.received_objects + current.received_objects,
+                        total_objects: base.total_objects + current.total_objects,
+                        received_bytes: base.received_bytes + current.received_bytes,
+                    })
+                });
+                let mut fetch_options = FetchOptions::new();
+                fetch_options.remote_callbacks(callbacks);
+                let mut options = SubmoduleUpdateOptions::new();
+                options.fetch(fetch_options);
+                submodule.update(true, Some(&mut options))
+            };
+            result.map_err(|e| match e.code() {
+                ErrorCode::User => format!("{}: Clone cancelled", E_TRANSFER_CANCELLED),
+                _ => format!("E_SUBMODULE_UPDATE_FAILED: {}: {}", progress.path, e),
+            })?;
+
+            transferred.received_objects += current.received_objects;
+            transferred.total_objects += current.total_objects;
+            transferred.received_bytes += current.received_bytes;
+            progress.completed += 1;
+            pending.push_back(path);
+        }
+    }
+    progress.path.clear();
+    on_submodule(&progress);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    #[test]
+    fn test_clone_repository_checks_out_requested_branch() {
+        let base_dir =
+            std::env::temp_dir().join(format!("gitlite-clone-test-{}", uuid::Uuid::new_v4()));
+        let source = base_dir.join("source");
+        fs::create_dir_all(&source).unwrap();
+        for args in [
+            vec!["init"],
+            vec!["config", "user.name", "Test User"],
+            vec!["config", "user.email", "test@example.com"],
+            vec!["commit", "--allow-empty", "-m", "Initial commit"],
+            vec!["branch", "develop"],
+        ] {
+            Command::new("git")
+                .args(&args)
+                .current_dir(&source)
+                .output()
+                .unwrap();
+        }
+
+        let dest = base_dir.join("dest");
+        clone_repository(
+            source.to_str().unwrap(),
+            &dest,
+            Some("develop"),
+            false,
+            |_| true,
+            |_| {},
+            |_| {},
+        )
+        .unwrap();
+
+        let repo = git2::Repository::open(&dest).unwrap();
+        assert_eq!(repo.head().unwrap().shorthand(), Some("develop"));
+
+        let again = clone_repository(
+            source.to_str().unwrap(),
+            &dest,
+            None,
+            false,
+            |_| true,
+            |_| {},
+            |_| {},
+        );
+        assert!(again.unwrap_err().starts_with("E_CLONE_DEST_EXISTS"));
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn recursive_clone_updates_nested_submodules() {
+        let base_dir =
+            std::env::temp_dir().join(format!("gitlite-clone-sub-{}", uuid::Uuid::new_v4()));
+        let git = |dir: &Path, args: &[&str]| {
+            let output = Command::new("git")
+                .args(["-c", "protocol.file.allow=always"])
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "git {:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        };
+        for name in ["inner", "middle", "top"] {
+            let dir = base_dir.join(name);
+            fs::create_dir_all(&dir).unwrap();
+            git(&dir, &["init"]);
+            git(&dir, &["config", "user.name", "Test User"]);
+            git(&dir, &["config", "user.email", "test@example.com"]);
+        }
+        fs::write(base_dir.join("inner/file.txt"), "inner\n").unwrap();
+        git(&base_dir.join("inner"), &["add", "file.txt"]);
+        git(&base_dir.join("inner"), &["commit", "-m", "inner"]);
+        for (parent, child) in [("middle", "inner"), ("top", "middle")] {
+            let dir = base_dir.join(parent);
+            let url = base_dir.join(child);
+            git(&dir, &["submodule", "add", url.to_str().unwrap(), child]);
+            git(&dir, &["commit", "-m", &format!("add {}", child)]);
+        }
+
+        let dest = base_dir.join("dest");
+        let mut updates = Vec::new();
+        clone_repository(
+            base_dir.join("top").to_str().unwrap(),
+            &dest,
+            None,
+            true,
+            |_| true,
+            |_| {},
+            |progress| updates.push(progress.clone()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dest.join("middle/inner/file.txt")).unwrap(),
+            "inner\n"
+        );
+        let paths: Vec<&str> = updates.iter().map(|update| update.path.as_str()).collect();
+        assert_eq!(paths, ["middle", "middle/inner", ""]);
+        assert_eq!(
+            (updates[2].completed, updates[2].total),
+            (2, 2),
+            "nested submodules are counted once discovered"
+        );
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+}
@@ -1,13 +1,118 @@
-use git2::Repository;
-use serde::Serialize;
+use super::emoji::expand_shortcodes;
+use super::issue_refs::{IssueMatcher, IssueRef};
+use git2::{Mailmap, Oid, Repository, Signature};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
 
 #[derive(Serialize, Debug, Clone)]
 pub struct Commit {
     pub hash: String,
+    /// Abbreviated hash, unique in this repository and honoring `core.abbrev`.
+    pub short_hash: String,
     pub author: String,
+    pub author_email: String,
+    /// Who applied the commit; differs from the author after a rebase,
+    /// cherry-pick or `git am`.
+    pub committer: String,
+    pub committer_email: String,
     pub message: String,
+    /// First paragraph of the message, joined onto one line.
+    pub subject: String,
+    /// `subject` with gitmoji `:shortcode:`s expanded to emoji.
+    pub display_subject: String,
+    /// Remainder of the message after the subject, if any. Trailers stay in
+    /// the body and are also listed in `trailers`.
+    pub body: Option<String>,
+    pub trailers: Vec<CommitTrailer>,
+    /// Committer time in seconds; kept for existing consumers.
     pub date: i64,
+    pub author_date: i64,
+    /// Offset from UTC in minutes at which the author recorded the commit.
+    pub author_tz_offset: i32,
+    pub committer_date: i64,
+    pub committer_tz_offset: i32,
     pub parents: Vec<String>,
+    /// Issue/PR references found in the message, linked when the tracker is known.
+    pub issue_refs: Vec<IssueRef>,
+}
+
+/// `Key: value` line from the message's trailer block, e.g.
+/// `Co-authored-by` or `Signed-off-by`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CommitTrailer {
+    pub key: String,
+    pub value: String,
+    /// Parsed from `Name <email>` values of `*-by` trailers.
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+/// Server-side history trimming, mirroring `git log --since/--until
+/// --no-merges --first-parent`. Dates are committer times in seconds.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct CommitFilter {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub no_merges: bool,
+    pub first_parent: bool,
+}
+
+impl CommitFilter {
+    pub(super) fn matches(&self, commit: &git2::Commit) -> bool {
+        let time = commit.time().seconds();
+        !(self.no_merges && commit.parent_count() > 1)
+            && self.since.is_none_or(|since| time >= since)
+            && self.until.is_none_or(|until| time <= until)
+    }
+}
+
+/// What to walk: up to `limit` commits passing `filter`, starting from
+/// `reference` (`None` = HEAD, `"all"` = every local branch) or from a
+/// `continuation` token returned by an earlier page. `use_mailmap` maps
+/// authors through `.mailmap` / `mailmap.file`.
+#[derive(Debug, Clone, Default)]
+pub struct CommitQuery {
+    pub limit: usize,
+    pub reference: Option<String>,
+    pub filter: CommitFilter,
+    pub continuation: Option<String>,
+    pub use_mailmap: bool,
+}
+
+/// Per-request ceilings so a pathological history can't tie up a backend
+/// thread. `max_objects` counts commits visited by the revwalk, including
+/// ones the filter drops.
+#[derive(Debug, Clone, Copy)]
+pub struct RevwalkGuards {
+    pub max_duration: Duration,
+    pub max_objects: usize,
+}
+
+impl RevwalkGuards {
+    pub fn unlimited() -> Self {
+        RevwalkGuards {
+            max_duration: Duration::MAX,
+            max_objects: usize::MAX,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CommitPage {
+    pub commits: Vec<Commit>,
+    /// A revwalk guard stopped the walk before `limit` was reached.
+    pub truncated: bool,
+    /// Pass back as `continuation` to resume after the last visited commit.
+    pub continuation: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WalkOutcome {
+    pub delivered: usize,
+    pub truncated: bool,
+    pub continuation: Option<String>,
 }
 
 pub fn get_commits(
@@ -16,6 +121,47 @@ pub fn get_commits(
     reference: Option<&str>,
 ) -> Result<Vec<Commit>, String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let query = CommitQuery {
+        limit,
+        reference: reference.map(str::to_string),
+        use_mailmap: true,
+        ..Default::default()
+    };
+    Ok(get_commits_in(&repo, &query, &RevwalkGuards::unlimited())?.commits)
+}
+
+pub fn get_commits_in(
+    repo: &Repository,
+    query: &CommitQuery,
+    guards: &RevwalkGuards,
+) -> Result<CommitPage, String> {
+    let mut commits = Vec::new();
+    let outcome = walk_commits(repo, query, guards, query.limit.max(1), |batch| {
+        commits.extend(batch);
+        true
+    })?;
+    Ok(CommitPage {
+        commits,
+        truncated: outcome.truncated,
+        continuation: outcome.continuation,
+    })
+}
+
+/// Walk history like `get_commits_in`, handing commits to `on_batch` in
+/// groups of `batch_size` as the revwalk progresses. `on_batch` returns
+/// `false` to stop early.
+pub fn walk_commits(
+    repo: &Repository,
+    query: &CommitQuery,
+    guards: &RevwalkGuards,
+    batch_size: usize,
+    mut on_batch: impl FnMut(Vec<Commit>) -> bool,
+) -> Result<WalkOutcome, String> {
+    let started = Instant::now();
+    let tips = match &query.continuation {
+        Some(token) => parse_continuation(token)?,
+        None => resolve_tips(repo, query.reference.as_deref())?,
+    };
 
     let mut revwalk = repo
         .revwalk()
@@ -24,59 +170,248 @@ pub fn get_commits(
     revwalk
         .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
         .map_err(|e| format!("Failed to set revwalk sorting: {}", e))?;
-
-    match reference {
-        Some("all") => {
-            revwalk
-                .push_glob("refs/heads/*")
-                .map_err(|e| format!("Failed to walk all local branches: {}", e))?;
-        }
-        Some(reference_name) => {
-            revwalk
-                .push_ref(reference_name)
-                .map_err(|e| format!("Failed to walk reference '{}': {}", reference_name, e))?;
-        }
-        None => {
-            revwalk
-                .push_head()
-                .map_err(|e| format!("Failed to push HEAD: {}", e))?;
+    if query.filter.first_parent {
+        revwalk
+            .simplify_first_parent()
+            .map_err(|e| format!("Failed to set first-parent walk: {}", e))?;
+    }
+    for tip in &tips {
+        match revwalk.push(*tip) {
+            // The frontier of a shallow clone can name parents it lacks.
+            Err(e) if query.continuation.is_some() && e.code() == git2::ErrorCode::NotFound => {}
+            result => result.map_err(|e| format!("Failed to walk from {}: {}", tip, e))?,
         }
     }
 
-    let mut commits = Vec::new();
-
-    for (count, oid) in revwalk.enumerate() {
-        if count >= limit {
-            break;
-        }
+    let issue_matcher = IssueMatcher::for_repository(repo);
+    let mailmap = load_mailmap(repo, query.use_mailmap);
+    let batch_size = batch_size.max(1);
+    let mut batch = Vec::with_capacity(batch_size.min(query.limit).min(1024));
+    let mut outcome = WalkOutcome {
+        delivered: 0,
+        truncated: false,
+        continuation: None,
+    };
+    let mut matched = 0;
+    let mut visited = 0;
+    if query.limit == 0 {
+        return Ok(outcome);
+    }
+    let mut revwalk = revwalk.peekable();
+    // Walked-to but unvisited commits. The walk is topological, so a parent
+    // is never visited before its children and this set alone describes
+    // where to resume.
+    let mut frontier: BTreeSet<Oid> = tips.iter().copied().collect();
 
+    while let Some(oid) = revwalk.next() {
         let oid = oid.map_err(|e| format!("Failed to get OID: {}", e))?;
+        visited += 1;
+
         let commit = repo
             .find_commit(oid)
             .map_err(|e| format!("Failed to find commit: {}", e))?;
-
-        let author_name = match commit.author().name() {
-            Some(name) => name.to_string(),
-            None => match commit.author().email() {
-                Some(email) => email.to_string(),
-                None => "Unknown".to_string(),
-            },
+        frontier.remove(&oid);
+        let parents = if query.filter.first_parent {
+            commit.parent_ids().take(1).collect::<Vec<_>>()
+        } else {
+            commit.parent_ids().collect()
         };
+        frontier.extend(parents);
+        if query.filter.matches(&commit) {
+            matched += 1;
+            batch.push(to_commit(&commit, &issue_matcher, mailmap.as_ref()));
+        }
 
-        let message = commit.message().unwrap_or("No message").trim().to_string();
+        if batch.len() == batch_size {
+            outcome.delivered += batch.len();
+            if !on_batch(std::mem::take(&mut batch)) {
+                break;
+            }
+        }
 
-        let parents: Vec<String> = commit.parent_ids().map(|p| p.to_string()).collect();
+        let more = revwalk.peek().is_some();
+        if !more {
+            break;
+        }
+        if matched >= query.limit {
+            outcome.continuation = Some(format_continuation(&frontier));
+            break;
+        }
+        if visited >= guards.max_objects || started.elapsed() >= guards.max_duration {
+            outcome.truncated = true;
+            outcome.continuation = Some(format_continuation(&frontier));
+            break;
+        }
+    }
 
-        commits.push(Commit {
-            hash: oid.to_string(),
-            author: author_name,
-            message,
-            date: commit.time().seconds(),
-            parents,
-        });
+    if !batch.is_empty() {
+        outcome.delivered += batch.len();
+        on_batch(batch);
+    }
+    Ok(outcome)
+}
+
+fn resolve_tips(repo: &Repository, reference: Option<&str>) -> Result<Vec<Oid>, String> {
+    let peel = |reference: git2::Reference| reference.peel_to_commit().map(|commit| commit.id());
+    match reference {
+        Some("all") => {
+            let references = repo
+                .references_glob("refs/heads/*")
+                .map_err(|e| format!("Failed to walk all local branches: {}", e))?;
+            let mut tips = Vec::new();
+            for reference in references {
+                let reference =
+                    reference.map_err(|e| format!("Failed to walk all local branches: {}", e))?;
+                tips.push(
+                    peel(reference)
+                        .map_err(|e| format!("Failed to walk all local branches: {}", e))?,
+                );
+            }
+            Ok(tips)
+        }
+        Some(reference_name) => repo
+            .find_reference(reference_name)
+            .and_then(peel)
+            .map(|tip| vec![tip])
+            .map_err(|e| format!("Failed to walk reference '{}': {}", reference_name, e)),
+        None => repo
+            .head()
+            .and_then(peel)
+            .map(|tip| vec![tip])
+            .map_err(|e| format!("Failed to push HEAD: {}", e)),
+    }
+}
+
+// Token: `v2:<oid>,<oid>...`, the unvisited frontier of the walk. The next
+// page walks from there, so it neither re-walks earlier pages nor sees
+// commits added to the branches in between.
+fn format_continuation(frontier: &BTreeSet<Oid>) -> String {
+    let oids: Vec<String> = frontier.iter().map(Oid::to_string).collect();
+    format!("v2:{}", oids.join(","))
+}
+
+fn parse_continuation(token: &str) -> Result<Vec<Oid>, String> {
+    let invalid = || format!("E_COMMIT_CONTINUATION_INVALID: {}", token);
+    token
+        .strip_prefix("v2:")
+        .filter(|oids| !oids.is_empty())
+        .ok_or_else(invalid)?
+        .split(',')
+        .map(|oid| Oid::from_str(oid).map_err(|_| invalid()))
+        .collect()
+}
+
+/// The repository's mailmap when `enabled`; a missing or unreadable
+/// mailmap simply leaves identities unmapped.
+pub(super) fn load_mailmap(repo: &Repository, enabled: bool) -> Option<Mailmap> {
+    if enabled {
+        repo.mailmap().ok()
+    } else {
+        None
     }
+}
+
+/// Commit author, canonicalized through `mailmap` when given.
+pub(super) fn mapped_author(
+    commit: &git2::Commit,
+    mailmap: Option<&Mailmap>,
+) -> Signature<'static> {
+    mailmap
+        .and_then(|mailmap| commit.author_with_mailmap(mailmap).ok())
+        .unwrap_or_else(|| commit.author().to_owned())
+}
+
+fn mapped_committer(commit: &git2::Commit, mailmap: Option<&Mailmap>) -> Signature<'static> {
+    mailmap
+        .and_then(|mailmap| commit.committer_with_mailmap(mailmap).ok())
+        .unwrap_or_else(|| commit.committer().to_owned())
+}
 
-    Ok(commits)
+/// Signature name, falling back to the email, then "Unknown".
+fn display_name(signature: &Signature) -> String {
+    signature
+        .name()
+        .or(signature.email())
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+fn parse_trailers(message: &str) -> Vec<CommitTrailer> {
+    let Ok(trailers) = git2::message_trailers_strs(message) else {
+        return Vec::new();
+    };
+    trailers
+        .iter()
+        .map(|(key, value)| {
+            let identity = key
+                .to_ascii_lowercase()
+                .ends_with("-by")
+                .then(|| parse_identity(value))
+                .flatten();
+            CommitTrailer {
+                key: key.to_string(),
+                value: value.to_string(),
+                name: identity.map(|(name, _)| name.to_string()),
+                email: identity.map(|(_, email)| email.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Split `Name <email>`.
+fn parse_identity(value: &str) -> Option<(&str, &str)> {
+    let (name, rest) = value.split_once('<')?;
+    let email = rest.strip_suffix('>')?;
+    Some((name.trim(), email.trim()))
+}
+
+fn to_commit(
+    commit: &git2::Commit,
+    issue_matcher: &IssueMatcher,
+    mailmap: Option<&Mailmap>,
+) -> Commit {
+    let oid = commit.id();
+    let author = mapped_author(commit, mailmap);
+    let committer = mapped_committer(commit, mailmap);
+
+    let message = commit.message().unwrap_or("No message").trim().to_string();
+
+    let subject = commit.summary().unwrap_or("").trim().to_string();
+    let body = commit
+        .body()
+        .map(|body| body.trim().to_string())
+        .filter(|body| !body.is_empty());
+    let short_hash = commit
+        .as_object()
+        .short_id()
+        .ok()
+        .and_then(|buf| buf.as_str().map(str::to_string))
+        .unwrap_or_else(|| oid.to_string()[..7].to_string());
+
+    let parents: Vec<String> = commit.parent_ids().map(|p| p.to_string()).collect();
+    let author_time = author.when();
+    let committer_time = committer.when();
+
+    Commit {
+        hash: oid.to_string(),
+        short_hash,
+        author: display_name(&author),
+        author_email: author.email().unwrap_or_default().to_string(),
+        committer: display_name(&committer),
+        committer_email: committer.email().unwrap_or_default().to_string(),
+        message,
+        display_subject: expand_shortcodes(&subject),
+        subject,
+        body,
+        trailers: parse_trailers(commit.message().unwrap_or("")),
+        date: commit.time().seconds(),
+        author_date: author_time.seconds(),
+        author_tz_offset: author_time.offset_minutes(),
+        committer_date: committer_time.seconds(),
+        committer_tz_offset: committer_time.offset_minutes(),
+        parents,
+        issue_refs: issue_matcher.extract(commit.message().unwrap_or("")),
+    }
 }
 
 #[cfg(test)]
@@ -163,6 +498,332 @@ mod tests {
         fs::remove_dir_all(test_repo).unwrap();
     }
 
+    #[test]
+    fn test_walk_commits_delivers_batches_and_stops_on_request() {
+        let test_repo = create_test_repo();
+        for i in 1..=4 {
+            Command::new("git")
+                .args(["commit", "--allow-empty", "-m", &format!("Commit {}", i)])
+                .current_dir(&test_repo)
+                .output()
+                .unwrap();
+        }
+        let repo = Repository::open(&test_repo).unwrap();
+        let query = CommitQuery {
+            limit: usize::MAX,
+            ..Default::default()
+        };
+        let guards = RevwalkGuards::unlimited();
+
+        let mut sizes = Vec::new();
+        let outcome = walk_commits(&repo, &query, &guards, 2, |batch| {
+            sizes.push(batch.len());
+            true
+        })
+        .unwrap();
+        assert_eq!(sizes, vec![2, 2, 1]);
+        assert_eq!(outcome.delivered, 5);
+        assert!(outcome.continuation.is_none());
+
+        let mut first = Vec::new();
+        let outcome = walk_commits(&repo, &query, &guards, 2, |batch| {
+            first.extend(batch);
+            false
+        })
+        .unwrap();
+        assert_eq!(outcome.delivered, 2);
+        assert_eq!(first[0].message, "Commit 4");
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_get_commits_in_truncates_at_guard_and_resumes() {
+        let test_repo = create_test_repo();
+        for i in 1..=4 {
+            Command::new("git")
+                .args(["commit", "--allow-empty", "-m", &format!("Commit {}", i)])
+                .current_dir(&test_repo)
+                .output()
+                .unwrap();
+        }
+        let repo = Repository::open(&test_repo).unwrap();
+        let guards = RevwalkGuards {
+            max_duration: Duration::from_secs(60),
+            max_objects: 3,
+        };
+        let mut query = CommitQuery {
+            limit: 100,
+            ..Default::default()
+        };
+
+        let first = get_commits_in(&repo, &query, &guards).unwrap();
+        assert!(first.truncated);
+        assert_eq!(first.commits.len(), 3);
+
+        // New commits after the first page don't shift the continued walk.
+        Command::new("git")
+            .args(["commit", "--allow-empty", "-m", "Later"])
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+        query.continuation = first.continuation;
+        let second = get_commits_in(&repo, &query, &guards).unwrap();
+        assert!(!second.truncated);
+        assert!(second.continuation.is_none());
+        let messages: Vec<&str> = second.commits.iter().map(|c| c.subject.as_str()).collect();
+        assert_eq!(messages, vec!["Commit 1", "Initial commit"]);
+
+        query.continuation = Some("v1:3:bogus".to_string());
+        assert!(get_commits_in(&repo, &query, &guards)
+            .unwrap_err()
+            .starts_with("E_COMMIT_CONTINUATION_INVALID"));
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_get_commits_pages_across_merges_match_a_full_walk() {
+        let test_repo = create_test_repo();
+        // Distinct commit times keep the time order of the walk unambiguous.
+        let clock = std::cell::Cell::new(1_700_000_000);
+        let git = |args: &[&str]| {
+            clock.set(clock.get() + 60);
+            let date = format!("{} +0000", clock.get());
+            let output = Command::new("git")
+                .args(args)
+                .env("GIT_COMMITTER_DATE", &date)
+                .env("GIT_AUTHOR_DATE", &date)
+                .current_dir(&test_repo)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+        };
+        git(&["checkout", "-b", "side"]);
+        git(&["commit", "--allow-empty", "-m", "Side 1"]);
+        git(&["commit", "--allow-empty", "-m", "Side 2"]);
+        git(&["checkout", "-"]);
+        git(&["commit", "--allow-empty", "-m", "Main 1"]);
+        git(&["merge", "--no-ff", "-m", "Merge side", "side"]);
+        git(&["commit", "--allow-empty", "-m", "Main 2"]);
+        let repo = Repository::open(&test_repo).unwrap();
+        let guards = RevwalkGuards::unlimited();
+        let hashes = |page: &CommitPage| -> Vec<String> {
+            page.commits.iter().map(|c| c.hash.clone()).collect()
+        };
+
+        let full = get_commits_in(
+            &repo,
+            &CommitQuery {
+                limit: 100,
+                ..Default::default()
+            },
+            &guards,
+        )
+        .unwrap();
+        let mut query = CommitQuery {
+            limit: 2,
+            ..Default::default()
+        };
+        let mut paged = Vec::new();
+        loop {
+            let page = get_commits_in(&repo, &query, &guards).unwrap();
+            paged.extend(hashes(&page));
+            match page.continuation {
+                Some(token) => query.continuation = Some(token),
+                None => break,
+            }
+        }
+        assert_eq!(paged.len(), 6);
+        assert_eq!(paged, hashes(&full));
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_get_commits_reports_author_and_committer_separately() {
+        let test_repo = create_test_repo();
+
+        fs::write(test_repo.join("test.txt"), "rebased").unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "Rebased commit"])
+            .env("GIT_AUTHOR_DATE", "2024-01-01T10:00:00+0900")
+            .env("GIT_COMMITTER_DATE", "2024-02-01T08:30:00-0230")
+            .env("GIT_COMMITTER_NAME", "Release Bot")
+            .env("GIT_COMMITTER_EMAIL", "bot@example.com")
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+
+        let commits = get_commits(test_repo.to_str().unwrap(), 1, None).unwrap();
+        let commit = &commits[0];
+        assert_eq!(commit.author_date, 1_704_070_800);
+        assert_eq!(commit.author_tz_offset, 540);
+        assert_eq!(commit.committer_date, 1_706_785_200);
+        assert_eq!(commit.committer_tz_offset, -150);
+        assert_eq!(commit.date, commit.committer_date);
+        assert_eq!(commit.author, "Test User");
+        assert_eq!(commit.author_email, "test@example.com");
+        assert_eq!(commit.committer, "Release Bot");
+        assert_eq!(commit.committer_email, "bot@example.com");
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_get_commits_splits_subject_body_and_abbreviates_hash() {
+        let test_repo = create_test_repo();
+
+        Command::new("git")
+            .args(["config", "core.abbrev", "10"])
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+        fs::write(test_repo.join("test.txt"), "changed").unwrap();
+        Command::new("git")
+            .args([
+                "commit",
+                "-am",
+                "Fix parser\n\nHandles empty input.\nAdds tests.\n\nCo-authored-by: Jane Doe <jane@example.com>\nSigned-off-by: Test User <test@example.com>\nFixes: parser crash",
+            ])
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+
+        let commits = get_commits(test_repo.to_str().unwrap(), 2, None).unwrap();
+        assert_eq!(commits[0].subject, "Fix parser");
+        let body = commits[0].body.as_deref().unwrap();
+        assert!(body.starts_with("Handles empty input.\nAdds tests.\n\nCo-authored-by"));
+        let trailers = &commits[0].trailers;
+        assert_eq!(trailers.len(), 3);
+        assert_eq!(trailers[0].key, "Co-authored-by");
+        assert_eq!(trailers[0].name.as_deref(), Some("Jane Doe"));
+        assert_eq!(trailers[0].email.as_deref(), Some("jane@example.com"));
+        assert_eq!(trailers[1].key, "Signed-off-by");
+        assert_eq!(trailers[2].value, "parser crash");
+        assert_eq!(trailers[2].email, None);
+        assert_eq!(commits[0].short_hash.len(), 10);
+        assert!(commits[0].hash.starts_with(&commits[0].short_hash));
+        assert_eq!(commits[1].subject, "Initial commit");
+        assert_eq!(commits[1].body, None);
+        assert!(commits[1].trailers.is_empty());
+        assert_eq!(commits[1].display_subject, "Initial commit");
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_get_commits_filters_dates_merges_and_first_parent() {
+        let test_repo = create_test_repo();
+        let git_at = |args: &[&str], date: &str| {
+            let output = Command::new("git")
+                .args(args)
+                .env("GIT_AUTHOR_DATE", date)
+                .env("GIT_COMMITTER_DATE", date)
+                .current_dir(&test_repo)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+        };
+        git_at(&["checkout", "-q", "-b", "feature"], "2001-01-01T00:00:00Z");
+        git_at(
+            &["commit", "--allow-empty", "-m", "Feature"],
+            "2001-01-01T00:00:00Z",
+        );
+        git_at(&["checkout", "-q", "-"], "2001-01-01T00:00:00Z");
+        git_at(
+            &["commit", "--allow-empty", "-m", "Mainline"],
+            "2002-01-01T00:00:00Z",
+        );
+        git_at(
+            &["merge", "--no-ff", "-m", "Merge feature", "feature"],
+            "2003-01-01T00:00:00Z",
+        );
+        let repo = Repository::open(&test_repo).unwrap();
+        let subjects = |filter: CommitFilter| -> Vec<String> {
+            let query = CommitQuery {
+                limit: 10,
+                filter,
+                ..Default::default()
+            };
+            get_commits_in(&repo, &query, &RevwalkGuards::unlimited())
+                .unwrap()
+                .commits
+                .into_iter()
+                .map(|commit| commit.subject)
+                .collect()
+        };
+
+        let no_merges = subjects(CommitFilter {
+            no_merges: true,
+            ..Default::default()
+        });
+        assert!(!no_merges.contains(&"Merge feature".to_string()));
+        assert!(no_merges.contains(&"Feature".to_string()));
+
+        let first_parent = subjects(CommitFilter {
+            first_parent: true,
+            ..Default::default()
+        });
+        assert!(!first_parent.contains(&"Feature".to_string()));
+        assert!(first_parent.contains(&"Mainline".to_string()));
+
+        let window = subjects(CommitFilter {
+            since: Some(978_307_200),   // 2001-01-01
+            until: Some(1_009_843_200), // 2002-01-01
+            ..Default::default()
+        });
+        assert_eq!(window, vec!["Mainline".to_string(), "Feature".to_string()]);
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_get_commits_maps_authors_through_mailmap() {
+        let test_repo = create_test_repo();
+        fs::write(
+            test_repo.join(".mailmap"),
+            "Jane Doe <jane@new.example> <jane@old.example>\n",
+        )
+        .unwrap();
+        Command::new("git")
+            .args(["add", ".mailmap"])
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args([
+                "-c",
+                "user.name=jd",
+                "-c",
+                "user.email=jane@old.example",
+                "commit",
+                "-m",
+                "Old identity",
+            ])
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+
+        let commits = get_commits(test_repo.to_str().unwrap(), 1, None).unwrap();
+        assert_eq!(commits[0].author, "Jane Doe");
+
+        let repo = Repository::open(&test_repo).unwrap();
+        let query = CommitQuery {
+            limit: 1,
+            ..Default::default()
+        };
+        let raw = get_commits_in(&repo, &query, &RevwalkGuards::unlimited()).unwrap();
+        assert_eq!(raw.commits[0].author, "jd");
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
     #[test]
     fn test_get_commits_invalid_path() {
         let result = get_commits("/nonexistent/path", 10, None);
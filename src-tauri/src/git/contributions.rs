@@ -0,0 +1,258 @@
+use super::commit::{load_mailmap, mapped_author, CommitFilter, RevwalkGuards};
+use git2::{ErrorCode, Repository, Sort};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// How far past `since` the walk continues, so commits whose parents were
+/// committed on a skewed clock are still reached.
+const CLOCK_SKEW_SLOP_SECS: i64 = 24 * 60 * 60;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AuthorContribution {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Committer times in Unix seconds of the oldest and newest counted commit.
+    pub first_commit: i64,
+    pub last_commit: i64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ContributionStats {
+    /// Most commits first.
+    pub authors: Vec<AuthorContribution>,
+    pub total_commits: usize,
+    /// A revwalk guard stopped the walk before it left the requested
+    /// period; older commits of that period are missing.
+    pub truncated: bool,
+}
+
+/// `git shortlog -sne --no-merges` with line counts: non-merge commits
+/// reachable from HEAD whose committer time lies in `since..=until`, grouped
+/// by author email. `use_mailmap` merges identities listed in `.mailmap`.
+pub fn get_contribution_stats(
+    path: &str,
+    since: Option<i64>,
+    until: Option<i64>,
+    use_mailmap: bool,
+    guards: &RevwalkGuards,
+) -> Result<ContributionStats, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut stats = ContributionStats {
+        authors: Vec::new(),
+        total_commits: 0,
+        truncated: false,
+    };
+    let head = match repo.head() {
+        Ok(head) => head,
+        Err(e) if e.code() == ErrorCode::UnbornBranch => return Ok(stats),
+        Err(e) => return Err(format!("Failed to get HEAD: {}", e)),
+    };
+    let head = head
+        .target()
+        .ok_or_else(|| "Failed to resolve HEAD commit".to_string())?;
+
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to create revwalk: {}", e))?;
+    revwalk
+        .set_sorting(Sort::TIME)
+        .and_then(|_| revwalk.push(head))
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+
+    let filter = CommitFilter {
+        since,
+        until,
+        no_merges: true,
+        first_parent: false,
+    };
+    let mailmap = load_mailmap(&repo, use_mailmap);
+    let started = Instant::now();
+    // keyed by lowercase email, or lowercase name when the email is empty
+    let mut authors: HashMap<String, AuthorContribution> = HashMap::new();
+
+    for (visited, oid) in revwalk.enumerate() {
+        let oid = oid.map_err(|e| format!("Failed to get OID: {}", e))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+        // The walk is newest first, so nothing older can be in range.
+        if since.is_some_and(|since| commit.time().seconds() < since - CLOCK_SKEW_SLOP_SECS) {
+            break;
+        }
+        if visited >= guards.max_objects || started.elapsed() >= guards.max_duration {
+            stats.truncated = true;
+            break;
+        }
+        if !filter.matches(&commit) {
+            continue;
+        }
+
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(
+                parent
+                    .tree()
+                    .map_err(|e| format!("Failed to read parent tree: {}", e))?,
+            ),
+            Err(_) => None,
+        };
+        let diff = commit
+            .tree()
+            .and_then(|tree| repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None))
+            .and_then(|diff| diff.stats())
+            .map_err(|e| format!("Failed to create diff: {}", e))?;
+
+        let author = mapped_author(&commit, mailmap.as_ref());
+        let name = author.name().unwrap_or("Unknown").to_string();
+        let email = author.email().unwrap_or("").to_string();
+        let key = if email.is_empty() {
+            name.to_lowercase()
+        } else {
+            email.to_lowercase()
+        };
+        let time = commit.time().seconds();
+        let entry = authors.entry(key).or_insert_with(|| AuthorContribution {
+            name,
+            email,
+            commits: 0,
+            insertions: 0,
+            deletions: 0,
+            first_commit: time,
+            last_commit: time,
+        });
+        entry.commits += 1;
+        entry.insertions += diff.insertions();
+        entry.deletions += diff.deletions();
+        entry.first_commit = entry.first_commit.min(time);
+        entry.last_commit = entry.last_commit.max(time);
+        stats.total_commits += 1;
+    }
+
+    stats.authors = authors.into_values().collect();
+    stats.authors.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str], env: &[(&str, &str)]) {
+        let output = Command::new("git")
+            .args(args)
+            .envs(env.iter().copied())
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    fn commit_as(dir: &Path, name: &str, email: &str, date: &str, file: &str, content: &str) {
+        fs::write(dir.join(file), content).unwrap();
+        git(dir, &["add", "."], &[]);
+        let env = [
+            ("GIT_AUTHOR_NAME", name),
+            ("GIT_AUTHOR_EMAIL", email),
+            ("GIT_COMMITTER_DATE", date),
+        ];
+        git(dir, &["commit", "-m", file], &env);
+    }
+
+    #[test]
+    fn contribution_stats_group_authors_through_mailmap() {
+        let dir = std::env::temp_dir().join(format!(
+            "gitlite-contributions-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        git(&dir, &["init"], &[]);
+        git(&dir, &["config", "user.name", "Test User"], &[]);
+        git(&dir, &["config", "user.email", "test@example.com"], &[]);
+        commit_as(
+            &dir,
+            "Ada",
+            "ada@old.example",
+            "1000000000 +0000",
+            "a.txt",
+            "1\n2\n",
+        );
+        commit_as(
+            &dir,
+            "Bob",
+            "bob@example.com",
+            "1100000000 +0000",
+            "b.txt",
+            "1\n",
+        );
+        commit_as(
+            &dir,
+            "Ada L",
+            "ada@new.example",
+            "1200000000 +0000",
+            "a.txt",
+            "1\n",
+        );
+        fs::write(
+            dir.join(".mailmap"),
+            "Ada Lovelace <ada@new.example> <ada@old.example>\nAda Lovelace <ada@new.example>\n",
+        )
+        .unwrap();
+        let path = dir.to_str().unwrap();
+        let guards = RevwalkGuards::unlimited();
+
+        let stats = get_contribution_stats(path, None, None, true, &guards).unwrap();
+        assert_eq!(stats.total_commits, 3);
+        assert!(!stats.truncated);
+        let ada = &stats.authors[0];
+        assert_eq!(
+            (ada.name.as_str(), ada.email.as_str()),
+            ("Ada Lovelace", "ada@new.example")
+        );
+        assert_eq!((ada.commits, ada.insertions, ada.deletions), (2, 2, 1));
+        assert_eq!(
+            (ada.first_commit, ada.last_commit),
+            (1_000_000_000, 1_200_000_000)
+        );
+        assert_eq!(stats.authors[1].name, "Bob");
+
+        let unmapped = get_contribution_stats(path, None, None, false, &guards).unwrap();
+        assert_eq!(unmapped.authors.len(), 3);
+
+        let period = get_contribution_stats(
+            path,
+            Some(1_050_000_000),
+            Some(1_150_000_000),
+            true,
+            &guards,
+        )
+        .unwrap();
+        assert_eq!(period.total_commits, 1);
+        assert_eq!(period.authors[0].name, "Bob");
+
+        // The guard allows one commit; the next is older than the period, so
+        // nothing in it was cut off.
+        let guarded = RevwalkGuards {
+            max_objects: 1,
+            ..RevwalkGuards::unlimited()
+        };
+        let recent =
+            get_contribution_stats(path, Some(1_150_000_000), None, true, &guarded).unwrap();
+        assert_eq!(recent.total_commits, 1);
+        assert!(!recent.truncated);
+        let all = get_contribution_stats(path, None, None, true, &guarded).unwrap();
+        assert_eq!(all.total_commits, 1);
+        assert!(all.truncated);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
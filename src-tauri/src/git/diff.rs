@@ -1,14 +1,30 @@
-use git2::{DiffLineType, DiffOptions, Oid, Repository};
+use super::word_diff::{annotate_hunk, IntraLineRange};
+use git2::{
+    Delta, DiffDelta, DiffFile as DiffFileEntry, DiffLineType, DiffOptions, Oid, Patch, Repository,
+    Tree,
+};
 use serde::Serialize;
-use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// Upper bound on `get_commit_stats` hashes, roughly a few history pages.
+const MAX_STATS_COMMITS: usize = 500;
+
 #[derive(Serialize, Debug, Clone)]
 pub struct DiffFile {
     pub path: String,
+    pub status: String, // "added" | "deleted" | "modified" | "renamed" | "typechange"
+    /// Octal git modes (`100644`, `100755`, `120000`, `160000`); `None` on
+    /// the side where the file does not exist.
+    pub old_mode: Option<String>,
+    pub new_mode: Option<String>,
     pub hunks: Vec<DiffHunk>,
     pub is_binary: bool,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Why `hunks` is incomplete: `file_limit`, `blob_size` or `line_limit`.
+    /// Use `load_full_file_diff` to fetch the rest.
+    pub truncated: Option<String>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -26,44 +42,97 @@ pub struct DiffLineData {
     pub content: String,
     pub old_lineno: Option<u32>,
     pub new_lineno: Option<u32>,
+    /// Changed words when this line pairs with a removed/added counterpart.
+    pub changes: Option<Vec<IntraLineRange>>,
 }
 
-pub fn get_commit_diff(path: &str, commit_hash: &str) -> Result<Vec<DiffFile>, String> {
-    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CommitStats {
+    pub hash: String,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
 
-    let oid = Oid::from_str(commit_hash).map_err(|e| format!("Invalid commit hash: {}", e))?;
+/// Ceilings that keep huge commits from freezing the diff view. Files past
+/// a limit are still listed, with `truncated` set.
+#[derive(Debug, Clone, Copy)]
+pub struct DiffLimits {
+    pub max_files: usize,
+    pub max_lines_per_file: usize,
+    pub max_blob_bytes: u64,
+    /// Unchanged lines around each hunk (`AppConfig.diff_context_lines`).
+    pub context_lines: u32,
+}
 
-    let commit = repo
-        .find_commit(oid)
-        .map_err(|e| format!("Failed to find commit: {}", e))?;
+impl DiffLimits {
+    pub fn unlimited() -> Self {
+        DiffLimits {
+            max_files: usize::MAX,
+            max_lines_per_file: usize::MAX,
+            max_blob_bytes: u64::MAX,
+            context_lines: 3,
+        }
+    }
+}
 
-    let tree = commit
-        .tree()
-        .map_err(|e| format!("Failed to get tree: {}", e))?;
+pub fn get_commit_diff(
+    path: &str,
+    commit_hash: &str,
+    limits: &DiffLimits,
+) -> Result<Vec<DiffFile>, String> {
+    commit_diff(path, commit_hash, None, limits)
+}
 
-    let parent_tree = if commit.parent_count() > 0 {
-        Some(
-            commit
-                .parent(0)
-                .map_err(|e| format!("Failed to get parent: {}", e))?
-                .tree()
-                .map_err(|e| format!("Failed to get parent tree: {}", e))?,
-        )
-    } else {
-        None
+/// Diff of a single file in a commit, ignoring the size limits but keeping
+/// the configured context.
+pub fn load_full_file_diff(
+    path: &str,
+    commit_hash: &str,
+    file: &str,
+    context_lines: u32,
+) -> Result<DiffFile, String> {
+    let limits = DiffLimits {
+        context_lines,
+        ..DiffLimits::unlimited()
     };
+    commit_diff(path, commit_hash, Some(file), &limits)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            format!(
+                "E_DIFF_FILE_NOT_FOUND: {} is not changed in {}",
+                file, commit_hash
+            )
+        })
+}
+
+fn commit_diff(
+    path: &str,
+    commit_hash: &str,
+    file: Option<&str>,
+    limits: &DiffLimits,
+) -> Result<Vec<DiffFile>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let (parent_tree, tree) = commit_trees(&repo, commit_hash)?;
 
     let mut diff_opts = DiffOptions::new();
-    diff_opts.context_lines(3);
+    diff_opts
+        .context_lines(limits.context_lines)
+        .include_typechange(true);
+    if let Some(file) = file {
+        diff_opts.pathspec(file).disable_pathspec_match(true);
+    }
 
     let diff = repo
         .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))
         .map_err(|e| format!("Failed to create diff: {}", e))?;
 
-    let mut diff_files = Vec::new();
+    let mut diff_files: Vec<DiffFile> = Vec::new();
     let mut file_index_by_path: HashMap<PathBuf, usize> = HashMap::new();
+    let mut line_counts: Vec<usize> = Vec::new();
 
-    for delta in diff.deltas() {
+    for (delta_index, delta) in diff.deltas().enumerate() {
         let path = delta
             .new_file()
             .path()
@@ -72,83 +141,214 @@ pub fn get_commit_diff(path: &str, commit_hash: &str) -> Result<Vec<DiffFile>, S
             .unwrap_or_else(|| PathBuf::from("unknown"));
         let path_string = path.to_string_lossy().to_string();
 
-        file_index_by_path.entry(path).or_insert_with(|| {
+        let file_index = *file_index_by_path.entry(path).or_insert_with(|| {
             let is_binary = delta.new_file().is_binary() || delta.old_file().is_binary();
-            let index = diff_files.len();
             diff_files.push(DiffFile {
-                path: path_string.clone(),
+                path: path_string,
+                status: delta_status(delta.status()).to_string(),
+                old_mode: None,
+                new_mode: None,
                 hunks: Vec::new(),
                 is_binary,
+                insertions: 0,
+                deletions: 0,
+                truncated: None,
             });
-            index
+            line_counts.push(0);
+            diff_files.len() - 1
         });
+
+        let diff_file = &mut diff_files[file_index];
+        record_modes(diff_file, &delta);
+        if diff_file.truncated.is_some() {
+            continue;
+        }
+        if file_index >= limits.max_files {
+            diff_file.truncated = Some("file_limit".to_string());
+            continue;
+        }
+        if blob_size(&repo, &delta.new_file()).max(blob_size(&repo, &delta.old_file()))
+            > limits.max_blob_bytes
+        {
+            diff_file.truncated = Some("blob_size".to_string());
+            continue;
+        }
+
+        let Some(patch) = Patch::from_diff(&diff, delta_index)
+            .map_err(|e| format!("Failed to create diff: {}", e))?
+        else {
+            continue;
+        };
+        diff_file.is_binary |= patch.delta().flags().is_binary();
+        if let Ok((_, insertions, deletions)) = patch.line_stats() {
+            diff_file.insertions += insertions;
+            diff_file.deletions += deletions;
+        }
+        if let Err(reason) = append_hunks(diff_file, &mut line_counts[file_index], &patch, limits) {
+            diff_file.truncated = Some(reason.to_string());
+        }
     }
 
-    let diff_files = RefCell::new(diff_files);
-    let current_file_index: Cell<Option<usize>> = Cell::new(None);
-    let current_hunk_index: Cell<Option<usize>> = Cell::new(None);
-
-    diff.foreach(
-        &mut |delta, _progress| {
-            let path = delta
-                .new_file()
-                .path()
-                .or_else(|| delta.old_file().path())
-                .map(PathBuf::from)
-                .unwrap_or_else(|| PathBuf::from("unknown"));
-            current_file_index.set(file_index_by_path.get(&path).copied());
-            current_hunk_index.set(None);
-            true
-        },
-        None,
-        Some(&mut |_delta, hunk| {
-            if let Some(file_index) = current_file_index.get() {
-                let mut diff_files = diff_files.borrow_mut();
-                let hunk_index = diff_files[file_index].hunks.len();
-                diff_files[file_index].hunks.push(DiffHunk {
-                    old_start: hunk.old_start(),
-                    old_lines: hunk.old_lines(),
-                    new_start: hunk.new_start(),
-                    new_lines: hunk.new_lines(),
-                    lines: Vec::new(),
-                });
-                current_hunk_index.set(Some(hunk_index));
-            }
-            true
-        }),
-        Some(&mut |_delta, _hunk, line| {
-            let (Some(file_index), Some(hunk_index)) =
-                (current_file_index.get(), current_hunk_index.get())
-            else {
-                return true;
-            };
+    for hunk in diff_files.iter_mut().flat_map(|file| file.hunks.iter_mut()) {
+        annotate_hunk(hunk);
+    }
+    Ok(diff_files)
+}
+
+/// Size of one side of a delta. Tree-to-tree deltas report 0 until the
+/// patch is loaded, so fall back to the object header.
+fn blob_size(repo: &Repository, file: &DiffFileEntry) -> u64 {
+    if file.size() > 0 || file.id().is_zero() {
+        return file.size();
+    }
+    repo.odb()
+        .and_then(|odb| odb.read_header(file.id()))
+        .map(|(size, _)| size as u64)
+        .unwrap_or(0)
+}
+
+fn delta_status(status: Delta) -> &'static str {
+    match status {
+        Delta::Added | Delta::Untracked => "added",
+        Delta::Deleted => "deleted",
+        Delta::Renamed => "renamed",
+        Delta::Typechange => "typechange",
+        _ => "modified",
+    }
+}
+
+fn file_mode(file: &DiffFileEntry) -> Option<String> {
+    let mode = u32::from(file.mode());
+    (file.exists() && mode != 0).then(|| format!("{:06o}", mode))
+}
+
+/// Fill in the modes from `delta`. A path that arrives as a delete plus an
+/// add is a type change split in two; report it as one.
+fn record_modes(file: &mut DiffFile, delta: &DiffDelta) {
+    let old_mode = file_mode(&delta.old_file());
+    let new_mode = file_mode(&delta.new_file());
+    if file.old_mode.is_none() {
+        file.old_mode = old_mode;
+    }
+    if file.new_mode.is_none() {
+        file.new_mode = new_mode;
+    }
+    let split = matches!(file.status.as_str(), "added" | "deleted")
+        && delta_status(delta.status()) != file.status;
+    if split && file.old_mode.is_some() && file.new_mode.is_some() {
+        file.status = "typechange".to_string();
+    }
+}
+
+/// Tree of the commit and of its first parent (`None` for a root commit).
+fn commit_trees<'r>(
+    repo: &'r Repository,
+    commit_hash: &str,
+) -> Result<(Option<Tree<'r>>, Tree<'r>), String> {
+    let oid = Oid::from_str(commit_hash).map_err(|e| format!("Invalid commit hash: {}", e))?;
+
+    let commit = repo
+        .find_commit(oid)
+        .map_err(|e| format!("Failed to find commit: {}", e))?;
+
+    let tree = commit
+        .tree()
+        .map_err(|e| format!("Failed to get tree: {}", e))?;
+
+    let parent_tree = if commit.parent_count() > 0 {
+        Some(
+            commit
+                .parent(0)
+                .map_err(|e| format!("Failed to get parent: {}", e))?
+                .tree()
+                .map_err(|e| format!("Failed to get parent tree: {}", e))?,
+        )
+    } else {
+        None
+    };
+    Ok((parent_tree, tree))
+}
+
+/// Files changed, insertions and deletions for each commit against its
+/// first parent, without building hunks. Unknown hashes fail the request.
+pub fn get_commit_stats(path: &str, hashes: &[String]) -> Result<Vec<CommitStats>, String> {
+    if hashes.len() > MAX_STATS_COMMITS {
+        return Err(format!(
+            "E_COMMIT_STATS_LIMIT: at most {} commits per request",
+            MAX_STATS_COMMITS
+        ));
+    }
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    hashes
+        .iter()
+        .map(|hash| {
+            let (parent_tree, tree) = commit_trees(&repo, hash)?;
+            let stats = repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+                .and_then(|diff| diff.stats())
+                .map_err(|e| format!("Failed to create diff: {}", e))?;
+            Ok(CommitStats {
+                hash: hash.clone(),
+                files_changed: stats.files_changed(),
+                insertions: stats.insertions(),
+                deletions: stats.deletions(),
+            })
+        })
+        .collect()
+}
 
+/// Copy the patch's hunks into `file`, stopping at `max_lines_per_file`.
+fn append_hunks(
+    file: &mut DiffFile,
+    line_count: &mut usize,
+    patch: &Patch,
+    limits: &DiffLimits,
+) -> Result<(), &'static str> {
+    for hunk_index in 0..patch.num_hunks() {
+        let Ok((hunk, line_total)) = patch.hunk(hunk_index) else {
+            continue;
+        };
+        let mut diff_hunk = DiffHunk {
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            new_start: hunk.new_start(),
+            new_lines: hunk.new_lines(),
+            lines: Vec::new(),
+        };
+
+        for line_index in 0..line_total {
+            let Ok(line) = patch.line_in_hunk(hunk_index, line_index) else {
+                continue;
+            };
             let line_type = match line.origin_value() {
                 DiffLineType::Addition | DiffLineType::AddEOFNL => "add",
                 DiffLineType::Deletion | DiffLineType::DeleteEOFNL => "delete",
                 DiffLineType::Context | DiffLineType::ContextEOFNL => "context",
-                _ => return true,
+                _ => continue,
             };
+            if *line_count >= limits.max_lines_per_file {
+                if !diff_hunk.lines.is_empty() {
+                    file.hunks.push(diff_hunk);
+                }
+                return Err("line_limit");
+            }
+            *line_count += 1;
 
             let content = String::from_utf8_lossy(line.content())
                 .trim_end_matches('\n')
                 .to_string();
 
-            diff_files.borrow_mut()[file_index].hunks[hunk_index]
-                .lines
-                .push(DiffLineData {
-                    line_type: line_type.to_string(),
-                    content,
-                    old_lineno: line.old_lineno(),
-                    new_lineno: line.new_lineno(),
-                });
-
-            true
-        }),
-    )
-    .map_err(|e| format!("Failed to iterate diff: {}", e))?;
-
-    Ok(diff_files.into_inner())
+            diff_hunk.lines.push(DiffLineData {
+                line_type: line_type.to_string(),
+                content,
+                old_lineno: line.old_lineno(),
+                new_lineno: line.new_lineno(),
+                changes: None,
+            });
+        }
+        file.hunks.push(diff_hunk);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -227,12 +427,33 @@ mod tests {
             .trim()
             .to_string();
 
-        let diff = get_commit_diff(test_dir.to_str().unwrap(), &commit_hash).unwrap();
+        let diff = get_commit_diff(
+            test_dir.to_str().unwrap(),
+            &commit_hash,
+            &DiffLimits::unlimited(),
+        )
+        .unwrap();
 
         assert_eq!(diff.len(), 1);
         assert_eq!(diff[0].path, "test.txt");
         assert!(!diff[0].is_binary);
         assert!(!diff[0].hunks.is_empty());
+        assert_eq!((diff[0].insertions, diff[0].deletions), (2, 1));
+
+        let stats = get_commit_stats(
+            test_dir.to_str().unwrap(),
+            std::slice::from_ref(&commit_hash),
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            vec![CommitStats {
+                hash: commit_hash,
+                files_changed: 1,
+                insertions: 2,
+                deletions: 1,
+            }]
+        );
 
         fs::remove_dir_all(&test_dir).ok();
     }
@@ -263,7 +484,12 @@ mod tests {
             .trim()
             .to_string();
 
-        let diff = get_commit_diff(test_dir.to_str().unwrap(), &commit_hash).unwrap();
+        let diff = get_commit_diff(
+            test_dir.to_str().unwrap(),
+            &commit_hash,
+            &DiffLimits::unlimited(),
+        )
+        .unwrap();
 
         assert_eq!(diff.len(), 1);
         assert_eq!(diff[0].path, "test.txt");
@@ -287,9 +513,143 @@ mod tests {
             .output()
             .unwrap();
 
-        let result = get_commit_diff(test_dir.to_str().unwrap(), "invalid_hash");
+        let result = get_commit_diff(
+            test_dir.to_str().unwrap(),
+            "invalid_hash",
+            &DiffLimits::unlimited(),
+        );
         assert!(result.is_err());
 
         fs::remove_dir_all(&test_dir).ok();
     }
+
+    #[test]
+    fn test_get_commit_diff_limits_truncate_and_full_file_loads() {
+        let test_dir = create_test_repo();
+
+        let long: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        fs::write(test_dir.join("a.txt"), &long).unwrap();
+        fs::write(test_dir.join("b.txt"), "small\n").unwrap();
+        fs::write(test_dir.join("c.txt"), "x".repeat(200)).unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(&test_dir)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "three files"])
+            .current_dir(&test_dir)
+            .output()
+            .unwrap();
+        let hash_output = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(&test_dir)
+            .output()
+            .unwrap();
+        let commit_hash = String::from_utf8(hash_output.stdout)
+            .unwrap()
+            .trim()
+            .to_string();
+        let path = test_dir.to_str().unwrap();
+
+        let limits = DiffLimits {
+            max_files: 2,
+            max_lines_per_file: 5,
+            max_blob_bytes: 180,
+            context_lines: 3,
+        };
+        let diff = get_commit_diff(path, &commit_hash, &limits).unwrap();
+        assert_eq!(diff.len(), 3);
+        assert_eq!(diff[0].truncated.as_deref(), Some("line_limit"));
+        assert_eq!(diff[0].hunks[0].lines.len(), 5);
+        assert_eq!(diff[1].truncated, None);
+        assert_eq!(diff[2].truncated.as_deref(), Some("file_limit"));
+        assert!(diff[2].hunks.is_empty());
+
+        let sized = DiffLimits {
+            max_files: 10,
+            ..limits
+        };
+        let diff = get_commit_diff(path, &commit_hash, &sized).unwrap();
+        assert_eq!(diff[2].path, "c.txt");
+        assert_eq!(diff[2].truncated.as_deref(), Some("blob_size"));
+        assert!(diff[2].hunks.is_empty());
+        assert_eq!(diff[1].truncated, None);
+
+        let full = load_full_file_diff(path, &commit_hash, "a.txt", 3).unwrap();
+        assert_eq!(full.truncated, None);
+        assert_eq!(full.hunks[0].lines.len(), 20);
+        assert!(load_full_file_diff(path, &commit_hash, "missing.txt", 3)
+            .unwrap_err()
+            .starts_with("E_DIFF_FILE_NOT_FOUND"));
+
+        fs::remove_dir_all(&test_dir).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_commit_diff_reports_mode_and_type_changes() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let test_dir = create_test_repo();
+        let commit_all = |message: &str| {
+            Command::new("git")
+                .args(["add", "-A"])
+                .current_dir(&test_dir)
+                .output()
+                .unwrap();
+            Command::new("git")
+                .args(["commit", "-m", message])
+                .current_dir(&test_dir)
+                .output()
+                .unwrap();
+            let hash_output = Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .current_dir(&test_dir)
+                .output()
+                .unwrap();
+            String::from_utf8(hash_output.stdout)
+                .unwrap()
+                .trim()
+                .to_string()
+        };
+        let diff_of = |hash: &str| {
+            get_commit_diff(test_dir.to_str().unwrap(), hash, &DiffLimits::unlimited()).unwrap()
+        };
+
+        fs::write(test_dir.join("run.sh"), "echo hi\n").unwrap();
+        fs::write(test_dir.join("config"), "a = 1\n").unwrap();
+        symlink("run.sh", test_dir.join("link")).unwrap();
+        let first = commit_all("initial");
+        let diff = diff_of(&first);
+        let link = diff.iter().find(|file| file.path == "link").unwrap();
+        assert_eq!(link.status, "added");
+        assert_eq!(link.old_mode, None);
+        assert_eq!(link.new_mode.as_deref(), Some("120000"));
+
+        fs::set_permissions(test_dir.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_file(test_dir.join("link")).unwrap();
+        symlink("config", test_dir.join("link")).unwrap();
+        fs::remove_file(test_dir.join("config")).unwrap();
+        symlink("run.sh", test_dir.join("config")).unwrap();
+        let second = commit_all("modes");
+        let diff = diff_of(&second);
+        assert_eq!(diff.len(), 3);
+
+        let script = diff.iter().find(|file| file.path == "run.sh").unwrap();
+        assert_eq!(script.status, "modified");
+        assert_eq!(script.old_mode.as_deref(), Some("100644"));
+        assert_eq!(script.new_mode.as_deref(), Some("100755"));
+        assert!(script.hunks.is_empty());
+
+        let link = diff.iter().find(|file| file.path == "link").unwrap();
+        assert_eq!((link.insertions, link.deletions), (1, 1));
+
+        let config = diff.iter().find(|file| file.path == "config").unwrap();
+        assert_eq!(config.status, "typechange");
+        assert_eq!(config.old_mode.as_deref(), Some("100644"));
+        assert_eq!(config.new_mode.as_deref(), Some("120000"));
+
+        fs::remove_dir_all(&test_dir).ok();
+    }
 }
@@ -0,0 +1,370 @@
+use git2::{Delta, Diff, DiffFindOptions, DiffOptions, Patch, Repository};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+
+/// `fn`, `def`, `func` and `function` definitions, capturing the name.
+static DEFINITION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\s*(?:(?:pub(?:\([^)]*\))?|export|static|async|public|private|protected)\s+)*(?:fn|def|func|function)\s+([A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .expect("valid definition regex")
+});
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FileDiffSummary {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub status: String, // "added" | "deleted" | "modified" | "renamed" | "typechange"
+    pub additions: usize,
+    pub deletions: usize,
+    pub is_binary: bool,
+    /// Functions whose bodies or definitions changed, as far as the hunk
+    /// headers and changed lines reveal them.
+    pub functions: Vec<String>,
+    pub summary: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DiffSummary {
+    pub files: Vec<FileDiffSummary>,
+    pub additions: usize,
+    pub deletions: usize,
+    pub summary: String,
+}
+
+/// Plain-language summary of a diff, for screen readers and notifications.
+/// `spec` is `staged`, `unstaged`, a revision (compared with its first
+/// parent), an `<old>..<new>` range or an `<old>...<new>` range, which
+/// compares `<new>` with the merge base of both sides.
+pub fn summarize_diff(path: &str, spec: &str) -> Result<DiffSummary, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut diff = spec_diff(&repo, spec.trim())?;
+
+    let mut find = DiffFindOptions::new();
+    find.renames(true);
+    diff.find_similar(Some(&mut find))
+        .map_err(|e| format!("Failed to detect renames: {}", e))?;
+
+    let mut files = Vec::new();
+    for index in 0..diff.deltas().len() {
+        let delta = diff.get_delta(index).expect("delta index in range");
+        let new_path = delta
+            .new_file()
+            .path()
+            .map(|p| p.to_string_lossy().into_owned());
+        let old_path = delta
+            .old_file()
+            .path()
+            .map(|p| p.to_string_lossy().into_owned());
+        let path = new_path
+            .clone()
+            .or_else(|| old_path.clone())
+            .unwrap_or_else(|| "unknown".to_string());
+        let status = match delta.status() {
+            Delta::Added | Delta::Untracked => "added",
+            Delta::Deleted => "deleted",
+            Delta::Renamed => "renamed",
+            Delta::Typechange => "typechange",
+            _ => "modified",
+        };
+
+        let mut additions = 0;
+        let mut deletions = 0;
+        let mut functions = BTreeSet::new();
+        let patch = Patch::from_diff(&diff, index)
+            .map_err(|e| format!("Failed to read diff for '{}': {}", path, e))?;
+        let is_binary = patch.is_none() || delta.flags().is_binary();
+        if let Some(patch) = patch.filter(|_| !is_binary) {
+            for hunk_index in 0..patch.num_hunks() {
+                let (hunk, _) = patch
+                    .hunk(hunk_index)
+                    .map_err(|e| format!("Failed to read diff hunk: {}", e))?;
+                let header = String::from_utf8_lossy(hunk.header()).into_owned();
+                if let Some(context) = header.splitn(3, "@@").nth(2) {
+                    if let Some(name) = DEFINITION.captures(context).and_then(|c| c.get(1)) {
+                        functions.insert(name.as_str().to_string());
+                    }
+                }
+                let line_count = patch
+                    .num_lines_in_hunk(hunk_index)
+                    .map_err(|e| format!("Failed to read diff hunk: {}", e))?;
+                for line_index in 0..line_count {
+                    let line = patch
+                        .line_in_hunk(hunk_index, line_index)
+                        .map_err(|e| format!("Failed to read diff line: {}", e))?;
+                    match line.origin() {
+                        '+' => additions += 1,
+                        '-' => deletions += 1,
+                        _ => continue,
+                    }
+                    let content = String::from_utf8_lossy(line.content());
+                    if let Some(name) = DEFINITION.captures(&content).and_then(|c| c.get(1)) {
+                        functions.insert(name.as_str().to_string());
+                    }
+                }
+            }
+        }
+
+        let functions: Vec<String> = functions.into_iter().collect();
+        let old_path = old_path.filter(|old| status == "renamed" && Some(old) != new_path.as_ref());
+        let summary = describe_file(
+            &path,
+            old_path.as_deref(),
+            status,
+            additions,
+            deletions,
+            is_binary,
+            functions.len(),
+        );
+        files.push(FileDiffSummary {
+            path,
+            old_path,
+            status: status.to_string(),
+            additions,
+            deletions,
+            is_binary,
+            functions,
+            summary,
+        });
+    }
+
+    let additions = files.iter().map(|file| file.additions).sum();
+    let deletions = files.iter().map(|file| file.deletions).sum();
+    let summary = if files.is_empty() {
+        "No changes".to_string()
+    } else {
+        format!(
+            "{} changed: {} added, {} removed",
+            count(files.len(), "file", "files"),
+            count(additions, "line", "lines"),
+            count(deletions, "line", "lines")
+        )
+    };
+
+    Ok(DiffSummary {
+        files,
+        additions,
+        deletions,
+        summary,
+    })
+}
+
+fn spec_diff<'r>(repo: &'r Repository, spec: &str) -> Result<Diff<'r>, String> {
+    let mut options = DiffOptions::new();
+    options.context_lines(0);
+
+    let commit_of = |rev: &str| {
+        let rev = if rev.is_empty() { "HEAD" } else { rev };
+        repo.revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| format!("E_DIFF_SPEC: '{}': {}", rev, e))
+    };
+    let tree_of = |rev: &str| {
+        commit_of(rev)?
+            .tree()
+            .map_err(|e| format!("E_DIFF_SPEC: '{}': {}", rev, e))
+    };
+    let head_tree = || repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+    let diff = match spec {
+        "" => return Err("E_DIFF_SPEC: diff spec is required".to_string()),
+        "staged" => repo.diff_tree_to_index(head_tree().as_ref(), None, Some(&mut options)),
+        "unstaged" => {
+            options.include_untracked(true).recurse_untracked_dirs(true);
+            repo.diff_index_to_workdir(None, Some(&mut options))
+        }
+        range if range.contains("...") => {
+            let (old, new) = range.split_once("...").unwrap_or((range, ""));
+            let new_commit = commit_of(new)?;
+            let base = repo
+                .merge_base(commit_of(old)?.id(), new_commit.id())
+                .map_err(|e| format!("E_DIFF_SPEC: '{}' has no merge base: {}", range, e))?;
+            let old_tree = repo
+                .find_commit(base)
+                .and_then(|commit| commit.tree())
+                .map_err(|e| format!("E_DIFF_SPEC: '{}': {}", range, e))?;
+            let new_tree = new_commit
+                .tree()
+                .map_err(|e| format!("E_DIFF_SPEC: '{}': {}", new, e))?;
+            repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut options))
+        }
+        range if range.contains("..") => {
+            let (old, new) = range.split_once("..").unwrap_or((range, ""));
+            let old_tree = tree_of(old)?;
+            let new_tree = tree_of(new)?;
+            repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut options))
+        }
+        rev => {
+            let commit = repo
+                .revparse_single(rev)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|e| format!("E_DIFF_SPEC: '{}': {}", rev, e))?;
+            let tree = commit
+                .tree()
+                .map_err(|e| format!("E_DIFF_SPEC: '{}': {}", rev, e))?;
+            let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+            repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))
+        }
+    };
+    diff.map_err(|e| format!("Failed to create diff: {}", e))
+}
+
+fn describe_file(
+    path: &str,
+    old_path: Option<&str>,
+    status: &str,
+    additions: usize,
+    deletions: usize,
+    is_binary: bool,
+    functions: usize,
+) -> String {
+    if is_binary {
+        return match status {
+            "added" => format!("Binary file {} added", path),
+            "deleted" => format!("Binary file {} deleted", path),
+            _ => format!("Binary file {} changed", path),
+        };
+    }
+    match status {
+        "added" => format!("Added {} with {}", path, count(additions, "line", "lines")),
+        "deleted" => format!(
+            "Deleted {} ({} removed)",
+            path,
+            count(deletions, "line", "lines")
+        ),
+        _ => {
+            let mut parts = Vec::new();
+            if functions > 0 {
+                parts.push(format!(
+                    "{} changed",
+                    count(functions, "function", "functions")
+                ));
+            }
+            if additions > 0 {
+                parts.push(format!("{} added", count(additions, "line", "lines")));
+            }
+            if deletions > 0 {
+                parts.push(format!("{} removed", count(deletions, "line", "lines")));
+            }
+            let changes = if parts.is_empty() {
+                "no content changes".to_string()
+            } else {
+                parts.join(", ")
+            };
+            match old_path {
+                Some(old_path) => format!("Renamed {} to {}, {}", old_path, path, changes),
+                None => format!("{} in {}", capitalize(&changes), path),
+            }
+        }
+    }
+}
+
+fn count(value: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", value, if value == 1 { singular } else { plural })
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_summarize_diff_describes_files_in_plain_language() {
+        let dir = std::env::temp_dir().join(format!(
+            "gitlite-diff-summary-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        run_git(&["init"], &dir);
+        run_git(&["config", "user.name", "Test User"], &dir);
+        run_git(&["config", "user.email", "test@example.com"], &dir);
+        fs::write(
+            dir.join("lib.rs"),
+            "fn alpha() {\n    1;\n}\n\nfn beta() {\n    2;\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("old.txt"), "gone\n").unwrap();
+        run_git(&["add", "."], &dir);
+        run_git(&["commit", "-m", "Initial commit"], &dir);
+
+        fs::write(
+            dir.join("lib.rs"),
+            "fn alpha() {\n    1;\n}\n\nfn beta() {\n    20;\n    21;\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("new.rs"), "fn gamma() {}\n").unwrap();
+        fs::remove_file(dir.join("old.txt")).unwrap();
+        run_git(&["add", "-A"], &dir);
+        let path = dir.to_str().unwrap();
+
+        let staged = summarize_diff(path, "staged").unwrap();
+        let summaries: Vec<&str> = staged.files.iter().map(|f| f.summary.as_str()).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "1 function changed, 2 lines added, 1 line removed in lib.rs",
+                "Added new.rs with 1 line",
+                "Deleted old.txt (1 line removed)",
+            ]
+        );
+        assert_eq!(staged.files[0].functions, vec!["beta".to_string()]);
+        assert_eq!(
+            staged.summary,
+            "3 files changed: 3 lines added, 2 lines removed"
+        );
+
+        run_git(&["commit", "-m", "Second"], &dir);
+        assert_eq!(
+            summarize_diff(path, "HEAD").unwrap().summary,
+            staged.summary
+        );
+        assert_eq!(
+            summarize_diff(path, "HEAD~1..HEAD").unwrap().summary,
+            staged.summary
+        );
+        // `...` compares with the merge base, ignoring work on the other side
+        run_git(&["checkout", "-b", "side", "HEAD~1"], &dir);
+        fs::write(dir.join("side.txt"), "side\n").unwrap();
+        run_git(&["add", "side.txt"], &dir);
+        run_git(&["commit", "-m", "Side"], &dir);
+        run_git(&["checkout", "-"], &dir);
+        assert_eq!(
+            summarize_diff(path, "side...HEAD").unwrap().summary,
+            staged.summary
+        );
+        assert_ne!(
+            summarize_diff(path, "side..HEAD").unwrap().summary,
+            staged.summary
+        );
+        assert_eq!(
+            summarize_diff(path, "unstaged").unwrap().summary,
+            "No changes"
+        );
+        assert!(summarize_diff(path, "nope")
+            .unwrap_err()
+            .starts_with("E_DIFF_SPEC"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GitmojiEntry {
+    pub shortcode: String,
+    pub emoji: String,
+    pub description: String,
+}
+
+// (shortcode without colons, emoji, description) following gitmoji.dev.
+const GITMOJI: &[(&str, &str, &str)] = &[
+    ("art", "🎨", "Improve structure / format of the code"),
+    ("zap", "⚡️", "Improve performance"),
+    ("fire", "🔥", "Remove code or files"),
+    ("bug", "🐛", "Fix a bug"),
+    ("ambulance", "🚑️", "Critical hotfix"),
+    ("sparkles", "✨", "Introduce new features"),
+    ("memo", "📝", "Add or update documentation"),
+    ("rocket", "🚀", "Deploy stuff"),
+    ("lipstick", "💄", "Add or update the UI and style files"),
+    ("tada", "🎉", "Begin a project"),
+    ("white_check_mark", "✅", "Add, update, or pass tests"),
+    ("lock", "🔒️", "Fix security or privacy issues"),
+    ("closed_lock_with_key", "🔐", "Add or update secrets"),
+    ("bookmark", "🔖", "Release / Version tags"),
+    ("rotating_light", "🚨", "Fix compiler / linter warnings"),
+    ("construction", "🚧", "Work in progress"),
+    ("green_heart", "💚", "Fix CI Build"),
+    ("arrow_down", "⬇️", "Downgrade dependencies"),
+    ("arrow_up", "⬆️", "Upgrade dependencies"),
+    ("pushpin", "📌", "Pin dependencies to specific versions"),
+    ("construction_worker", "👷", "Add or update CI build system"),
+    (
+        "chart_with_upwards_trend",
+        "📈",
+        "Add or update analytics or track code",
+    ),
+    ("recycle", "♻️", "Refactor code"),
+    ("heavy_plus_sign", "➕", "Add a dependency"),
+    ("heavy_minus_sign", "➖", "Remove a dependency"),
+    ("wrench", "🔧", "Add or update configuration files"),
+    ("hammer", "🔨", "Add or update development scripts"),
+    (
+        "globe_with_meridians",
+        "🌐",
+        "Internationalization and localization",
+    ),
+    ("pencil2", "✏️", "Fix typos"),
+    ("poop", "💩", "Write bad code that needs to be improved"),
+    ("rewind", "⏪️", "Revert changes"),
+    ("twisted_rightwards_arrows", "🔀", "Merge branches"),
+    ("package", "📦️", "Add or update compiled files or packages"),
+    ("alien", "👽️", "Update code due to external API changes"),
+    ("truck", "🚚", "Move or rename resources"),
+    ("page_facing_up", "📄", "Add or update license"),
+    ("boom", "💥", "Introduce breaking changes"),
+    ("bento", "🍱", "Add or update assets"),
+    ("wheelchair", "♿️", "Improve accessibility"),
+    ("bulb", "💡", "Add or update comments in source code"),
+    ("beers", "🍻", "Write code drunkenly"),
+    ("speech_balloon", "💬", "Add or update text and literals"),
+    ("card_file_box", "🗃️", "Perform database related changes"),
+    ("loud_sound", "🔊", "Add or update logs"),
+    ("mute", "🔇", "Remove logs"),
+    ("busts_in_silhouette", "👥", "Add or update contributor(s)"),
+    (
+        "children_crossing",
+        "🚸",
+        "Improve user experience / usability",
+    ),
+    ("building_construction", "🏗️", "Make architectural changes"),
+    ("iphone", "📱", "Work on responsive design"),
+    ("clown_face", "🤡", "Mock things"),
+    ("egg", "🥚", "Add or update an easter egg"),
+    ("see_no_evil", "🙈", "Add or update a .gitignore file"),
+    ("camera_flash", "📸", "Add or update snapshots"),
+    ("alembic", "⚗️", "Perform experiments"),
+    ("mag", "🔍️", "Improve SEO"),
+    ("label", "🏷️", "Add or update types"),
+    ("seedling", "🌱", "Add or update seed files"),
+    (
+        "triangular_flag_on_post",
+        "🚩",
+        "Add, update, or remove feature flags",
+    ),
+    ("goal_net", "🥅", "Catch errors"),
+    ("dizzy", "💫", "Add or update animations and transitions"),
+    (
+        "wastebasket",
+        "🗑️",
+        "Deprecate code that needs to be cleaned up",
+    ),
+    (
+        "passport_control",
+        "🛂",
+        "Work on code related to authorization, roles and permissions",
+    ),
+    (
+        "adhesive_bandage",
+        "🩹",
+        "Simple fix for a non-critical issue",
+    ),
+    ("monocle_face", "🧐", "Data exploration/inspection"),
+    ("coffin", "⚰️", "Remove dead code"),
+    ("test_tube", "🧪", "Add a failing test"),
+    ("necktie", "👔", "Add or update business logic"),
+    ("stethoscope", "🩺", "Add or update healthcheck"),
+    ("bricks", "🧱", "Infrastructure related changes"),
+    ("technologist", "🧑‍💻", "Improve developer experience"),
+    (
+        "money_with_wings",
+        "💸",
+        "Add sponsorships or money related infrastructure",
+    ),
+    (
+        "thread",
+        "🧵",
+        "Add or update code related to multithreading or concurrency",
+    ),
+    (
+        "safety_vest",
+        "🦺",
+        "Add or update code related to validation",
+    ),
+    ("airplane", "✈️", "Improve offline support"),
+];
+
+/// Shortcode table for pickers and reverse lookup (emoji -> `:shortcode:`).
+pub fn gitmoji_table() -> Vec<GitmojiEntry> {
+    GITMOJI
+        .iter()
+        .map(|(shortcode, emoji, description)| GitmojiEntry {
+            shortcode: format!(":{}:", shortcode),
+            emoji: emoji.to_string(),
+            description: description.to_string(),
+        })
+        .collect()
+}
+
+/// Replace known `:shortcode:` tokens with their emoji. Unknown shortcodes and
+/// stray colons (e.g. `12:30`, `std::io`) are left untouched.
+pub fn expand_shortcodes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(':') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let candidate = after.find(':').map(|end| &after[..end]);
+
+        match candidate.and_then(lookup) {
+            Some(emoji) => {
+                result.push_str(emoji);
+                rest = &after[candidate.map(str::len).unwrap_or(0) + 1..];
+            }
+            None => {
+                result.push(':');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn lookup(name: &str) -> Option<&'static str> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+' || c == '-')
+    {
+        return None;
+    }
+    GITMOJI
+        .iter()
+        .find(|(shortcode, _, _)| *shortcode == name)
+        .map(|(_, emoji, _)| *emoji)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_shortcodes() {
+        assert_eq!(expand_shortcodes(":sparkles: Add login"), "✨ Add login");
+        assert_eq!(
+            expand_shortcodes(":bug::memo: fix at 12:30"),
+            "🐛📝 fix at 12:30"
+        );
+        assert_eq!(
+            expand_shortcodes("keep :unknown: and std::io"),
+            "keep :unknown: and std::io"
+        );
+    }
+
+    #[test]
+    fn test_gitmoji_table_has_unique_shortcodes() {
+        let table = gitmoji_table();
+        let mut shortcodes: Vec<&str> = table.iter().map(|e| e.shortcode.as_str()).collect();
+        shortcodes.sort();
+        shortcodes.dedup();
+        assert_eq!(shortcodes.len(), table.len());
+        assert!(table
+            .iter()
+            .any(|e| e.shortcode == ":sparkles:" && e.emoji == "✨"));
+    }
+}
@@ -0,0 +1,356 @@
+use git2::{ObjectType, Oid, Repository, RepositoryState, TreeWalkMode, TreeWalkResult};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+const MAX_MISSING_REPORTED: usize = 20;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HealthIssue {
+    pub kind: String, // "stale_lock" | "corrupt_index" | "stale_merge_state" | "dangling_ref" | "missing_object"
+    pub severity: String, // "error" | "warning"
+    pub message: String,
+    pub remediation: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub objects_checked: usize,
+    pub issues: Vec<HealthIssue>,
+}
+
+/// fsck-style health check: leftover lock files, a corrupt index, stale
+/// merge/cherry-pick state, refs pointing at missing objects, and
+/// connectivity of every commit, tree and blob reachable from refs.
+pub fn check_repository(path: &str) -> Result<HealthReport, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut issues = Vec::new();
+
+    check_lock_files(repo.path(), &mut issues);
+    check_index(&repo, &mut issues);
+    check_operation_state(&repo, &mut issues);
+    let tips = check_refs(&repo, &mut issues)?;
+    let objects_checked = check_connectivity(&repo, &tips, &mut issues);
+
+    Ok(HealthReport {
+        healthy: !issues.iter().any(|issue| issue.severity == "error"),
+        objects_checked,
+        issues,
+    })
+}
+
+fn issue(kind: &str, severity: &str, message: String, remediation: String) -> HealthIssue {
+    HealthIssue {
+        kind: kind.to_string(),
+        severity: severity.to_string(),
+        message,
+        remediation,
+    }
+}
+
+fn check_lock_files(git_dir: &Path, issues: &mut Vec<HealthIssue>) {
+    let mut locks = Vec::new();
+    for name in ["index.lock", "HEAD.lock", "config.lock", "packed-refs.lock"] {
+        if git_dir.join(name).exists() {
+            locks.push(name.to_string());
+        }
+    }
+    collect_ref_locks(git_dir, &git_dir.join("refs"), &mut locks);
+
+    for lock in locks {
+        issues.push(issue(
+            "stale_lock",
+            "warning",
+            format!("Lock file '{}' was left behind", lock),
+            format!(
+                "Make sure no other git process is running, then delete .git/{}",
+                lock
+            ),
+        ));
+    }
+}
+
+fn collect_ref_locks(git_dir: &Path, dir: &Path, locks: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_ref_locks(git_dir, &path, locks);
+        } else if path.extension().is_some_and(|ext| ext == "lock") {
+            if let Ok(relative) = path.strip_prefix(git_dir) {
+                locks.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+}
+
+fn check_index(repo: &Repository, issues: &mut Vec<HealthIssue>) {
+    if repo.is_bare() {
+        return;
+    }
+    let result = repo.index().and_then(|mut index| index.read(true));
+    if let Err(error) = result {
+        issues.push(issue(
+            "corrupt_index",
+            "error",
+            format!("The index could not be read: {}", error.message()),
+            "Delete .git/index and run `git reset` to rebuild it from HEAD (unstaged work is kept)"
+                .to_string(),
+        ));
+    }
+}
+
+fn check_operation_state(repo: &Repository, issues: &mut Vec<HealthIssue>) {
+    let (head_file, label, abort) = match repo.state() {
+        RepositoryState::Merge => ("MERGE_HEAD", "merge", "git merge --abort"),
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+            ("CHERRY_PICK_HEAD", "cherry-pick", "git cherry-pick --abort")
+        }
+        RepositoryState::Revert | RepositoryState::RevertSequence => {
+            ("REVERT_HEAD", "revert", "git revert --abort")
+        }
+        _ => return,
+    };
+
+    let Some(pending) = fs::read_to_string(repo.path().join(head_file))
+        .ok()
+        .and_then(|content| content.lines().next().map(str::trim).map(str::to_string))
+        .and_then(|line| Oid::from_str(&line).ok())
+    else {
+        return;
+    };
+    let Some(head) = repo.head().ok().and_then(|head| head.target()) else {
+        return;
+    };
+
+    // Only stale when the pending commit is already part of HEAD.
+    let already_applied = label == "merge"
+        && (pending == head || repo.graph_descendant_of(head, pending).unwrap_or(false));
+    let has_conflicts = repo
+        .index()
+        .map(|index| index.has_conflicts())
+        .unwrap_or(false);
+    if already_applied && !has_conflicts {
+        issues.push(issue(
+            "stale_merge_state",
+            "warning",
+            format!(
+                "{} exists but the {} is already part of HEAD",
+                head_file, label
+            ),
+            format!(
+                "Run `{}` or delete .git/{} to leave the {} state",
+                abort, head_file, label
+            ),
+        ));
+    }
+}
+
+/// Returns the object ids of all resolvable ref tips.
+fn check_refs(repo: &Repository, issues: &mut Vec<HealthIssue>) -> Result<Vec<Oid>, String> {
+    let mut tips = Vec::new();
+    let references = repo
+        .references()
+        .map_err(|e| format!("Failed to list references: {}", e))?;
+
+    for reference in references {
+        let reference = match reference {
+            Ok(reference) => reference,
+            Err(error) => {
+                issues.push(issue(
+                    "dangling_ref",
+                    "error",
+                    format!("A reference could not be read: {}", error.message()),
+                    "Run `git fsck` to locate the broken ref file".to_string(),
+                ));
+                continue;
+            }
+        };
+        let name = reference.name().unwrap_or("<invalid utf-8>").to_string();
+
+        match reference
+            .resolve()
+            .ok()
+            .and_then(|resolved| resolved.target())
+        {
+            Some(oid) if repo.find_object(oid, None).is_ok() => tips.push(oid),
+            target => {
+                let detail = target
+                    .map(|oid| format!("missing object {}", oid))
+                    .unwrap_or_else(|| "an unresolvable target".to_string());
+                issues.push(issue(
+                    "dangling_ref",
+                    "error",
+                    format!("Reference '{}' points to {}", name, detail),
+                    format!(
+                        "Delete it with `git update-ref -d {}` or fetch the missing objects",
+                        name
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(tips)
+}
+
+fn note_missing(oid: Oid, missing: &mut Vec<Oid>) {
+    if !missing.contains(&oid) {
+        missing.push(oid);
+    }
+}
+
+fn check_connectivity(repo: &Repository, tips: &[Oid], issues: &mut Vec<HealthIssue>) -> usize {
+    let Ok(odb) = repo.odb() else {
+        return 0;
+    };
+    let Ok(mut revwalk) = repo.revwalk() else {
+        return 0;
+    };
+    for tip in tips {
+        if repo.find_commit(*tip).is_ok() {
+            let _ = revwalk.push(*tip);
+        }
+    }
+
+    let mut seen: HashSet<Oid> = HashSet::new();
+    let mut missing: Vec<Oid> = Vec::new();
+
+    for oid in revwalk {
+        let commit = match oid.and_then(|oid| repo.find_commit(oid)) {
+            Ok(commit) => commit,
+            Err(error) => {
+                issues.push(issue(
+                    "missing_object",
+                    "error",
+                    format!("History walk failed: {}", error.message()),
+                    "Fetch from a remote that has the history or restore from a backup".to_string(),
+                ));
+                break;
+            }
+        };
+        seen.insert(commit.id());
+
+        let tree_id = commit.tree_id();
+        if !seen.insert(tree_id) {
+            continue;
+        }
+        let Ok(tree) = repo.find_tree(tree_id) else {
+            note_missing(tree_id, &mut missing);
+            continue;
+        };
+        let _ = tree.walk(TreeWalkMode::PreOrder, |_, entry| {
+            // Submodule commits live in another repository.
+            if entry.kind() == Some(ObjectType::Commit) {
+                return TreeWalkResult::Skip;
+            }
+            let id = entry.id();
+            if !seen.insert(id) {
+                return if entry.kind() == Some(ObjectType::Tree) {
+                    TreeWalkResult::Skip
+                } else {
+                    TreeWalkResult::Ok
+                };
+            }
+            if !odb.exists(id) {
+                note_missing(id, &mut missing);
+                return TreeWalkResult::Skip;
+            }
+            TreeWalkResult::Ok
+        });
+    }
+
+    let total_missing = missing.len();
+    for oid in missing.into_iter().take(MAX_MISSING_REPORTED) {
+        issues.push(issue(
+            "missing_object",
+            "error",
+            format!("Object {} is referenced but missing", oid),
+            "Fetch from a remote that has the object or restore it from a backup; `git fsck --full` shows which commits need it"
+                .to_string(),
+        ));
+    }
+    if total_missing > MAX_MISSING_REPORTED {
+        issues.push(issue(
+            "missing_object",
+            "error",
+            format!(
+                "{} more missing objects were not listed",
+                total_missing - MAX_MISSING_REPORTED
+            ),
+            "Run `git fsck --full` for the complete list".to_string(),
+        ));
+    }
+
+    seen.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &PathBuf) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn create_test_repo() -> PathBuf {
+        let test_dir =
+            std::env::temp_dir().join(format!("gitlite-health-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&test_dir).unwrap();
+        run_git(&["init"], &test_dir);
+        run_git(&["config", "user.name", "Test User"], &test_dir);
+        run_git(&["config", "user.email", "test@example.com"], &test_dir);
+        fs::write(test_dir.join("a.txt"), "alpha").unwrap();
+        run_git(&["add", "."], &test_dir);
+        run_git(&["commit", "-m", "Initial commit"], &test_dir);
+        test_dir
+    }
+
+    #[test]
+    fn test_check_repository_healthy() {
+        let test_repo = create_test_repo();
+
+        let report = check_repository(test_repo.to_str().unwrap()).unwrap();
+        assert!(report.healthy);
+        assert!(report.issues.is_empty());
+        assert_eq!(report.objects_checked, 3);
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_check_repository_reports_locks_dangling_refs_and_missing_objects() {
+        let test_repo = create_test_repo();
+        let git_dir = test_repo.join(".git");
+
+        fs::write(git_dir.join("index.lock"), "").unwrap();
+        fs::write(
+            git_dir.join("refs/heads/broken"),
+            "1111111111111111111111111111111111111111\n",
+        )
+        .unwrap();
+        let blob = run_git(&["rev-parse", "HEAD:a.txt"], &test_repo);
+        fs::remove_file(git_dir.join("objects").join(&blob[..2]).join(&blob[2..])).unwrap();
+
+        let report = check_repository(test_repo.to_str().unwrap()).unwrap();
+        let kinds: Vec<&str> = report.issues.iter().map(|i| i.kind.as_str()).collect();
+        assert!(!report.healthy);
+        assert!(kinds.contains(&"stale_lock"));
+        assert!(kinds.contains(&"dangling_ref"));
+        assert!(report
+            .issues
+            .iter()
+            .any(|i| i.kind == "missing_object" && i.message.contains(&blob)));
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+}
@@ -1,4 +1,8 @@
-use git2::{build::CheckoutBuilder, Repository, ResetType};
+use super::index_backup::backup_index;
+use super::repository::{ensure_worktree, report_checkout_progress, CheckoutProgress};
+use super::staging::{author_signature, CommitOptions};
+use git2::{build::CheckoutBuilder, Oid, Repository, ResetType, Sort};
+use std::collections::HashMap;
 
 fn ensure_branch_head(repo: &Repository) -> Result<(), String> {
     let head = repo
@@ -10,7 +14,12 @@ fn ensure_branch_head(repo: &Repository) -> Result<(), String> {
     Ok(())
 }
 
-pub fn reset_current_branch(path: &str, commit_hash: &str, mode: &str) -> Result<(), String> {
+pub fn reset_current_branch(
+    path: &str,
+    commit_hash: &str,
+    mode: &str,
+    on_progress: impl FnMut(&CheckoutProgress),
+) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
     ensure_branch_head(&repo)?;
 
@@ -36,8 +45,14 @@ pub fn reset_current_branch(path: &str, commit_hash: &str, mode: &str) -> Result
         }
     };
 
+    if !matches!(reset_type, ResetType::Soft) {
+        ensure_worktree(&repo, "Mixed and hard reset")?;
+        backup_index(&repo, &format!("{} reset", mode))?;
+    }
+
     let mut checkout = CheckoutBuilder::new();
     checkout.force();
+    report_checkout_progress(&mut checkout, on_progress);
 
     let checkout_opt = if matches!(reset_type, ResetType::Hard) {
         Some(&mut checkout)
@@ -53,6 +68,7 @@ pub fn reset_current_branch(path: &str, commit_hash: &str, mode: &str) -> Result
 
 pub fn cherry_pick_commit(path: &str, commit_hash: &str) -> Result<String, String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Cherry-pick")?;
     ensure_branch_head(&repo)?;
 
     let oid = git2::Oid::from_str(commit_hash).map_err(|e| {
@@ -141,8 +157,13 @@ pub fn create_branch_from_commit(path: &str, name: &str, commit_hash: &str) -> R
     Ok(())
 }
 
-pub fn checkout_commit(path: &str, commit_hash: &str) -> Result<(), String> {
+pub fn checkout_commit(
+    path: &str,
+    commit_hash: &str,
+    on_progress: impl FnMut(&CheckoutProgress),
+) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Checkout")?;
 
     let oid = git2::Oid::from_str(commit_hash).map_err(|e| {
         format!(
@@ -156,6 +177,7 @@ pub fn checkout_commit(path: &str, commit_hash: &str) -> Result<(), String> {
 
     let mut checkout = CheckoutBuilder::new();
     checkout.safe();
+    report_checkout_progress(&mut checkout, on_progress);
     repo.checkout_tree(commit.as_object(), Some(&mut checkout))
         .map_err(|e| format!("E_CHECKOUT_FAILED: {}", e))?;
     repo.set_head_detached(oid)
@@ -165,6 +187,7 @@ pub fn checkout_commit(path: &str, commit_hash: &str) -> Result<(), String> {
 
 pub fn revert_commit(path: &str, commit_hash: &str) -> Result<String, String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Revert")?;
     ensure_branch_head(&repo)?;
 
     let oid = git2::Oid::from_str(commit_hash).map_err(|e| {
@@ -226,6 +249,144 @@ pub fn revert_commit(path: &str, commit_hash: &str) -> Result<String, String> {
     Ok(new_oid.to_string())
 }
 
+/// Rewrite the tip commit of the current branch with the `author_*`
+/// overrides in `options` and the configured identity as committer. Tree,
+/// parents and message are kept; omitted author fields keep their old value.
+pub fn amend_commit_metadata(path: &str, options: &CommitOptions) -> Result<String, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_branch_head(&repo)?;
+
+    let mut head = repo
+        .head()
+        .map_err(|e| format!("E_HEAD_INVALID: failed to read HEAD: {}", e))?;
+    let tip = head
+        .peel_to_commit()
+        .map_err(|e| format!("E_HEAD_INVALID: HEAD has no commit: {}", e))?;
+    let author = author_signature(&tip.author(), options)?;
+    let committer = repo
+        .signature()
+        .map_err(|e| format!("E_AMEND_SIGNATURE: {}", e))?;
+
+    let parents = tip.parents().collect::<Vec<_>>();
+    let amended = repo
+        .commit(
+            None,
+            &author,
+            &committer,
+            tip.message_raw().unwrap_or_default(),
+            &tip.tree().map_err(|e| format!("E_AMEND_FAILED: {}", e))?,
+            &parents.iter().collect::<Vec<_>>(),
+        )
+        .map_err(|e| format!("E_AMEND_FAILED: {}", e))?;
+    head.set_target(amended, "amend: author and committer")
+        .map_err(|e| format!("E_AMEND_FAILED: {}", e))?;
+    Ok(amended.to_string())
+}
+
+/// Change a commit message on the current branch and return the new tip.
+/// HEAD is amended; for an older commit its descendants are recreated on
+/// top of the reworded one. Trees are reused as-is, so the worktree and
+/// index are untouched and merges keep their shape.
+pub fn reword_commit(path: &str, commit_hash: &str, new_message: &str) -> Result<String, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_branch_head(&repo)?;
+
+    let message = new_message.trim();
+    if message.is_empty() {
+        return Err("E_REWORD_EMPTY_MESSAGE: commit message is empty".to_string());
+    }
+    let oid = Oid::from_str(commit_hash).map_err(|e| {
+        format!(
+            "E_REWORD_BAD_HASH: invalid commit hash '{}': {}",
+            commit_hash, e
+        )
+    })?;
+    let target = repo
+        .find_commit(oid)
+        .map_err(|e| format!("E_REWORD_COMMIT_NOT_FOUND: {}", e))?;
+
+    let mut head = repo
+        .head()
+        .map_err(|e| format!("E_HEAD_INVALID: failed to read HEAD: {}", e))?;
+    let head_oid = head
+        .target()
+        .ok_or_else(|| "E_HEAD_INVALID: HEAD has no target".to_string())?;
+    let on_branch = head_oid == oid
+        || repo
+            .graph_descendant_of(head_oid, oid)
+            .map_err(|e| format!("E_REWORD_FAILED: {}", e))?;
+    if !on_branch {
+        return Err(format!(
+            "E_REWORD_NOT_ON_BRANCH: {} is not part of the current branch",
+            commit_hash
+        ));
+    }
+
+    let committer = repo
+        .signature()
+        .map_err(|e| format!("E_REWORD_SIGNATURE: {}", e))?;
+    let parents = target.parents().collect::<Vec<_>>();
+    let reworded = repo
+        .commit(
+            None,
+            &target.author(),
+            &committer,
+            message,
+            &target
+                .tree()
+                .map_err(|e| format!("E_REWORD_FAILED: {}", e))?,
+            &parents.iter().collect::<Vec<_>>(),
+        )
+        .map_err(|e| format!("E_REWORD_FAILED: {}", e))?;
+
+    let mut rewritten = HashMap::from([(oid, reworded)]);
+    let mut walk = repo
+        .revwalk()
+        .map_err(|e| format!("E_REWORD_FAILED: {}", e))?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .and_then(|_| walk.push(head_oid))
+        .and_then(|_| walk.hide(oid))
+        .map_err(|e| format!("E_REWORD_FAILED: {}", e))?;
+    for descendant in walk {
+        let descendant = descendant.map_err(|e| format!("E_REWORD_FAILED: {}", e))?;
+        let commit = repo
+            .find_commit(descendant)
+            .map_err(|e| format!("E_REWORD_FAILED: {}", e))?;
+        if !commit
+            .parent_ids()
+            .any(|parent| rewritten.contains_key(&parent))
+        {
+            continue;
+        }
+        let parents = commit
+            .parent_ids()
+            .map(|parent| repo.find_commit(*rewritten.get(&parent).unwrap_or(&parent)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("E_REWORD_FAILED: {}", e))?;
+        let new_oid = repo
+            .commit(
+                None,
+                &commit.author(),
+                &committer,
+                commit.message_raw().unwrap_or_default(),
+                &commit
+                    .tree()
+                    .map_err(|e| format!("E_REWORD_FAILED: {}", e))?,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .map_err(|e| format!("E_REWORD_FAILED: {}", e))?;
+        rewritten.insert(descendant, new_oid);
+    }
+
+    let new_tip = rewritten[&head_oid];
+    head.set_target(
+        new_tip,
+        &format!("reword: {}", target.summary().unwrap_or_default()),
+    )
+    .map_err(|e| format!("E_REWORD_FAILED: {}", e))?;
+    Ok(new_tip.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,7 +451,7 @@ mod tests {
         run_git(&repo, &["add", "."]);
         run_git(&repo, &["commit", "-m", "Third"]);
 
-        let result = reset_current_branch(repo.to_str().unwrap(), &second_hash, "hard");
+        let result = reset_current_branch(repo.to_str().unwrap(), &second_hash, "hard", |_| {});
         assert!(result.is_ok());
 
         let head = Command::new("git")
@@ -356,7 +517,7 @@ mod tests {
             .unwrap();
         let first_hash = String::from_utf8_lossy(&first.stdout).trim().to_string();
 
-        let result = checkout_commit(repo.to_str().unwrap(), &first_hash);
+        let result = checkout_commit(repo.to_str().unwrap(), &first_hash, |_| {});
         assert!(result.is_ok());
 
         let head_state = Command::new("git")
@@ -395,4 +556,108 @@ mod tests {
         assert!(show.status.success());
         fs::remove_dir_all(repo).unwrap();
     }
+
+    #[test]
+    fn test_reword_commit_rewrites_descendants() {
+        let repo = setup_repo();
+        let path = repo.to_str().unwrap();
+        let log = |repo: &PathBuf| {
+            let output = Command::new("git")
+                .args(["log", "--format=%s", "--first-parent"])
+                .current_dir(repo)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+
+        fs::write(repo.join("a.txt"), "v2\n").unwrap();
+        run_git(&repo, &["commit", "-am", "Secnod"]);
+        let typo = Repository::open(&repo)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap()
+            .to_string();
+        fs::write(repo.join("a.txt"), "v3\n").unwrap();
+        run_git(&repo, &["commit", "-am", "Third"]);
+        let head_before = Repository::open(&repo).unwrap().head().unwrap().target();
+
+        let tip = reword_commit(path, &typo, "Second\n").unwrap();
+        assert_eq!(log(&repo), "Third\nSecond\nInitial");
+        assert_ne!(Some(Oid::from_str(&tip).unwrap()), head_before);
+        let status = Command::new("git")
+            .args(["status", "--porcelain"])
+            .current_dir(&repo)
+            .output()
+            .unwrap();
+        assert!(status.stdout.is_empty());
+
+        let amended = reword_commit(path, &tip, "Third, reworded").unwrap();
+        assert_eq!(log(&repo), "Third, reworded\nSecond\nInitial");
+        let repo_handle = Repository::open(&repo).unwrap();
+        let head = repo_handle.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id().to_string(), amended);
+        assert_eq!(head.parent_id(0).unwrap().to_string(), {
+            let reworded = repo_handle
+                .find_commit(Oid::from_str(&tip).unwrap())
+                .unwrap();
+            reworded.parent_id(0).unwrap().to_string()
+        });
+
+        assert!(reword_commit(path, &typo, "Orphaned")
+            .unwrap_err()
+            .starts_with("E_REWORD_NOT_ON_BRANCH"));
+        assert!(reword_commit(path, &amended, "   ")
+            .unwrap_err()
+            .starts_with("E_REWORD_EMPTY_MESSAGE"));
+        fs::remove_dir_all(repo).unwrap();
+    }
+
+    #[test]
+    fn test_amend_commit_metadata_keeps_content() {
+        let repo = setup_repo();
+        let path = repo.to_str().unwrap();
+        let repo_handle = Repository::open(&repo).unwrap();
+        let before = repo_handle.head().unwrap().peel_to_commit().unwrap();
+        let (tree, message, parents) = (
+            before.tree_id(),
+            before.message_raw().unwrap().to_string(),
+            before.parent_ids().collect::<Vec<_>>(),
+        );
+        let original_time = before.author().when().seconds();
+
+        let options = CommitOptions {
+            author_email: Some("fixed@example.com".to_string()),
+            ..Default::default()
+        };
+        let tip = amend_commit_metadata(path, &options).unwrap();
+        let after = repo_handle.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(after.id().to_string(), tip);
+        assert_ne!(after.id(), before.id());
+        assert_eq!(after.tree_id(), tree);
+        assert_eq!(after.message_raw().unwrap(), message);
+        assert_eq!(after.parent_ids().collect::<Vec<_>>(), parents);
+        assert_eq!(after.author().email(), Some("fixed@example.com"));
+        assert_eq!(after.author().name(), before.author().name());
+        assert_eq!(after.author().when().seconds(), original_time);
+
+        let dated = CommitOptions {
+            author_time: Some(1_600_000_000),
+            author_offset_minutes: Some(120),
+            ..Default::default()
+        };
+        amend_commit_metadata(path, &dated).unwrap();
+        let author = repo_handle
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .author()
+            .to_owned();
+        assert_eq!(author.when().seconds(), 1_600_000_000);
+        assert_eq!(author.when().offset_minutes(), 120);
+        assert_eq!(author.email(), Some("fixed@example.com"));
+        fs::remove_dir_all(repo).unwrap();
+    }
 }
@@ -0,0 +1,234 @@
+use super::repository::ensure_worktree;
+use git2::{Index, IndexConflict, IndexEntry, Oid, Repository, Signature, Tree};
+use serde::Serialize;
+
+/// Ref holding a commit whose tree is the index as it was before the last
+/// staged-state-destroying operation.
+pub const INDEX_BACKUP_REF: &str = "refs/gitlite/index-backup";
+const MESSAGE_PREFIX: &str = "gitlite index backup before ";
+/// Marks a backup of an index with conflicts. Its tree then holds the
+/// resolved entries under `staged/` and each side of the conflicts under
+/// `ancestor/`, `ours/` and `theirs/`.
+const CONFLICTS_TRAILER: &str = "\n\nConflicts: yes";
+const CONFLICT_SIDES: [&str; 3] = ["ancestor", "ours", "theirs"];
+const STAGE_MASK: u16 = 0x3000;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct IndexBackup {
+    pub commit: String,
+    pub operation: String,
+    pub created_at: i64,
+}
+
+/// Snapshot the current index under `INDEX_BACKUP_REF`, conflicts included.
+pub(super) fn backup_index(repo: &Repository, operation: &str) -> Result<(), String> {
+    let index = repo
+        .index()
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    let (tree_id, trailer) = if index.has_conflicts() {
+        (write_conflicted_tree(repo, &index)?, CONFLICTS_TRAILER)
+    } else {
+        (write_entries(repo, index.iter())?, "")
+    };
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("GitLite", "gitlite@localhost"))
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+
+    let commit_id = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            &format!("{}{}{}", MESSAGE_PREFIX, operation, trailer),
+            &tree,
+            &parents,
+        )
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    repo.reference(INDEX_BACKUP_REF, commit_id, true, "index backup")
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    Ok(())
+}
+
+/// Tree of `entries`, all written at stage 0.
+fn write_entries(
+    repo: &Repository,
+    entries: impl IntoIterator<Item = IndexEntry>,
+) -> Result<Oid, String> {
+    let mut index = Index::new().map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    for mut entry in entries {
+        entry.flags &= !STAGE_MASK;
+        index
+            .add(&entry)
+            .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    }
+    index
+        .write_tree_to(repo)
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))
+}
+
+fn write_conflicted_tree(repo: &Repository, index: &Index) -> Result<Oid, String> {
+    let staged = write_entries(
+        repo,
+        index.iter().filter(|entry| entry.flags & STAGE_MASK == 0),
+    )?;
+    let mut sides: [Vec<IndexEntry>; 3] = Default::default();
+    for conflict in index
+        .conflicts()
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?
+    {
+        let IndexConflict {
+            ancestor,
+            our,
+            their,
+        } = conflict.map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+        for (side, entry) in [ancestor, our, their].into_iter().enumerate() {
+            sides[side].extend(entry);
+        }
+    }
+    let sides = sides.map(|entries| write_entries(repo, entries));
+
+    let mut builder = repo
+        .treebuilder(None)
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    for (name, tree) in [("staged", staged)]
+        .into_iter()
+        .map(|(name, tree)| (name, Ok(tree)))
+        .chain(CONFLICT_SIDES.into_iter().zip(sides))
+    {
+        builder
+            .insert(name, tree?, 0o040000)
+            .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    }
+    builder
+        .write()
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))
+}
+
+/// Rebuild a conflicted index from a tree written by `write_conflicted_tree`.
+fn read_conflicted_tree(repo: &Repository, tree: &Tree, index: &mut Index) -> Result<(), String> {
+    let subtree = |name: &str| {
+        tree.get_name(name)
+            .and_then(|entry| entry.to_object(repo).ok())
+            .and_then(|object| object.into_tree().ok())
+            .ok_or_else(|| format!("E_INDEX_BACKUP_FAILED: backup has no {} tree", name))
+    };
+    index
+        .read_tree(&subtree("staged")?)
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+
+    for (side, name) in (1u16..).zip(CONFLICT_SIDES) {
+        let mut entries = Index::new().map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+        entries
+            .read_tree(&subtree(name)?)
+            .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+        for mut entry in entries.iter() {
+            entry.flags = (entry.flags & !STAGE_MASK) | (side << 12);
+            index
+                .add(&entry)
+                .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace the index with the last backup. The working tree is untouched.
+pub fn restore_index_backup(path: &str) -> Result<IndexBackup, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Restoring the index")?;
+
+    let commit = repo
+        .find_reference(INDEX_BACKUP_REF)
+        .and_then(|reference| reference.peel_to_commit())
+        .map_err(|_| "E_INDEX_BACKUP_NOT_FOUND: no index backup exists".to_string())?;
+    let tree = commit
+        .tree()
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+    let message = commit.message().unwrap_or_default();
+    match message.strip_suffix(CONFLICTS_TRAILER) {
+        Some(_) => read_conflicted_tree(&repo, &tree, &mut index)?,
+        None => index
+            .read_tree(&tree)
+            .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?,
+    }
+    index
+        .write()
+        .map_err(|e| format!("E_INDEX_BACKUP_FAILED: {}", e))?;
+
+    let message = message.strip_suffix(CONFLICTS_TRAILER).unwrap_or(message);
+    Ok(IndexBackup {
+        commit: commit.id().to_string(),
+        operation: message
+            .strip_prefix(MESSAGE_PREFIX)
+            .unwrap_or(message)
+            .to_string(),
+        created_at: commit.time().seconds(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::reset_current_branch;
+    use std::fs;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &std::path::Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn hard_reset_backs_up_staged_changes_for_restore() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-index-backup-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        run_git(&["init"], &dir);
+        run_git(&["config", "user.name", "Test User"], &dir);
+        run_git(&["config", "user.email", "test@example.com"], &dir);
+        fs::write(dir.join("a.txt"), "one\n").unwrap();
+        run_git(&["add", "a.txt"], &dir);
+        run_git(&["commit", "-m", "init"], &dir);
+        let path = dir.to_str().unwrap();
+
+        assert!(restore_index_backup(path)
+            .unwrap_err()
+            .starts_with("E_INDEX_BACKUP_NOT_FOUND"));
+
+        fs::write(dir.join("a.txt"), "one\ntwo\n").unwrap();
+        run_git(&["add", "a.txt"], &dir);
+        let head = Repository::open(&dir)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap()
+            .to_string();
+        reset_current_branch(path, &head, "hard", |_| {}).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "one\n");
+
+        let backup = restore_index_backup(path).unwrap();
+        assert_eq!(backup.operation, "hard reset");
+        let repo = Repository::open(&dir).unwrap();
+        let index = repo.index().unwrap();
+        let entry = index.get_path(std::path::Path::new("a.txt"), 0).unwrap();
+        let blob = repo.find_blob(entry.id).unwrap();
+        assert_eq!(blob.content(), b"one\ntwo\n");
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "one\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,213 @@
+use super::repository::ensure_worktree;
+use git2::{Index, IndexEntry, IndexEntryExtendedFlag, IndexEntryFlag, Repository};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Per-entry bits that make git stop looking at a tracked file's worktree
+/// copy, so local edits stay out of status.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(super) struct IndexFlags {
+    pub skip_worktree: bool,
+    pub assume_unchanged: bool,
+}
+
+impl IndexFlags {
+    fn of(entry: &IndexEntry) -> Self {
+        IndexFlags {
+            skip_worktree: IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended)
+                .is_skip_worktree(),
+            assume_unchanged: IndexEntryFlag::from_bits_truncate(entry.flags).is_valid(),
+        }
+    }
+}
+
+/// Flags of the entry at `path`, without scanning the whole index.
+pub(super) fn flags_of(index: &Index, path: &str) -> IndexFlags {
+    index
+        .get_path(Path::new(path), 0)
+        .map(|entry| IndexFlags::of(&entry))
+        .unwrap_or_default()
+}
+
+/// Index entries with at least one of the flags set, by path. In a sparse
+/// checkout, skip-worktree entries missing from the worktree are the files
+/// the sparse patterns leave out rather than ones a user hid, so they are
+/// not listed.
+pub(super) fn flagged_entries(repo: &Repository) -> Result<HashMap<String, IndexFlags>, String> {
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let sparse_workdir = repo.workdir().filter(|_| sparse_checkout_enabled(repo));
+    Ok(index
+        .iter()
+        .filter_map(|entry| {
+            let flags = IndexFlags::of(&entry);
+            if flags == IndexFlags::default() {
+                return None;
+            }
+            let path = String::from_utf8_lossy(&entry.path).into_owned();
+            let sparse = flags.skip_worktree
+                && !flags.assume_unchanged
+                && sparse_workdir
+                    .is_some_and(|workdir| std::fs::symlink_metadata(workdir.join(&path)).is_err());
+            (!sparse).then_some((path, flags))
+        })
+        .collect())
+}
+
+/// `core.sparseCheckout`, which `git sparse-checkout` writes to the
+/// per-worktree `config.worktree` that libgit2 does not read.
+fn sparse_checkout_enabled(repo: &Repository) -> bool {
+    let enabled = |config: Result<git2::Config, git2::Error>| {
+        config
+            .and_then(|config| config.get_bool("core.sparseCheckout"))
+            .unwrap_or(false)
+    };
+    let worktree_config = repo.path().join("config.worktree");
+    enabled(repo.config())
+        || (worktree_config.is_file() && enabled(git2::Config::open(&worktree_config)))
+}
+
+/// Set or clear `skip-worktree` or `assume-unchanged` on tracked files.
+pub fn set_index_flag(
+    path: &str,
+    files: &[String],
+    flag: &str,
+    enabled: bool,
+) -> Result<(), String> {
+    if !matches!(flag, "skip-worktree" | "assume-unchanged") {
+        return Err(format!(
+            "E_INDEX_FLAG_UNKNOWN: '{}' (expected skip-worktree or assume-unchanged)",
+            flag
+        ));
+    }
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Changing index flags")?;
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    for file in files {
+        let mut entry = index
+            .get_path(Path::new(file), 0)
+            .ok_or_else(|| format!("E_INDEX_FLAG_NOT_TRACKED: '{}' is not in the index", file))?;
+        if flag == "skip-worktree" {
+            let mut bits = IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended);
+            bits.set(IndexEntryExtendedFlag::SKIP_WORKTREE, enabled);
+            entry.flags_extended = bits.bits();
+        } else {
+            let mut bits = IndexEntryFlag::from_bits_truncate(entry.flags);
+            bits.set(IndexEntryFlag::VALID, enabled);
+            entry.flags = bits.bits();
+        }
+        index
+            .add(&entry)
+            .map_err(|e| format!("E_INDEX_FLAG_WRITE: '{}': {}", file, e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("E_INDEX_FLAG_WRITE: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::{get_status, get_status_in, StatusQuery};
+    use std::fs;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &Path) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    #[test]
+    fn flags_hide_local_edits_and_show_in_status() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-index-flags-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        run_git(&["init"], &dir);
+        run_git(&["config", "user.name", "Test User"], &dir);
+        run_git(&["config", "user.email", "test@example.com"], &dir);
+        fs::write(dir.join("config.toml"), "debug = false\n").unwrap();
+        fs::write(dir.join("local.env"), "A=1\n").unwrap();
+        run_git(&["add", "."], &dir);
+        run_git(&["commit", "-m", "init"], &dir);
+        let path = dir.to_str().unwrap();
+
+        set_index_flag(path, &["config.toml".to_string()], "skip-worktree", true).unwrap();
+        set_index_flag(path, &["local.env".to_string()], "assume-unchanged", true).unwrap();
+        fs::write(dir.join("config.toml"), "debug = true\n").unwrap();
+        fs::write(dir.join("local.env"), "A=2\n").unwrap();
+
+        let files = run_git(&["ls-files", "-v"], &dir);
+        assert!(files.contains("S config.toml"), "{}", files);
+        assert!(files.contains("h local.env"), "{}", files);
+        assert!(run_git(&["status", "--porcelain"], &dir).is_empty());
+
+        assert!(get_status(path).unwrap().is_empty());
+        let repo = Repository::open(&dir).unwrap();
+        let with_hidden = StatusQuery {
+            include_hidden: true,
+            ..StatusQuery::default()
+        };
+        let status = get_status_in(&repo, &with_hidden).unwrap();
+        assert_eq!(status.len(), 2);
+        assert!(status.iter().all(|file| file.status == "hidden"));
+        assert!(status[0].skip_worktree && !status[0].assume_unchanged);
+        assert!(status[1].assume_unchanged && !status[1].skip_worktree);
+
+        set_index_flag(path, &["config.toml".to_string()], "skip-worktree", false).unwrap();
+        let status = get_status(path).unwrap();
+        let config = status
+            .iter()
+            .find(|file| file.path == "config.toml")
+            .unwrap();
+        assert_eq!(config.status, "modified");
+        assert!(!config.skip_worktree);
+
+        assert!(
+            set_index_flag(path, &["missing".to_string()], "skip-worktree", true)
+                .unwrap_err()
+                .starts_with("E_INDEX_FLAG_NOT_TRACKED")
+        );
+        assert!(set_index_flag(path, &[], "sparse", true)
+            .unwrap_err()
+            .starts_with("E_INDEX_FLAG_UNKNOWN"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn files_outside_a_sparse_checkout_are_not_hidden_entries() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-index-sparse-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        run_git(&["init"], &dir);
+        run_git(&["config", "user.name", "Test User"], &dir);
+        run_git(&["config", "user.email", "test@example.com"], &dir);
+        fs::write(dir.join("app.toml"), "a\n").unwrap();
+        fs::write(dir.join("docs/guide.md"), "b\n").unwrap();
+        run_git(&["add", "."], &dir);
+        run_git(&["commit", "-m", "init"], &dir);
+        run_git(
+            &["sparse-checkout", "set", "--no-cone", "/*", "!/docs/"],
+            &dir,
+        );
+        assert!(!dir.join("docs/guide.md").exists());
+        let path = dir.to_str().unwrap();
+        set_index_flag(path, &["app.toml".to_string()], "skip-worktree", true).unwrap();
+
+        let repo = Repository::open(&dir).unwrap();
+        let flagged = flagged_entries(&repo).unwrap();
+        assert_eq!(flagged.keys().collect::<Vec<_>>(), vec!["app.toml"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,370 @@
+use git2::{Repository, RepositoryInitOptions};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FALLBACK_BRANCH: &str = "master";
+
+const GITIGNORE_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "go",
+        "# Binaries\n*.exe\n*.test\n*.out\n/bin/\n\n# Dependencies\n/vendor/\n",
+    ),
+    (
+        "java",
+        "*.class\n*.jar\n*.war\n/target/\n/build/\n.gradle/\n.idea/\n",
+    ),
+    (
+        "macos",
+        ".DS_Store\n.AppleDouble\n.LSOverride\n._*\n.Spotlight-V100\n.Trashes\n",
+    ),
+    (
+        "node",
+        "node_modules/\ndist/\ncoverage/\n.env\nnpm-debug.log*\nyarn-debug.log*\nyarn-error.log*\n",
+    ),
+    (
+        "python",
+        "__pycache__/\n*.py[cod]\n.venv/\nvenv/\nbuild/\ndist/\n*.egg-info/\n.pytest_cache/\n",
+    ),
+    ("rust", "/target/\n**/*.rs.bk\n"),
+    (
+        "windows",
+        "Thumbs.db\nehthumbs.db\nDesktop.ini\n$RECYCLE.BIN/\n",
+    ),
+];
+
+const LICENSES: &[&str] = &["Apache-2.0", "MIT", "Unlicense"];
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct InitOptions {
+    /// Defaults to `init.defaultBranch`, then `master`.
+    pub initial_branch: Option<String>,
+    /// Write a `README.md` titled after the folder.
+    #[serde(default)]
+    pub readme: bool,
+    /// Names from [`init_templates`], concatenated into `.gitignore`.
+    #[serde(default)]
+    pub gitignore_templates: Vec<String>,
+    /// SPDX id from [`init_templates`].
+    pub license: Option<String>,
+    /// Copyright holder for the license; defaults to `user.name`.
+    pub author: Option<String>,
+    /// Commit the generated files on the new branch.
+    #[serde(default)]
+    pub initial_commit: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct InitResult {
+    pub branch: String,
+    /// Starter files written; existing files are never overwritten.
+    pub created_files: Vec<String>,
+    pub commit: Option<String>,
+}
+
+/// Catalog offered by the "new repository" form.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct InitTemplates {
+    pub gitignore: Vec<String>,
+    pub licenses: Vec<String>,
+}
+
+pub fn init_templates() -> InitTemplates {
+    InitTemplates {
+        gitignore: GITIGNORE_TEMPLATES
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        licenses: LICENSES.iter().map(|id| id.to_string()).collect(),
+    }
+}
+
+/// `git init` with an initial branch name, optional starter files and an
+/// optional first commit. Re-running it on an existing repository keeps its
+/// history and branch.
+pub fn init_repository(path: &str, options: &InitOptions) -> Result<InitResult, String> {
+    let branch = match options.initial_branch.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => default_branch(),
+    };
+    if !git2::Branch::name_is_valid(&branch).unwrap_or(false) {
+        return Err(format!(
+            "E_INIT_BRANCH_INVALID: '{}' is not a valid branch name",
+            branch
+        ));
+    }
+    let gitignore = options
+        .gitignore_templates
+        .iter()
+        .map(|name| gitignore_template(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(id) = &options.license {
+        if !LICENSES.iter().any(|known| known.eq_ignore_ascii_case(id)) {
+            return Err(format!("E_INIT_LICENSE_UNKNOWN: no license '{}'", id));
+        }
+    }
+
+    let mut init = RepositoryInitOptions::new();
+    init.initial_head(&branch);
+    let repo = Repository::init_opts(path, &init)
+        .map_err(|e| format!("Failed to initialize repository: {}", e))?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| "Failed to initialize repository: no working directory".to_string())?
+        .to_path_buf();
+
+    let mut files = Vec::new();
+    if options.readme {
+        let title = workdir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Project".to_string());
+        files.push(("README.md", format!("# {}\n", title)));
+    }
+    if !gitignore.is_empty() {
+        files.push((".gitignore", gitignore.join("\n")));
+    }
+    if let Some(id) = &options.license {
+        let author = options
+            .author
+            .clone()
+            .filter(|author| !author.trim().is_empty())
+            .or_else(|| repo.config().ok()?.get_string("user.name").ok())
+            .unwrap_or_else(|| "the authors".to_string());
+        files.push(("LICENSE", license_text(id, current_year(), author.trim())));
+    }
+
+    let mut created_files = Vec::new();
+    for (name, content) in files {
+        let target = workdir.join(name);
+        if target.exists() {
+            continue;
+        }
+        fs::write(&target, content)
+            .map_err(|e| format!("E_INIT_WRITE_FAILED: cannot write {}: {}", name, e))?;
+        created_files.push(name.to_string());
+    }
+
+    let commit = if options.initial_commit {
+        Some(initial_commit(&repo, &created_files)?)
+    } else {
+        None
+    };
+
+    Ok(InitResult {
+        branch: current_branch(&repo).unwrap_or(branch),
+        created_files,
+        commit,
+    })
+}
+
+fn default_branch() -> String {
+    git2::Config::open_default()
+        .and_then(|config| config.get_string("init.defaultBranch"))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| FALLBACK_BRANCH.to_string())
+}
+
+fn current_branch(repo: &Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    let target = head.symbolic_target()?;
+    target.strip_prefix("refs/heads/").map(str::to_string)
+}
+
+fn gitignore_template(name: &str) -> Result<&'static str, String> {
+    GITIGNORE_TEMPLATES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name.trim()))
+        .map(|(_, template)| *template)
+        .ok_or_else(|| format!("E_INIT_TEMPLATE_UNKNOWN: no .gitignore template '{}'", name))
+}
+
+/// Commit `files` as the root commit. Refused once the branch has history.
+fn initial_commit(repo: &Repository, files: &[String]) -> Result<String, String> {
+    if !repo
+        .head()
+        .is_err_and(|e| e.code() == git2::ErrorCode::UnbornBranch)
+    {
+        return Err("E_INIT_HAS_COMMITS: the repository already has commits".to_string());
+    }
+    let signature = repo
+        .signature()
+        .map_err(|e| format!("Failed to get signature: {}", e))?;
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    for file in files {
+        index
+            .add_path(Path::new(file))
+            .map_err(|e| format!("Failed to stage {}: {}", file, e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+    let tree_id = index
+        .write_tree()
+        .map_err(|e| format!("Failed to write tree: {}", e))?;
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| format!("Failed to find tree: {}", e))?;
+    let oid = repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )
+        .map_err(|e| format!("Failed to create commit: {}", e))?;
+    Ok(oid.to_string())
+}
+
+fn license_text(id: &str, year: i64, author: &str) -> String {
+    if id.eq_ignore_ascii_case("MIT") {
+        format!(
+            "MIT License\n\n\
+             Copyright (c) {year} {author}\n\n\
+             Permission is hereby granted, free of charge, to any person obtaining a copy\n\
+             of this software and associated documentation files (the \"Software\"), to deal\n\
+             in the Software without restriction, including without limitation the rights\n\
+             to use, copy, modify, merge, publish, distribute, sublicense, and/or sell\n\
+             copies of the Software, and to permit persons to whom the Software is\n\
+             furnished to do so, subject to the following conditions:\n\n\
+             The above copyright notice and this permission notice shall be included in all\n\
+             copies or substantial portions of the Software.\n\n\
+             THE SOFTWARE IS PROVIDED \"AS IS\", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR\n\
+             IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,\n\
+             FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE\n\
+             AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER\n\
+             LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,\n\
+             OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE\n\
+             SOFTWARE.\n"
+        )
+    } else if id.eq_ignore_ascii_case("Apache-2.0") {
+        format!(
+            "Copyright {year} {author}\n\n\
+             Licensed under the Apache License, Version 2.0 (the \"License\");\n\
+             you may not use this file except in compliance with the License.\n\
+             You may obtain a copy of the License at\n\n    \
+             http://www.apache.org/licenses/LICENSE-2.0\n\n\
+             Unless required by applicable law or agreed to in writing, software\n\
+             distributed under the License is distributed on an \"AS IS\" BASIS,\n\
+             WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.\n\
+             See the License for the specific language governing permissions and\n\
+             limitations under the License.\n"
+        )
+    } else {
+        "This is free and unencumbered software released into the public domain.\n\n\
+         Anyone is free to copy, modify, publish, use, compile, sell, or distribute this\n\
+         software, either in source code form or as a compiled binary, for any purpose,\n\
+         commercial or non-commercial, and by any means.\n\n\
+         For more information, please refer to <https://unlicense.org>\n"
+            .to_string()
+    }
+}
+
+fn current_year() -> i64 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+    // Civil-from-days (Howard Hinnant), reduced to the year.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let month_index = (5 * doy + 2) / 153;
+    yoe + era * 400 + i64::from(month_index >= 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_writes_starter_files_and_initial_commit() {
+        let dir = std::env::temp_dir().join(format!("gitlite-init-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("README.md"), "mine\n").unwrap();
+        let path = dir.to_str().unwrap();
+
+        let options = InitOptions {
+            initial_branch: Some("trunk".to_string()),
+            readme: true,
+            gitignore_templates: vec!["Rust".to_string(), "macos".to_string()],
+            license: Some("mit".to_string()),
+            author: Some("Octo Cat".to_string()),
+            initial_commit: true,
+        };
+        // An identity is needed for the commit; re-initializing keeps config.
+        let mut init = RepositoryInitOptions::new();
+        init.initial_head("trunk");
+        let repo = Repository::init_opts(path, &init).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Tester").unwrap();
+        config.set_str("user.email", "tester@example.com").unwrap();
+
+        let result = init_repository(path, &options).unwrap();
+        assert_eq!(result.branch, "trunk");
+        assert_eq!(result.created_files, vec![".gitignore", "LICENSE"]);
+        assert_eq!(fs::read_to_string(dir.join("README.md")).unwrap(), "mine\n");
+        let gitignore = fs::read_to_string(dir.join(".gitignore")).unwrap();
+        assert!(gitignore.contains("/target/") && gitignore.contains(".DS_Store"));
+        let license = fs::read_to_string(dir.join("LICENSE")).unwrap();
+        assert!(license.contains(&format!("Copyright (c) {} Octo Cat", current_year())));
+
+        let head = repo.head().unwrap();
+        assert_eq!(head.shorthand(), Some("trunk"));
+        let commit = head.peel_to_commit().unwrap();
+        assert_eq!(Some(commit.id().to_string()), result.commit);
+        assert_eq!(commit.tree().unwrap().len(), 2, "README.md stays untracked");
+
+        let again = init_repository(path, &options).unwrap_err();
+        assert!(again.starts_with("E_INIT_HAS_COMMITS"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn init_rejects_unknown_options() {
+        let dir = std::env::temp_dir().join(format!("gitlite-init-{}", uuid::Uuid::new_v4()));
+        let path = dir.to_str().unwrap();
+        let reject = |options: InitOptions, code: &str| {
+            assert!(init_repository(path, &options)
+                .unwrap_err()
+                .starts_with(code));
+        };
+        reject(
+            InitOptions {
+                initial_branch: Some("bad..name".to_string()),
+                ..Default::default()
+            },
+            "E_INIT_BRANCH_INVALID",
+        );
+        reject(
+            InitOptions {
+                gitignore_templates: vec!["cobol".to_string()],
+                ..Default::default()
+            },
+            "E_INIT_TEMPLATE_UNKNOWN",
+        );
+        reject(
+            InitOptions {
+                license: Some("GPL-9".to_string()),
+                ..Default::default()
+            },
+            "E_INIT_LICENSE_UNKNOWN",
+        );
+        assert!(
+            !dir.exists(),
+            "validation happens before anything is created"
+        );
+        let templates = init_templates();
+        assert!(templates.licenses.contains(&"MIT".to_string()));
+        assert!(templates.gitignore.contains(&"rust".to_string()));
+    }
+}
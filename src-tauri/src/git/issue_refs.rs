@@ -0,0 +1,188 @@
+use super::remote_url::{classify_provider, web_base_url};
+use git2::Repository;
+use regex::Regex;
+use serde::Serialize;
+
+/// Tracker base URL (e.g. `https://acme.atlassian.net`) that enables
+/// `ABC-123` style Jira keys.
+const JIRA_URL_KEY: &str = "gitlite.jiraUrl";
+/// Multivar of `<regex> <url template>`; capture group 1 (or the whole match)
+/// replaces `{id}` in the template.
+const ISSUE_PATTERN_KEY: &str = "gitlite.issuePattern";
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct IssueRef {
+    /// Text as it appears in the message, e.g. `#123` or `PROJ-45`.
+    pub text: String,
+    pub id: String,
+    pub provider: String, // "github" | "gitlab" | "bitbucket" | "jira" | "custom" | "other"
+    pub url: Option<String>,
+}
+
+struct Pattern {
+    regex: Regex,
+    provider: String,
+    url_template: Option<String>,
+}
+
+/// Issue/PR reference patterns for one repository: `#123` and `GH-123` linked
+/// to the `origin` host, plus Jira keys and custom patterns from git config.
+pub struct IssueMatcher {
+    patterns: Vec<Pattern>,
+}
+
+impl IssueMatcher {
+    pub fn for_repository(repo: &Repository) -> Self {
+        let remote_url = repo
+            .find_remote("origin")
+            .ok()
+            .and_then(|remote| remote.url().map(str::to_string));
+        let provider = remote_url
+            .as_deref()
+            .map(classify_provider)
+            .unwrap_or("other");
+        let issue_base = remote_url
+            .as_deref()
+            .and_then(web_base_url)
+            .map(|base| match provider {
+                "gitlab" => format!("{}/-/issues/{{id}}", base),
+                _ => format!("{}/issues/{{id}}", base),
+            })
+            .filter(|_| provider != "other");
+
+        let mut patterns = vec![Pattern {
+            regex: Regex::new(r"(?:^|[^\w&/])(?:#|GH-)(\d+)\b").expect("valid issue regex"),
+            provider: provider.to_string(),
+            url_template: issue_base,
+        }];
+
+        if let Ok(config) = repo.config() {
+            if let Ok(jira_url) = config.get_string(JIRA_URL_KEY) {
+                let base = jira_url.trim().trim_end_matches('/').to_string();
+                if !base.is_empty() {
+                    patterns.push(Pattern {
+                        regex: Regex::new(r"\b([A-Z][A-Z0-9]+-\d+)\b").expect("valid jira regex"),
+                        provider: "jira".to_string(),
+                        url_template: Some(format!("{}/browse/{{id}}", base)),
+                    });
+                }
+            }
+
+            if let Ok(entries) = config.multivar(ISSUE_PATTERN_KEY, None) {
+                let _ = entries.for_each(|entry| {
+                    if let Some(pattern) = entry.value().and_then(parse_custom_pattern) {
+                        patterns.push(pattern);
+                    }
+                });
+            }
+        }
+
+        IssueMatcher { patterns }
+    }
+
+    pub fn extract(&self, message: &str) -> Vec<IssueRef> {
+        let mut refs: Vec<IssueRef> = Vec::new();
+        for pattern in &self.patterns {
+            for captures in pattern.regex.captures_iter(message) {
+                let whole = captures.get(0).map(|m| m.as_str()).unwrap_or("");
+                let id = captures
+                    .get(1)
+                    .map(|m| m.as_str())
+                    .unwrap_or(whole)
+                    .to_string();
+                let text = whole
+                    .trim_start_matches(|c: char| !c.is_alphanumeric() && c != '#')
+                    .to_string();
+                if text.is_empty() || refs.iter().any(|existing| existing.text == text) {
+                    continue;
+                }
+                refs.push(IssueRef {
+                    url: pattern
+                        .url_template
+                        .as_ref()
+                        .map(|template| template.replace("{id}", &id)),
+                    text,
+                    id,
+                    provider: pattern.provider.clone(),
+                });
+            }
+        }
+        refs
+    }
+}
+
+fn parse_custom_pattern(value: &str) -> Option<Pattern> {
+    let (regex, template) = match value.trim().rsplit_once(char::is_whitespace) {
+        Some((regex, template)) if template.contains("{id}") => (regex.trim(), Some(template)),
+        _ => (value.trim(), None),
+    };
+    Some(Pattern {
+        regex: Regex::new(regex).ok()?,
+        provider: "custom".to_string(),
+        url_template: template.map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn repo_with_config(config: &[(&str, &str)]) -> (std::path::PathBuf, Repository) {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-issue-refs-test-{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        {
+            let mut cfg = repo.config().unwrap();
+            for (key, value) in config {
+                cfg.set_multivar(key, "^$", value).unwrap();
+            }
+        }
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_extract_links_github_and_jira_references() {
+        let (dir, repo) = repo_with_config(&[
+            ("remote.origin.url", "git@github.com:octo/app.git"),
+            ("gitlite.jiraUrl", "https://acme.atlassian.net/"),
+        ]);
+
+        let matcher = IssueMatcher::for_repository(&repo);
+        let refs = matcher.extract("Fix crash (#12, GH-7) for PROJ-45; see a#3");
+
+        let texts: Vec<&str> = refs.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, vec!["#12", "GH-7", "PROJ-45"]);
+        assert_eq!(
+            refs[0].url.as_deref(),
+            Some("https://github.com/octo/app/issues/12")
+        );
+        assert_eq!(refs[2].provider, "jira");
+        assert_eq!(
+            refs[2].url.as_deref(),
+            Some("https://acme.atlassian.net/browse/PROJ-45")
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_extract_custom_pattern_and_unlinked_remote() {
+        let (dir, repo) = repo_with_config(&[(
+            "gitlite.issuePattern",
+            "TICKET/([0-9]+) https://tracker.example.com/t/{id}",
+        )]);
+
+        let refs = IssueMatcher::for_repository(&repo).extract("Closes #4 and TICKET/99");
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].url, None);
+        assert_eq!(refs[1].provider, "custom");
+        assert_eq!(refs[1].id, "99");
+        assert_eq!(
+            refs[1].url.as_deref(),
+            Some("https://tracker.example.com/t/99")
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,309 @@
+use super::mirror::unattended_callbacks;
+use crate::json_store::unix_now;
+use git2::{FetchOptions, FetchPrune, ObjectType, Oid, PushOptions, Repository};
+use serde::{Deserialize, Serialize};
+
+const LOCKS_PREFIX: &str = "refs/gitlite/locks/";
+
+/// Advisory lock on one file. Each lock is a ref under `refs/gitlite/locks/`
+/// pointing at a parentless commit whose message is the lock as JSON, so it
+/// can be shared through any git remote and a competing lock is rejected as
+/// non-fast-forward.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileLock {
+    pub path: String,
+    pub owner_name: String,
+    pub owner_email: String,
+    pub locked_at: i64,
+    /// Whether the lock belongs to the configured `user.email`.
+    #[serde(skip_deserializing, default)]
+    pub is_mine: bool,
+}
+
+/// Lock `file`. With a `remote`, locks are fetched first and the new lock is
+/// pushed without force, so a lock taken elsewhere wins.
+pub fn lock_file(path: &str, file: &str, remote: Option<&str>) -> Result<FileLock, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let file = normalize_file(file)?;
+    let (owner_name, owner_email) = identity(&repo)?;
+    if let Some(remote) = remote {
+        fetch_locks(&repo, remote)?;
+    }
+
+    let refname = lock_refname(&file);
+    if let Some(existing) = read_lock(&repo, &refname, &owner_email) {
+        if existing.is_mine {
+            return Ok(existing);
+        }
+        return Err(held_error(&existing));
+    }
+
+    let lock = FileLock {
+        path: file,
+        owner_name,
+        owner_email,
+        locked_at: unix_now(),
+        is_mine: true,
+    };
+    let json = serde_json::to_string(&lock).map_err(|e| format!("Failed to encode lock: {}", e))?;
+    let signature = repo
+        .signature()
+        .map_err(|e| format!("Failed to write lock: {}", e))?;
+    let empty_tree = repo
+        .treebuilder(None)
+        .and_then(|builder| builder.write())
+        .and_then(|id| repo.find_tree(id))
+        .map_err(|e| format!("Failed to write lock: {}", e))?;
+    repo.commit(
+        Some(&refname),
+        &signature,
+        &signature,
+        &json,
+        &empty_tree,
+        &[],
+    )
+    .map_err(|e| format!("Failed to write lock: {}", e))?;
+
+    if let Some(remote) = remote {
+        if let Err(error) = push_lock_ref(&repo, remote, &format!("{}:{}", refname, refname)) {
+            if let Ok(mut reference) = repo.find_reference(&refname) {
+                let _ = reference.delete();
+            }
+            return Err(error);
+        }
+    }
+    Ok(lock)
+}
+
+/// Release the lock on `file`. Locks owned by someone else need `force`.
+pub fn unlock_file(
+    path: &str,
+    file: &str,
+    remote: Option<&str>,
+    force: bool,
+) -> Result<(), String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let file = normalize_file(file)?;
+    let (_, owner_email) = identity(&repo)?;
+    if let Some(remote) = remote {
+        fetch_locks(&repo, remote)?;
+    }
+
+    let refname = lock_refname(&file);
+    let lock = read_lock(&repo, &refname, &owner_email)
+        .ok_or_else(|| format!("E_LOCK_NOT_FOUND: '{}' is not locked", file))?;
+    if !lock.is_mine && !force {
+        return Err(format!(
+            "E_LOCK_NOT_OWNER: '{}' is locked by {} <{}>",
+            lock.path, lock.owner_name, lock.owner_email
+        ));
+    }
+
+    if let Some(remote) = remote {
+        push_lock_ref(&repo, remote, &format!(":{}", refname))?;
+    }
+    repo.find_reference(&refname)
+        .and_then(|mut reference| reference.delete())
+        .map_err(|e| format!("Failed to remove lock: {}", e))
+}
+
+/// All known locks, refreshed from `remote` first when given.
+pub fn list_locks(path: &str, remote: Option<&str>) -> Result<Vec<FileLock>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    if let Some(remote) = remote {
+        fetch_locks(&repo, remote)?;
+    }
+    let owner_email = identity(&repo).map(|(_, email)| email).unwrap_or_default();
+
+    let mut locks: Vec<FileLock> = repo
+        .references_glob(&format!("{}*", LOCKS_PREFIX))
+        .map_err(|e| format!("Failed to list locks: {}", e))?
+        .flatten()
+        .filter_map(|reference| {
+            reference
+                .name()
+                .and_then(|name| read_lock(&repo, name, &owner_email))
+        })
+        .collect();
+    locks.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(locks)
+}
+
+/// Locks held by someone else on any of `files`, from the local registry
+/// only (no network), for warnings before staging.
+pub fn locked_by_others(path: &str, files: &[String]) -> Result<Vec<FileLock>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let owner_email = identity(&repo).map(|(_, email)| email).unwrap_or_default();
+    Ok(files
+        .iter()
+        .filter_map(|file| normalize_file(file).ok())
+        .filter_map(|file| read_lock(&repo, &lock_refname(&file), &owner_email))
+        .filter(|lock| !lock.is_mine)
+        .collect())
+}
+
+fn read_lock(repo: &Repository, refname: &str, owner_email: &str) -> Option<FileLock> {
+    let target = repo.refname_to_id(refname).ok()?;
+    let commit = repo.find_commit(target).ok()?;
+    let mut lock: FileLock = serde_json::from_slice(commit.message_bytes()).ok()?;
+    lock.is_mine = !owner_email.is_empty() && lock.owner_email.eq_ignore_ascii_case(owner_email);
+    Some(lock)
+}
+
+fn fetch_locks(repo: &Repository, remote_name: &str) -> Result<(), String> {
+    let mut remote = repo
+        .find_remote(remote_name)
+        .map_err(|e| format!("E_LOCK_SYNC: remote '{}': {}", remote_name, e))?;
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read repository config: {}", e))?;
+
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(unattended_callbacks(
+        &config,
+        remote.url().unwrap_or_default(),
+    ));
+    options.prune(FetchPrune::On);
+    options.download_tags(git2::AutotagOption::None);
+    let refspec = format!("+{}*:{}*", LOCKS_PREFIX, LOCKS_PREFIX);
+    remote
+        .fetch(&[refspec.as_str()], Some(&mut options), None)
+        .map_err(|e| format!("E_LOCK_SYNC: failed to fetch locks: {}", e))
+}
+
+fn push_lock_ref(repo: &Repository, remote_name: &str, refspec: &str) -> Result<(), String> {
+    let mut remote = repo
+        .find_remote(remote_name)
+        .map_err(|e| format!("E_LOCK_SYNC: remote '{}': {}", remote_name, e))?;
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read repository config: {}", e))?;
+
+    let mut rejected: Option<String> = None;
+    let push_result = {
+        let mut callbacks = unattended_callbacks(
+            &config,
+            remote.pushurl().or(remote.url()).unwrap_or_default(),
+        );
+        callbacks.push_update_reference(|_refname, status| {
+            if let Some(status) = status {
+                rejected = Some(status.to_string());
+            }
+            Ok(())
+        });
+        let mut options = PushOptions::new();
+        options.remote_callbacks(callbacks);
+        remote.push(&[refspec], Some(&mut options))
+    };
+
+    match (push_result, rejected) {
+        (Ok(()), None) => Ok(()),
+        (Ok(()), Some(status)) => Err(format!(
+            "E_LOCK_HELD: remote rejected the lock update ({}); refresh locks and retry",
+            status
+        )),
+        (Err(error), _) if error.code() == git2::ErrorCode::NotFastForward => Err(
+            "E_LOCK_HELD: the lock was taken on the remote; refresh locks and retry".to_string(),
+        ),
+        (Err(error), _) => Err(format!("E_LOCK_SYNC: failed to push lock: {}", error)),
+    }
+}
+
+fn identity(repo: &Repository) -> Result<(String, String), String> {
+    let signature = repo.signature().map_err(|e| {
+        format!(
+            "E_LOCK_IDENTITY: set user.name and user.email to use locks: {}",
+            e
+        )
+    })?;
+    Ok((
+        signature.name().unwrap_or("").to_string(),
+        signature.email().unwrap_or("").to_string(),
+    ))
+}
+
+fn normalize_file(file: &str) -> Result<String, String> {
+    let file = file.trim().replace('\\', "/");
+    let file = file.trim_start_matches("./").trim_matches('/');
+    if file.is_empty() {
+        return Err("E_LOCK_INVALID_PATH: file path is required".to_string());
+    }
+    Ok(file.to_string())
+}
+
+fn lock_refname(file: &str) -> String {
+    let id = Oid::hash_object(ObjectType::Blob, file.as_bytes()).unwrap_or_else(|_| Oid::zero());
+    format!("{}{}", LOCKS_PREFIX, id)
+}
+
+fn held_error(lock: &FileLock) -> String {
+    format!(
+        "E_LOCK_HELD: '{}' is locked by {} <{}>",
+        lock.path, lock.owner_name, lock.owner_email
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    fn clone_as(remote: &Path, dest: &Path, user: &str) -> String {
+        run_git(
+            &["clone", remote.to_str().unwrap(), dest.to_str().unwrap()],
+            remote.parent().unwrap(),
+        );
+        run_git(&["config", "user.name", user], dest);
+        run_git(
+            &["config", "user.email", &format!("{}@example.com", user)],
+            dest,
+        );
+        dest.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_locks_are_shared_through_the_remote() {
+        let base_dir =
+            std::env::temp_dir().join(format!("gitlite-locks-test-{}", uuid::Uuid::new_v4()));
+        let remote = base_dir.join("remote.git");
+        fs::create_dir_all(&remote).unwrap();
+        run_git(&["init", "--bare"], &remote);
+        let alice = clone_as(&remote, &base_dir.join("alice"), "alice");
+        let bob = clone_as(&remote, &base_dir.join("bob"), "bob");
+
+        let lock = lock_file(&alice, "./art/hero.psd", Some("origin")).unwrap();
+        assert_eq!(lock.path, "art/hero.psd");
+        assert!(lock.is_mine);
+
+        let seen = list_locks(&bob, Some("origin")).unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].owner_email, "alice@example.com");
+        assert!(!seen[0].is_mine);
+        assert!(lock_file(&bob, "art/hero.psd", Some("origin"))
+            .unwrap_err()
+            .starts_with("E_LOCK_HELD"));
+        assert!(unlock_file(&bob, "art/hero.psd", None, false)
+            .unwrap_err()
+            .starts_with("E_LOCK_NOT_OWNER"));
+        let warnings =
+            locked_by_others(&bob, &["art/hero.psd".to_string(), "README.md".to_string()]).unwrap();
+        assert_eq!(warnings.len(), 1);
+
+        unlock_file(&alice, "art/hero.psd", Some("origin"), false).unwrap();
+        assert!(list_locks(&bob, Some("origin")).unwrap().is_empty());
+        assert!(lock_file(&bob, "art/hero.psd", Some("origin")).is_ok());
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+}
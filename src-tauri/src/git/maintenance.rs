@@ -0,0 +1,315 @@
+use git2::{Oid, Repository};
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const PROGRESS_EVERY: usize = 1000;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RepoStats {
+    pub loose_objects: usize,
+    pub loose_size_bytes: u64,
+    pub pack_count: usize,
+    pub pack_size_bytes: u64,
+    /// Total size of the `.git` directory (or the bare repository).
+    pub repo_size_bytes: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MaintenanceProgress {
+    pub action: String,
+    pub stage: String, // "started" | "counting" | "packing" | "finished"
+    pub current: usize,
+    pub total: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MaintenanceResult {
+    pub action: String,
+    pub method: String, // "git-cli" | "libgit2"
+    pub before: RepoStats,
+    pub after: RepoStats,
+}
+
+pub fn get_repo_stats(path: &str) -> Result<RepoStats, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    collect_stats(repo.path())
+}
+
+/// Run `gc` or `repack`. The git CLI is used when available; otherwise loose
+/// objects are packed with libgit2's packbuilder and then removed.
+pub fn run_maintenance(
+    path: &str,
+    action: &str,
+    mut on_progress: impl FnMut(&MaintenanceProgress),
+) -> Result<MaintenanceResult, String> {
+    let args: &[&str] = match action {
+        "gc" => &["gc", "--quiet"],
+        "repack" => &["repack", "-a", "-d", "--quiet"],
+        other => {
+            return Err(format!(
+                "E_MAINTENANCE_BAD_ACTION: '{}' (expected gc or repack)",
+                other
+            ))
+        }
+    };
+
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let git_dir = repo.path().to_path_buf();
+    let before = collect_stats(&git_dir)?;
+
+    let mut report = |stage: &str, current: usize, total: usize| {
+        on_progress(&MaintenanceProgress {
+            action: action.to_string(),
+            stage: stage.to_string(),
+            current,
+            total,
+        })
+    };
+    report("started", 0, 0);
+
+    let method = if git_cli_available() {
+        let output = Command::new("git")
+            .arg("--git-dir")
+            .arg(&git_dir)
+            .args(args)
+            .output()
+            .map_err(|e| format!("E_MAINTENANCE_FAILED: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "E_MAINTENANCE_FAILED: git {} failed: {}",
+                action,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        "git-cli"
+    } else {
+        pack_loose_objects(&repo, &mut report)?;
+        "libgit2"
+    };
+
+    let after = collect_stats(&git_dir)?;
+    report("finished", 1, 1);
+
+    Ok(MaintenanceResult {
+        action: action.to_string(),
+        method: method.to_string(),
+        before,
+        after,
+    })
+}
+
+fn git_cli_available() -> bool {
+    Command::new("git")
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Write every loose object into a new pack, then delete the loose copies.
+fn pack_loose_objects(
+    repo: &Repository,
+    report: &mut impl FnMut(&str, usize, usize),
+) -> Result<(), String> {
+    let loose = loose_object_files(&repo.path().join("objects"))?;
+    if loose.is_empty() {
+        return Ok(());
+    }
+
+    let mut builder = repo
+        .packbuilder()
+        .map_err(|e| format!("E_MAINTENANCE_FAILED: {}", e))?;
+    let total = loose.len();
+    for (index, (oid, _)) in loose.iter().enumerate() {
+        builder
+            .insert_object(*oid, None)
+            .map_err(|e| format!("E_MAINTENANCE_FAILED: {}", e))?;
+        if (index + 1) % PROGRESS_EVERY == 0 || index + 1 == total {
+            report("counting", index + 1, total);
+        }
+    }
+
+    report("packing", 0, total);
+    let mut buf = git2::Buf::new();
+    builder
+        .write_buf(&mut buf)
+        .map_err(|e| format!("E_MAINTENANCE_FAILED: {}", e))?;
+    report("packing", total, total);
+
+    let odb = repo
+        .odb()
+        .map_err(|e| format!("E_MAINTENANCE_FAILED: {}", e))?;
+    let mut writer = odb
+        .packwriter()
+        .map_err(|e| format!("E_MAINTENANCE_FAILED: {}", e))?;
+    writer
+        .write_all(&buf)
+        .map_err(|e| format!("E_MAINTENANCE_FAILED: {}", e))?;
+    writer
+        .commit()
+        .map_err(|e| format!("E_MAINTENANCE_FAILED: {}", e))?;
+
+    for (_, file) in loose {
+        let _ = fs::remove_file(file);
+    }
+    Ok(())
+}
+
+fn loose_object_files(objects_dir: &Path) -> Result<Vec<(Oid, PathBuf)>, String> {
+    let mut result = Vec::new();
+    let entries = match fs::read_dir(objects_dir) {
+        Ok(entries) => entries,
+        Err(e) => return Err(format!("Failed to read objects directory: {}", e)),
+    };
+
+    for entry in entries.flatten() {
+        let prefix = entry.file_name().to_string_lossy().into_owned();
+        if prefix.len() != 2 || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            continue;
+        }
+        for object in fs::read_dir(entry.path()).into_iter().flatten().flatten() {
+            let rest = object.file_name().to_string_lossy().into_owned();
+            if let Ok(oid) = Oid::from_str(&format!("{}{}", prefix, rest)) {
+                result.push((oid, object.path()));
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn collect_stats(git_dir: &Path) -> Result<RepoStats, String> {
+    let objects_dir = git_dir.join("objects");
+    let loose = loose_object_files(&objects_dir)?;
+    let loose_size_bytes = loose
+        .iter()
+        .filter_map(|(_, file)| fs::metadata(file).ok())
+        .map(|meta| meta.len())
+        .sum();
+
+    let mut pack_count = 0;
+    let mut pack_size_bytes = 0;
+    for entry in fs::read_dir(objects_dir.join("pack"))
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with("pack-") {
+            continue;
+        }
+        if name.ends_with(".pack") {
+            pack_count += 1;
+        }
+        pack_size_bytes += entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+    }
+
+    Ok(RepoStats {
+        loose_objects: loose.len(),
+        loose_size_bytes,
+        pack_count,
+        pack_size_bytes,
+        repo_size_bytes: dir_size(git_dir),
+    })
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map(|meta| meta.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn create_test_repo() -> PathBuf {
+        let test_dir =
+            std::env::temp_dir().join(format!("gitlite-maintenance-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&test_dir).unwrap();
+        for args in [
+            vec!["init"],
+            vec!["config", "user.name", "Test User"],
+            vec!["config", "user.email", "test@example.com"],
+        ] {
+            Command::new("git")
+                .args(&args)
+                .current_dir(&test_dir)
+                .output()
+                .unwrap();
+        }
+        for i in 0..3 {
+            fs::write(test_dir.join("file.txt"), format!("content {}", i)).unwrap();
+            Command::new("git")
+                .args(["add", "."])
+                .current_dir(&test_dir)
+                .output()
+                .unwrap();
+            Command::new("git")
+                .args(["commit", "-m", &format!("Commit {}", i)])
+                .current_dir(&test_dir)
+                .output()
+                .unwrap();
+        }
+        test_dir
+    }
+
+    #[test]
+    fn test_run_maintenance_gc_packs_loose_objects() {
+        let test_repo = create_test_repo();
+        let path = test_repo.to_str().unwrap();
+
+        let before = get_repo_stats(path).unwrap();
+        assert!(before.loose_objects >= 9);
+        assert_eq!(before.pack_count, 0);
+
+        let mut stages = Vec::new();
+        let result = run_maintenance(path, "gc", |p| stages.push(p.stage.clone())).unwrap();
+        assert_eq!(result.before, before);
+        assert_eq!(result.after.loose_objects, 0);
+        assert_eq!(result.after.pack_count, 1);
+        assert_eq!(stages.first().map(String::as_str), Some("started"));
+        assert_eq!(stages.last().map(String::as_str), Some("finished"));
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_pack_loose_objects_with_libgit2() {
+        let test_repo = create_test_repo();
+        let repo = Repository::open(&test_repo).unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+
+        let mut stages = Vec::new();
+        pack_loose_objects(&repo, &mut |stage: &str, _, _| {
+            stages.push(stage.to_string())
+        })
+        .unwrap();
+
+        let stats = get_repo_stats(test_repo.to_str().unwrap()).unwrap();
+        assert_eq!(stats.loose_objects, 0);
+        assert_eq!(stats.pack_count, 1);
+        assert!(stages.iter().any(|stage| stage == "counting"));
+
+        let reopened = Repository::open(&test_repo).unwrap();
+        assert!(reopened.find_commit(head).is_ok());
+
+        assert!(
+            run_maintenance(test_repo.to_str().unwrap(), "prune", |_| {})
+                .unwrap_err()
+                .starts_with("E_MAINTENANCE_BAD_ACTION")
+        );
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+}
@@ -1,8 +1,18 @@
+use super::repository::ensure_worktree;
 use git2::{AnnotatedCommit, Repository};
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MergePreview {
+    pub outcome: String, // "up_to_date" | "fast_forward" | "clean" | "conflict"
+    pub conflicts: Vec<String>,
+    pub merge_base: Option<String>,
+}
 
 /// Merge a branch into the current branch
 pub fn merge_branch(path: &str, source_branch: &str) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Merge")?;
 
     // Get the current HEAD
     let head = repo
@@ -54,6 +64,63 @@ pub fn merge_branch(path: &str, source_branch: &str) -> Result<(), String> {
     Err("Cannot perform merge: unhandled merge analysis result".to_string())
 }
 
+/// Dry run of `merge_branch`: merges in memory and reports what would
+/// happen. Neither the worktree, the index nor any ref is touched.
+pub fn preview_merge(path: &str, source_branch: &str) -> Result<MergePreview, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let head_commit = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+    let source_commit = repo
+        .find_branch(source_branch, git2::BranchType::Local)
+        .and_then(|branch| branch.get().peel_to_commit())
+        .map_err(|e| format!("Failed to find branch '{}': {}", source_branch, e))?;
+    let merge_base = repo
+        .merge_base(head_commit.id(), source_commit.id())
+        .ok()
+        .map(|oid| oid.to_string());
+
+    let annotated_commit = repo
+        .find_annotated_commit(source_commit.id())
+        .map_err(|e| format!("Failed to create annotated commit: {}", e))?;
+    let (merge_analysis, _merge_pref) = repo
+        .merge_analysis(&[&annotated_commit])
+        .map_err(|e| format!("Failed to analyze merge: {}", e))?;
+
+    let preview = |outcome: &str, conflicts: Vec<String>| MergePreview {
+        outcome: outcome.to_string(),
+        conflicts,
+        merge_base: merge_base.clone(),
+    };
+    if merge_analysis.is_up_to_date() {
+        return Ok(preview("up_to_date", Vec::new()));
+    }
+    if merge_analysis.is_fast_forward() {
+        return Ok(preview("fast_forward", Vec::new()));
+    }
+
+    let index = repo
+        .merge_commits(&head_commit, &source_commit, None)
+        .map_err(|e| format!("Failed to merge: {}", e))?;
+    if !index.has_conflicts() {
+        return Ok(preview("clean", Vec::new()));
+    }
+
+    let mut conflicts = Vec::new();
+    for conflict in index
+        .conflicts()
+        .map_err(|e| format!("Failed to get conflicts: {}", e))?
+    {
+        let conflict = conflict.map_err(|e| format!("Failed to read conflict: {}", e))?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            conflicts.push(String::from_utf8_lossy(&entry.path).into_owned());
+        }
+    }
+    Ok(preview("conflict", conflicts))
+}
+
 fn fast_forward_merge(
     repo: &Repository,
     head: &git2::Reference,
@@ -234,6 +301,13 @@ mod tests {
         repo.checkout_tree(&obj, None).unwrap();
         repo.set_head("refs/heads/master").unwrap();
 
+        let path = temp_dir.path().to_str().unwrap();
+        assert_eq!(
+            preview_merge(path, "feature").unwrap().outcome,
+            "fast_forward"
+        );
+        assert!(!temp_dir.path().join("file2.txt").exists());
+
         // Merge feature into main (should be fast-forward)
         let result = merge_branch(temp_dir.path().to_str().unwrap(), "feature");
         assert!(
@@ -274,6 +348,16 @@ mod tests {
         repo.checkout_tree(&obj, None).unwrap();
         repo.set_head("refs/heads/master").unwrap();
 
+        let preview = preview_merge(temp_dir.path().to_str().unwrap(), "feature").unwrap();
+        assert_eq!(preview.outcome, "conflict");
+        assert_eq!(preview.conflicts, vec!["file1.txt".to_string()]);
+        assert_eq!(
+            preview.merge_base,
+            Some(head_commit.id().to_string()),
+            "base is the commit both branches started from"
+        );
+        assert!(repo.state() == git2::RepositoryState::Clean);
+
         // Merge feature into main (should conflict)
         let result = merge_branch(temp_dir.path().to_str().unwrap(), "feature");
         assert!(result.is_err(), "Merge should fail due to conflict");
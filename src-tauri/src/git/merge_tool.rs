@@ -0,0 +1,397 @@
+use super::repository::ensure_worktree;
+use git2::{Config, IndexEntry, Repository};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Arguments for tools git knows without a `mergetool.<name>.cmd`.
+const BUILTIN_MERGE_TOOLS: &[(&str, &str)] = &[
+    (
+        "bc",
+        "bcompare \"$LOCAL\" \"$REMOTE\" \"$BASE\" -mergeoutput=\"$MERGED\"",
+    ),
+    (
+        "kdiff3",
+        "kdiff3 --auto \"$BASE\" \"$LOCAL\" \"$REMOTE\" -o \"$MERGED\"",
+    ),
+    (
+        "meld",
+        "meld \"$LOCAL\" \"$BASE\" \"$REMOTE\" --output=\"$MERGED\"",
+    ),
+    (
+        "opendiff",
+        "opendiff \"$LOCAL\" \"$REMOTE\" -ancestor \"$BASE\" -merge \"$MERGED\"",
+    ),
+    (
+        "p4merge",
+        "p4merge \"$BASE\" \"$LOCAL\" \"$REMOTE\" \"$MERGED\"",
+    ),
+    (
+        "vimdiff",
+        "vimdiff -f -d \"$LOCAL\" \"$BASE\" \"$REMOTE\" \"$MERGED\"",
+    ),
+    (
+        "vscode",
+        "code --wait --merge \"$REMOTE\" \"$LOCAL\" \"$BASE\" \"$MERGED\"",
+    ),
+];
+
+const BUILTIN_DIFF_TOOLS: &[(&str, &str)] = &[
+    ("bc", "bcompare \"$LOCAL\" \"$REMOTE\""),
+    ("kdiff3", "kdiff3 \"$LOCAL\" \"$REMOTE\""),
+    ("meld", "meld \"$LOCAL\" \"$REMOTE\""),
+    ("opendiff", "opendiff \"$LOCAL\" \"$REMOTE\""),
+    ("p4merge", "p4merge \"$LOCAL\" \"$REMOTE\""),
+    ("vimdiff", "vimdiff -f -d \"$LOCAL\" \"$REMOTE\""),
+    ("vscode", "code --wait --diff \"$LOCAL\" \"$REMOTE\""),
+];
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ExternalTool {
+    /// Value of `merge.tool` / `diff.tool`.
+    pub name: String,
+    /// `mergetool.<name>.cmd` (or `difftool.<name>.cmd`), else the built-in
+    /// command line for known tools.
+    pub cmd: Option<String>,
+    /// `mergetool.<name>.path`, the executable to use.
+    pub path: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ExternalTools {
+    pub merge_tool: Option<ExternalTool>,
+    pub diff_tool: Option<ExternalTool>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MergeToolResult {
+    pub exit_code: Option<i32>,
+    /// The file was staged and its conflict cleared.
+    pub resolved: bool,
+}
+
+pub fn get_external_tools(path: &str) -> Result<ExternalTools, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+    Ok(ExternalTools {
+        merge_tool: configured_tool(&config, "merge.tool", "mergetool", BUILTIN_MERGE_TOOLS),
+        diff_tool: configured_tool(&config, "diff.tool", "difftool", BUILTIN_DIFF_TOOLS),
+    })
+}
+
+fn configured_tool(
+    config: &Config,
+    key: &str,
+    section: &str,
+    builtins: &[(&str, &'static str)],
+) -> Option<ExternalTool> {
+    let name = config
+        .get_string(key)
+        .ok()
+        .filter(|name| !name.trim().is_empty())?;
+    let setting = |field: &str| {
+        config
+            .get_string(&format!("{}.{}.{}", section, name, field))
+            .ok()
+    };
+    let path = setting("path");
+    let cmd = setting("cmd").or_else(|| {
+        let builtin = builtins
+            .iter()
+            .find(|(known, _)| *known == name || (name == "bc3" && *known == "bc"))?
+            .1;
+        // `path` replaces the executable of a built-in command line.
+        Some(match &path {
+            Some(path) => match builtin.split_once(' ') {
+                Some((_, args)) => format!("\"{}\" {}", path, args),
+                None => builtin.to_string(),
+            },
+            None => builtin.to_string(),
+        })
+    });
+    Some(ExternalTool { name, cmd, path })
+}
+
+/// Write the conflict stages of `file` to temp files and run the configured
+/// merge tool on them, like `git mergetool`. Blocks until the tool exits;
+/// returns its exit code (`None` when killed by a signal).
+pub fn run_merge_tool(path: &str, file: &str) -> Result<Option<i32>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Running a merge tool")?;
+    let tool = get_external_tools(path)?.merge_tool.ok_or_else(|| {
+        "E_MERGE_TOOL_NOT_CONFIGURED: set merge.tool in your git config".to_string()
+    })?;
+    let cmd = tool.cmd.ok_or_else(|| {
+        format!(
+            "E_MERGE_TOOL_NOT_CONFIGURED: merge tool '{}' has no mergetool.{}.cmd",
+            tool.name, tool.name
+        )
+    })?;
+
+    let (base, ours, theirs) = conflict_stages(&repo, file)?;
+    let workdir = repo.workdir().unwrap_or_else(|| Path::new(path));
+    let temp_dir = std::env::temp_dir().join(format!("gitlite-mergetool-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&temp_dir)
+        .map_err(|e| format!("E_MERGE_TOOL_FAILED: cannot create temp dir: {}", e))?;
+    let result = (|| {
+        let stage_file = |label: &str, entry: Option<&IndexEntry>| {
+            write_stage(&repo, &temp_dir, file, label, entry)
+        };
+        let base = stage_file("BASE", base.as_ref())?;
+        let local = stage_file("LOCAL", ours.as_ref())?;
+        let remote = stage_file("REMOTE", theirs.as_ref())?;
+
+        let status = shell(&cmd)
+            .current_dir(workdir)
+            .env("BASE", base)
+            .env("LOCAL", local)
+            .env("REMOTE", remote)
+            .env("MERGED", file)
+            .status()
+            .map_err(|e| format!("E_MERGE_TOOL_FAILED: cannot start '{}': {}", tool.name, e))?;
+        Ok(status.code())
+    })();
+    let _ = fs::remove_dir_all(&temp_dir);
+    result
+}
+
+/// Stage the tool's result for `file`, which clears its conflict. Refused
+/// while conflict markers remain.
+pub fn mark_conflict_resolved(path: &str, file: &str) -> Result<(), String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Resolving a conflict")?;
+    conflict_stages(&repo, file)?;
+    let merged = repo.workdir().unwrap_or_else(|| Path::new(path)).join(file);
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    if merged.exists() {
+        let content = fs::read(&merged).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+        if has_conflict_markers(&content) {
+            return Err(format!(
+                "E_MERGE_TOOL_UNRESOLVED: {} still contains conflict markers",
+                file
+            ));
+        }
+        index
+            .add_path(Path::new(file))
+            .map_err(|e| format!("Failed to stage {}: {}", file, e))?;
+    } else {
+        index
+            .remove_path(Path::new(file))
+            .map_err(|e| format!("Failed to stage deletion of {}: {}", file, e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))
+}
+
+type ConflictStages = (Option<IndexEntry>, Option<IndexEntry>, Option<IndexEntry>);
+
+fn conflict_stages(repo: &Repository, file: &str) -> Result<ConflictStages, String> {
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let conflicts = index
+        .conflicts()
+        .map_err(|e| format!("Failed to read conflicts: {}", e))?;
+    for conflict in conflicts.flatten() {
+        let matches = [&conflict.ancestor, &conflict.our, &conflict.their]
+            .into_iter()
+            .flatten()
+            .any(|entry| entry.path == file.as_bytes());
+        if matches {
+            return Ok((conflict.ancestor, conflict.our, conflict.their));
+        }
+    }
+    Err(format!(
+        "E_MERGE_TOOL_NO_CONFLICT: {} is not conflicted",
+        file
+    ))
+}
+
+/// `<name>.<LABEL>.<ext>` in `dir`; an empty file when the side is missing
+/// (added or deleted on one side).
+fn write_stage(
+    repo: &Repository,
+    dir: &Path,
+    file: &str,
+    label: &str,
+    entry: Option<&IndexEntry>,
+) -> Result<PathBuf, String> {
+    let original = Path::new(file);
+    let stem = original
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match original.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, label, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, label),
+    };
+    let content = match entry {
+        Some(entry) => repo
+            .find_blob(entry.id)
+            .map_err(|e| format!("Failed to read {} version of {}: {}", label, file, e))?
+            .content()
+            .to_vec(),
+        None => Vec::new(),
+    };
+    let target = dir.join(name);
+    fs::write(&target, content).map_err(|e| {
+        format!(
+            "E_MERGE_TOOL_FAILED: cannot write {}: {}",
+            target.display(),
+            e
+        )
+    })?;
+    Ok(target)
+}
+
+fn has_conflict_markers(content: &[u8]) -> bool {
+    content.split(|byte| *byte == b'\n').any(|line| {
+        line.starts_with(b"<<<<<<< ") || line.starts_with(b">>>>>>> ") || line == b"======="
+    })
+}
+
+#[cfg(not(windows))]
+fn shell(cmd: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(cmd);
+    command
+}
+
+/// `cmd /C`, passed verbatim so the quotes of the command line reach
+/// cmd.exe as written.
+#[cfg(windows)]
+fn shell(cmd: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut command = Command::new("cmd");
+    command.arg("/C").raw_arg(cmd_variables(cmd));
+    command
+}
+
+/// `cmd` with `$NAME` and `${NAME}` of the merge variables written as
+/// cmd.exe's `%NAME%`.
+#[cfg(any(windows, test))]
+fn cmd_variables(cmd: &str) -> String {
+    regex::Regex::new(r"\$(?:\{(BASE|LOCAL|REMOTE|MERGED)\}|(BASE|LOCAL|REMOTE|MERGED)\b)")
+        .expect("valid variable regex")
+        .replace_all(cmd, |captures: &regex::Captures| {
+            let name = captures.get(1).or_else(|| captures.get(2)).unwrap();
+            format!("%{}%", name.as_str())
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command as Git;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = Git::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}: {:?}", args, output);
+    }
+
+    fn conflicted_repo() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gitlite-mergetool-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        git(&dir, &["init", "-q", "-b", "master"]);
+        git(&dir, &["config", "user.name", "Tester"]);
+        git(&dir, &["config", "user.email", "tester@example.com"]);
+        fs::write(dir.join("a.txt"), "base\n").unwrap();
+        git(&dir, &["add", "."]);
+        git(&dir, &["commit", "-qm", "base"]);
+        git(&dir, &["checkout", "-qb", "other"]);
+        fs::write(dir.join("a.txt"), "theirs\n").unwrap();
+        git(&dir, &["commit", "-qam", "theirs"]);
+        git(&dir, &["checkout", "-q", "master"]);
+        fs::write(dir.join("a.txt"), "ours\n").unwrap();
+        git(&dir, &["commit", "-qam", "ours"]);
+        let _ = Git::new("git")
+            .args(["merge", "-q", "other"])
+            .current_dir(&dir)
+            .output();
+        dir
+    }
+
+    #[test]
+    fn merge_tool_gets_stage_files_and_result_is_staged() {
+        let dir = conflicted_repo();
+        let path = dir.to_str().unwrap();
+        assert!(run_merge_tool(path, "a.txt")
+            .unwrap_err()
+            .starts_with("E_MERGE_TOOL_NOT_CONFIGURED"));
+
+        git(&dir, &["config", "merge.tool", "fake"]);
+        git(
+            &dir,
+            &[
+                "config",
+                "mergetool.fake.cmd",
+                "cat \"$LOCAL\" \"$REMOTE\" \"$BASE\" > \"$MERGED\"",
+            ],
+        );
+        let tools = get_external_tools(path).unwrap();
+        assert_eq!(tools.merge_tool.unwrap().name, "fake");
+        assert_eq!(tools.diff_tool, None);
+
+        assert!(mark_conflict_resolved(path, "a.txt")
+            .unwrap_err()
+            .starts_with("E_MERGE_TOOL_UNRESOLVED"));
+        assert_eq!(run_merge_tool(path, "a.txt").unwrap(), Some(0));
+        assert_eq!(
+            fs::read_to_string(dir.join("a.txt")).unwrap(),
+            "ours\ntheirs\nbase\n"
+        );
+        mark_conflict_resolved(path, "a.txt").unwrap();
+        let repo = Repository::open(path).unwrap();
+        assert!(!repo.index().unwrap().has_conflicts());
+        assert!(mark_conflict_resolved(path, "a.txt")
+            .unwrap_err()
+            .starts_with("E_MERGE_TOOL_NO_CONFLICT"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn builtin_tools_use_their_configured_path() {
+        let dir = std::env::temp_dir().join(format!("gitlite-mergetool-{}", uuid::Uuid::new_v4()));
+        Repository::init(&dir).unwrap();
+        git(&dir, &["config", "merge.tool", "meld"]);
+        git(
+            &dir,
+            &["config", "mergetool.meld.path", "/opt/meld/bin/meld"],
+        );
+        git(&dir, &["config", "diff.tool", "vimdiff"]);
+
+        let tools = get_external_tools(dir.to_str().unwrap()).unwrap();
+        let merge = tools.merge_tool.unwrap();
+        assert!(merge
+            .cmd
+            .unwrap()
+            .starts_with("\"/opt/meld/bin/meld\" \"$LOCAL\""));
+        assert_eq!(
+            tools.diff_tool.unwrap().cmd.as_deref(),
+            Some("vimdiff -f -d \"$LOCAL\" \"$REMOTE\"")
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn windows_commands_use_cmd_variables() {
+        assert_eq!(
+            cmd_variables("meld \"$LOCAL\" \"${BASE}\" --output=\"$MERGED\" $REMOTE_X"),
+            "meld \"%LOCAL%\" \"%BASE%\" --output=\"%MERGED%\" $REMOTE_X"
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use super::remote::saved_https_cred;
+use git2::{
+    Config, Cred, CredentialType, Direction, ErrorClass, ErrorCode, FetchOptions, PushOptions,
+    RemoteCallbacks, Repository,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+const E_MIRROR_SAME_REMOTE: &str = "E_MIRROR_SAME_REMOTE";
+const E_MIRROR_REMOTE_NOT_FOUND: &str = "E_MIRROR_REMOTE_NOT_FOUND";
+const E_MIRROR_AUTH: &str = "E_MIRROR_AUTH";
+const E_MIRROR_NETWORK: &str = "E_MIRROR_NETWORK";
+const E_MIRROR_FETCH: &str = "E_MIRROR_FETCH";
+const E_MIRROR_PUSH: &str = "E_MIRROR_PUSH";
+
+/// Local namespace holding the last fetched copy of the source remote, kept
+/// apart from `refs/remotes/*` so mirroring never moves the user's branches.
+const MIRROR_NAMESPACE: &str = "refs/gitlite-mirror";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MirrorSummary {
+    pub updated_refs: usize,
+    pub deleted_refs: usize,
+}
+
+/// Fetch every branch and tag from `source_remote`, then mirror-push them to
+/// `destination_remote`: refs are force-updated and branches/tags missing from
+/// the source are deleted on the destination. Authentication is
+/// non-interactive (SSH agent, then git credential helpers).
+pub fn mirror_remote(
+    path: &str,
+    source_remote: &str,
+    destination_remote: &str,
+) -> Result<MirrorSummary, String> {
+    let source_remote = source_remote.trim();
+    let destination_remote = destination_remote.trim();
+    if source_remote == destination_remote {
+        return Err(format!(
+            "{}: source and destination must be different remotes",
+            E_MIRROR_SAME_REMOTE
+        ));
+    }
+
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read repository config: {}", e))?;
+
+    let source_refs = fetch_source(&repo, &config, source_remote)?;
+
+    let mut destination = repo.find_remote(destination_remote).map_err(|e| {
+        format!(
+            "{}: remote '{}': {}",
+            E_MIRROR_REMOTE_NOT_FOUND, destination_remote, e
+        )
+    })?;
+
+    let destination_refs =
+        list_remote_refs(&mut destination, &config, Direction::Push, E_MIRROR_PUSH)?;
+
+    let mut refspecs: Vec<String> = source_refs
+        .keys()
+        .map(|name| format!("+{}:{}", local_mirror_ref(source_remote, name), name))
+        .collect();
+    let deletions: Vec<String> = destination_refs
+        .iter()
+        .filter(|name| !source_refs.contains_key(*name))
+        .map(|name| format!(":{}", name))
+        .collect();
+    let updated_refs = refspecs.len();
+    let deleted_refs = deletions.len();
+    refspecs.extend(deletions);
+
+    if refspecs.is_empty() {
+        return Ok(MirrorSummary {
+            updated_refs: 0,
+            deleted_refs: 0,
+        });
+    }
+
+    let mut rejected: Vec<String> = Vec::new();
+    let push_result = {
+        let mut callbacks = unattended_callbacks(
+            &config,
+            destination
+                .pushurl()
+                .or(destination.url())
+                .unwrap_or_default(),
+        );
+        callbacks.push_update_reference(|refname, status| {
+            if let Some(status) = status {
+                rejected.push(format!("{} ({})", refname, status));
+            }
+            Ok(())
+        });
+
+        let mut options = PushOptions::new();
+        options.remote_callbacks(callbacks);
+        destination.push(&refspecs, Some(&mut options))
+    };
+
+    push_result.map_err(|e| format_error(E_MIRROR_PUSH, e))?;
+    if !rejected.is_empty() {
+        return Err(format!(
+            "{}: destination rejected {}",
+            E_MIRROR_PUSH,
+            rejected.join(", ")
+        ));
+    }
+
+    Ok(MirrorSummary {
+        updated_refs,
+        deleted_refs,
+    })
+}
+
+/// Refresh the local mirror namespace for `source_remote` and return the
+/// source's branch/tag names mapped to their local namespace refs.
+fn fetch_source(
+    repo: &Repository,
+    config: &Config,
+    source_remote: &str,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut remote = repo.find_remote(source_remote).map_err(|e| {
+        format!(
+            "{}: remote '{}': {}",
+            E_MIRROR_REMOTE_NOT_FOUND, source_remote, e
+        )
+    })?;
+
+    let advertised = list_remote_refs(&mut remote, config, Direction::Fetch, E_MIRROR_FETCH)?;
+
+    let prefix = format!("{}/{}/", MIRROR_NAMESPACE, source_remote);
+    let refspecs = [
+        format!("+refs/heads/*:{}refs/heads/*", prefix),
+        format!("+refs/tags/*:{}refs/tags/*", prefix),
+    ];
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(unattended_callbacks(
+        config,
+        remote.url().unwrap_or_default(),
+    ));
+    options.download_tags(git2::AutotagOption::None);
+    remote
+        .fetch(&refspecs, Some(&mut options), None)
+        .map_err(|e| format_error(E_MIRROR_FETCH, e))?;
+
+    let local_names: Vec<String> = repo
+        .references_glob(&format!("{}*", prefix))
+        .map_err(|e| format!("{}: {}", E_MIRROR_FETCH, e))?
+        .flatten()
+        .filter_map(|reference| reference.name().map(str::to_string))
+        .collect();
+
+    // Drop refs that disappeared on the source so they are deleted downstream.
+    let mut source_refs = BTreeMap::new();
+    for local_name in local_names {
+        let Some(name) = local_name.strip_prefix(&prefix) else {
+            continue;
+        };
+        if advertised.contains(name) {
+            source_refs.insert(name.to_string(), local_name.clone());
+        } else if let Ok(mut stale) = repo.find_reference(&local_name) {
+            let _ = stale.delete();
+        }
+    }
+
+    Ok(source_refs)
+}
+
+/// Branch and tag names advertised by `remote`, without peeled `^{}` entries.
+fn list_remote_refs(
+    remote: &mut git2::Remote,
+    config: &Config,
+    direction: Direction,
+    code: &str,
+) -> Result<HashSet<String>, String> {
+    let url = match direction {
+        Direction::Push => remote.pushurl().or(remote.url()),
+        Direction::Fetch => remote.url(),
+    }
+    .unwrap_or_default()
+    .to_string();
+    let connection = remote
+        .connect_auth(direction, Some(unattended_callbacks(config, &url)), None)
+        .map_err(|e| format_error(code, e))?;
+    let heads = connection.list().map_err(|e| format_error(code, e))?;
+    Ok(heads
+        .iter()
+        .map(|head| head.name().to_string())
+        .filter(|name| is_mirrored_ref(name) && !name.ends_with("^{}"))
+        .collect())
+}
+
+fn local_mirror_ref(source_remote: &str, name: &str) -> String {
+    format!("{}/{}/{}", MIRROR_NAMESPACE, source_remote, name)
+}
+
+fn is_mirrored_ref(name: &str) -> bool {
+    name.starts_with("refs/heads/") || name.starts_with("refs/tags/")
+}
+
+/// Credential callbacks for unattended operations (SSH agent, then git
+/// credential helpers, then the saved keychain credential); nothing
+/// prompts the user, so SSH hosts must already be in known_hosts.
+pub(super) fn unattended_callbacks<'a>(config: &'a Config, url: &str) -> RemoteCallbacks<'a> {
+    saved_credential_callbacks(config, url, false)
+}
+
+/// Like `unattended_callbacks`, but for operations the user started
+/// (clone, submodule update): unknown SSH host keys are offered for trust.
+pub(super) fn saved_credential_callbacks<'a>(
+    config: &'a Config,
+    url: &str,
+    interactive: bool,
+) -> RemoteCallbacks<'a> {
+    crate::tls_certs::load_configured_ca(config);
+    let mut callbacks = RemoteCallbacks::new();
+    let mut asked = false;
+    callbacks.credentials(move |url, username_from_url, allowed_types| {
+        let rejected = std::mem::replace(&mut asked, true);
+        resolve_cred(config, url, username_from_url, allowed_types, rejected)
+    });
+    crate::known_hosts::verify_host_keys(&mut callbacks, url, interactive);
+    callbacks
+}
+
+fn resolve_cred(
+    config: &Config,
+    url: &str,
+    username_from_url: Option<&str>,
+    allowed_types: CredentialType,
+    rejected: bool,
+) -> Result<Cred, git2::Error> {
+    if allowed_types.contains(CredentialType::SSH_KEY) {
+        if let Ok(cred) = super::ssh_agent::agent_credential(username_from_url.unwrap_or("git")) {
+            return Ok(cred);
+        }
+    }
+    if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) {
+        if let Ok(cred) = Cred::credential_helper(config, url, username_from_url) {
+            return Ok(cred);
+        }
+        if let Some((username, secret)) = saved_https_cred(config, url, username_from_url, rejected)
+        {
+            return Cred::userpass_plaintext(&username, &secret);
+        }
+    }
+    Cred::default()
+}
+
+fn format_error(code: &str, error: git2::Error) -> String {
+    match error.code() {
+        ErrorCode::Auth => format!("{}: Authentication failed: {}", E_MIRROR_AUTH, error),
+        _ => match error.class() {
+            ErrorClass::Net | ErrorClass::Http | ErrorClass::Ssh | ErrorClass::Ssl => {
+                format!("{}: Network error: {}", E_MIRROR_NETWORK, error)
+            }
+            _ => format!("{}: {}", code, error),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &Path) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    fn create_mirror_setup() -> (PathBuf, PathBuf, PathBuf, PathBuf) {
+        let base_dir =
+            std::env::temp_dir().join(format!("gitlite-mirror-test-{}", uuid::Uuid::new_v4()));
+        let work = base_dir.join("work");
+        let source = base_dir.join("source.git");
+        let destination = base_dir.join("destination.git");
+        fs::create_dir_all(&work).unwrap();
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&destination).unwrap();
+
+        run_git(&["init", "--bare"], &source);
+        run_git(&["init", "--bare"], &destination);
+        run_git(&["init"], &work);
+        run_git(&["config", "user.name", "Test User"], &work);
+        run_git(&["config", "user.email", "test@example.com"], &work);
+        fs::write(work.join("a.txt"), "a").unwrap();
+        run_git(&["add", "."], &work);
+        run_git(&["commit", "-m", "Initial commit"], &work);
+        run_git(&["branch", "feature"], &work);
+        run_git(&["tag", "v1"], &work);
+        run_git(
+            &["remote", "add", "source", source.to_str().unwrap()],
+            &work,
+        );
+        run_git(
+            &["remote", "add", "backup", destination.to_str().unwrap()],
+            &work,
+        );
+        run_git(&["push", "source", "--all"], &work);
+        run_git(&["push", "source", "--tags"], &work);
+
+        // A stale branch that only exists on the destination.
+        run_git(&["push", "backup", "HEAD:refs/heads/stale"], &work);
+
+        (base_dir, work, source, destination)
+    }
+
+    #[test]
+    fn test_mirror_remote_pushes_and_prunes_refs() {
+        let (base_dir, work, source, destination) = create_mirror_setup();
+
+        let summary = mirror_remote(work.to_str().unwrap(), "source", "backup").unwrap();
+        assert_eq!(summary.deleted_refs, 1);
+
+        let source_refs = run_git(&["show-ref"], &source);
+        let destination_refs = run_git(&["show-ref"], &destination);
+        assert_eq!(source_refs, destination_refs);
+        assert!(destination_refs.contains("refs/heads/feature"));
+        assert!(destination_refs.contains("refs/tags/v1"));
+        assert!(!destination_refs.contains("refs/heads/stale"));
+
+        // Deleting a branch on the source removes it from the destination.
+        run_git(&["branch", "-D", "feature"], &source);
+        let summary = mirror_remote(work.to_str().unwrap(), "source", "backup").unwrap();
+        assert_eq!(summary.deleted_refs, 1);
+        assert!(!run_git(&["show-ref"], &destination).contains("refs/heads/feature"));
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn test_mirror_remote_rejects_same_remote() {
+        let (base_dir, work, _, _) = create_mirror_setup();
+
+        let result = mirror_remote(work.to_str().unwrap(), "source", "source");
+        assert!(result.unwrap_err().starts_with(E_MIRROR_SAME_REMOTE));
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+}
@@ -1,26 +1,113 @@
+mod archive;
 mod branch;
+mod clone;
 mod commit;
+mod contributions;
 mod diff;
+mod diff_summary;
+mod emoji;
+mod health;
 mod history_ops;
+mod index_backup;
+mod index_flags;
+mod init;
+mod issue_refs;
+mod locks;
+mod maintenance;
 mod merge;
+mod merge_tool;
+mod mirror;
+mod move_paths;
+mod operation;
+mod overview;
+mod ownership;
+mod patch;
+mod permalink;
+mod policy;
 mod pull_policy;
+mod pull_request;
 mod remote;
+mod remote_url;
+mod repo_manager;
+mod repository;
+mod scan;
 mod ssh;
+mod ssh_agent;
 mod staging;
 mod stash;
+mod web_url;
+mod word_diff;
 
-pub use branch::{checkout_branch, create_branch, delete_branch, get_branches, Branch};
-pub use commit::{get_commits, Commit};
-pub use diff::{get_commit_diff, DiffFile, DiffHunk, DiffLineData};
+pub use archive::{export_archive, ArchiveExport};
+pub use branch::{
+    branches_containing, checkout_branch, create_branch, delete_branch, get_branches,
+    get_branches_in, tags_containing, Branch,
+};
+pub use clone::{clone_repository, SubmoduleProgress};
+pub use commit::{
+    get_commits, get_commits_in, walk_commits, Commit, CommitFilter, CommitPage, CommitQuery,
+    CommitTrailer, RevwalkGuards,
+};
+pub use contributions::{get_contribution_stats, AuthorContribution, ContributionStats};
+pub use diff::{
+    get_commit_diff, get_commit_stats, load_full_file_diff, CommitStats, DiffFile, DiffHunk,
+    DiffLimits, DiffLineData,
+};
+pub use diff_summary::{summarize_diff, DiffSummary, FileDiffSummary};
+pub use emoji::{expand_shortcodes, gitmoji_table, GitmojiEntry};
+pub use health::{check_repository, HealthIssue, HealthReport};
 pub use history_ops::{
-    checkout_commit, cherry_pick_commit, create_branch_from_commit, reset_current_branch,
-    revert_commit,
+    amend_commit_metadata, checkout_commit, cherry_pick_commit, create_branch_from_commit,
+    reset_current_branch, revert_commit, reword_commit,
+};
+pub use index_backup::{restore_index_backup, IndexBackup};
+pub use index_flags::set_index_flag;
+pub use init::{init_repository, init_templates, InitOptions, InitResult, InitTemplates};
+pub use issue_refs::IssueRef;
+pub use locks::{list_locks, lock_file, locked_by_others, unlock_file, FileLock};
+pub use maintenance::{
+    get_repo_stats, run_maintenance, MaintenanceProgress, MaintenanceResult, RepoStats,
 };
-pub use merge::merge_branch;
+pub use merge::{merge_branch, preview_merge, MergePreview};
+pub use merge_tool::{
+    get_external_tools, mark_conflict_resolved, run_merge_tool, ExternalTool, ExternalTools,
+    MergeToolResult,
+};
+pub use mirror::{mirror_remote, MirrorSummary};
+pub use move_paths::{git_move, git_move_many, FileMove};
+pub use operation::abort_operation;
+pub use overview::{
+    get_head_state, get_head_state_in, get_repo_overview, get_repo_overview_in, HeadState,
+    RepoOverview,
+};
+pub use ownership::{get_directory_ownership, ContributorShare, DirectoryOwnership};
+pub use patch::{apply_patch, PatchFileResult};
+pub use permalink::{get_permalink, Permalink};
+pub use policy::{
+    check_commit_policy, get_repo_policy_preset, set_repo_policy_preset, validate_preset,
+    with_sign_off, CommitMessageRules, PolicyPreset, PolicyViolation,
+};
+pub use pull_policy::PullOptions;
+pub use pull_request::checkout_pull_request;
 pub use remote::{
-    add_remote, fetch_remote, list_remotes, pull, push, remove_remote, rename_remote,
-    set_remote_url, sync_status, RemoteInfo, SyncStatus,
+    add_fetch_refspec, add_remote, configure_fork_remotes, fetch_remote,
+    fetch_remote_with_progress, fetch_with_sync_status, get_fetch_refspecs, get_use_github_token,
+    list_remotes, list_remotes_in, preview_push, pull, push, push_branch, push_tag,
+    push_with_progress, remove_fetch_refspec, remove_remote, rename_remote, set_remote_push_url,
+    set_remote_url, set_use_github_token, sync_status, FetchScope, FetchUpdate, PushPreview,
+    RemoteInfo, SyncStatus, TransferProgress, E_TRANSFER_CANCELLED,
 };
+pub use remote_url::{classify_provider, parse_remote_host, rewrite_url, web_base_url};
+pub use repo_manager::RepoManager;
+pub use repository::{get_repo_kind, is_bare_repository, CheckoutProgress, RepoKind, E_BARE};
+pub use scan::{scan_for_repositories, DiscoveredRepository};
 pub use ssh::{detect_ssh_keys, fetch_ssh, pull_ssh, push_ssh};
-pub use staging::{commit_changes, get_status, stage_files, unstage_files, FileStatus};
+pub use ssh_agent::{list_ssh_agent_keys, prefer_ssh_identity, SshAgentKey};
+pub use staging::{
+    commit_changes, commit_changes_with, get_status, get_status_in, list_ignored_files,
+    stage_files, stage_pathspec, unstage_files, unstage_pathspec, CommitOptions, FileStatus,
+    StatusQuery,
+};
 pub use stash::{apply_stash, create_stash, drop_stash, list_stashes, StashEntry};
+pub use web_url::{get_web_url, WebUrlArgs};
+pub use word_diff::IntraLineRange;
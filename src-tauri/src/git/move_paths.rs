@@ -0,0 +1,231 @@
+use super::repository::ensure_worktree;
+use git2::{IndexEntry, Repository};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileMove {
+    pub from: String,
+    pub to: String,
+}
+
+/// Rename a tracked file or directory like `git mv`: the worktree is moved
+/// and index entries keep their staged content under the new path, so the
+/// change shows up as a rename rather than a delete + add pair.
+/// Returns the final destination (moving onto a directory moves into it).
+pub fn git_move(path: &str, from: &str, to: &str) -> Result<FileMove, String> {
+    let moves = [FileMove {
+        from: from.to_string(),
+        to: to.to_string(),
+    }];
+    Ok(git_move_many(path, &moves)?.remove(0))
+}
+
+/// Apply several moves in order. Stops at the first failure; moves already
+/// applied stay in place.
+pub fn git_move_many(path: &str, moves: &[FileMove]) -> Result<Vec<FileMove>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Move")?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| "E_BARE: repository has no working tree".to_string())?
+        .to_path_buf();
+
+    let mut applied = Vec::new();
+    for file_move in moves {
+        applied.push(move_one(&repo, &workdir, &file_move.from, &file_move.to)?);
+    }
+    Ok(applied)
+}
+
+fn move_one(repo: &Repository, workdir: &Path, from: &str, to: &str) -> Result<FileMove, String> {
+    let from = normalize_path(from)?;
+    let mut to = normalize_path(to)?;
+    if workdir.join(&to).is_dir() {
+        let name = from.rsplit('/').next().unwrap_or(&from);
+        to = format!("{}/{}", to, name);
+    }
+    if to == from || to.starts_with(&format!("{}/", from)) {
+        return Err(format!(
+            "E_MOVE_INVALID_PATH: cannot move '{}' into itself",
+            from
+        ));
+    }
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let entries: Vec<IndexEntry> = index
+        .iter()
+        .filter(|entry| is_under(&entry.path, &from))
+        .collect();
+    if entries.is_empty() {
+        return Err(format!("E_MOVE_NOT_TRACKED: '{}' is not tracked", from));
+    }
+    if entries.iter().any(|entry| (entry.flags >> 12) & 0x3 != 0) {
+        return Err(format!(
+            "E_MOVE_CONFLICTED: '{}' has unresolved conflicts",
+            from
+        ));
+    }
+    if fs::symlink_metadata(workdir.join(&to)).is_ok()
+        || index.iter().any(|entry| is_under(&entry.path, &to))
+    {
+        return Err(format!(
+            "E_MOVE_DESTINATION_EXISTS: '{}' already exists",
+            to
+        ));
+    }
+
+    let source = workdir.join(&from);
+    if fs::symlink_metadata(&source).is_err() {
+        return Err(format!(
+            "E_MOVE_SOURCE_MISSING: '{}' is missing from the working tree",
+            from
+        ));
+    }
+    let destination = workdir.join(&to);
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory for '{}': {}", to, e))?;
+    }
+    fs::rename(&source, &destination)
+        .map_err(|e| format!("Failed to move '{}' to '{}': {}", from, to, e))?;
+
+    for mut entry in entries {
+        let old_path = String::from_utf8_lossy(&entry.path).into_owned();
+        let new_path = format!("{}{}", to, &old_path[from.len()..]);
+        index
+            .remove_path(Path::new(&old_path))
+            .map_err(|e| format!("Failed to update index for '{}': {}", old_path, e))?;
+        entry.path = new_path.into_bytes();
+        index
+            .add(&entry)
+            .map_err(|e| format!("Failed to update index for '{}': {}", to, e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    Ok(FileMove { from, to })
+}
+
+fn is_under(entry_path: &[u8], path: &str) -> bool {
+    entry_path == path.as_bytes()
+        || (entry_path.starts_with(path.as_bytes()) && entry_path.get(path.len()) == Some(&b'/'))
+}
+
+fn normalize_path(value: &str) -> Result<String, String> {
+    let value = value.trim().replace('\\', "/");
+    let value = value.trim_start_matches("./").trim_end_matches('/');
+    let path = Path::new(value);
+    if value.is_empty()
+        || path
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "E_MOVE_INVALID_PATH: '{}' is not a path inside the repository",
+            value
+        ));
+    }
+    Ok(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &Path) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    fn create_test_repo() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gitlite-move-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        run_git(&["init"], &dir);
+        run_git(&["config", "user.name", "Test User"], &dir);
+        run_git(&["config", "user.email", "test@example.com"], &dir);
+        fs::write(dir.join("notes.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+        fs::write(dir.join("src/a.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.join("src/b.rs"), "fn b() {}\n").unwrap();
+        run_git(&["add", "."], &dir);
+        run_git(&["commit", "-m", "Initial commit"], &dir);
+        dir
+    }
+
+    #[test]
+    fn test_git_move_preserves_staged_and_unstaged_content() {
+        let dir = create_test_repo();
+        fs::write(dir.join("notes.txt"), "one\ntwo\nthree\nfour\nstaged\n").unwrap();
+        run_git(&["add", "notes.txt"], &dir);
+        fs::write(
+            dir.join("notes.txt"),
+            "one\ntwo\nthree\nfour\nstaged\nunstaged\n",
+        )
+        .unwrap();
+
+        let moved = git_move(dir.to_str().unwrap(), "notes.txt", "docs/notes.md").unwrap();
+        assert_eq!(moved.to, "docs/notes.md");
+
+        assert_eq!(
+            run_git(&["show", ":docs/notes.md"], &dir),
+            "one\ntwo\nthree\nfour\nstaged\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("docs/notes.md")).unwrap(),
+            "one\ntwo\nthree\nfour\nstaged\nunstaged\n"
+        );
+        let status = run_git(&["status", "--porcelain"], &dir);
+        assert_eq!(status.trim(), "RM notes.txt -> docs/notes.md");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_git_move_many_moves_directories_and_rejects_conflicts() {
+        let dir = create_test_repo();
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        let path = dir.to_str().unwrap();
+
+        let moved = git_move_many(
+            path,
+            &[FileMove {
+                from: "src".to_string(),
+                to: "lib".to_string(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(moved[0].to, "lib/src");
+        let status = run_git(&["status", "--porcelain"], &dir);
+        assert!(status.contains("R  src/a.rs -> lib/src/a.rs"), "{}", status);
+        assert!(status.contains("R  src/b.rs -> lib/src/b.rs"), "{}", status);
+
+        let exists = git_move(path, "lib/src/a.rs", "lib/src/b.rs").unwrap_err();
+        assert!(
+            exists.starts_with("E_MOVE_DESTINATION_EXISTS"),
+            "{}",
+            exists
+        );
+        let untracked = git_move(path, "missing.txt", "other.txt").unwrap_err();
+        assert!(untracked.starts_with("E_MOVE_NOT_TRACKED"), "{}", untracked);
+        let outside = git_move(path, "notes.txt", "../notes.txt").unwrap_err();
+        assert!(outside.starts_with("E_MOVE_INVALID_PATH"), "{}", outside);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
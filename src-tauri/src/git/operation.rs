@@ -0,0 +1,257 @@
+use super::index_backup::backup_index;
+use super::overview::operation_name;
+use super::pull_policy::restore_autostash;
+use super::repository::ensure_worktree;
+use git2::build::CheckoutBuilder;
+use git2::{Oid, Repository, ResetType};
+use std::fs;
+
+/// Leave the in-progress merge, cherry-pick, revert or rebase the way the
+/// matching `git <operation> --abort` would. Returns the aborted operation.
+/// The index is backed up first so staged resolutions can be restored.
+pub fn abort_operation(path: &str) -> Result<String, String> {
+    let mut repo =
+        Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Aborting an operation")?;
+
+    let operation = operation_name(repo.state()).ok_or_else(|| {
+        "E_ABORT_NO_OPERATION: no merge, cherry-pick, revert or rebase is in progress".to_string()
+    })?;
+    match operation {
+        "merge" | "cherry-pick" | "revert" => abort_merge_like(&repo, operation)?,
+        "rebase" => abort_rebase(&mut repo)?,
+        other => {
+            return Err(format!(
+                "E_ABORT_UNSUPPORTED: aborting {} is not supported; run `git {} --abort`",
+                other, other
+            ))
+        }
+    }
+    Ok(operation.to_string())
+}
+
+/// `git reset --merge`: restore only the paths the operation staged or left
+/// conflicted, so unrelated unstaged edits survive.
+fn abort_merge_like(repo: &Repository, operation: &str) -> Result<(), String> {
+    // A multi-commit cherry-pick or revert returns to where the sequence began.
+    let target = match read_state_oid(repo, "sequencer/head") {
+        Some(oid) => oid,
+        None => repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .ok_or_else(|| "E_ABORT_FAILED: HEAD does not point at a commit".to_string())?,
+    };
+    let commit = repo
+        .find_commit(target)
+        .map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    let tree = commit
+        .tree()
+        .map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    backup_index(repo, &format!("{} abort", operation))?;
+
+    let index = repo.index().map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    let diff = repo
+        .diff_tree_to_index(Some(&tree), Some(&index), None)
+        .map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    let mut touched: Vec<String> = diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    touched.sort();
+    touched.dedup();
+
+    if !touched.is_empty() {
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force();
+        for path in &touched {
+            checkout.path(path);
+        }
+        repo.checkout_tree(commit.as_object(), Some(&mut checkout))
+            .map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    }
+    repo.reset(commit.as_object(), ResetType::Mixed, None)
+        .map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    repo.cleanup_state()
+        .map_err(|e| format!("E_ABORT_FAILED: {}", e))
+}
+
+/// `git rebase --abort`: hard reset to the original tip, put HEAD back on
+/// the branch being rebased and re-apply a `--autostash` stash. Changes
+/// that no longer apply are kept in `stash@{0}`.
+fn abort_rebase(repo: &mut Repository) -> Result<(), String> {
+    let state_dir = ["rebase-merge", "rebase-apply"]
+        .into_iter()
+        .find(|dir| repo.path().join(dir).is_dir())
+        .ok_or_else(|| "E_ABORT_FAILED: rebase state directory is missing".to_string())?;
+    let orig_head = read_state_oid(repo, &format!("{}/orig-head", state_dir))
+        .ok_or_else(|| "E_ABORT_FAILED: rebase state has no orig-head".to_string())?;
+    let head_name = fs::read_to_string(repo.path().join(state_dir).join("head-name"))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| name.starts_with("refs/heads/"));
+    let autostash = read_state_oid(repo, &format!("{}/autostash", state_dir));
+    let commit = repo
+        .find_commit(orig_head)
+        .map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    backup_index(repo, "rebase abort")?;
+
+    repo.reset(commit.as_object(), ResetType::Hard, None)
+        .map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    if let Some(branch) = head_name {
+        repo.reference(&branch, orig_head, true, "rebase: aborting")
+            .and_then(|_| repo.set_head(&branch))
+            .map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    }
+    repo.cleanup_state()
+        .map_err(|e| format!("E_ABORT_FAILED: {}", e))?;
+    drop(commit);
+    match autostash {
+        Some(stash) => {
+            // `git stash store`, then pop it like git's own autostash does.
+            repo.reference_ensure_log("refs/stash")
+                .and_then(|_| repo.reference("refs/stash", stash, true, "autostash"))
+                .map_err(|e| format!("E_ABORT_FAILED: cannot keep the autostash: {}", e))?;
+            restore_autostash(repo, "E_ABORT_AUTOSTASH_CONFLICT")
+        }
+        None => Ok(()),
+    }
+}
+
+fn read_state_oid(repo: &Repository, file: &str) -> Option<Oid> {
+    let content = fs::read_to_string(repo.path().join(file)).ok()?;
+    Oid::from_str(content.lines().next()?.trim()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::get_head_state;
+    use std::path::Path;
+    use std::process::{Command, Output};
+
+    fn git(args: &[&str], cwd: &Path) -> Output {
+        Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap()
+    }
+
+    fn run_git(args: &[&str], cwd: &Path) -> String {
+        let output = git(args, cwd);
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn conflicting_repo() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("gitlite-abort-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        run_git(&["init", "-b", "master"], &dir);
+        run_git(&["config", "user.name", "Test User"], &dir);
+        run_git(&["config", "user.email", "test@example.com"], &dir);
+        fs::write(dir.join("a.txt"), "base\n").unwrap();
+        fs::write(dir.join("notes.txt"), "notes\n").unwrap();
+        run_git(&["add", "."], &dir);
+        run_git(&["commit", "-m", "base"], &dir);
+        run_git(&["checkout", "-b", "topic"], &dir);
+        fs::write(dir.join("a.txt"), "topic\n").unwrap();
+        fs::write(dir.join("new.txt"), "from topic\n").unwrap();
+        run_git(&["add", "."], &dir);
+        run_git(&["commit", "-m", "topic"], &dir);
+        run_git(&["checkout", "master"], &dir);
+        fs::write(dir.join("a.txt"), "master\n").unwrap();
+        run_git(&["commit", "-am", "master"], &dir);
+        dir
+    }
+
+    #[test]
+    fn abort_merge_keeps_unrelated_edits() {
+        let dir = conflicting_repo();
+        let path = dir.to_str().unwrap();
+        let head = run_git(&["rev-parse", "HEAD"], &dir);
+        assert!(abort_operation(path)
+            .unwrap_err()
+            .starts_with("E_ABORT_NO_OPERATION"));
+
+        fs::write(dir.join("notes.txt"), "local edit\n").unwrap();
+        assert!(!git(&["merge", "topic"], &dir).status.success());
+        assert_eq!(abort_operation(path).unwrap(), "merge");
+
+        assert_eq!(get_head_state(path).unwrap().operation, None);
+        assert_eq!(run_git(&["rev-parse", "HEAD"], &dir), head);
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "master\n");
+        assert!(!dir.join("new.txt").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("notes.txt")).unwrap(),
+            "local edit\n"
+        );
+        assert_eq!(run_git(&["status", "--porcelain"], &dir), "M notes.txt");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn abort_rebase_restores_the_branch() {
+        let dir = conflicting_repo();
+        let path = dir.to_str().unwrap();
+        run_git(&["checkout", "topic"], &dir);
+        let tip = run_git(&["rev-parse", "HEAD"], &dir);
+        assert!(!git(&["rebase", "master"], &dir).status.success());
+        assert_eq!(
+            get_head_state(path).unwrap().operation.as_deref(),
+            Some("rebase")
+        );
+
+        assert_eq!(abort_operation(path).unwrap(), "rebase");
+        let state = get_head_state(path).unwrap();
+        assert_eq!(state.operation, None);
+        assert_eq!(state.branch.as_deref(), Some("topic"));
+        assert_eq!(state.commit.as_deref(), Some(tip.as_str()));
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "topic\n");
+        assert_eq!(run_git(&["status", "--porcelain"], &dir), "");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn abort_rebase_reapplies_the_autostash() {
+        let dir = conflicting_repo();
+        let path = dir.to_str().unwrap();
+        run_git(&["checkout", "topic"], &dir);
+        fs::write(dir.join("notes.txt"), "local edit\n").unwrap();
+        assert!(!git(&["rebase", "--autostash", "master"], &dir)
+            .status
+            .success());
+
+        assert_eq!(abort_operation(path).unwrap(), "rebase");
+        assert_eq!(
+            fs::read_to_string(dir.join("notes.txt")).unwrap(),
+            "local edit\n"
+        );
+        assert_eq!(run_git(&["status", "--porcelain"], &dir), "M notes.txt");
+        assert_eq!(run_git(&["stash", "list"], &dir), "");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn conflicted_index_is_backed_up_and_restored() {
+        let dir = conflicting_repo();
+        let path = dir.to_str().unwrap();
+        assert!(!git(&["merge", "topic"], &dir).status.success());
+        let conflicted = run_git(&["ls-files", "--stage"], &dir);
+        assert!(conflicted.contains("\ta.txt") && conflicted.contains(" 3\ta.txt"));
+
+        abort_operation(path).unwrap();
+        assert!(!run_git(&["ls-files", "--stage"], &dir).contains(" 3\t"));
+
+        let backup = crate::git::restore_index_backup(path).unwrap();
+        assert_eq!(backup.operation, "merge abort");
+        assert_eq!(run_git(&["ls-files", "--stage"], &dir), conflicted);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
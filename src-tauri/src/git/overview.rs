@@ -0,0 +1,324 @@
+use super::remote::{list_remotes_in, RemoteInfo};
+use git2::{ErrorCode, Repository, RepositoryState, Status, StatusOptions};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct RepoOverview {
+    pub current_branch: Option<String>,
+    pub head_state: String, // "branch" | "detached" | "unborn"
+    pub head_commit: Option<String>,
+    /// In-progress operation, e.g. "merge", "rebase", "cherry-pick".
+    pub operation: Option<String>,
+    pub is_bare: bool,
+    pub staged_count: usize,
+    pub unstaged_count: usize,
+    pub untracked_count: usize,
+    pub conflicted_count: usize,
+    pub stash_count: usize,
+    pub remotes: Vec<RemoteInfo>,
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// Everything the repository screen needs on open, gathered from a single
+/// repository handle.
+pub fn get_repo_overview(path: &str) -> Result<RepoOverview, String> {
+    let mut repo =
+        Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    get_repo_overview_in(&mut repo)
+}
+
+pub fn get_repo_overview_in(repo: &mut Repository) -> Result<RepoOverview, String> {
+    let (current_branch, head_state, head_commit) = resolve_head(repo)?;
+
+    let is_bare = repo.is_bare();
+    let mut overview = RepoOverview {
+        current_branch,
+        head_state: head_state.to_string(),
+        head_commit,
+        operation: operation_name(repo.state()).map(str::to_string),
+        is_bare,
+        staged_count: 0,
+        unstaged_count: 0,
+        untracked_count: 0,
+        conflicted_count: 0,
+        stash_count: 0,
+        remotes: list_remotes_in(repo)?,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+    };
+
+    if !is_bare {
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).include_ignored(false);
+        let statuses = repo
+            .statuses(Some(&mut options))
+            .map_err(|e| format!("Failed to get status: {}", e))?;
+        for entry in statuses.iter() {
+            let status = entry.status();
+            if status.contains(Status::CONFLICTED) {
+                overview.conflicted_count += 1;
+                continue;
+            }
+            if status.intersects(
+                Status::INDEX_NEW
+                    | Status::INDEX_MODIFIED
+                    | Status::INDEX_DELETED
+                    | Status::INDEX_RENAMED
+                    | Status::INDEX_TYPECHANGE,
+            ) {
+                overview.staged_count += 1;
+            }
+            if status.contains(Status::WT_NEW) {
+                overview.untracked_count += 1;
+            } else if status.intersects(
+                Status::WT_MODIFIED
+                    | Status::WT_DELETED
+                    | Status::WT_RENAMED
+                    | Status::WT_TYPECHANGE,
+            ) {
+                overview.unstaged_count += 1;
+            }
+        }
+    }
+
+    if let (Some(branch), Some(head_commit)) = (&overview.current_branch, &overview.head_commit) {
+        if let Some((upstream, tip)) = upstream_tip(repo, branch) {
+            let local = git2::Oid::from_str(head_commit)
+                .map_err(|e| format!("Failed to parse HEAD: {}", e))?;
+            let (ahead, behind) = repo
+                .graph_ahead_behind(local, tip)
+                .map_err(|e| format!("Failed to compute ahead/behind: {}", e))?;
+            overview.upstream = Some(upstream);
+            overview.ahead = ahead;
+            overview.behind = behind;
+        }
+    }
+
+    // A repository without stashes has no refs/stash; count zero.
+    let mut stash_count = 0;
+    let _ = repo.stash_foreach(|_, _, _| {
+        stash_count += 1;
+        true
+    });
+    overview.stash_count = stash_count;
+
+    Ok(overview)
+}
+
+/// What HEAD points at and which operation, if any, is in progress.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HeadState {
+    pub kind: String, // "branch" | "detached" | "unborn"
+    /// Current branch, also set for an unborn branch.
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+    /// Tags pointing at a detached HEAD, sorted.
+    pub tags: Vec<String>,
+    /// In-progress operation, e.g. "merge", "rebase", "cherry-pick".
+    pub operation: Option<String>,
+}
+
+/// Describe HEAD so the UI can explain why branch commands are refused
+/// (detached HEAD, nothing committed yet, a merge or rebase in progress).
+pub fn get_head_state(path: &str) -> Result<HeadState, String> {
+    let mut repo =
+        Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    get_head_state_in(&mut repo)
+}
+
+pub fn get_head_state_in(repo: &mut Repository) -> Result<HeadState, String> {
+    let (branch, kind, commit) = resolve_head(repo)?;
+    let mut tags = Vec::new();
+    if let (None, Some(commit)) = (&branch, &commit) {
+        let references = repo
+            .references_glob("refs/tags/*")
+            .map_err(|e| format!("Failed to list tags: {}", e))?;
+        for reference in references.flatten() {
+            let points_here = reference
+                .peel_to_commit()
+                .is_ok_and(|target| target.id().to_string() == *commit);
+            if let (true, Some(name)) = (points_here, reference.shorthand()) {
+                tags.push(name.to_string());
+            }
+        }
+        tags.sort();
+    }
+    Ok(HeadState {
+        kind: kind.to_string(),
+        branch,
+        commit,
+        tags,
+        operation: operation_name(repo.state()).map(str::to_string),
+    })
+}
+
+/// Branch name, HEAD kind and commit. An unborn HEAD still names the
+/// branch the first commit will create.
+fn resolve_head(
+    repo: &Repository,
+) -> Result<(Option<String>, &'static str, Option<String>), String> {
+    match repo.head() {
+        Ok(head) if head.is_branch() => Ok((
+            head.shorthand().map(str::to_string),
+            "branch",
+            head.target().map(|oid| oid.to_string()),
+        )),
+        Ok(head) => Ok((None, "detached", head.target().map(|oid| oid.to_string()))),
+        Err(e) if e.code() == ErrorCode::UnbornBranch => {
+            let branch = repo
+                .find_reference("HEAD")
+                .ok()
+                .and_then(|head| head.symbolic_target().map(str::to_string))
+                .map(|target| target.trim_start_matches("refs/heads/").to_string());
+            Ok((branch, "unborn", None))
+        }
+        Err(e) => Err(format!("Failed to get HEAD: {}", e)),
+    }
+}
+
+/// Configured upstream of `branch`, falling back to `origin/<branch>`.
+fn upstream_tip(repo: &Repository, branch: &str) -> Option<(String, git2::Oid)> {
+    let configured = repo
+        .branch_upstream_name(&format!("refs/heads/{}", branch))
+        .ok()
+        .and_then(|name| name.as_str().map(str::to_string));
+    let refname = configured.unwrap_or_else(|| format!("refs/remotes/origin/{}", branch));
+    let tip = repo.refname_to_id(&refname).ok()?;
+    Some((refname.trim_start_matches("refs/remotes/").to_string(), tip))
+}
+
+pub(super) fn operation_name(state: RepositoryState) -> Option<&'static str> {
+    match state {
+        RepositoryState::Clean => None,
+        RepositoryState::Merge => Some("merge"),
+        RepositoryState::Revert | RepositoryState::RevertSequence => Some("revert"),
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => Some("cherry-pick"),
+        RepositoryState::Bisect => Some("bisect"),
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge => Some("rebase"),
+        RepositoryState::ApplyMailbox | RepositoryState::ApplyMailboxOrRebase => Some("am"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn git(args: &[&str], cwd: &Path) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn test_get_repo_overview_counts_changes_and_sync() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-overview-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.to_str().unwrap();
+
+        git(&["init", "-b", "master"], &dir);
+        git(&["config", "user.name", "Test User"], &dir);
+        git(&["config", "user.email", "test@example.com"], &dir);
+        let unborn = get_repo_overview(path).unwrap();
+        assert_eq!(unborn.head_state, "unborn");
+        assert_eq!(unborn.current_branch.as_deref(), Some("master"));
+
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        fs::write(dir.join("b.txt"), "b").unwrap();
+        git(&["add", "."], &dir);
+        git(&["commit", "-m", "Initial commit"], &dir);
+        git(
+            &["remote", "add", "origin", "https://github.com/octo/app.git"],
+            &dir,
+        );
+        let base = git(&["rev-parse", "HEAD"], &dir);
+        git(&["update-ref", "refs/remotes/origin/master", &base], &dir);
+        git(&["commit", "--allow-empty", "-m", "Local"], &dir);
+
+        fs::write(dir.join("a.txt"), "stash me").unwrap();
+        git(&["stash"], &dir);
+        fs::write(dir.join("a.txt"), "staged").unwrap();
+        git(&["add", "a.txt"], &dir);
+        fs::write(dir.join("b.txt"), "unstaged").unwrap();
+        fs::write(dir.join("c.txt"), "untracked").unwrap();
+
+        let overview = get_repo_overview(path).unwrap();
+        assert_eq!(overview.head_state, "branch");
+        assert_eq!(overview.operation, None);
+        assert_eq!(
+            (
+                overview.staged_count,
+                overview.unstaged_count,
+                overview.untracked_count,
+                overview.conflicted_count
+            ),
+            (1, 1, 1, 0)
+        );
+        assert_eq!(overview.stash_count, 1);
+        assert_eq!(overview.remotes[0].provider, "github");
+        assert_eq!(overview.upstream.as_deref(), Some("origin/master"));
+        assert_eq!((overview.ahead, overview.behind), (1, 0));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_head_state_reports_detached_tags_and_operations() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-head-state-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.to_str().unwrap();
+        git(&["init", "-b", "master"], &dir);
+        git(&["config", "user.name", "Test User"], &dir);
+        git(&["config", "user.email", "test@example.com"], &dir);
+        assert_eq!(get_head_state(path).unwrap().kind, "unborn");
+
+        fs::write(dir.join("a.txt"), "base\n").unwrap();
+        git(&["add", "."], &dir);
+        git(&["commit", "-m", "base"], &dir);
+        git(&["tag", "-a", "v1.0", "-m", "release"], &dir);
+        git(&["tag", "latest"], &dir);
+        let base = git(&["rev-parse", "HEAD"], &dir);
+        let branch = get_head_state(path).unwrap();
+        assert_eq!(branch.kind, "branch");
+        assert_eq!(branch.branch.as_deref(), Some("master"));
+        assert!(branch.tags.is_empty());
+
+        git(&["checkout", "--detach", "v1.0"], &dir);
+        let detached = get_head_state(path).unwrap();
+        assert_eq!(detached.kind, "detached");
+        assert_eq!(detached.branch, None);
+        assert_eq!(detached.commit.as_deref(), Some(base.as_str()));
+        assert_eq!(detached.tags, vec!["latest", "v1.0"]);
+
+        git(&["checkout", "-b", "topic"], &dir);
+        fs::write(dir.join("a.txt"), "topic\n").unwrap();
+        git(&["commit", "-am", "topic"], &dir);
+        git(&["checkout", "master"], &dir);
+        fs::write(dir.join("a.txt"), "master\n").unwrap();
+        git(&["commit", "-am", "master"], &dir);
+        let merge = Command::new("git")
+            .args(["merge", "topic"])
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        assert!(!merge.status.success());
+        let merging = get_head_state(path).unwrap();
+        assert_eq!(merging.kind, "branch");
+        assert_eq!(merging.operation.as_deref(), Some("merge"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+use git2::{BlameOptions, ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+const TOP_CONTRIBUTORS: usize = 5;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ContributorShare {
+    pub name: String,
+    pub email: String,
+    pub lines: usize,
+    pub percentage: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DirectoryOwnership {
+    pub path: String,
+    pub file_count: usize,
+    pub total_lines: usize,
+    pub last_modified: i64,
+    pub top_contributors: Vec<ContributorShare>,
+}
+
+#[derive(Default)]
+struct Bucket {
+    file_count: usize,
+    total_lines: usize,
+    last_modified: i64,
+    // keyed by lowercase email, value is (name, email, lines)
+    authors: HashMap<String, (String, String, usize)>,
+}
+
+/// Blame every text file under `dir` at HEAD and aggregate line ownership per
+/// subdirectory, `depth` levels below `dir`. Files directly inside `dir` are
+/// reported under `dir` itself. `use_mailmap` merges identities listed in
+/// `.mailmap`.
+pub fn get_directory_ownership(
+    path: &str,
+    dir: &str,
+    depth: usize,
+    use_mailmap: bool,
+) -> Result<Vec<DirectoryOwnership>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?
+        .peel_to_commit()
+        .map_err(|e| format!("Failed to resolve HEAD commit: {}", e))?;
+    let root_tree = head
+        .tree()
+        .map_err(|e| format!("Failed to read HEAD tree: {}", e))?;
+
+    let dir = dir.trim().trim_matches('/');
+    let tree = if dir.is_empty() || dir == "." {
+        root_tree
+    } else {
+        root_tree
+            .get_path(Path::new(dir))
+            .and_then(|entry| entry.to_object(&repo))
+            .and_then(|object| object.peel_to_tree())
+            .map_err(|_| format!("E_OWNERSHIP_DIR_NOT_FOUND: '{}' is not a directory", dir))?
+    };
+    let base = if dir.is_empty() || dir == "." {
+        ""
+    } else {
+        dir
+    };
+
+    let mut files: Vec<(String, Oid)> = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |parent, entry| {
+        if entry.kind() == Some(ObjectType::Blob) {
+            if let Some(name) = entry.name() {
+                files.push((format!("{}{}", parent, name), entry.id()));
+            }
+        }
+        TreeWalkResult::Ok
+    })
+    .map_err(|e| format!("Failed to walk tree: {}", e))?;
+
+    let mut buckets: BTreeMap<String, Bucket> = BTreeMap::new();
+    let mut commit_times: HashMap<Oid, i64> = HashMap::new();
+
+    for (relative, blob_id) in files {
+        let is_binary = repo
+            .find_blob(blob_id)
+            .map(|blob| blob.is_binary())
+            .unwrap_or(true);
+        if is_binary {
+            continue;
+        }
+
+        let full_path = if base.is_empty() {
+            relative.clone()
+        } else {
+            format!("{}/{}", base, relative)
+        };
+        let mut options = BlameOptions::new();
+        options.newest_commit(head.id()).use_mailmap(use_mailmap);
+        let blame = repo
+            .blame_file(Path::new(&full_path), Some(&mut options))
+            .map_err(|e| format!("Failed to blame '{}': {}", full_path, e))?;
+
+        let bucket = buckets
+            .entry(bucket_path(base, &relative, depth))
+            .or_default();
+        bucket.file_count += 1;
+
+        for hunk in blame.iter() {
+            let lines = hunk.lines_in_hunk();
+            let signature = hunk.final_signature();
+            let name = signature.name().unwrap_or("Unknown").to_string();
+            let email = signature.email().unwrap_or("").to_string();
+            let key = if email.is_empty() {
+                name.to_lowercase()
+            } else {
+                email.to_lowercase()
+            };
+
+            let commit_id = hunk.final_commit_id();
+            let time = *commit_times.entry(commit_id).or_insert_with(|| {
+                repo.find_commit(commit_id)
+                    .map(|commit| commit.time().seconds())
+                    .unwrap_or(0)
+            });
+
+            bucket.total_lines += lines;
+            bucket.last_modified = bucket.last_modified.max(time);
+            bucket
+                .authors
+                .entry(key)
+                .or_insert_with(|| (name, email, 0))
+                .2 += lines;
+        }
+    }
+
+    Ok(buckets
+        .into_iter()
+        .map(|(path, bucket)| {
+            let mut authors: Vec<(String, String, usize)> = bucket.authors.into_values().collect();
+            authors.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+            let top_contributors = authors
+                .into_iter()
+                .take(TOP_CONTRIBUTORS)
+                .map(|(name, email, lines)| ContributorShare {
+                    name,
+                    email,
+                    lines,
+                    percentage: if bucket.total_lines == 0 {
+                        0.0
+                    } else {
+                        lines as f64 * 100.0 / bucket.total_lines as f64
+                    },
+                })
+                .collect();
+
+            DirectoryOwnership {
+                path,
+                file_count: bucket.file_count,
+                total_lines: bucket.total_lines,
+                last_modified: bucket.last_modified,
+                top_contributors,
+            }
+        })
+        .collect())
+}
+
+/// Directory that `relative` (a file path below `base`) is attributed to.
+fn bucket_path(base: &str, relative: &str, depth: usize) -> String {
+    let components: Vec<&str> = relative.split('/').collect();
+    let dir_components = &components[..components.len() - 1];
+    let kept = &dir_components[..dir_components.len().min(depth)];
+
+    let mut parts: Vec<&str> = Vec::new();
+    if !base.is_empty() {
+        parts.push(base);
+    }
+    parts.extend_from_slice(kept);
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &PathBuf, author: &str) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .env("GIT_AUTHOR_NAME", author)
+            .env("GIT_AUTHOR_EMAIL", format!("{}@example.com", author))
+            .env("GIT_COMMITTER_NAME", author)
+            .env("GIT_COMMITTER_EMAIL", format!("{}@example.com", author))
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_get_directory_ownership_groups_by_subdirectory() {
+        let test_dir =
+            std::env::temp_dir().join(format!("gitlite-ownership-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(test_dir.join("src/api")).unwrap();
+        fs::create_dir_all(test_dir.join("src/ui")).unwrap();
+        run_git(&["init"], &test_dir, "alice");
+
+        fs::write(test_dir.join("src/api/handler.rs"), "a\nb\nc\n").unwrap();
+        fs::write(test_dir.join("src/main.rs"), "main\n").unwrap();
+        run_git(&["add", "."], &test_dir, "alice");
+        run_git(&["commit", "-m", "api"], &test_dir, "alice");
+
+        fs::write(test_dir.join("src/ui/view.rs"), "x\ny\n").unwrap();
+        fs::write(test_dir.join("src/api/handler.rs"), "a\nb\nC\n").unwrap();
+        run_git(&["add", "."], &test_dir, "bob");
+        run_git(&["commit", "-m", "ui"], &test_dir, "bob");
+
+        let result = get_directory_ownership(test_dir.to_str().unwrap(), "src", 1, true).unwrap();
+        let paths: Vec<&str> = result.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, vec!["src", "src/api", "src/ui"]);
+
+        let api = &result[1];
+        assert_eq!(api.total_lines, 3);
+        assert_eq!(api.top_contributors[0].name, "alice");
+        assert_eq!(api.top_contributors[0].lines, 2);
+        assert_eq!(api.top_contributors[1].name, "bob");
+
+        let ui = &result[2];
+        assert_eq!(ui.top_contributors.len(), 1);
+        assert_eq!(ui.top_contributors[0].percentage, 100.0);
+
+        let missing = get_directory_ownership(test_dir.to_str().unwrap(), "nope", 1, true);
+        assert!(missing
+            .unwrap_err()
+            .starts_with("E_OWNERSHIP_DIR_NOT_FOUND"));
+
+        fs::remove_dir_all(test_dir).unwrap();
+    }
+
+    #[test]
+    fn test_bucket_path_respects_depth() {
+        assert_eq!(bucket_path("", "README.md", 2), ".");
+        assert_eq!(bucket_path("", "a/b/c/file.rs", 2), "a/b");
+        assert_eq!(bucket_path("src", "file.rs", 1), "src");
+        assert_eq!(bucket_path("src", "a/b/file.rs", 0), "src");
+    }
+}
@@ -0,0 +1,190 @@
+use super::repository::ensure_worktree;
+use git2::{ApplyLocation, ApplyOptions, Diff, Repository};
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PatchFileResult {
+    pub path: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Apply unified diff text to the working tree, or to the index only when
+/// `to_index` is set (like `git apply --cached`). Each file is checked on its
+/// own so one bad hunk does not block the rest of the patch; files that fail
+/// the check are reported and skipped. With `check_only` nothing is written.
+pub fn apply_patch(
+    path: &str,
+    patch_text: &str,
+    to_index: bool,
+    check_only: bool,
+) -> Result<Vec<PatchFileResult>, String> {
+    if patch_text.trim().is_empty() {
+        return Err("E_PATCH_EMPTY: patch text is required".to_string());
+    }
+
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Applying a patch")?;
+
+    let diff = Diff::from_buffer(patch_text.as_bytes())
+        .map_err(|e| format!("E_PATCH_PARSE: failed to parse patch: {}", e))?;
+    if diff.deltas().len() == 0 {
+        return Err("E_PATCH_PARSE: patch does not contain any file changes".to_string());
+    }
+
+    let location = if to_index {
+        ApplyLocation::Index
+    } else {
+        ApplyLocation::WorkDir
+    };
+
+    let mut results = Vec::new();
+    let mut failed: HashSet<usize> = HashSet::new();
+
+    for (target, delta) in diff.deltas().enumerate() {
+        let file_path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let mut current = 0usize;
+        let mut options = ApplyOptions::new();
+        options.check(true);
+        options.delta_callback(move |_delta| {
+            let selected = current == target;
+            current += 1;
+            selected
+        });
+
+        match repo.apply(&diff, location, Some(&mut options)) {
+            Ok(()) => results.push(PatchFileResult {
+                path: file_path,
+                success: true,
+                error: None,
+            }),
+            Err(e) => {
+                failed.insert(target);
+                results.push(PatchFileResult {
+                    path: file_path,
+                    success: false,
+                    error: Some(format!("E_PATCH_APPLY_FAILED: {}", e.message())),
+                });
+            }
+        }
+    }
+
+    if check_only || failed.len() == results.len() {
+        return Ok(results);
+    }
+
+    let mut current = 0usize;
+    let mut options = ApplyOptions::new();
+    options.delta_callback(move |_delta| {
+        let apply = !failed.contains(&current);
+        current += 1;
+        apply
+    });
+
+    repo.apply(&diff, location, Some(&mut options))
+        .map_err(|e| format!("E_PATCH_APPLY_FAILED: {}", e))?;
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &PathBuf) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    fn create_test_repo() -> PathBuf {
+        let test_dir =
+            std::env::temp_dir().join(format!("gitlite-patch-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&test_dir).unwrap();
+
+        run_git(&["init"], &test_dir);
+        run_git(&["config", "user.name", "Test User"], &test_dir);
+        run_git(&["config", "user.email", "test@example.com"], &test_dir);
+
+        fs::write(test_dir.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        fs::write(test_dir.join("b.txt"), "alpha\nbeta\n").unwrap();
+        run_git(&["add", "."], &test_dir);
+        run_git(&["commit", "-m", "Initial commit"], &test_dir);
+
+        test_dir
+    }
+
+    fn capture_patch(repo: &PathBuf) -> String {
+        fs::write(repo.join("a.txt"), "one\n2\nthree\n").unwrap();
+        fs::write(repo.join("b.txt"), "alpha\nBETA\n").unwrap();
+        let patch = run_git(&["diff"], repo);
+        run_git(&["checkout", "--", "."], repo);
+        patch
+    }
+
+    #[test]
+    fn test_apply_patch_to_worktree() {
+        let repo = create_test_repo();
+        let patch = capture_patch(&repo);
+
+        let results = apply_patch(repo.to_str().unwrap(), &patch, false, false).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.success));
+        assert_eq!(
+            fs::read_to_string(repo.join("a.txt")).unwrap(),
+            "one\n2\nthree\n"
+        );
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+
+    #[test]
+    fn test_apply_patch_check_only_reports_failures_without_writing() {
+        let repo = create_test_repo();
+        let patch = capture_patch(&repo);
+
+        fs::write(repo.join("b.txt"), "completely different\n").unwrap();
+
+        let results = apply_patch(repo.to_str().unwrap(), &patch, false, true).unwrap();
+        let a = results.iter().find(|r| r.path == "a.txt").unwrap();
+        let b = results.iter().find(|r| r.path == "b.txt").unwrap();
+        assert!(a.success);
+        assert!(!b.success);
+        assert!(b.error.as_deref().unwrap().contains("E_PATCH_APPLY_FAILED"));
+        assert_eq!(
+            fs::read_to_string(repo.join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+
+    #[test]
+    fn test_apply_patch_rejects_garbage() {
+        let repo = create_test_repo();
+
+        let result = apply_patch(repo.to_str().unwrap(), "not a patch", false, false);
+        assert!(result.unwrap_err().contains("E_PATCH_PARSE"));
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+}
@@ -0,0 +1,231 @@
+use super::web_url::{get_web_url, WebUrlArgs};
+use git2::{DiffOptions, Oid, Patch, Repository};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Permalink {
+    pub url: String,
+    /// Pushed commit the link points at.
+    pub revision: String,
+    /// Line number at `revision`; `None` when the line is not pushed yet and
+    /// the link falls back to the whole file.
+    pub line: Option<u32>,
+    pub warning: Option<String>,
+}
+
+/// Build a stable provider link for `line` of the working-tree `file`. The
+/// link targets the newest commit shared by HEAD and its pushed counterpart,
+/// with the line number mapped back to that commit. Lines that only exist in
+/// unpushed or uncommitted work link to the file instead, with a warning.
+pub fn get_permalink(
+    path: &str,
+    file: &str,
+    line: u32,
+    remote: Option<&str>,
+) -> Result<Permalink, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    super::repository::ensure_worktree(&repo, "permalink")?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| "E_BARE: repository has no working tree".to_string())?;
+
+    let file = file.trim().trim_start_matches('/');
+    let content =
+        fs::read(workdir.join(file)).map_err(|e| format!("E_PERMALINK_FILE: '{}': {}", file, e))?;
+    let line_count =
+        content.split(|byte| *byte == b'\n').count() - usize::from(content.ends_with(b"\n"));
+    if line == 0 || line as usize > line_count {
+        return Err(format!(
+            "E_PERMALINK_LINE: line {} is outside '{}' ({} lines)",
+            line, file, line_count
+        ));
+    }
+
+    let remote_name = remote
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("origin");
+    let pushed = pushed_commit(&repo, remote_name)?;
+
+    let pushed_blob = repo
+        .find_commit(pushed)
+        .and_then(|commit| commit.tree())
+        .ok()
+        .and_then(|tree| tree.get_path(Path::new(file)).ok())
+        .and_then(|entry| repo.find_blob(entry.id()).ok());
+    let mapped = match &pushed_blob {
+        Some(blob) => {
+            let mut options = DiffOptions::new();
+            options.context_lines(0);
+            let patch = Patch::from_blob_and_buffer(
+                blob,
+                Some(Path::new(file)),
+                &content,
+                Some(Path::new(file)),
+                Some(&mut options),
+            )
+            .map_err(|e| format!("Failed to diff '{}': {}", file, e))?;
+            map_line_to_old(&patch, line)?
+        }
+        None => None,
+    };
+
+    let args = WebUrlArgs {
+        remote: Some(remote_name.to_string()),
+        revision: Some(pushed.to_string()),
+        file: Some(file.to_string()),
+        start_line: mapped,
+        end_line: None,
+    };
+    let (url, warning) = match (mapped, &pushed_blob) {
+        (Some(_), _) => (get_web_url(path, "lines", &args)?, None),
+        (None, Some(_)) => (
+            get_web_url(path, "file", &args)?,
+            Some(format!(
+                "Line {} only exists in unpushed or uncommitted changes; linking to the pushed file",
+                line
+            )),
+        ),
+        (None, None) => (
+            get_web_url(path, "commit", &args)?,
+            Some(format!(
+                "'{}' does not exist in the pushed commit; linking to the commit",
+                file
+            )),
+        ),
+    };
+
+    Ok(Permalink {
+        url,
+        revision: pushed.to_string(),
+        line: mapped,
+        warning,
+    })
+}
+
+/// Newest commit reachable from both HEAD and the branch's upstream (or
+/// `<remote>/<branch>`, falling back to `<remote>/HEAD`).
+fn pushed_commit(repo: &Repository, remote: &str) -> Result<Oid, String> {
+    let head = repo
+        .head()
+        .map_err(|e| format!("E_PERMALINK_NOT_PUSHED: HEAD is not set: {}", e))?;
+    let head_id = head
+        .target()
+        .ok_or_else(|| "E_PERMALINK_NOT_PUSHED: HEAD has no commit".to_string())?;
+
+    let mut candidates = Vec::new();
+    if let Some(name) = head.name().filter(|_| head.is_branch()) {
+        if let Ok(upstream) = repo.branch_upstream_name(name) {
+            if let Some(upstream) = upstream.as_str() {
+                candidates.push(upstream.to_string());
+            }
+        }
+        if let Some(short) = head.shorthand() {
+            candidates.push(format!("refs/remotes/{}/{}", remote, short));
+        }
+    }
+    candidates.push(format!("refs/remotes/{}/HEAD", remote));
+
+    candidates
+        .iter()
+        .filter_map(|name| repo.refname_to_id(name).ok())
+        .find_map(|tip| repo.merge_base(head_id, tip).ok())
+        .ok_or_else(|| {
+            format!(
+                "E_PERMALINK_NOT_PUSHED: no commit of the current branch has been pushed to '{}'",
+                remote
+            )
+        })
+}
+
+/// Map a 1-based line of the new side of a zero-context patch to the old side,
+/// or `None` when the line was added or changed.
+fn map_line_to_old(patch: &Patch, line: u32) -> Result<Option<u32>, String> {
+    let mut offset: i64 = 0;
+    for index in 0..patch.num_hunks() {
+        let (hunk, _) = patch
+            .hunk(index)
+            .map_err(|e| format!("Failed to read diff hunk: {}", e))?;
+        let (new_start, new_lines) = (hunk.new_start(), hunk.new_lines());
+        if new_lines > 0 && line >= new_start && line < new_start + new_lines {
+            return Ok(None);
+        }
+        let before = if new_lines == 0 {
+            line <= new_start
+        } else {
+            line < new_start
+        };
+        if before {
+            break;
+        }
+        offset += hunk.old_lines() as i64 - new_lines as i64;
+    }
+    Ok(Some((line as i64 + offset) as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(args: &[&str], cwd: &Path) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn test_get_permalink_maps_lines_to_pushed_commit() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-permalink-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        git(&["init", "-b", "master"], &dir);
+        git(&["config", "user.name", "Test User"], &dir);
+        git(&["config", "user.email", "test@example.com"], &dir);
+        git(
+            &["remote", "add", "origin", "git@github.com:octo/app.git"],
+            &dir,
+        );
+        fs::write(dir.join("main.rs"), "a\nb\nc\n").unwrap();
+        git(&["add", "."], &dir);
+        git(&["commit", "-m", "Initial commit"], &dir);
+        let pushed = git(&["rev-parse", "HEAD"], &dir);
+        git(&["update-ref", "refs/remotes/origin/master", &pushed], &dir);
+
+        fs::write(dir.join("main.rs"), "new\na\nb\nc\n").unwrap();
+        git(&["commit", "-am", "Local only"], &dir);
+        fs::write(dir.join("main.rs"), "new\na\nb\nc\nwip\n").unwrap();
+        let path = dir.to_str().unwrap();
+
+        let link = get_permalink(path, "main.rs", 3, None).unwrap();
+        assert_eq!(link.revision, pushed);
+        assert_eq!(link.line, Some(2));
+        assert_eq!(
+            link.url,
+            format!("https://github.com/octo/app/blob/{}/main.rs#L2", pushed)
+        );
+        assert!(link.warning.is_none());
+
+        for unpushed in [1, 5] {
+            let link = get_permalink(path, "main.rs", unpushed, None).unwrap();
+            assert_eq!(link.line, None);
+            assert!(link.warning.is_some());
+            assert!(link.url.ends_with("/main.rs"));
+        }
+
+        assert!(get_permalink(path, "main.rs", 6, None)
+            .unwrap_err()
+            .starts_with("E_PERMALINK_LINE"));
+        assert!(get_permalink(path, "main.rs", 1, Some("upstream"))
+            .unwrap_err()
+            .starts_with("E_PERMALINK_NOT_PUSHED"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
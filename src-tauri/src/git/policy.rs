@@ -0,0 +1,389 @@
+use git2::{Repository, Status, StatusOptions};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Repository config key naming the policy preset that applies to it.
+pub const POLICY_PRESET_KEY: &str = "gitlite.policyPreset";
+const SIGN_OFF_TRAILER: &str = "Signed-off-by";
+
+/// Organization-wide guardrails distributed as one JSON file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct PolicyPreset {
+    pub name: String,
+    pub description: Option<String>,
+    /// Branch names or `*` globs (e.g. `release/*`) that must not be committed to directly.
+    pub protected_branches: Vec<String>,
+    pub commit_message: CommitMessageRules,
+    /// Trailer keys every commit must carry, e.g. `Signed-off-by`.
+    pub required_trailers: Vec<String>,
+    /// Append `Signed-off-by: <user.name> <user.email>` when committing.
+    pub sign_off: bool,
+    pub max_file_size_bytes: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct CommitMessageRules {
+    pub subject_max_length: Option<usize>,
+    /// Regex the subject line must match, e.g. `^(feat|fix|docs)(\(.+\))?: `.
+    pub subject_pattern: Option<String>,
+    pub require_body: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PolicyViolation {
+    /// "protected_branch" | "subject_length" | "subject_pattern" | "body_required"
+    /// | "missing_trailer" | "file_too_large" | "preset_missing"
+    pub rule: String,
+    pub message: String,
+    pub path: Option<String>,
+}
+
+pub fn validate_preset(preset: &PolicyPreset) -> Result<(), String> {
+    if preset.name.trim().is_empty() {
+        return Err("E_POLICY_INVALID: preset name is required".to_string());
+    }
+    if let Some(pattern) = &preset.commit_message.subject_pattern {
+        Regex::new(pattern).map_err(|e| format!("E_POLICY_INVALID: subject_pattern: {}", e))?;
+    }
+    Ok(())
+}
+
+pub fn get_repo_policy_preset(path: &str) -> Result<Option<String>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read repository config: {}", e))?;
+    Ok(config
+        .get_string(POLICY_PRESET_KEY)
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty()))
+}
+
+/// Reference a preset from the repository's local config, or clear it.
+pub fn set_repo_policy_preset(path: &str, name: Option<&str>) -> Result<(), String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut config = repo
+        .config()
+        .and_then(|config| config.open_level(git2::ConfigLevel::Local))
+        .map_err(|e| format!("Failed to read repository config: {}", e))?;
+    match name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => config
+            .set_str(POLICY_PRESET_KEY, name)
+            .map_err(|e| format!("Failed to set policy preset: {}", e)),
+        None => match config.remove(POLICY_PRESET_KEY) {
+            Err(e) if e.code() != git2::ErrorCode::NotFound => {
+                Err(format!("Failed to clear policy preset: {}", e))
+            }
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Append a `Signed-off-by` trailer for the configured identity unless the
+/// description already carries it.
+pub fn with_sign_off(path: &str, description: &str) -> Result<String, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let signature = repo
+        .signature()
+        .map_err(|e| format!("Failed to get signature: {}", e))?;
+    let trailer = format!(
+        "{}: {} <{}>",
+        SIGN_OFF_TRAILER,
+        signature.name().unwrap_or(""),
+        signature.email().unwrap_or("")
+    );
+
+    let description = description.trim_end();
+    if description.lines().any(|line| line.trim() == trailer) {
+        return Ok(description.to_string());
+    }
+    if description.is_empty() {
+        return Ok(trailer);
+    }
+    let last_paragraph_is_trailers = description
+        .rsplit("\n\n")
+        .next()
+        .map(|paragraph| paragraph.lines().all(is_trailer_line))
+        .unwrap_or(false);
+    let separator = if last_paragraph_is_trailers {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    Ok(format!("{}{}{}", description, separator, trailer))
+}
+
+/// Check a pending commit (current branch, message and staged files) against
+/// `preset`. An empty result means the commit is allowed.
+pub fn check_commit_policy(
+    path: &str,
+    preset: &PolicyPreset,
+    message: &str,
+    description: &str,
+) -> Result<Vec<PolicyViolation>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut violations = Vec::new();
+
+    let branch = match repo.head() {
+        Ok(head) if head.is_branch() => head.shorthand().map(str::to_string),
+        Ok(_) => None,
+        Err(_) => repo
+            .find_reference("HEAD")
+            .ok()
+            .and_then(|head| head.symbolic_target().map(str::to_string))
+            .map(|target| target.trim_start_matches("refs/heads/").to_string()),
+    };
+    if let Some(branch) = branch {
+        if let Some(pattern) = preset
+            .protected_branches
+            .iter()
+            .find(|pattern| glob_match(pattern.trim(), &branch))
+        {
+            violations.push(violation(
+                "protected_branch",
+                format!("'{}' is protected by '{}'", branch, pattern),
+                None,
+            ));
+        }
+    }
+
+    let subject = message.trim();
+    let rules = &preset.commit_message;
+    if let Some(max) = rules.subject_max_length {
+        let length = subject.chars().count();
+        if length > max {
+            violations.push(violation(
+                "subject_length",
+                format!("subject is {} characters (max {})", length, max),
+                None,
+            ));
+        }
+    }
+    if let Some(pattern) = &rules.subject_pattern {
+        let regex =
+            Regex::new(pattern).map_err(|e| format!("E_POLICY_INVALID: subject_pattern: {}", e))?;
+        if !regex.is_match(subject) {
+            violations.push(violation(
+                "subject_pattern",
+                format!("subject does not match '{}'", pattern),
+                None,
+            ));
+        }
+    }
+
+    let body = description.trim();
+    let body_without_trailers: Vec<&str> = body
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !is_trailer_line(line))
+        .collect();
+    if rules.require_body && body_without_trailers.is_empty() {
+        violations.push(violation(
+            "body_required",
+            "a commit body is required".to_string(),
+            None,
+        ));
+    }
+
+    if !preset.required_trailers.is_empty() {
+        let full_message = format!("{}\n\n{}\n", subject, body);
+        let present: Vec<String> = git2::message_trailers_strs(&full_message)
+            .map(|trailers| {
+                trailers
+                    .iter()
+                    .map(|(key, _)| key.to_ascii_lowercase())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for required in &preset.required_trailers {
+            if !present.contains(&required.trim().to_ascii_lowercase()) {
+                violations.push(violation(
+                    "missing_trailer",
+                    format!("missing '{}:' trailer", required.trim()),
+                    None,
+                ));
+            }
+        }
+    }
+
+    if let Some(limit) = preset.max_file_size_bytes {
+        for (file, size) in staged_file_sizes(&repo)? {
+            if size > limit {
+                violations.push(violation(
+                    "file_too_large",
+                    format!("{} is {} bytes (max {})", file, size, limit),
+                    Some(file),
+                ));
+            }
+        }
+    }
+
+    Ok(violations)
+}
+
+fn staged_file_sizes(repo: &Repository) -> Result<Vec<(String, u64)>, String> {
+    if repo.is_bare() {
+        return Ok(Vec::new());
+    }
+    let mut options = StatusOptions::new();
+    options.include_untracked(false);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| format!("Failed to get status: {}", e))?;
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+
+    let mut sizes = Vec::new();
+    for entry in statuses.iter() {
+        if !entry
+            .status()
+            .intersects(Status::INDEX_NEW | Status::INDEX_MODIFIED | Status::INDEX_TYPECHANGE)
+        {
+            continue;
+        }
+        let Some(file) = entry.path() else {
+            continue;
+        };
+        if let Some(blob) = index
+            .get_path(Path::new(file), 0)
+            .and_then(|staged| repo.find_blob(staged.id).ok())
+        {
+            sizes.push((file.to_string(), blob.size() as u64));
+        }
+    }
+    Ok(sizes)
+}
+
+fn is_trailer_line(line: &str) -> bool {
+    line.split_once(": ")
+        .map(|(key, _)| {
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        .unwrap_or(false)
+}
+
+/// `*` matches any run of characters (including `/`); everything else is literal.
+fn glob_match(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !value.starts_with(first) || !value.ends_with(last) || value.len() < first.len() + last.len()
+    {
+        return false;
+    }
+    let mut rest = &value[first.len()..value.len() - last.len()];
+    for middle in &parts[1..parts.len() - 1] {
+        match rest.find(middle) {
+            Some(index) => rest = &rest[index + middle.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn violation(rule: &str, message: String, path: Option<String>) -> PolicyViolation {
+    PolicyViolation {
+        rule: rule.to_string(),
+        message,
+        path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_check_commit_policy_reports_each_rule() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-policy-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        run_git(&["init"], &dir);
+        run_git(&["config", "user.name", "Test User"], &dir);
+        run_git(&["config", "user.email", "test@example.com"], &dir);
+        fs::write(dir.join("big.bin"), vec![0u8; 2048]).unwrap();
+        fs::write(dir.join("small.txt"), "ok").unwrap();
+        run_git(&["add", "."], &dir);
+        let path = dir.to_str().unwrap();
+
+        let preset = PolicyPreset {
+            name: "acme".to_string(),
+            protected_branches: vec!["main".to_string(), "mas*".to_string()],
+            commit_message: CommitMessageRules {
+                subject_max_length: Some(20),
+                subject_pattern: Some("^(feat|fix): ".to_string()),
+                require_body: true,
+            },
+            required_trailers: vec![SIGN_OFF_TRAILER.to_string()],
+            max_file_size_bytes: Some(1024),
+            ..Default::default()
+        };
+        validate_preset(&preset).unwrap();
+
+        let violations =
+            check_commit_policy(path, &preset, "Update everything at once", "").unwrap();
+        let rules: Vec<&str> = violations.iter().map(|v| v.rule.as_str()).collect();
+        assert_eq!(
+            rules,
+            vec![
+                "protected_branch",
+                "subject_length",
+                "subject_pattern",
+                "body_required",
+                "missing_trailer",
+                "file_too_large"
+            ]
+        );
+        assert_eq!(violations[5].path.as_deref(), Some("big.bin"));
+
+        run_git(&["checkout", "-b", "topic"], &dir);
+        fs::remove_file(dir.join("big.bin")).unwrap();
+        run_git(&["rm", "--cached", "-q", "big.bin"], &dir);
+        let body = with_sign_off(path, "Explain the change.").unwrap();
+        assert_eq!(
+            body,
+            "Explain the change.\n\nSigned-off-by: Test User <test@example.com>"
+        );
+        assert_eq!(with_sign_off(path, &body).unwrap(), body);
+        assert!(check_commit_policy(path, &preset, "feat: small", &body)
+            .unwrap()
+            .is_empty());
+
+        set_repo_policy_preset(path, Some("acme")).unwrap();
+        assert_eq!(
+            get_repo_policy_preset(path).unwrap().as_deref(),
+            Some("acme")
+        );
+        set_repo_policy_preset(path, None).unwrap();
+        assert_eq!(get_repo_policy_preset(path).unwrap(), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("release/*", "release/1.0"));
+        assert!(glob_match("*-stable", "v2-stable"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("release/*", "main"));
+        assert!(!glob_match("main", "main2"));
+        assert!(!glob_match("ab*ba", "aba"));
+    }
+}
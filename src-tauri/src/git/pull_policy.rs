@@ -1,17 +1,91 @@
-use git2::{Oid, Repository, Status, StatusOptions};
+use super::index_backup::backup_index;
+use git2::{
+    Commit, Oid, Repository, Signature, Sort, StashApplyOptions, StashFlags, Status, StatusOptions,
+};
 
+pub const E_PULL_AUTOSTASH_CONFLICT: &str = "E_PULL_AUTOSTASH_CONFLICT";
+pub const E_PULL_CONFLICT: &str = "E_PULL_CONFLICT";
 pub const E_PULL_DIRTY: &str = "E_PULL_DIRTY";
 pub const E_PULL_NON_FF: &str = "E_PULL_NON_FF";
+pub const E_PULL_STRATEGY_INVALID: &str = "E_PULL_STRATEGY_INVALID";
 pub const E_PULL_DETACHED: &str = "E_PULL_DETACHED";
 pub const E_HEAD_UNBORN: &str = "E_HEAD_UNBORN";
 
+const AUTOSTASH_MESSAGE: &str = "gitlite autostash";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PullStrategy {
+    FastForwardOnly,
+    Merge,
+    Rebase,
+}
+
+impl PullStrategy {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "ff-only" => Ok(PullStrategy::FastForwardOnly),
+            "merge" => Ok(PullStrategy::Merge),
+            "rebase" => Ok(PullStrategy::Rebase),
+            other => Err(format!(
+                "{}: unknown pull strategy '{}' (expected ff-only, merge or rebase)",
+                E_PULL_STRATEGY_INVALID, other
+            )),
+        }
+    }
+
+    /// What plain `git pull` would do. A `pull.rebase` that asks for a
+    /// rebase (any true boolean, `merges` or `interactive`) wins; otherwise
+    /// `pull.ff=only` keeps to fast-forwards, and `pull.rebase=false` or any
+    /// other `pull.ff` merges. Without either we stay fast-forward only
+    /// rather than create merges the user never asked for.
+    fn from_config(repo: &Repository) -> Self {
+        let Ok(config) = repo.config() else {
+            return PullStrategy::FastForwardOnly;
+        };
+        let rebase = config.get_bool("pull.rebase").ok().or_else(|| {
+            config
+                .get_string("pull.rebase")
+                .ok()
+                .filter(|value| matches!(value.as_str(), "merges" | "m" | "interactive" | "i"))
+                .map(|_| true)
+        });
+        match (rebase, config.get_string("pull.ff").ok().as_deref()) {
+            (Some(true), _) => PullStrategy::Rebase,
+            (_, Some("only")) | (None, None) => PullStrategy::FastForwardOnly,
+            (Some(false), _) | (None, Some(_)) => PullStrategy::Merge,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PullOptions {
+    /// Stash a dirty worktree before updating the branch and re-apply it
+    /// afterwards instead of failing with `E_PULL_DIRTY`.
+    pub autostash: bool,
+    /// How to integrate diverged history; `None` follows the repository's
+    /// `pull.rebase` setting.
+    pub strategy: Option<PullStrategy>,
+}
+
+impl PullOptions {
+    /// Options from the optional IPC arguments.
+    pub fn from_args(autostash: Option<bool>, strategy: Option<&str>) -> Result<Self, String> {
+        Ok(PullOptions {
+            autostash: autostash.unwrap_or(false),
+            strategy: strategy.map(PullStrategy::parse).transpose()?,
+        })
+    }
+}
+
 pub struct PullTarget {
     pub branch_ref_name: String,
     pub head_oid: Oid,
 }
 
-pub fn prepare_pull_target(repo: &Repository) -> Result<PullTarget, String> {
-    ensure_clean_worktree(repo)?;
+pub fn prepare_pull_target(repo: &Repository, options: &PullOptions) -> Result<PullTarget, String> {
+    if !options.autostash {
+        ensure_clean_worktree(repo)?;
+    }
 
     let head = repo
         .head()
@@ -46,6 +120,70 @@ pub fn fetch_head_oid(repo: &Repository) -> Result<Oid, String> {
         .ok_or("FETCH_HEAD has no target".to_string())
 }
 
+/// Bring the branch up to `fetch_oid` with the configured strategy,
+/// stashing local changes around the update when `options.autostash` is
+/// set. If they no longer apply cleanly
+/// the update stands and the changes stay in `stash@{0}`.
+pub fn apply_pull(
+    repo: &mut Repository,
+    target: &PullTarget,
+    fetch_oid: Oid,
+    options: &PullOptions,
+) -> Result<(), String> {
+    let strategy = options
+        .strategy
+        .unwrap_or_else(|| PullStrategy::from_config(repo));
+    let stash = options.autostash && has_local_changes(repo)?;
+    if stash || strategy == PullStrategy::Rebase {
+        backup_index(repo, "pull")?;
+    }
+    let stashed = stash && {
+        let signature = repo
+            .signature()
+            .or_else(|_| Signature::now("GitLite", "gitlite@localhost"))
+            .map_err(|e| format!("Failed to get signature: {}", e))?;
+        repo.stash_save(
+            &signature,
+            AUTOSTASH_MESSAGE,
+            Some(StashFlags::INCLUDE_UNTRACKED),
+        )
+        .map_err(|e| format!("Failed to stash local changes: {}", e))?;
+        true
+    };
+
+    let result = match strategy {
+        PullStrategy::FastForwardOnly => {
+            apply_fast_forward(repo, &target.branch_ref_name, target.head_oid, fetch_oid)
+        }
+        PullStrategy::Merge => apply_merge(repo, target, fetch_oid),
+        PullStrategy::Rebase => apply_rebase(repo, target, fetch_oid),
+    };
+    if !stashed {
+        return result;
+    }
+    match (result, restore_autostash(repo, E_PULL_AUTOSTASH_CONFLICT)) {
+        (Ok(()), restored) => restored,
+        (Err(error), Ok(())) => Err(error),
+        (Err(error), Err(restore_error)) => Err(format!("{}; {}", error, restore_error)),
+    }
+}
+
+/// Pop the autostash, keeping staged changes staged when possible. A
+/// failure is reported as `code`.
+pub(super) fn restore_autostash(repo: &mut Repository, code: &str) -> Result<(), String> {
+    let mut with_index = StashApplyOptions::new();
+    with_index.reinstantiate_index();
+    repo.stash_pop(0, Some(&mut with_index))
+        .or_else(|_| repo.stash_pop(0, None))
+        .map_err(|e| {
+            format!(
+                "{}: re-applying local changes failed ({}); they are kept in stash@{{0}}",
+                code,
+                e.message()
+            )
+        })
+}
+
 pub fn apply_fast_forward(
     repo: &Repository,
     branch_ref_name: &str,
@@ -66,22 +204,181 @@ pub fn apply_fast_forward(
         ));
     }
 
+    move_branch(repo, branch_ref_name, fetch_oid, "pull: Fast-forward")
+}
+
+/// Merge the fetched commit into the branch, or fast-forward when there is
+/// nothing local to merge. Conflicts abort before anything is written.
+fn apply_merge(repo: &Repository, target: &PullTarget, fetch_oid: Oid) -> Result<(), String> {
+    if is_ancestor(repo, fetch_oid, target.head_oid)? {
+        return Ok(());
+    }
+    if is_ancestor(repo, target.head_oid, fetch_oid)? {
+        return move_branch(
+            repo,
+            &target.branch_ref_name,
+            fetch_oid,
+            "pull: Fast-forward",
+        );
+    }
+
+    let head_commit = find_commit(repo, target.head_oid)?;
+    let fetch_commit = find_commit(repo, fetch_oid)?;
+    let mut index = repo
+        .merge_commits(&head_commit, &fetch_commit, None)
+        .map_err(|e| format!("Failed to merge: {}", e))?;
+    if index.has_conflicts() {
+        return Err(format!(
+            "{}: merging the fetched changes conflicts; nothing was changed",
+            E_PULL_CONFLICT
+        ));
+    }
+    let tree = index
+        .write_tree_to(repo)
+        .and_then(|tree_id| repo.find_tree(tree_id))
+        .map_err(|e| format!("Failed to write merge tree: {}", e))?;
+
+    let signature = repo
+        .signature()
+        .map_err(|e| format!("Failed to get signature: {}", e))?;
+    let branch = target
+        .branch_ref_name
+        .strip_prefix("refs/heads/")
+        .unwrap_or(&target.branch_ref_name);
+    let merge_oid = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            &format!("Merge fetched changes into {}", branch),
+            &tree,
+            &[&head_commit, &fetch_commit],
+        )
+        .map_err(|e| format!("Failed to create merge commit: {}", e))?;
+    move_branch(repo, &target.branch_ref_name, merge_oid, "pull: Merge")
+}
+
+/// Replay local commits onto the fetched commit in memory, dropping merge
+/// commits and changes already upstream like `git rebase` does. Conflicts
+/// abort before anything is written.
+fn apply_rebase(repo: &Repository, target: &PullTarget, fetch_oid: Oid) -> Result<(), String> {
+    if is_ancestor(repo, fetch_oid, target.head_oid)? {
+        return Ok(());
+    }
+    if is_ancestor(repo, target.head_oid, fetch_oid)? {
+        return move_branch(
+            repo,
+            &target.branch_ref_name,
+            fetch_oid,
+            "pull: Fast-forward",
+        );
+    }
+
+    let mut walk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to list local commits: {}", e))?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .and_then(|_| walk.push(target.head_oid))
+        .and_then(|_| walk.hide(fetch_oid))
+        .map_err(|e| format!("Failed to list local commits: {}", e))?;
+
+    let committer = repo
+        .signature()
+        .map_err(|e| format!("Failed to get signature: {}", e))?;
+    let mut onto = find_commit(repo, fetch_oid)?;
+    for oid in walk {
+        let oid = oid.map_err(|e| format!("Failed to list local commits: {}", e))?;
+        let commit = find_commit(repo, oid)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let mut index = repo
+            .cherrypick_commit(&commit, &onto, 0, None)
+            .map_err(|e| format!("Failed to rebase {}: {}", commit.id(), e))?;
+        if index.has_conflicts() {
+            return Err(format!(
+                "{}: rebasing {} onto the fetched changes conflicts; nothing was changed",
+                E_PULL_CONFLICT,
+                commit.id()
+            ));
+        }
+        let tree_id = index
+            .write_tree_to(repo)
+            .map_err(|e| format!("Failed to write rebased tree: {}", e))?;
+        if tree_id == onto.tree_id() {
+            continue;
+        }
+        let tree = repo
+            .find_tree(tree_id)
+            .map_err(|e| format!("Failed to write rebased tree: {}", e))?;
+        let rebased = repo
+            .commit(
+                None,
+                &commit.author(),
+                &committer,
+                commit.message_raw().unwrap_or_default(),
+                &tree,
+                &[&onto],
+            )
+            .map_err(|e| format!("Failed to create rebased commit: {}", e))?;
+        onto = find_commit(repo, rebased)?;
+    }
+    move_branch(repo, &target.branch_ref_name, onto.id(), "pull: Rebase")
+}
+
+fn is_ancestor(repo: &Repository, ancestor: Oid, descendant: Oid) -> Result<bool, String> {
+    if ancestor == descendant {
+        return Ok(true);
+    }
+    repo.graph_descendant_of(descendant, ancestor)
+        .map_err(|e| format!("Failed to check fast-forward: {}", e))
+}
+
+fn find_commit(repo: &Repository, oid: Oid) -> Result<Commit<'_>, String> {
+    repo.find_commit(oid)
+        .map_err(|e| format!("Failed to find commit {}: {}", oid, e))
+}
+
+/// Check out `new_oid` and point the branch at it. The checkout comes
+/// first: once HEAD points at the new tree a checkout of HEAD sees nothing
+/// to update.
+fn move_branch(
+    repo: &Repository,
+    branch_ref_name: &str,
+    new_oid: Oid,
+    reflog_message: &str,
+) -> Result<(), String> {
+    let new_commit = repo
+        .find_object(new_oid, None)
+        .map_err(|e| format!("Failed to find commit {}: {}", new_oid, e))?;
+    repo.checkout_tree(&new_commit, None)
+        .map_err(|e| format!("Failed to checkout {}: {}", new_oid, e))?;
+
     let mut branch_ref = repo
         .find_reference(branch_ref_name)
         .map_err(|e| format!("Failed to find branch reference: {}", e))?;
     branch_ref
-        .set_target(fetch_oid, "pull: Fast-forward")
+        .set_target(new_oid, reflog_message)
         .map_err(|e| format!("Failed to update branch: {}", e))?;
 
     repo.set_head(branch_ref_name)
         .map_err(|e| format!("Failed to set HEAD: {}", e))?;
-    repo.checkout_head(None)
-        .map_err(|e| format!("Failed to checkout HEAD: {}", e))?;
 
     Ok(())
 }
 
 fn ensure_clean_worktree(repo: &Repository) -> Result<(), String> {
+    if has_local_changes(repo)? {
+        return Err(format!(
+            "{}: Pull blocked because working tree has uncommitted changes",
+            E_PULL_DIRTY
+        ));
+    }
+
+    Ok(())
+}
+
+fn has_local_changes(repo: &Repository) -> Result<bool, String> {
     let mut options = StatusOptions::new();
     options.include_untracked(true);
     options.include_ignored(false);
@@ -90,7 +387,7 @@ fn ensure_clean_worktree(repo: &Repository) -> Result<(), String> {
         .statuses(Some(&mut options))
         .map_err(|e| format!("Failed to get status: {}", e))?;
 
-    let has_changes = statuses.iter().any(|entry| {
+    Ok(statuses.iter().any(|entry| {
         let s = entry.status();
         s.intersects(
             Status::INDEX_NEW
@@ -104,14 +401,64 @@ fn ensure_clean_worktree(repo: &Repository) -> Result<(), String> {
                 | Status::WT_RENAMED
                 | Status::WT_TYPECHANGE,
         )
-    });
+    }))
+}
 
-    if has_changes {
-        return Err(format!(
-            "{}: Pull blocked because working tree has uncommitted changes",
-            E_PULL_DIRTY
-        ));
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(())
+    #[test]
+    fn strategy_follows_pull_rebase_and_pull_ff_like_git() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-pull-policy-{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let strategy = |settings: &[(&str, &str)]| {
+            let mut config = repo
+                .config()
+                .unwrap()
+                .open_level(git2::ConfigLevel::Local)
+                .unwrap();
+            for key in ["pull.rebase", "pull.ff"] {
+                let _ = config.remove(key);
+            }
+            for (key, value) in settings {
+                config.set_str(key, value).unwrap();
+            }
+            PullStrategy::from_config(&repo)
+        };
+
+        assert_eq!(strategy(&[]), PullStrategy::FastForwardOnly);
+        for value in ["true", "yes", "on", "1", "merges", "interactive"] {
+            assert_eq!(
+                strategy(&[("pull.rebase", value)]),
+                PullStrategy::Rebase,
+                "{}",
+                value
+            );
+        }
+        for value in ["false", "no", "off", "0"] {
+            assert_eq!(
+                strategy(&[("pull.rebase", value)]),
+                PullStrategy::Merge,
+                "{}",
+                value
+            );
+        }
+        assert_eq!(
+            strategy(&[("pull.ff", "only")]),
+            PullStrategy::FastForwardOnly
+        );
+        assert_eq!(
+            strategy(&[("pull.rebase", "false"), ("pull.ff", "only")]),
+            PullStrategy::FastForwardOnly
+        );
+        assert_eq!(
+            strategy(&[("pull.rebase", "true"), ("pull.ff", "only")]),
+            PullStrategy::Rebase
+        );
+        assert_eq!(strategy(&[("pull.ff", "true")]), PullStrategy::Merge);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,148 @@
+use super::branch::checkout_branch;
+use super::remote::{fetch_remote, FetchScope};
+use super::repository::{ensure_worktree, report_checkout_progress, CheckoutProgress};
+use git2::Repository;
+
+/// Fetch `refs/pull/<number>/head` from `remote_name` into
+/// `refs/remotes/<remote>/pr/<number>` and check it out as the local branch
+/// `pr/<number>`. An existing branch is fast-forwarded; one with commits the
+/// pull request lacks is left alone. Returns the local branch name.
+pub fn checkout_pull_request(
+    path: &str,
+    remote_name: &str,
+    number: u64,
+    on_progress: impl FnMut(&CheckoutProgress),
+) -> Result<String, String> {
+    if number == 0 {
+        return Err("E_PR_NUMBER_INVALID: pull request numbers start at 1".to_string());
+    }
+    let branch = format!("pr/{}", number);
+    let tracking_ref = format!("refs/remotes/{}/{}", remote_name, branch);
+    let scope = FetchScope {
+        branch: None,
+        refspec: Some(format!("+refs/pull/{}/head:{}", number, tracking_ref)),
+    };
+    fetch_remote(path, remote_name, "", "", &scope)?;
+
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Checkout")?;
+    let target = repo.refname_to_id(&tracking_ref).map_err(|_| {
+        format!(
+            "E_PR_NOT_FOUND: {} has no pull request #{}",
+            remote_name, number
+        )
+    })?;
+    let local_ref = format!("refs/heads/{}", branch);
+    let reflog = format!("checkout pull request #{}", number);
+
+    let current = repo
+        .head()
+        .ok()
+        .and_then(|head| head.name().map(str::to_string));
+    let new_tip = match repo.refname_to_id(&local_ref) {
+        Err(_) => Some(target),
+        Ok(local) if local == target => None,
+        Ok(local) => {
+            if repo.graph_descendant_of(target, local).unwrap_or(false) {
+                Some(target)
+            } else if repo.graph_descendant_of(local, target).unwrap_or(false) {
+                None
+            } else {
+                return Err(format!(
+                    "E_PR_BRANCH_DIVERGED: branch '{}' has commits not in pull request #{}",
+                    branch, number
+                ));
+            }
+        }
+    };
+
+    if current.as_deref() == Some(local_ref.as_str()) {
+        // Already on the branch: move the worktree first so the checkout
+        // baseline is still the old tip.
+        if let Some(tip) = new_tip {
+            let commit = repo
+                .find_commit(tip)
+                .map_err(|e| format!("Failed to find commit: {}", e))?;
+            let mut checkout = git2::build::CheckoutBuilder::new();
+            checkout.safe();
+            report_checkout_progress(&mut checkout, on_progress);
+            repo.checkout_tree(commit.as_object(), Some(&mut checkout))
+                .map_err(|e| format!("Failed to checkout tree: {}", e))?;
+            repo.reference(&local_ref, tip, true, &reflog)
+                .map_err(|e| format!("Failed to update branch: {}", e))?;
+        }
+        return Ok(branch);
+    }
+
+    if let Some(tip) = new_tip {
+        repo.reference(&local_ref, tip, true, &reflog)
+            .map_err(|e| format!("Failed to update branch: {}", e))?;
+    }
+    checkout_branch(path, &branch, on_progress)?;
+    Ok(branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &Path) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn checks_out_and_fast_forwards_pull_request_heads() {
+        let base =
+            std::env::temp_dir().join(format!("gitlite-pr-checkout-{}", uuid::Uuid::new_v4()));
+        let (local, remote) = (base.join("local"), base.join("remote"));
+        fs::create_dir_all(&local).unwrap();
+        fs::create_dir_all(&remote).unwrap();
+        run_git(&["init", "--bare"], &remote);
+        run_git(&["init"], &local);
+        run_git(&["config", "user.name", "Test User"], &local);
+        run_git(&["config", "user.email", "test@example.com"], &local);
+        run_git(&["commit", "--allow-empty", "-m", "base"], &local);
+        run_git(
+            &["remote", "add", "origin", remote.to_str().unwrap()],
+            &local,
+        );
+        run_git(&["commit", "--allow-empty", "-m", "pr v1"], &local);
+        run_git(&["push", "origin", "HEAD:refs/pull/7/head"], &local);
+        run_git(&["reset", "--hard", "HEAD~1"], &local);
+        let path = local.to_str().unwrap();
+
+        assert_eq!(
+            checkout_pull_request(path, "origin", 7, |_| {}).unwrap(),
+            "pr/7"
+        );
+        assert_eq!(
+            run_git(&["rev-parse", "--abbrev-ref", "HEAD"], &local),
+            "pr/7"
+        );
+        assert_eq!(run_git(&["log", "-1", "--format=%s"], &local), "pr v1");
+
+        fs::write(local.join("update.txt"), "v2\n").unwrap();
+        run_git(&["add", "update.txt"], &local);
+        run_git(&["commit", "-m", "pr v2"], &local);
+        run_git(&["push", "-f", "origin", "HEAD:refs/pull/7/head"], &local);
+        run_git(&["reset", "--hard", "HEAD~1"], &local);
+        checkout_pull_request(path, "origin", 7, |_| {}).unwrap();
+        assert_eq!(run_git(&["log", "-1", "--format=%s"], &local), "pr v2");
+        assert!(local.join("update.txt").exists());
+
+        assert!(checkout_pull_request(path, "origin", 8, |_| {})
+            .unwrap_err()
+            .starts_with("E_PR_NOT_FOUND"));
+
+        fs::remove_dir_all(base).unwrap();
+    }
+}
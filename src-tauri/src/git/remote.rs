@@ -1,8 +1,11 @@
-use super::pull_policy::{apply_fast_forward, fetch_head_oid, prepare_pull_target};
+use super::pull_policy::{apply_pull, fetch_head_oid, prepare_pull_target, PullOptions};
+use super::remote_url::classify_provider;
+use super::repository::ensure_worktree;
 use git2::{
     Cred, CredentialType, ErrorClass, ErrorCode, FetchOptions, PushOptions, RemoteCallbacks,
     Repository,
 };
+use std::cell::{Cell, RefCell};
 
 const E_PULL_AUTH: &str = "E_PULL_AUTH";
 const E_PULL_NETWORK: &str = "E_PULL_NETWORK";
@@ -10,40 +13,115 @@ const E_PUSH_AUTH: &str = "E_PUSH_AUTH";
 const E_PUSH_NETWORK: &str = "E_PUSH_NETWORK";
 const E_PUSH_NON_FF: &str = "E_PUSH_NON_FF";
 const E_PUSH_REJECTED: &str = "E_PUSH_REJECTED";
+pub const E_TRANSFER_CANCELLED: &str = "E_TRANSFER_CANCELLED";
+/// Set to `false` to keep the saved GitHub OAuth token out of this repository's
+/// HTTPS operations.
+const USE_GITHUB_TOKEN_KEY: &str = "gitlite.useGitHubToken";
+
+/// Network transfer progress. Progress hooks return `false` to abort the
+/// transfer, which then fails with `E_TRANSFER_CANCELLED`.
+#[derive(serde::Serialize, Debug, Clone, Default)]
+pub struct TransferProgress {
+    pub received_objects: usize,
+    pub total_objects: usize,
+    pub received_bytes: usize,
+}
 
 #[derive(serde::Serialize)]
 pub struct RemoteInfo {
     pub name: String,
+    /// Effective fetch URL after `url.<base>.insteadOf` rewrites.
     pub url: Option<String>,
+    /// URL exactly as written in `remote.<name>.url`.
+    pub raw_url: Option<String>,
+    /// Separate push URL as written in `remote.<name>.pushurl`, if set.
+    pub push_url: Option<String>,
+    /// Effective push URL after `pushInsteadOf`/`insteadOf` rewrites.
+    pub effective_push_url: Option<String>,
+    pub provider: String,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, Clone, Debug)]
 pub struct SyncStatus {
     pub branch: String,
     pub has_upstream: bool,
+    pub upstream_state: String, // "tracking" | "none" | "gone"
+    /// Cleanup for a gone upstream: "delete_branch" when the branch tip is
+    /// still on its remote, otherwise "delete_branch_unmerged" (deleting drops
+    /// commits the remote never had, as after a squash merge, so warn first).
+    pub suggested_action: Option<String>,
     pub ahead: usize,
     pub behind: usize,
 }
 
+/// What `push` would do, judged from the last-fetched remote-tracking ref.
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct PushPreview {
+    pub local_ref: String,
+    pub remote_ref: String,
+    pub local_commit: String,
+    /// Remote tip as of the last fetch; `None` when the branch is new there.
+    pub remote_commit: Option<String>,
+    pub outcome: String, // "up_to_date" | "new_branch" | "fast_forward" | "non_fast_forward"
+    /// Commits that would be sent.
+    pub commits: usize,
+    /// Remote commits missing locally; non-zero means the push is rejected.
+    pub behind: usize,
+    /// Uncompressed size of the commits, trees and blobs to send.
+    pub estimated_bytes: u64,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FetchUpdate {
+    pub status: SyncStatus,
+    /// Commits that reached the upstream branch with this fetch.
+    pub new_commits: usize,
+}
+
 pub fn list_remotes(path: &str) -> Result<Vec<RemoteInfo>, String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    list_remotes_in(&repo)
+}
 
+pub fn list_remotes_in(repo: &Repository) -> Result<Vec<RemoteInfo>, String> {
     let remotes = repo
         .remotes()
         .map_err(|e| format!("Failed to list remotes: {}", e))?;
 
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read repository config: {}", e))?;
+
     let mut remote_infos = Vec::new();
 
     for name_str in remotes.iter().flatten() {
+        // libgit2 applies insteadOf/pushInsteadOf when the remote is loaded,
+        // so the remote object already carries the effective URLs.
         let remote = repo
             .find_remote(name_str)
             .map_err(|e| format!("Failed to find remote '{}': {}", name_str, e))?;
 
         let url = remote.url().map(|s| s.to_string());
+        let raw_url = config
+            .get_string(&format!("remote.{}.url", name_str))
+            .ok()
+            .or_else(|| url.clone());
+        let push_url = config
+            .get_string(&format!("remote.{}.pushurl", name_str))
+            .ok();
+        let effective_push_url = remote
+            .pushurl()
+            .or_else(|| remote.url())
+            .map(|s| s.to_string());
+        let provider = classify_provider(url.as_deref().unwrap_or("")).to_string();
 
         remote_infos.push(RemoteInfo {
             name: name_str.to_string(),
             url,
+            raw_url,
+            push_url,
+            effective_push_url,
+            provider,
         });
     }
 
@@ -98,7 +176,137 @@ pub fn set_remote_url(path: &str, name: &str, new_url: &str) -> Result<(), Strin
     Ok(())
 }
 
+/// Point `origin` at a fork and `upstream` at the repository it was forked
+/// from, creating or re-pointing `origin` as needed. An `upstream` that
+/// points elsewhere is left alone and reported as `E_FORK_UPSTREAM_EXISTS`.
+pub fn configure_fork_remotes(
+    path: &str,
+    fork_url: &str,
+    upstream_url: &str,
+) -> Result<Vec<RemoteInfo>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    for (name, url) in [
+        ("origin", fork_url.trim()),
+        ("upstream", upstream_url.trim()),
+    ] {
+        if url.is_empty() {
+            return Err(format!("E_FORK_URL_EMPTY: {} URL is required", name));
+        }
+    }
+    if let Ok(upstream) = repo.find_remote("upstream") {
+        let existing = upstream.url().unwrap_or("");
+        if existing != upstream_url.trim() {
+            return Err(format!(
+                "E_FORK_UPSTREAM_EXISTS: upstream already points at {}",
+                existing
+            ));
+        }
+    }
+    for (name, url) in [
+        ("origin", fork_url.trim()),
+        ("upstream", upstream_url.trim()),
+    ] {
+        let result = match repo.find_remote(name) {
+            Ok(_) => repo.remote_set_url(name, url),
+            Err(_) => repo.remote(name, url).map(|_| ()),
+        };
+        result.map_err(|e| format!("Failed to configure remote '{}': {}", name, e))?;
+    }
+    list_remotes_in(&repo)
+}
+
+/// Set `remote.<name>.pushurl` so pushes go to a different server than
+/// fetches. `None` or an empty URL clears it; pushes then use the fetch URL.
+pub fn set_remote_push_url(path: &str, name: &str, push_url: Option<&str>) -> Result<(), String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    repo.find_remote(name)
+        .map_err(|e| format!("E_REMOTE_NOT_FOUND: '{}': {}", name, e.message()))?;
+
+    let push_url = push_url.map(str::trim).filter(|url| !url.is_empty());
+    repo.remote_set_pushurl(name, push_url)
+        .map_err(|e| format!("Failed to set push URL for remote '{}': {}", name, e))?;
+    Ok(())
+}
+
+/// Fetch refspecs configured for `name`, in config order.
+pub fn get_fetch_refspecs(path: &str, name: &str) -> Result<Vec<String>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    fetch_refspecs_in(&repo, name)
+}
+
+/// Add a fetch refspec such as `+refs/pull/*/head:refs/remotes/origin/pr/*`.
+/// Adding one that is already configured is a no-op.
+pub fn add_fetch_refspec(path: &str, name: &str, refspec: &str) -> Result<Vec<String>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let refspecs = fetch_refspecs_in(&repo, name)?;
+    let refspec = refspec.trim();
+    if refspecs.iter().any(|existing| existing == refspec) {
+        return Ok(refspecs);
+    }
+    repo.remote_add_fetch(name, refspec)
+        .map_err(|e| format!("E_REFSPEC_INVALID: '{}': {}", refspec, e.message()))?;
+    fetch_refspecs_in(&repo, name)
+}
+
+/// Remove one configured fetch refspec. Refs it already fetched are kept.
+pub fn remove_fetch_refspec(path: &str, name: &str, refspec: &str) -> Result<Vec<String>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let refspec = refspec.trim();
+    if !fetch_refspecs_in(&repo, name)?
+        .iter()
+        .any(|existing| existing == refspec)
+    {
+        return Err(format!(
+            "E_REFSPEC_NOT_FOUND: '{}' is not a fetch refspec of '{}'",
+            refspec, name
+        ));
+    }
+    let mut config = repo
+        .config()
+        .map_err(|e| format!("Failed to read repository config: {}", e))?;
+    // The value argument is a regex; anchor and escape it to match exactly.
+    let pattern = format!("^{}$", escape_regex(refspec));
+    config
+        .remove_multivar(&format!("remote.{}.fetch", name), &pattern)
+        .map_err(|e| format!("Failed to remove refspec '{}': {}", refspec, e))?;
+    fetch_refspecs_in(&repo, name)
+}
+
+fn fetch_refspecs_in(repo: &Repository, name: &str) -> Result<Vec<String>, String> {
+    let remote = repo
+        .find_remote(name)
+        .map_err(|e| format!("E_REMOTE_NOT_FOUND: '{}': {}", name, e.message()))?;
+    let refspecs = remote
+        .fetch_refspecs()
+        .map_err(|e| format!("Failed to read refspecs of '{}': {}", name, e))?;
+    Ok(refspecs.iter().flatten().map(str::to_string).collect())
+}
+
+fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if "\\.^$|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
 pub fn push(path: &str, remote_name: &str, username: &str, password: &str) -> Result<(), String> {
+    push_with_progress(path, remote_name, username, password, |_| true)
+}
+
+/// Push like `push`, reporting upload progress. Returning `false` from
+/// `on_progress` aborts before the pack is sent, or at the next server
+/// message once the upload has started.
+pub fn push_with_progress(
+    path: &str,
+    remote_name: &str,
+    username: &str,
+    password: &str,
+    on_progress: impl FnMut(&TransferProgress) -> bool,
+) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
     let remote_name = normalize_remote_name(remote_name);
 
@@ -109,9 +317,56 @@ pub fn push(path: &str, remote_name: &str, username: &str, password: &str) -> Re
         .shorthand()
         .ok_or("Failed to get HEAD branch name".to_string())?;
     let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
+    push_refspec_in(
+        &repo,
+        &remote_name,
+        &refspec,
+        username,
+        password,
+        on_progress,
+    )
+}
+
+/// Push the local branch `branch` to the same name on `remote_name`,
+/// whichever branch is checked out.
+pub fn push_branch(
+    path: &str,
+    remote_name: &str,
+    branch: &str,
+    username: &str,
+    password: &str,
+) -> Result<(), String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let remote_name = normalize_remote_name(remote_name);
+    let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
+    push_refspec_in(&repo, &remote_name, &refspec, username, password, |_| true)
+}
+
+/// Push the tag `tag` to `remote_name`. A tag the remote already has at the
+/// same object is a no-op; a different one is rejected.
+pub fn push_tag(
+    path: &str,
+    remote_name: &str,
+    tag: &str,
+    username: &str,
+    password: &str,
+) -> Result<(), String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let remote_name = normalize_remote_name(remote_name);
+    let refspec = format!("refs/tags/{}:refs/tags/{}", tag, tag);
+    push_refspec_in(&repo, &remote_name, &refspec, username, password, |_| true)
+}
 
+fn push_refspec_in(
+    repo: &Repository,
+    remote_name: &str,
+    refspec: &str,
+    username: &str,
+    password: &str,
+    on_progress: impl FnMut(&TransferProgress) -> bool,
+) -> Result<(), String> {
     let mut remote = repo
-        .find_remote(&remote_name)
+        .find_remote(remote_name)
         .map_err(|e| format!("Failed to find remote '{}': {}", remote_name, e))?;
 
     let provided_username = username.trim().to_string();
@@ -119,34 +374,70 @@ pub fn push(path: &str, remote_name: &str, username: &str, password: &str) -> Re
     let config = repo
         .config()
         .map_err(|e| format!("Failed to read repository config: {}", e))?;
+    crate::tls_certs::load_configured_ca(&config);
     let mut push_status: Option<String> = None;
+    let on_progress = RefCell::new(on_progress);
+    let cancelled = Cell::new(false);
 
     let push_result = {
         let mut callbacks = RemoteCallbacks::new();
+        let mut attempts = CredentialAttempts::default();
         callbacks.credentials(move |url, username_from_url, allowed_types| {
             resolve_https_cred(
                 &config,
                 Some(url),
                 username_from_url,
                 allowed_types,
-                &provided_username,
-                &provided_password,
+                (&provided_username, &provided_password),
+                true,
+                &mut attempts,
             )
         });
+        crate::known_hosts::verify_host_keys(
+            &mut callbacks,
+            remote.pushurl().or(remote.url()).unwrap_or_default(),
+            true,
+        );
         callbacks.push_update_reference(|_refname, status| {
             if let Some(status) = status {
                 push_status = Some(status.to_string());
             }
             Ok(())
         });
+        callbacks.push_negotiation(|_updates| {
+            if on_progress.borrow_mut()(&TransferProgress::default()) {
+                Ok(())
+            } else {
+                cancelled.set(true);
+                Err(git2::Error::new(
+                    ErrorCode::User,
+                    ErrorClass::Callback,
+                    "push cancelled",
+                ))
+            }
+        });
+        callbacks.push_transfer_progress(|current, total, bytes| {
+            let keep_going = on_progress.borrow_mut()(&TransferProgress {
+                received_objects: current,
+                total_objects: total,
+                received_bytes: bytes,
+            });
+            if !keep_going {
+                cancelled.set(true);
+            }
+        });
+        callbacks.sideband_progress(|_message| !cancelled.get());
 
         let mut options = PushOptions::new();
         options.remote_callbacks(callbacks);
 
-        remote.push(&[refspec.as_str()], Some(&mut options))
+        remote.push(&[refspec], Some(&mut options))
     };
 
     if let Err(error) = push_result {
+        if cancelled.get() {
+            return Err(format!("{}: Push cancelled", E_TRANSFER_CANCELLED));
+        }
         return Err(format_push_error(error));
     }
 
@@ -163,27 +454,246 @@ pub fn push(path: &str, remote_name: &str, username: &str, password: &str) -> Re
     Ok(())
 }
 
-pub fn pull(path: &str, remote_name: &str, username: &str, password: &str) -> Result<(), String> {
+/// Dry run of `push`: nothing is contacted, so run a fetch first for an
+/// up-to-date answer.
+pub fn preview_push(path: &str, remote_name: &str) -> Result<PushPreview, String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
     let remote_name = normalize_remote_name(remote_name);
-    fetch_remote_internal(&repo, &remote_name, username, password)?;
+    repo.find_remote(&remote_name)
+        .map_err(|e| format!("E_REMOTE_NOT_FOUND: '{}': {}", remote_name, e.message()))?;
+
+    let head = repo.head().map_err(|e| {
+        if e.code() == ErrorCode::UnbornBranch {
+            "E_HEAD_UNBORN: nothing to push before the first commit".to_string()
+        } else {
+            format!("Failed to get HEAD: {}", e)
+        }
+    })?;
+    if !head.is_branch() {
+        return Err("E_HEAD_DETACHED: pushing needs a checked-out branch".to_string());
+    }
+    let branch = head
+        .shorthand()
+        .ok_or("Failed to get HEAD branch name".to_string())?
+        .to_string();
+    let local = head.target().ok_or("Failed to resolve HEAD".to_string())?;
+    let remote_tip = repo
+        .refname_to_id(&format!("refs/remotes/{}/{}", remote_name, branch))
+        .ok();
+
+    let mut walk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    walk.push(local)
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    match remote_tip {
+        Some(tip) => walk.hide(tip),
+        // A new branch only sends what no branch of the remote already has.
+        None => walk.hide_glob(&format!("refs/remotes/{}/*", remote_name)),
+    }
+    .map_err(|e| format!("Failed to walk history: {}", e))?;
+    let new_commits = walk
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+
+    let behind = match remote_tip {
+        Some(tip) => {
+            repo.graph_ahead_behind(local, tip)
+                .map_err(|e| format!("Failed to compute ahead/behind: {}", e))?
+                .1
+        }
+        None => 0,
+    };
+    let outcome = match remote_tip {
+        None => "new_branch",
+        Some(tip) if tip == local => "up_to_date",
+        Some(_) if behind > 0 => "non_fast_forward",
+        Some(_) => "fast_forward",
+    };
+
+    Ok(PushPreview {
+        local_ref: format!("refs/heads/{}", branch),
+        remote_ref: format!("refs/heads/{}", branch),
+        local_commit: local.to_string(),
+        remote_commit: remote_tip.map(|tip| tip.to_string()),
+        outcome: outcome.to_string(),
+        commits: new_commits.len(),
+        behind,
+        estimated_bytes: estimate_push_bytes(&repo, &new_commits)?,
+    })
+}
+
+/// Sum the sizes of each commit, its root tree and the blobs it changes
+/// relative to its first parent, counting every object once.
+fn estimate_push_bytes(repo: &Repository, commits: &[git2::Oid]) -> Result<u64, String> {
+    let odb = repo
+        .odb()
+        .map_err(|e| format!("Failed to open object database: {}", e))?;
+    let mut seen = std::collections::HashSet::new();
+    let mut total = 0u64;
+    let mut add = |oid: git2::Oid| {
+        if !oid.is_zero() && seen.insert(oid) {
+            total += odb
+                .read_header(oid)
+                .map(|(size, _)| size as u64)
+                .unwrap_or(0);
+        }
+    };
+
+    for &oid in commits {
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| format!("Failed to find commit {}: {}", oid, e))?;
+        let tree = commit
+            .tree()
+            .map_err(|e| format!("Failed to get tree: {}", e))?;
+        add(oid);
+        add(tree.id());
+        let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+        let diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .map_err(|e| format!("Failed to diff commit {}: {}", oid, e))?;
+        for delta in diff.deltas() {
+            add(delta.new_file().id());
+        }
+    }
+    Ok(total)
+}
+
+pub fn pull(
+    path: &str,
+    remote_name: &str,
+    username: &str,
+    password: &str,
+    options: &PullOptions,
+) -> Result<(), String> {
+    let mut repo =
+        Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Pull")?;
+    let remote_name = normalize_remote_name(remote_name);
+    fetch_remote_internal(&repo, &remote_name, username, password, &[], true, None)?;
 
-    let target = prepare_pull_target(&repo)?;
+    let target = prepare_pull_target(&repo, options)?;
     let fetch_oid = fetch_head_oid(&repo)?;
-    apply_fast_forward(&repo, &target.branch_ref_name, target.head_oid, fetch_oid)?;
+    apply_pull(&mut repo, &target, fetch_oid, options)?;
 
     Ok(())
 }
 
+/// Limits a fetch to part of the remote. Both unset fetches every ref the
+/// remote's configured refspecs cover.
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct FetchScope {
+    /// Remote branch to fetch alone; `HEAD` means the current branch's
+    /// upstream (or the same-named branch when none is configured).
+    pub branch: Option<String>,
+    /// Explicit refspec, e.g. `+refs/pull/42/head:refs/remotes/origin/pr/42`.
+    pub refspec: Option<String>,
+}
+
+impl FetchScope {
+    /// Refspecs to pass to the fetch; empty means the configured defaults.
+    pub(crate) fn refspecs(
+        &self,
+        repo: &Repository,
+        remote_name: &str,
+    ) -> Result<Vec<String>, String> {
+        let branch = self.branch.as_deref().map(str::trim);
+        let refspec = self.refspec.as_deref().map(str::trim);
+        match (branch, refspec) {
+            (Some(_), Some(_)) => Err(
+                "E_FETCH_SCOPE_INVALID: pass either a branch or a refspec, not both".to_string(),
+            ),
+            (None, Some(refspec)) => Ok(vec![refspec.to_string()]),
+            (Some(branch), None) => {
+                let remote_ref = if branch == "HEAD" {
+                    current_upstream_ref(repo, remote_name)?
+                } else {
+                    format!("refs/heads/{}", branch.trim_start_matches("refs/heads/"))
+                };
+                let name = remote_ref.trim_start_matches("refs/heads/");
+                Ok(vec![format!(
+                    "+{}:refs/remotes/{}/{}",
+                    remote_ref, remote_name, name
+                )])
+            }
+            (None, None) => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Remote ref the current branch pulls from: `branch.<name>.merge` when it
+/// tracks `remote_name`, else the same-named branch.
+fn current_upstream_ref(repo: &Repository, remote_name: &str) -> Result<String, String> {
+    let head = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .ok_or_else(|| {
+            "E_FETCH_SCOPE_INVALID: fetching the current branch needs a checked-out branch"
+                .to_string()
+        })?;
+    let refname = head.name().unwrap_or_default();
+    let tracks_remote = repo
+        .branch_upstream_remote(refname)
+        .ok()
+        .and_then(|remote| remote.as_str().map(|name| name == remote_name))
+        .unwrap_or(false);
+    let merge = repo.config().ok().and_then(|config| {
+        config
+            .get_string(&format!("branch.{}.merge", head.shorthand()?))
+            .ok()
+    });
+    Ok(match merge {
+        Some(merge) if tracks_remote => merge,
+        _ => refname.to_string(),
+    })
+}
+
 pub fn fetch_remote(
     path: &str,
     remote_name: &str,
     username: &str,
     password: &str,
+    scope: &FetchScope,
 ) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
     let remote_name = normalize_remote_name(remote_name);
-    fetch_remote_internal(&repo, &remote_name, username, password)
+    let refspecs = scope.refspecs(&repo, &remote_name)?;
+    fetch_remote_internal(
+        &repo,
+        &remote_name,
+        username,
+        password,
+        &refspecs,
+        true,
+        None,
+    )
+}
+
+/// Fetch like `fetch_remote`, reporting download progress. Returning `false`
+/// from `on_progress` aborts the transfer.
+pub fn fetch_remote_with_progress(
+    path: &str,
+    remote_name: &str,
+    username: &str,
+    password: &str,
+    scope: &FetchScope,
+    mut on_progress: impl FnMut(&TransferProgress) -> bool,
+) -> Result<(), String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let remote_name = normalize_remote_name(remote_name);
+    let refspecs = scope.refspecs(&repo, &remote_name)?;
+    fetch_remote_internal(
+        &repo,
+        &remote_name,
+        username,
+        password,
+        &refspecs,
+        true,
+        Some(&mut on_progress),
+    )
 }
 
 pub fn sync_status(path: &str, remote_name: &str) -> Result<SyncStatus, String> {
@@ -210,10 +720,15 @@ pub fn sync_status(path: &str, remote_name: &str) -> Result<SyncStatus, String>
     ))?;
 
     let remote_ref = format!("refs/remotes/{}/{}", remote_name, branch);
-    let remote_target = repo
-        .find_reference(&remote_ref)
+    let configured = repo
+        .branch_upstream_name(&format!("refs/heads/{}", branch))
         .ok()
-        .and_then(|reference| reference.target());
+        .and_then(|name| name.as_str().map(str::to_string));
+    let remote_target = repo.refname_to_id(&remote_ref).ok().or_else(|| {
+        configured
+            .as_deref()
+            .and_then(|name| repo.refname_to_id(name).ok())
+    });
 
     if let Some(remote_oid) = remote_target {
         let (ahead, behind) = repo
@@ -222,19 +737,94 @@ pub fn sync_status(path: &str, remote_name: &str) -> Result<SyncStatus, String>
         return Ok(SyncStatus {
             branch,
             has_upstream: true,
+            upstream_state: "tracking".to_string(),
+            suggested_action: None,
             ahead,
             behind,
         });
     }
 
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+    let gone = config
+        .get_string(&format!("branch.{}.merge", branch))
+        .is_ok();
+    // Never suggest a push here: it would recreate a branch the remote deleted.
+    let suggested_action = gone.then(|| {
+        let upstream_remote = config
+            .get_string(&format!("branch.{}.remote", branch))
+            .unwrap_or_else(|_| remote_name.clone());
+        if on_remote(&repo, &upstream_remote, local_oid) {
+            "delete_branch".to_string()
+        } else {
+            "delete_branch_unmerged".to_string()
+        }
+    });
     Ok(SyncStatus {
         branch,
         has_upstream: false,
+        upstream_state: if gone { "gone" } else { "none" }.to_string(),
+        suggested_action,
         ahead: 0,
         behind: 0,
     })
 }
 
+/// Whether `commit` is reachable from any remote-tracking branch of `remote_name`.
+fn on_remote(repo: &Repository, remote_name: &str, commit: git2::Oid) -> bool {
+    let Ok(references) = repo.references_glob(&format!("refs/remotes/{}/*", remote_name)) else {
+        return false;
+    };
+    references
+        .flatten()
+        .filter_map(|reference| reference.resolve().ok()?.target())
+        .any(|tip| tip == commit || repo.graph_descendant_of(tip, commit).unwrap_or(false))
+}
+
+/// Fetch `remote_name` and report how the current branch's upstream moved.
+pub fn fetch_with_sync_status(
+    path: &str,
+    remote_name: &str,
+    username: &str,
+    password: &str,
+) -> Result<FetchUpdate, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let remote_name = normalize_remote_name(remote_name);
+    let upstream_tip = |repo: &Repository| {
+        let head = repo.head().ok().filter(|head| head.is_branch())?;
+        let branch = head.shorthand()?.to_string();
+        repo.find_reference(&format!("refs/remotes/{}/{}", remote_name, branch))
+            .ok()
+            .and_then(|reference| reference.target())
+    };
+
+    let before = upstream_tip(&repo);
+    // Runs unattended from the auto-fetch scheduler; never prompt.
+    fetch_remote_internal(&repo, &remote_name, username, password, &[], false, None)?;
+    let after = upstream_tip(&repo);
+
+    let new_commits = match (before, after) {
+        (Some(before), Some(after)) if before != after => repo
+            .graph_ahead_behind(after, before)
+            .map(|(ahead, _)| ahead)
+            .unwrap_or(0),
+        (None, Some(after)) => repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .and_then(|local| repo.graph_ahead_behind(after, local).ok())
+            .map(|(ahead, _)| ahead)
+            .unwrap_or(0),
+        _ => 0,
+    };
+
+    Ok(FetchUpdate {
+        status: sync_status(path, &remote_name)?,
+        new_commits,
+    })
+}
+
 fn normalize_remote_name(remote_name: &str) -> String {
     let trimmed = remote_name.trim();
     if trimmed.is_empty() {
@@ -249,6 +839,9 @@ fn fetch_remote_internal(
     remote_name: &str,
     username: &str,
     password: &str,
+    refspecs: &[String],
+    interactive: bool,
+    on_progress: Option<&mut dyn FnMut(&TransferProgress) -> bool>,
 ) -> Result<(), String> {
     let mut remote = repo
         .find_remote(remote_name)
@@ -259,24 +852,42 @@ fn fetch_remote_internal(
     let config = repo
         .config()
         .map_err(|e| format!("Failed to read repository config: {}", e))?;
+    crate::tls_certs::load_configured_ca(&config);
 
     let fetch_result = {
         let mut callbacks = RemoteCallbacks::new();
+        let mut attempts = CredentialAttempts::default();
         callbacks.credentials(move |url, username_from_url, allowed_types| {
             resolve_https_cred(
                 &config,
                 Some(url),
                 username_from_url,
                 allowed_types,
-                &provided_username,
-                &provided_password,
+                (&provided_username, &provided_password),
+                interactive,
+                &mut attempts,
             )
         });
+        crate::known_hosts::verify_host_keys(
+            &mut callbacks,
+            remote.url().unwrap_or_default(),
+            interactive,
+        );
+
+        if let Some(on_progress) = on_progress {
+            callbacks.transfer_progress(move |stats| {
+                on_progress(&TransferProgress {
+                    received_objects: stats.received_objects(),
+                    total_objects: stats.total_objects(),
+                    received_bytes: stats.received_bytes(),
+                })
+            });
+        }
 
         let mut options = FetchOptions::new();
         options.remote_callbacks(callbacks);
 
-        remote.fetch(&[] as &[&str], Some(&mut options), None)
+        remote.fetch(refspecs, Some(&mut options), None)
     };
 
     if let Err(error) = fetch_result {
@@ -286,16 +897,32 @@ fn fetch_remote_internal(
     Ok(())
 }
 
+/// Credential sources one operation has already offered. libgit2 asks again
+/// only after the server rejected the last answer, so each source is tried
+/// once before falling through to the next.
+#[derive(Default)]
+struct CredentialAttempts {
+    helper: bool,
+    provided: bool,
+    saved: bool,
+    /// Secret of the saved credential last offered.
+    saved_secret: Option<String>,
+    refreshed: bool,
+    default: bool,
+}
+
 fn resolve_https_cred(
     config: &git2::Config,
     url: Option<&str>,
     username_from_url: Option<&str>,
     allowed_types: CredentialType,
-    provided_username: &str,
-    provided_password: &str,
+    (provided_username, provided_password): (&str, &str),
+    interactive: bool,
+    attempts: &mut CredentialAttempts,
 ) -> Result<Cred, git2::Error> {
     // 1) Try system credential helper first (Keychain/GCM/libsecret/etc.)
-    if let Some(remote_url) = url {
+    let try_helper = !std::mem::replace(&mut attempts.helper, true);
+    if let Some(remote_url) = url.filter(|_| try_helper) {
         let fallback_username = if provided_username.is_empty() {
             None
         } else {
@@ -311,12 +938,116 @@ fn resolve_https_cred(
     if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT)
         && !provided_username.is_empty()
         && !provided_password.is_empty()
+        && !std::mem::replace(&mut attempts.provided, true)
     {
         return Cred::userpass_plaintext(provided_username, provided_password);
     }
 
-    // 3) Last resort: default credential provider (platform specific)
-    Cred::default()
+    // 3) Credential saved in the OS keychain for this host, the GitHub or
+    //    GitLab OAuth token, or a Bitbucket app password for the remote's
+    //    workspace. Once it was rejected, only a refreshed OAuth token with a
+    //    new secret is offered again.
+    if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) && !attempts.refreshed {
+        let username =
+            username_from_url.or(Some(provided_username).filter(|name| !name.is_empty()));
+        let rejected = std::mem::replace(&mut attempts.saved, true);
+        attempts.refreshed = rejected;
+        if let Some((username, secret)) =
+            url.and_then(|url| saved_https_cred(config, url, username, rejected))
+        {
+            if attempts.saved_secret.as_deref() != Some(secret.as_str()) {
+                let cred = Cred::userpass_plaintext(&username, &secret);
+                attempts.saved_secret = Some(secret);
+                return cred;
+            }
+        }
+    }
+
+    // 4) Ask the user through the frontend
+    if interactive && allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) {
+        if let Some(remote_url) = url {
+            let username =
+                username_from_url.or(Some(provided_username).filter(|name| !name.is_empty()));
+            return match crate::credential_prompt::request(remote_url, username) {
+                Some(answer) => Cred::userpass_plaintext(&answer.username, &answer.password),
+                None => Err(git2::Error::new(
+                    ErrorCode::Auth,
+                    ErrorClass::Callback,
+                    "credential prompt was cancelled or timed out",
+                )),
+            };
+        }
+    }
+
+    // 5) Last resort: default credential provider (platform specific)
+    if !std::mem::replace(&mut attempts.default, true) {
+        return Cred::default();
+    }
+    Err(git2::Error::new(
+        ErrorCode::Auth,
+        ErrorClass::Callback,
+        "every available credential was rejected",
+    ))
+}
+
+/// `(username, secret)` GitLite has saved for `url`, without prompting.
+/// `rejected` means the server turned down the previous answer, so an
+/// expiring OAuth token is refreshed before it is offered again.
+pub(super) fn saved_https_cred(
+    config: &git2::Config,
+    url: &str,
+    username: Option<&str>,
+    rejected: bool,
+) -> Option<(String, String)> {
+    crate::credentials::find_for_url(url, username)
+        .or_else(|| github_token_cred(config, url))
+        .or_else(|| crate::gitlab_auth::find_for_url(url, rejected))
+        .or_else(|| crate::bitbucket_auth::find_for_url(url))
+}
+
+/// The OAuth token saved for the remote's host, in the
+/// `x-access-token:<token>` form GitHub accepts for git over HTTPS. Any host
+/// with a saved token counts, since GitHub Enterprise hosts need not have
+/// "github" in their name.
+fn github_token_cred(config: &git2::Config, url: &str) -> Option<(String, String)> {
+    if !config.get_bool(USE_GITHUB_TOKEN_KEY).unwrap_or(true) {
+        return None;
+    }
+    let host = crate::credentials::normalize_host(url).ok()?;
+    let token = crate::github_auth::load_token_from_keychain(Some(&host))
+        .ok()
+        .flatten()?;
+    Some(("x-access-token".to_string(), token))
+}
+
+/// Whether HTTPS operations in this repository may use the saved GitHub token.
+pub fn get_use_github_token(path: &str) -> Result<bool, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read repository config: {}", e))?;
+    Ok(config.get_bool(USE_GITHUB_TOKEN_KEY).unwrap_or(true))
+}
+
+/// Opt this repository out of (or back into) the saved GitHub token.
+/// Enabling removes the local override so the default applies again.
+pub fn set_use_github_token(path: &str, enabled: bool) -> Result<(), String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut config = repo
+        .config()
+        .and_then(|config| config.open_level(git2::ConfigLevel::Local))
+        .map_err(|e| format!("Failed to read repository config: {}", e))?;
+    let result = if enabled {
+        config.remove(USE_GITHUB_TOKEN_KEY)
+    } else {
+        config.set_bool(USE_GITHUB_TOKEN_KEY, false)
+    };
+    match result {
+        Err(e) if e.code() != ErrorCode::NotFound => {
+            Err(format!("Failed to update {}: {}", USE_GITHUB_TOKEN_KEY, e))
+        }
+        _ => Ok(()),
+    }
 }
 
 fn is_non_fast_forward(status: &str) -> bool {
@@ -327,6 +1058,12 @@ fn is_non_fast_forward(status: &str) -> bool {
 }
 
 fn format_push_error(error: git2::Error) -> String {
+    if let Some(rejection) = crate::known_hosts::rejection(&error) {
+        return rejection;
+    }
+    if let Some(message) = crate::tls_certs::certificate_error(&error) {
+        return message;
+    }
     match error.code() {
         ErrorCode::NotFastForward => format!(
             "{}: Push rejected: non-fast-forward. Please pull and try again.",
@@ -343,7 +1080,14 @@ fn format_push_error(error: git2::Error) -> String {
 }
 
 fn format_fetch_error(error: git2::Error) -> String {
+    if let Some(rejection) = crate::known_hosts::rejection(&error) {
+        return rejection;
+    }
+    if let Some(message) = crate::tls_certs::certificate_error(&error) {
+        return message;
+    }
     match error.code() {
+        ErrorCode::User => format!("{}: Fetch cancelled", E_TRANSFER_CANCELLED),
         ErrorCode::Auth => format!("{}: Authentication failed: {}", E_PULL_AUTH, error),
         _ => match error.class() {
             ErrorClass::Net | ErrorClass::Http | ErrorClass::Ssh | ErrorClass::Ssl => {
@@ -359,10 +1103,10 @@ mod tests {
     use super::*;
     use git2::Repository;
     use std::fs;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use std::process::Command;
 
-    fn run_git(args: &[&str], cwd: &PathBuf) {
+    fn run_git(args: &[&str], cwd: &Path) {
         let output = Command::new("git")
             .args(args)
             .current_dir(cwd)
@@ -482,6 +1226,7 @@ mod tests {
             "origin",
             "test-user",
             "test-pass",
+            &PullOptions::default(),
         );
         assert!(result.is_ok());
 
@@ -510,6 +1255,7 @@ mod tests {
             "origin",
             "test-user",
             "test-pass",
+            &PullOptions::default(),
         );
 
         assert!(result.is_err());
@@ -523,6 +1269,113 @@ mod tests {
         fs::remove_dir_all(base_dir).unwrap();
     }
 
+    /// Push a commit adding `file` from a second clone, so the local
+    /// repository falls behind the remote.
+    fn push_from_other_clone(base_dir: &Path, remote_dir: &Path, branch_name: &str, file: &str) {
+        let other_dir = base_dir.join("other");
+        if !other_dir.exists() {
+            run_git(
+                &[
+                    "clone",
+                    remote_dir.to_str().unwrap(),
+                    other_dir.to_str().unwrap(),
+                ],
+                base_dir,
+            );
+            run_git(&["config", "user.name", "Other User"], &other_dir);
+            run_git(&["config", "user.email", "other@example.com"], &other_dir);
+            run_git(&["checkout", branch_name], &other_dir);
+        } else {
+            run_git(&["pull", "--ff-only", "origin", branch_name], &other_dir);
+        }
+        fs::write(other_dir.join(file), "remote line").unwrap();
+        run_git(&["add", "."], &other_dir);
+        run_git(&["commit", "-m", "Remote commit"], &other_dir);
+        run_git(&["push", "origin", branch_name], &other_dir);
+    }
+
+    #[test]
+    fn test_pull_autostash_restores_local_changes() {
+        let (base_dir, local_dir, remote_dir) = create_test_repo();
+        let branch_name = current_branch_name(&local_dir);
+        assert!(push(local_dir.to_str().unwrap(), "origin", "u", "p").is_ok());
+
+        push_from_other_clone(&base_dir, &remote_dir, &branch_name, "remote.txt");
+
+        fs::write(local_dir.join("test.txt"), "dirty local change").unwrap();
+        fs::write(local_dir.join("untracked.txt"), "new file").unwrap();
+        let options = PullOptions {
+            autostash: true,
+            ..PullOptions::default()
+        };
+        let result = pull(local_dir.to_str().unwrap(), "origin", "u", "p", &options);
+        assert!(result.is_ok(), "{:?}", result);
+
+        assert!(local_dir.join("remote.txt").exists());
+        assert_eq!(
+            fs::read_to_string(local_dir.join("test.txt")).unwrap(),
+            "dirty local change"
+        );
+        assert!(local_dir.join("untracked.txt").exists());
+        let mut repo = Repository::open(&local_dir).unwrap();
+        let mut stashes = 0;
+        repo.stash_foreach(|_, _, _| {
+            stashes += 1;
+            true
+        })
+        .unwrap();
+        assert_eq!(stashes, 0);
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn test_pull_merge_and_rebase_strategies() {
+        let (base_dir, local_dir, remote_dir) = create_test_repo();
+        let branch_name = current_branch_name(&local_dir);
+        let path = local_dir.to_str().unwrap();
+        assert!(push(path, "origin", "u", "p").is_ok());
+
+        push_from_other_clone(&base_dir, &remote_dir, &branch_name, "first.txt");
+        fs::write(local_dir.join("local-one.txt"), "local line").unwrap();
+        run_git(&["add", "."], &local_dir);
+        run_git(&["commit", "-m", "Local commit"], &local_dir);
+
+        let merge = PullOptions::from_args(None, Some("merge")).unwrap();
+        assert!(pull(path, "origin", "u", "p", &merge).is_ok());
+        let repo = Repository::open(&local_dir).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 2);
+        assert!(local_dir.join("first.txt").exists());
+        assert!(local_dir.join("local-one.txt").exists());
+        assert!(push(path, "origin", "u", "p").is_ok());
+
+        push_from_other_clone(&base_dir, &remote_dir, &branch_name, "second.txt");
+        fs::write(local_dir.join("local-two.txt"), "local line").unwrap();
+        run_git(&["add", "."], &local_dir);
+        run_git(&["commit", "-m", "Second local commit"], &local_dir);
+
+        run_git(&["config", "pull.rebase", "true"], &local_dir);
+        assert!(pull(path, "origin", "u", "p", &PullOptions::default()).is_ok());
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary(), Some("Second local commit"));
+        assert_eq!(head.parent_count(), 1);
+        assert_eq!(
+            head.parent(0).unwrap().summary(),
+            Some("Remote commit"),
+            "local commit replayed on top of the fetched one"
+        );
+        assert!(local_dir.join("second.txt").exists());
+        let statuses = repo.statuses(None).unwrap();
+        assert!(statuses.is_empty(), "index and worktree match the new HEAD");
+
+        assert!(PullOptions::from_args(None, Some("squash"))
+            .unwrap_err()
+            .starts_with("E_PULL_STRATEGY_INVALID"));
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
     #[test]
     fn test_pull_rejects_non_fast_forward() {
         let (base_dir, local_dir, remote_dir) = create_test_repo();
@@ -530,28 +1383,19 @@ mod tests {
 
         assert!(push(local_dir.to_str().unwrap(), "origin", "u", "p").is_ok());
 
-        let other_dir = base_dir.join("other");
-        run_git(
-            &[
-                "clone",
-                remote_dir.to_str().unwrap(),
-                other_dir.to_str().unwrap(),
-            ],
-            &base_dir,
-        );
-        run_git(&["config", "user.name", "Other User"], &other_dir);
-        run_git(&["config", "user.email", "other@example.com"], &other_dir);
-        run_git(&["checkout", &branch_name], &other_dir);
-        fs::write(other_dir.join("shared.txt"), "remote line").unwrap();
-        run_git(&["add", "."], &other_dir);
-        run_git(&["commit", "-m", "Remote commit"], &other_dir);
-        run_git(&["push", "origin", &branch_name], &other_dir);
+        push_from_other_clone(&base_dir, &remote_dir, &branch_name, "shared.txt");
 
         fs::write(local_dir.join("local-only.txt"), "local line").unwrap();
         run_git(&["add", "."], &local_dir);
         run_git(&["commit", "-m", "Local commit"], &local_dir);
 
-        let result = pull(local_dir.to_str().unwrap(), "origin", "u", "p");
+        let result = pull(
+            local_dir.to_str().unwrap(),
+            "origin",
+            "u",
+            "p",
+            &PullOptions::default(),
+        );
         assert!(result.is_err());
         let message = result.unwrap_err();
         assert!(
@@ -575,7 +1419,13 @@ mod tests {
         let head_oid = String::from_utf8(output.stdout).unwrap().trim().to_string();
         run_git(&["checkout", &head_oid], &local_dir);
 
-        let result = pull(local_dir.to_str().unwrap(), "origin", "u", "p");
+        let result = pull(
+            local_dir.to_str().unwrap(),
+            "origin",
+            "u",
+            "p",
+            &PullOptions::default(),
+        );
         assert!(result.is_err());
         let message = result.unwrap_err();
         assert!(
@@ -625,6 +1475,63 @@ mod tests {
         fs::remove_dir_all(base_dir).unwrap();
     }
 
+    #[test]
+    fn test_list_remotes_reports_raw_and_effective_urls() {
+        let (base_dir, local_dir, _remote_dir) = create_test_repo();
+
+        run_git(
+            &["config", "url.https://github.com/.insteadOf", "gh:"],
+            &local_dir,
+        );
+        run_git(
+            &["config", "url.git@github.com:.pushInsteadOf", "gh:"],
+            &local_dir,
+        );
+        run_git(&["remote", "add", "short", "gh:test/repo.git"], &local_dir);
+
+        let remotes = list_remotes(local_dir.to_str().unwrap()).unwrap();
+        let short = remotes.iter().find(|r| r.name == "short").unwrap();
+        assert_eq!(short.raw_url.as_deref(), Some("gh:test/repo.git"));
+        assert_eq!(
+            short.url.as_deref(),
+            Some("https://github.com/test/repo.git")
+        );
+        assert_eq!(
+            short.effective_push_url.as_deref(),
+            Some("git@github.com:test/repo.git")
+        );
+        assert_eq!(short.provider, "github");
+        assert_eq!(short.push_url, None);
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn test_set_remote_push_url() {
+        let (base_dir, local_dir, _remote_dir) = create_test_repo();
+        let path = local_dir.to_str().unwrap();
+        let fork = "https://github.com/me/fork.git";
+
+        set_remote_push_url(path, "origin", Some(fork)).unwrap();
+        let remotes = list_remotes(path).unwrap();
+        let origin = remotes.iter().find(|r| r.name == "origin").unwrap();
+        assert_eq!(origin.push_url.as_deref(), Some(fork));
+        assert_eq!(origin.effective_push_url.as_deref(), Some(fork));
+        assert_ne!(origin.url.as_deref(), Some(fork));
+
+        set_remote_push_url(path, "origin", Some("")).unwrap();
+        let remotes = list_remotes(path).unwrap();
+        let origin = remotes.iter().find(|r| r.name == "origin").unwrap();
+        assert_eq!(origin.push_url, None);
+        assert_eq!(origin.effective_push_url, origin.url);
+
+        assert!(set_remote_push_url(path, "missing", None)
+            .unwrap_err()
+            .starts_with("E_REMOTE_NOT_FOUND"));
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
     #[test]
     fn test_add_remote() {
         let (base_dir, local_dir, _remote_dir) = create_test_repo();
@@ -647,6 +1554,37 @@ mod tests {
         fs::remove_dir_all(base_dir).unwrap();
     }
 
+    #[test]
+    fn test_fetch_refspec_management() {
+        let (base_dir, local_dir, _remote_dir) = create_test_repo();
+        let path = local_dir.to_str().unwrap();
+        let default = "+refs/heads/*:refs/remotes/origin/*";
+        let pulls = "+refs/pull/*/head:refs/remotes/origin/pr/*";
+        assert_eq!(get_fetch_refspecs(path, "origin").unwrap(), vec![default]);
+
+        assert_eq!(
+            add_fetch_refspec(path, "origin", pulls).unwrap(),
+            vec![default, pulls]
+        );
+        assert_eq!(add_fetch_refspec(path, "origin", pulls).unwrap().len(), 2);
+        assert!(add_fetch_refspec(path, "origin", "refs/heads/*:")
+            .unwrap_err()
+            .starts_with("E_REFSPEC_INVALID"));
+
+        assert_eq!(
+            remove_fetch_refspec(path, "origin", default).unwrap(),
+            vec![pulls]
+        );
+        assert!(remove_fetch_refspec(path, "origin", default)
+            .unwrap_err()
+            .starts_with("E_REFSPEC_NOT_FOUND"));
+        assert!(get_fetch_refspecs(path, "missing")
+            .unwrap_err()
+            .starts_with("E_REMOTE_NOT_FOUND"));
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
     #[test]
     fn test_remove_remote() {
         let (base_dir, local_dir, _remote_dir) = create_test_repo();
@@ -733,12 +1671,65 @@ mod tests {
         let status = sync_status(local_dir.to_str().unwrap(), "origin").unwrap();
         assert_eq!(status.branch, current_branch_name(&local_dir));
         assert!(!status.has_upstream);
+        assert_eq!(status.upstream_state, "none");
+        assert!(status.suggested_action.is_none());
         assert_eq!(status.ahead, 0);
         assert_eq!(status.behind, 0);
 
         fs::remove_dir_all(base_dir).unwrap();
     }
 
+    #[test]
+    fn test_sync_status_reports_gone_upstream() {
+        let (base_dir, local_dir, _remote_dir) = create_test_repo();
+        let local = local_dir.to_str().unwrap();
+        let branch = current_branch_name(&local_dir);
+        assert!(push(local, "origin", "u", "p").is_ok());
+        run_git(&["checkout", "-b", "feature"], &local_dir);
+        assert!(push(local, "origin", "u", "p").is_ok());
+        run_git(
+            &["branch", "--set-upstream-to", "origin/feature"],
+            &local_dir,
+        );
+        assert_eq!(
+            sync_status(local, "origin").unwrap().upstream_state,
+            "tracking"
+        );
+
+        run_git(
+            &["update-ref", "-d", "refs/remotes/origin/feature"],
+            &local_dir,
+        );
+        let status = sync_status(local, "origin").unwrap();
+        assert!(!status.has_upstream);
+        assert_eq!(status.upstream_state, "gone");
+        assert_eq!(status.suggested_action.as_deref(), Some("delete_branch"));
+
+        // Reachability is judged on the branch's own remote, not the one asked about.
+        run_git(&["config", "branch.feature.remote", "fork"], &local_dir);
+        let status = sync_status(local, "origin").unwrap();
+        assert_eq!(
+            status.suggested_action.as_deref(),
+            Some("delete_branch_unmerged")
+        );
+        run_git(&["config", "branch.feature.remote", "origin"], &local_dir);
+
+        fs::write(local_dir.join("unpushed.txt"), "work").unwrap();
+        run_git(&["add", "."], &local_dir);
+        run_git(&["commit", "-m", "Unpushed work"], &local_dir);
+        let status = sync_status(local, "origin").unwrap();
+        assert_eq!(
+            status.suggested_action.as_deref(),
+            Some("delete_branch_unmerged")
+        );
+
+        run_git(&["checkout", &branch], &local_dir);
+        let status = sync_status(local, "origin").unwrap();
+        assert_eq!(status.upstream_state, "tracking");
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
     #[test]
     fn test_sync_status_ahead_after_local_commit() {
         let (base_dir, local_dir, _remote_dir) = create_test_repo();
@@ -755,4 +1746,203 @@ mod tests {
 
         fs::remove_dir_all(base_dir).unwrap();
     }
+
+    #[test]
+    fn test_fetch_with_sync_status_counts_new_upstream_commits() {
+        let (base_dir, local_dir, remote_dir) = create_test_repo();
+        let local = local_dir.to_str().unwrap();
+        assert!(push(local, "origin", "u", "p").is_ok());
+
+        let other_dir = base_dir.join("other");
+        run_git(
+            &[
+                "clone",
+                remote_dir.to_str().unwrap(),
+                other_dir.to_str().unwrap(),
+            ],
+            &base_dir,
+        );
+        run_git(&["config", "user.name", "Other User"], &other_dir);
+        run_git(&["config", "user.email", "other@example.com"], &other_dir);
+        fs::write(other_dir.join("upstream.txt"), "upstream").unwrap();
+        run_git(&["add", "."], &other_dir);
+        run_git(&["commit", "-m", "Upstream commit"], &other_dir);
+        run_git(&["push", "origin", "HEAD"], &other_dir);
+
+        let update = fetch_with_sync_status(local, "origin", "", "").unwrap();
+        assert_eq!(update.new_commits, 1);
+        assert!(update.status.has_upstream);
+        assert_eq!(update.status.behind, 1);
+
+        let again = fetch_with_sync_status(local, "origin", "", "").unwrap();
+        assert_eq!(again.new_commits, 0);
+        assert_eq!(again.status.behind, 1);
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_single_branch() {
+        let (base_dir, local_dir, remote_dir) = create_test_repo();
+        let local = local_dir.to_str().unwrap();
+        assert!(push(local, "origin", "u", "p").is_ok());
+        run_git(&["push", "origin", "HEAD:refs/heads/other"], &local_dir);
+        run_git(
+            &["update-ref", "-d", "refs/remotes/origin/other"],
+            &local_dir,
+        );
+        let branch = current_branch_name(&local_dir);
+        push_from_other_clone(&base_dir, &remote_dir, &branch, "upstream.txt");
+
+        let tip = |name: &str| {
+            Repository::open(&local_dir)
+                .unwrap()
+                .refname_to_id(&format!("refs/remotes/origin/{}", name))
+                .ok()
+        };
+        let before = tip(&branch);
+        let current = FetchScope {
+            branch: Some("HEAD".to_string()),
+            refspec: None,
+        };
+        fetch_remote(local, "origin", "", "", &current).unwrap();
+        assert_ne!(tip(&branch), before);
+        assert_eq!(tip("other"), None, "only the current branch is fetched");
+
+        let other = FetchScope {
+            branch: Some("other".to_string()),
+            refspec: None,
+        };
+        fetch_remote(local, "origin", "", "", &other).unwrap();
+        assert!(tip("other").is_some());
+
+        let both = FetchScope {
+            branch: Some("other".to_string()),
+            refspec: Some("refs/heads/other".to_string()),
+        };
+        assert!(fetch_remote(local, "origin", "", "", &both)
+            .unwrap_err()
+            .starts_with("E_FETCH_SCOPE_INVALID"));
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn test_preview_push() {
+        let (base_dir, local_dir, remote_dir) = create_test_repo();
+        let local = local_dir.to_str().unwrap();
+        let branch = current_branch_name(&local_dir);
+
+        let fresh = preview_push(local, "origin").unwrap();
+        assert_eq!(fresh.outcome, "new_branch");
+        assert_eq!((fresh.commits, fresh.remote_commit.clone()), (1, None));
+        assert!(fresh.estimated_bytes > "test content".len() as u64);
+        assert_eq!(fresh.remote_ref, format!("refs/heads/{}", branch));
+
+        push(local, "origin", "u", "p").unwrap();
+        assert_eq!(preview_push(local, "origin").unwrap().outcome, "up_to_date");
+
+        fs::write(local_dir.join("local.txt"), "local").unwrap();
+        run_git(&["add", "."], &local_dir);
+        run_git(&["commit", "-m", "Local commit"], &local_dir);
+        let ahead = preview_push(local, "origin").unwrap();
+        assert_eq!((ahead.outcome.as_str(), ahead.commits), ("fast_forward", 1));
+
+        push_from_other_clone(&base_dir, &remote_dir, &branch, "upstream.txt");
+        run_git(&["fetch", "origin"], &local_dir);
+        let diverged = preview_push(local, "origin").unwrap();
+        assert_eq!(diverged.outcome, "non_fast_forward");
+        assert_eq!((diverged.commits, diverged.behind), (1, 1));
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn fork_remotes_repoint_origin_and_add_upstream() {
+        let (base_dir, local_dir, remote_dir) = create_test_repo();
+        let path = local_dir.to_str().unwrap();
+        let parent = remote_dir.to_str().unwrap();
+
+        let remotes =
+            configure_fork_remotes(path, "https://github.com/me/app.git", parent).unwrap();
+        let url_of = |name: &str| {
+            remotes
+                .iter()
+                .find(|remote| remote.name == name)
+                .and_then(|remote| remote.raw_url.clone())
+        };
+        assert_eq!(
+            url_of("origin").as_deref(),
+            Some("https://github.com/me/app.git")
+        );
+        assert_eq!(url_of("upstream").as_deref(), Some(parent));
+        assert!(matches!(
+            configure_fork_remotes(path, " ", parent),
+            Err(error) if error.starts_with("E_FORK_URL_EMPTY")
+        ));
+        assert!(configure_fork_remotes(path, "https://github.com/me/app.git", parent).is_ok());
+        assert!(matches!(
+            configure_fork_remotes(path, "https://github.com/you/app.git", "https://example.com/other.git"),
+            Err(error) if error.starts_with("E_FORK_UPSTREAM_EXISTS")
+        ));
+        let origin = Repository::open(&local_dir)
+            .unwrap()
+            .find_remote("origin")
+            .unwrap()
+            .url()
+            .map(str::to_string);
+        assert_eq!(origin.as_deref(), Some("https://github.com/me/app.git"));
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn github_token_opt_out_is_stored_per_repo() {
+        let (base_dir, local_dir, _) = create_test_repo();
+        let path = local_dir.to_str().unwrap();
+        assert!(get_use_github_token(path).unwrap());
+
+        set_use_github_token(path, false).unwrap();
+        assert!(!get_use_github_token(path).unwrap());
+        let config = Repository::open(&local_dir).unwrap().config().unwrap();
+        assert_eq!(
+            github_token_cred(&config, "https://github.com/octo/app.git"),
+            None
+        );
+
+        set_use_github_token(path, true).unwrap();
+        set_use_github_token(path, true).unwrap();
+        assert!(get_use_github_token(path).unwrap());
+        let config = Repository::open(&local_dir).unwrap().config().unwrap();
+        assert_eq!(
+            github_token_cred(&config, "https://gitlab.com/octo/app.git"),
+            None
+        );
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn each_credential_source_is_offered_once_per_operation() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let config = git2::Config::new().unwrap();
+        let mut attempts = CredentialAttempts::default();
+        let mut resolve = || {
+            resolve_https_cred(
+                &config,
+                Some("https://example.invalid/repo.git"),
+                None,
+                CredentialType::USER_PASS_PLAINTEXT | CredentialType::DEFAULT,
+                ("alice", "secret"),
+                false,
+                &mut attempts,
+            )
+        };
+
+        assert!(resolve().is_ok());
+        assert!(resolve().is_ok());
+        let error = resolve().err().unwrap();
+        assert_eq!(error.code(), ErrorCode::Auth);
+        assert!(attempts.provided && attempts.default);
+    }
 }
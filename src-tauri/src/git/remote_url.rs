@@ -0,0 +1,183 @@
+use git2::{Config, Direction};
+
+/// Apply `url.<base>.insteadOf` (and `pushInsteadOf` for pushes) rewrites the
+/// same way git does: the longest matching prefix wins. For pushes, a matching
+/// `pushInsteadOf` takes precedence over `insteadOf`.
+pub fn rewrite_url(config: &Config, url: &str, direction: Direction) -> String {
+    if matches!(direction, Direction::Push) {
+        if let Some(rewritten) = apply_rewrite(config, url, "pushinsteadof") {
+            return rewritten;
+        }
+    }
+    apply_rewrite(config, url, "insteadof").unwrap_or_else(|| url.to_string())
+}
+
+/// Best-effort hosting provider classification from a remote URL.
+pub fn classify_provider(url: &str) -> &'static str {
+    let Some(host) = parse_remote_host(url) else {
+        return "other";
+    };
+
+    if host == "github.com" || host.contains("github") {
+        "github"
+    } else if host == "gitlab.com" || host.contains("gitlab") {
+        "gitlab"
+    } else if host == "bitbucket.org" || host.contains("bitbucket") {
+        "bitbucket"
+    } else {
+        "other"
+    }
+}
+
+/// Extract the lowercase host from https, ssh and scp-like (`git@host:path`) URLs.
+pub fn parse_remote_host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let authority = if let Some((_, rest)) = url.split_once("://") {
+        rest.split('/').next().unwrap_or("")
+    } else if let Some((authority, _)) = url.split_once(':') {
+        // scp-like syntax requires no slash before the colon.
+        if authority.contains('/') {
+            return None;
+        }
+        authority
+    } else {
+        return None;
+    };
+
+    let host = authority
+        .rsplit_once('@')
+        .map(|(_, host)| host)
+        .unwrap_or(authority);
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_ascii_lowercase())
+    }
+}
+
+/// Browser URL of the repository behind a remote (`https://host/owner/repo`),
+/// derived from https, ssh and scp-like remote URLs.
+pub fn web_base_url(url: &str) -> Option<String> {
+    let host = parse_remote_host(url)?;
+    let url = url.trim();
+    let repo_path = if let Some((_, rest)) = url.split_once("://") {
+        rest.split_once('/').map(|(_, path)| path)?
+    } else {
+        url.split_once(':').map(|(_, path)| path)?
+    };
+    let repo_path = repo_path.trim_matches('/');
+    let repo_path = repo_path.strip_suffix(".git").unwrap_or(repo_path);
+    if repo_path.is_empty() {
+        return None;
+    }
+    Some(format!("https://{}/{}", host, repo_path))
+}
+
+fn apply_rewrite(config: &Config, url: &str, suffix: &str) -> Option<String> {
+    let mut best: Option<(String, String)> = None;
+    let entries = config.entries(Some(r"^url\..*\.")).ok()?;
+
+    let _ = entries.for_each(|entry| {
+        let (Some(name), Some(prefix)) = (entry.name(), entry.value()) else {
+            return;
+        };
+        let Some(base) = name
+            .strip_prefix("url.")
+            .and_then(|rest| rest.strip_suffix(&format!(".{}", suffix)))
+        else {
+            return;
+        };
+        if prefix.is_empty() || !url.starts_with(prefix) {
+            return;
+        }
+        let is_longer = best
+            .as_ref()
+            .is_none_or(|(_, best_prefix)| prefix.len() > best_prefix.len());
+        if is_longer {
+            best = Some((base.to_string(), prefix.to_string()));
+        }
+    });
+
+    best.map(|(base, prefix)| format!("{}{}", base, &url[prefix.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn config_with(content: &str) -> (std::path::PathBuf, Config) {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-remote-url-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config");
+        fs::write(&path, content).unwrap();
+        let config = Config::open(&path).unwrap();
+        (dir, config)
+    }
+
+    #[test]
+    fn test_rewrite_url_prefers_longest_match_and_push_rules() {
+        let (dir, config) = config_with(
+            "[url \"git@github.com:\"]\n\tinsteadOf = gh:\n\
+             [url \"git@github.com:acme/\"]\n\tinsteadOf = gh:acme/\n\
+             [url \"ssh://push.example.com/\"]\n\tpushInsteadOf = https://example.com/\n",
+        );
+
+        assert_eq!(
+            rewrite_url(&config, "gh:octo/repo.git", Direction::Fetch),
+            "git@github.com:octo/repo.git"
+        );
+        assert_eq!(
+            rewrite_url(&config, "gh:acme/tool.git", Direction::Fetch),
+            "git@github.com:acme/tool.git"
+        );
+        assert_eq!(
+            rewrite_url(&config, "https://example.com/x.git", Direction::Push),
+            "ssh://push.example.com/x.git"
+        );
+        assert_eq!(
+            rewrite_url(&config, "https://example.com/x.git", Direction::Fetch),
+            "https://example.com/x.git"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_web_base_url() {
+        assert_eq!(
+            web_base_url("git@github.com:octo/repo.git").as_deref(),
+            Some("https://github.com/octo/repo")
+        );
+        assert_eq!(
+            web_base_url("ssh://git@gitlab.com:2222/group/sub/repo.git").as_deref(),
+            Some("https://gitlab.com/group/sub/repo")
+        );
+        assert_eq!(
+            web_base_url("https://user@bitbucket.org/team/repo").as_deref(),
+            Some("https://bitbucket.org/team/repo")
+        );
+        assert_eq!(web_base_url("/srv/git/repo.git"), None);
+    }
+
+    #[test]
+    fn test_classify_provider() {
+        assert_eq!(classify_provider("https://github.com/o/r.git"), "github");
+        assert_eq!(classify_provider("git@gitlab.com:o/r.git"), "gitlab");
+        assert_eq!(
+            classify_provider("ssh://git@bitbucket.org:22/o/r.git"),
+            "bitbucket"
+        );
+        assert_eq!(classify_provider("/srv/git/repo.git"), "other");
+        assert_eq!(
+            parse_remote_host("https://user@Git.Example.com:8443/r.git").as_deref(),
+            Some("git.example.com")
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use git2::Repository;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+const MAX_CACHED_REPOS: usize = 16;
+
+/// Files whose modification invalidates a cached handle: re-inits, config
+/// edits (core.bare, worktree, remotes) and HEAD/ref rewrites done by other
+/// tools.
+const FINGERPRINT_FILES: &[&str] = &["HEAD", "config", "index", "packed-refs"];
+
+type Fingerprint = Vec<Option<(SystemTime, u64)>>;
+
+struct CachedRepo {
+    repo: Arc<Mutex<Repository>>,
+    /// Kept outside the mutex so checking freshness never waits on a
+    /// command that is using the handle.
+    git_dir: PathBuf,
+    fingerprint: Fingerprint,
+    last_used: u64,
+}
+
+/// Open `Repository` handles keyed by canonical path, kept in Tauri managed
+/// state so hot read commands skip re-reading config and refs. A handle is
+/// reopened whenever its git directory fingerprint changes on disk. Clones
+/// share the same cache, so commands can move one onto a worker thread.
+#[derive(Clone, Default)]
+pub struct RepoManager {
+    entries: Arc<Mutex<HashMap<PathBuf, CachedRepo>>>,
+    clock: Arc<Mutex<u64>>,
+}
+
+impl RepoManager {
+    /// Run `f` against the cached handle for `path`, opening (or reopening)
+    /// it as needed. Calls for the same repository are serialized.
+    pub fn with_repo<T>(
+        &self,
+        path: &str,
+        f: impl FnOnce(&mut Repository) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let handle = self.handle(path)?;
+        let mut repo = handle.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut repo)
+    }
+
+    /// Drop the cached handle for `path`, e.g. after it was deleted or moved.
+    pub fn invalidate(&self, path: &str) {
+        self.lock_entries().remove(&cache_key(path));
+    }
+
+    pub fn cached_count(&self) -> usize {
+        self.lock_entries().len()
+    }
+
+    fn handle(&self, path: &str) -> Result<Arc<Mutex<Repository>>, String> {
+        let key = cache_key(path);
+        let tick = {
+            let mut clock = self.clock.lock().unwrap_or_else(|e| e.into_inner());
+            *clock += 1;
+            *clock
+        };
+
+        {
+            let mut entries = self.lock_entries();
+            if let Some(entry) = entries.get_mut(&key) {
+                if fingerprint(&entry.git_dir) == entry.fingerprint {
+                    entry.last_used = tick;
+                    return Ok(entry.repo.clone());
+                }
+                entries.remove(&key);
+            }
+        }
+
+        // Open without holding `entries` so other repositories stay usable.
+        let repo =
+            Repository::open(&key).map_err(|e| format!("Failed to open repository: {}", e))?;
+        let git_dir = repo.path().to_path_buf();
+        let fingerprint = fingerprint(&git_dir);
+        let handle = Arc::new(Mutex::new(repo));
+
+        let mut entries = self.lock_entries();
+        if entries.len() >= MAX_CACHED_REPOS && !entries.contains_key(&key) {
+            if let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            CachedRepo {
+                repo: handle.clone(),
+                git_dir,
+                fingerprint,
+                last_used: tick,
+            },
+        );
+        Ok(handle)
+    }
+
+    fn lock_entries(&self) -> MutexGuard<'_, HashMap<PathBuf, CachedRepo>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn cache_key(path: &str) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+fn fingerprint(git_dir: &Path) -> Fingerprint {
+    FINGERPRINT_FILES
+        .iter()
+        .map(|name| {
+            fs::metadata(git_dir.join(name))
+                .ok()
+                .and_then(|meta| Some((meta.modified().ok()?, meta.len())))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_with_repo_reuses_and_reopens_on_change() {
+        let dir = std::env::temp_dir().join(format!(
+            "gitlite-repo-manager-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        Repository::init(&dir).unwrap();
+        let path = dir.to_str().unwrap();
+
+        let manager = RepoManager::default();
+        let first = manager.handle(path).unwrap();
+        let second = manager.handle(path).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(manager.cached_count(), 1);
+        {
+            // A command holding the handle must not block lookups.
+            let _busy = first.lock().unwrap();
+            assert!(Arc::ptr_eq(&first, &manager.handle(path).unwrap()));
+        }
+
+        Command::new("git")
+            .args(["config", "core.bare", "true"])
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        // Make sure the config write is visible even on coarse mtime clocks.
+        let config = dir.join(".git/config");
+        let content = fs::read_to_string(&config).unwrap();
+        fs::write(&config, format!("{}\n", content)).unwrap();
+
+        let reopened = manager.handle(path).unwrap();
+        assert!(!Arc::ptr_eq(&first, &reopened));
+        assert!(manager.with_repo(path, |repo| Ok(repo.is_bare())).unwrap());
+
+        manager.invalidate(path);
+        assert_eq!(manager.cached_count(), 0);
+        assert!(manager
+            .with_repo(dir.join("missing").to_str().unwrap(), |_| Ok(()))
+            .is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,156 @@
+use git2::build::CheckoutBuilder;
+use git2::Repository;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const E_BARE: &str = "E_BARE";
+
+const CHECKOUT_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct CheckoutProgress {
+    /// File being written when the update was taken.
+    pub file: Option<String>,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Report whether `path` is a bare repository so the UI can hide
+/// worktree-only panels (status, staging, stash).
+pub fn is_bare_repository(path: &str) -> Result<bool, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    Ok(repo.is_bare())
+}
+
+/// What kind of repository `path` opens, so the UI can pick the right
+/// panels before calling worktree-only commands.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RepoKind {
+    /// Has a working tree (regular clones, linked worktrees and submodules).
+    pub worktree: bool,
+    pub bare: bool,
+    /// Checked out as a submodule of another repository.
+    pub submodule: bool,
+    pub git_dir: String,
+    pub workdir: Option<String>,
+    /// Working tree of the enclosing repository, for submodules.
+    pub superproject: Option<String>,
+}
+
+pub fn get_repo_kind(path: &str) -> Result<RepoKind, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let git_dir = repo.path().to_path_buf();
+    let workdir = repo.workdir().map(Path::to_path_buf);
+    let superproject = workdir
+        .as_ref()
+        .filter(|workdir| workdir.join(".git").is_file())
+        .and_then(|_| superproject_of(&git_dir));
+    Ok(RepoKind {
+        worktree: workdir.is_some(),
+        bare: repo.is_bare(),
+        submodule: superproject.is_some(),
+        git_dir: display_path(&git_dir),
+        workdir: workdir.as_deref().map(display_path),
+        superproject: superproject.as_deref().map(display_path),
+    })
+}
+
+/// Submodules keep their git dir in `<superproject>/.git/modules/<name>`;
+/// linked worktrees (`.git/worktrees/<name>`) are not submodules.
+fn superproject_of(git_dir: &Path) -> Option<PathBuf> {
+    let mut dir = git_dir;
+    while let Some(parent) = dir.parent() {
+        if dir.file_name()? == "modules" && parent.file_name()? == ".git" {
+            return parent.parent().map(Path::to_path_buf);
+        }
+        dir = parent;
+    }
+    None
+}
+
+fn display_path(path: &Path) -> String {
+    path.to_string_lossy()
+        .trim_end_matches(['/', '\\'])
+        .replace('\\', "/")
+}
+
+/// Reject operations that need a working tree (status, staging, checkout,
+/// merge, ...) when the repository is bare, instead of surfacing libgit2's
+/// lower-level error.
+pub fn ensure_worktree(repo: &Repository, operation: &str) -> Result<(), String> {
+    if repo.is_bare() {
+        return Err(format!(
+            "{}: {} requires a working tree, but this repository is bare",
+            E_BARE, operation
+        ));
+    }
+    Ok(())
+}
+
+/// Forward checkout progress to `on_progress`, throttled so huge worktrees
+/// don't flood the UI: the first and last updates are always reported.
+pub(super) fn report_checkout_progress<'cb>(
+    checkout: &mut CheckoutBuilder<'cb>,
+    mut on_progress: impl FnMut(&CheckoutProgress) + 'cb,
+) {
+    let mut last_report: Option<Instant> = None;
+    checkout.progress(move |file, completed, total| {
+        let due = last_report.is_none_or(|at| at.elapsed() >= CHECKOUT_PROGRESS_INTERVAL);
+        if !due && completed < total {
+            return;
+        }
+        last_report = Some(Instant::now());
+        on_progress(&CheckoutProgress {
+            file: file.map(|path| path.to_string_lossy().replace('\\', "/")),
+            completed,
+            total,
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_worktree_rejects_bare_repository() {
+        let dir = std::env::temp_dir().join(format!("gitlite-bare-test-{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init_bare(&dir).unwrap();
+
+        assert!(is_bare_repository(dir.to_str().unwrap()).unwrap());
+        let error = ensure_worktree(&repo, "Status").unwrap_err();
+        assert!(error.starts_with(E_BARE), "actual: {}", error);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_repo_kind_distinguishes_bare_and_submodule() {
+        let dir = std::env::temp_dir().join(format!("gitlite-kind-test-{}", uuid::Uuid::new_v4()));
+        Repository::init_bare(dir.join("bare.git")).unwrap();
+        let bare = get_repo_kind(dir.join("bare.git").to_str().unwrap()).unwrap();
+        assert!(bare.bare && !bare.worktree && !bare.submodule);
+        assert_eq!(bare.workdir, None);
+
+        let main = Repository::init(dir.join("main")).unwrap();
+        let child = dir.join("main/.git/modules/lib");
+        Repository::init_opts(
+            &child,
+            git2::RepositoryInitOptions::new()
+                .bare(false)
+                .no_dotgit_dir(true)
+                .workdir_path(&dir.join("main/lib")),
+        )
+        .unwrap();
+        drop(main);
+
+        let plain = get_repo_kind(dir.join("main").to_str().unwrap()).unwrap();
+        assert!(plain.worktree && !plain.bare && !plain.submodule);
+        let kind = get_repo_kind(dir.join("main/lib").to_str().unwrap()).unwrap();
+        assert!(kind.worktree && kind.submodule, "{:?}", kind);
+        assert_eq!(kind.superproject, Some(display_path(&dir.join("main"))));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+use git2::{Repository, StatusOptions};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+const MAX_RESULTS: usize = 500;
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "vendor",
+    "dist",
+    "build",
+    "Library",
+    "AppData",
+    "__pycache__",
+];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DiscoveredRepository {
+    pub path: String,
+    pub name: String,
+    pub branch: Option<String>,
+    pub is_dirty: bool,
+    pub is_bare: bool,
+}
+
+/// Walk `base_dir` up to `max_depth` levels looking for repositories. Hidden
+/// folders, dependency/build folders and symlinks are skipped, and the walk
+/// does not descend into a repository once found.
+pub fn scan_for_repositories(
+    base_dir: &str,
+    max_depth: usize,
+) -> Result<Vec<DiscoveredRepository>, String> {
+    let base = Path::new(base_dir);
+    if !base.is_dir() {
+        return Err(format!(
+            "E_SCAN_BASE_DIR: '{}' is not a directory",
+            base_dir
+        ));
+    }
+
+    let mut found = Vec::new();
+    scan_dir(base, 0, max_depth, &mut found);
+    found.sort_by(|a: &DiscoveredRepository, b| a.path.cmp(&b.path));
+    Ok(found)
+}
+
+fn scan_dir(dir: &Path, depth: usize, max_depth: usize, found: &mut Vec<DiscoveredRepository>) {
+    if found.len() >= MAX_RESULTS {
+        return;
+    }
+    if let Some(repo) = describe(dir) {
+        found.push(repo);
+        return;
+    }
+    if depth >= max_depth {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if !file_type.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let looks_bare = name.ends_with(".git") && name != ".git";
+        if (name.starts_with('.') && !looks_bare) || SKIPPED_DIRS.contains(&name.as_str()) {
+            continue;
+        }
+        scan_dir(&entry.path(), depth + 1, max_depth, found);
+    }
+}
+
+fn describe(dir: &Path) -> Option<DiscoveredRepository> {
+    let repo = if dir.join(".git").exists() {
+        Repository::open(dir).ok()?
+    } else if dir.join("HEAD").is_file() && dir.join("objects").is_dir() {
+        Repository::open_bare(dir).ok()?
+    } else {
+        return None;
+    };
+
+    let branch = repo.head().ok().and_then(|head| {
+        if head.is_branch() {
+            head.shorthand().map(str::to_string)
+        } else {
+            None
+        }
+    });
+    let is_bare = repo.is_bare();
+    let is_dirty = !is_bare && {
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).include_ignored(false);
+        repo.statuses(Some(&mut options))
+            .map(|statuses| !statuses.is_empty())
+            .unwrap_or(false)
+    };
+
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Some(DiscoveredRepository {
+        path: dir.to_string_lossy().into_owned(),
+        name: name.strip_suffix(".git").unwrap_or(&name).to_string(),
+        branch,
+        is_dirty,
+        is_bare,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(args: &[&str], cwd: &Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_scan_for_repositories_respects_depth_and_skips() {
+        let base = std::env::temp_dir().join(format!("gitlite-scan-test-{}", uuid::Uuid::new_v4()));
+        let clean = base.join("work/clean");
+        let dirty = base.join("work/dirty");
+        let hidden = base.join("node_modules/pkg");
+        let deep = base.join("a/b/c/deep");
+        let bare = base.join("mirrors/app.git");
+        for dir in [&clean, &dirty, &hidden, &deep, &bare] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        for dir in [&clean, &dirty, &hidden, &deep] {
+            git(&["init"], dir);
+        }
+        git(&["init", "--bare"], &bare);
+        fs::write(dirty.join("new.txt"), "x").unwrap();
+
+        let found = scan_for_repositories(base.to_str().unwrap(), 2).unwrap();
+        let names: Vec<&str> = found.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["app", "clean", "dirty"]);
+        assert!(found[0].is_bare);
+        assert!(!found[1].is_dirty);
+        assert!(found[2].is_dirty);
+
+        let deeper = scan_for_repositories(base.to_str().unwrap(), 4).unwrap();
+        assert!(deeper.iter().any(|r| r.name == "deep"));
+
+        fs::remove_dir_all(base).unwrap();
+    }
+}
@@ -1,4 +1,6 @@
-use super::pull_policy::{apply_fast_forward, fetch_head_oid, prepare_pull_target};
+use super::pull_policy::{apply_pull, fetch_head_oid, prepare_pull_target, PullOptions};
+use super::remote::FetchScope;
+use super::repository::ensure_worktree;
 use git2::{
     Cred, CredentialType, ErrorClass, ErrorCode, FetchOptions, PushOptions, RemoteCallbacks,
     Repository,
@@ -65,6 +67,11 @@ pub fn push_ssh(
                 passphrase_clone.as_deref(),
             )
         });
+        crate::known_hosts::verify_host_keys(
+            &mut callbacks,
+            remote.pushurl().or(remote.url()).unwrap_or_default(),
+            true,
+        );
 
         let mut options = PushOptions::new();
         options.remote_callbacks(callbacks);
@@ -73,6 +80,9 @@ pub fn push_ssh(
     };
 
     if let Err(error) = push_result {
+        if let Some(rejection) = crate::known_hosts::rejection(&error) {
+            return Err(rejection);
+        }
         return Err(format!("SSH push failed: {}", error));
     }
 
@@ -84,9 +94,12 @@ pub fn pull_ssh(
     remote_name: &str,
     key_path: &str,
     passphrase: Option<String>,
+    options: &PullOptions,
 ) -> Result<(), String> {
-    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
-    let target = prepare_pull_target(&repo)?;
+    let mut repo =
+        Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Pull")?;
+    let target = prepare_pull_target(&repo, options)?;
 
     let remote_name = if remote_name.trim().is_empty() {
         "origin"
@@ -111,6 +124,11 @@ pub fn pull_ssh(
                 passphrase_clone.as_deref(),
             )
         });
+        crate::known_hosts::verify_host_keys(
+            &mut callbacks,
+            remote.url().unwrap_or_default(),
+            true,
+        );
 
         let mut options = FetchOptions::new();
         options.remote_callbacks(callbacks);
@@ -118,12 +136,13 @@ pub fn pull_ssh(
         remote.fetch(&[] as &[&str], Some(&mut options), None)
     };
 
+    drop(remote);
     if let Err(error) = fetch_result {
         return Err(format_fetch_error(error));
     }
 
     let fetch_oid = fetch_head_oid(&repo)?;
-    apply_fast_forward(&repo, &target.branch_ref_name, target.head_oid, fetch_oid)?;
+    apply_pull(&mut repo, &target, fetch_oid, options)?;
 
     Ok(())
 }
@@ -133,6 +152,7 @@ pub fn fetch_ssh(
     remote_name: &str,
     key_path: &str,
     passphrase: Option<String>,
+    scope: &FetchScope,
 ) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
     let remote_name = if remote_name.trim().is_empty() {
@@ -140,6 +160,7 @@ pub fn fetch_ssh(
     } else {
         remote_name
     };
+    let refspecs = scope.refspecs(&repo, remote_name)?;
 
     let mut remote = repo
         .find_remote(remote_name)
@@ -158,11 +179,16 @@ pub fn fetch_ssh(
                 passphrase_clone.as_deref(),
             )
         });
+        crate::known_hosts::verify_host_keys(
+            &mut callbacks,
+            remote.url().unwrap_or_default(),
+            true,
+        );
 
         let mut options = FetchOptions::new();
         options.remote_callbacks(callbacks);
 
-        remote.fetch(&[] as &[&str], Some(&mut options), None)
+        remote.fetch(&refspecs, Some(&mut options), None)
     };
 
     if let Err(error) = fetch_result {
@@ -184,14 +210,24 @@ fn resolve_ssh_cred(
     if allowed_types.contains(CredentialType::SSH_KEY)
         || allowed_types.contains(CredentialType::SSH_MEMORY)
     {
-        if let Ok(cred) = Cred::ssh_key_from_agent(username) {
+        if let Ok(cred) = super::ssh_agent::agent_credential(username) {
             return Ok(cred);
         }
     }
 
-    // Fallback: explicit key path if provided
+    // Fallback: explicit key path if provided, unlocked with the passphrase
+    // passed in or the one saved in the keychain
     if !key_path.is_empty() && Path::new(key_path).exists() {
-        return Cred::ssh_key(username, None, Path::new(key_path), passphrase);
+        let saved = match passphrase {
+            Some(_) => None,
+            None => crate::ssh_passphrase::find(key_path),
+        };
+        return Cred::ssh_key(
+            username,
+            None,
+            Path::new(key_path),
+            passphrase.or(saved.as_deref()),
+        );
     }
 
     // Last resort platform default
@@ -199,6 +235,9 @@ fn resolve_ssh_cred(
 }
 
 fn format_fetch_error(error: git2::Error) -> String {
+    if let Some(rejection) = crate::known_hosts::rejection(&error) {
+        return rejection;
+    }
     match error.code() {
         ErrorCode::Auth => format!("{}: Authentication failed: {}", E_PULL_AUTH, error),
         _ => match error.class() {
@@ -292,7 +331,13 @@ mod tests {
 
         run_git(&["reset", "--hard", "HEAD~1"], &local_dir);
 
-        let result = pull_ssh(local_dir.to_str().unwrap(), "origin", dummy_key, None);
+        let result = pull_ssh(
+            local_dir.to_str().unwrap(),
+            "origin",
+            dummy_key,
+            None,
+            &PullOptions::default(),
+        );
         assert!(result.is_ok());
 
         fs::remove_dir_all(base_dir).unwrap();
@@ -305,7 +350,13 @@ mod tests {
 
         fs::write(local_dir.join("test.txt"), "dirty local change").unwrap();
 
-        let result = pull_ssh(local_dir.to_str().unwrap(), "origin", dummy_key, None);
+        let result = pull_ssh(
+            local_dir.to_str().unwrap(),
+            "origin",
+            dummy_key,
+            None,
+            &PullOptions::default(),
+        );
         assert!(result.is_err());
         let message = result.unwrap_err();
         assert!(
@@ -347,7 +398,13 @@ mod tests {
         run_git(&["add", "."], &local_dir);
         run_git(&["commit", "-m", "Local commit"], &local_dir);
 
-        let result = pull_ssh(local_dir.to_str().unwrap(), "origin", dummy_key, None);
+        let result = pull_ssh(
+            local_dir.to_str().unwrap(),
+            "origin",
+            dummy_key,
+            None,
+            &PullOptions::default(),
+        );
         assert!(result.is_err());
         let message = result.unwrap_err();
         assert!(
@@ -372,7 +429,13 @@ mod tests {
         let head_oid = String::from_utf8(output.stdout).unwrap().trim().to_string();
         run_git(&["checkout", &head_oid], &local_dir);
 
-        let result = pull_ssh(local_dir.to_str().unwrap(), "origin", dummy_key, None);
+        let result = pull_ssh(
+            local_dir.to_str().unwrap(),
+            "origin",
+            dummy_key,
+            None,
+            &PullOptions::default(),
+        );
         assert!(result.is_err());
         let message = result.unwrap_err();
         assert!(
@@ -1,5 +1,11 @@
-use git2::{ErrorCode, Repository, Status, StatusOptions};
-use serde::Serialize;
+use super::index_flags::{flagged_entries, flags_of};
+use super::repository::ensure_worktree;
+use git2::{
+    Delta, DiffDelta, DiffFile, DiffOptions, ErrorCode, IndexAddOption, Pathspec, PathspecFlags,
+    Repository, Signature, Status, StatusOptions, Time,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
 #[derive(Serialize, Debug, Clone)]
@@ -7,24 +13,99 @@ pub struct FileStatus {
     pub path: String,
     pub status: String,
     pub is_staged: bool,
+    /// Previous path of a `renamed` entry.
+    pub old_path: Option<String>,
+    pub skip_worktree: bool,
+    pub assume_unchanged: bool,
+    /// Which sides changed a `conflicted` path: `both_modified`,
+    /// `both_added`, `both_deleted`, `added_by_us`, `added_by_them`,
+    /// `deleted_by_us` or `deleted_by_them`.
+    pub conflict: Option<String>,
+}
+
+/// Knobs for very large worktrees, mirroring `git status -u<mode>
+/// [--] <pathspec>...`. `untracked_files` is `normal` (default: untracked
+/// directories reported once with a trailing `/`), `all` (every file) or
+/// `no`. `update_index` writes refreshed stat data back to the index so
+/// the next call can trust it instead of re-hashing unchanged files.
+/// `include_hidden` also lists skip-worktree and assume-unchanged files git
+/// no longer compares, which takes a scan of the whole index.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct StatusQuery {
+    pub pathspecs: Vec<String>,
+    pub untracked_files: Option<String>,
+    pub update_index: bool,
+    pub include_hidden: bool,
 }
 
 pub fn get_status(path: &str) -> Result<Vec<FileStatus>, String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    get_status_in(&repo, &StatusQuery::default())
+}
+
+pub fn get_status_in(repo: &Repository, query: &StatusQuery) -> Result<Vec<FileStatus>, String> {
+    ensure_worktree(repo, "Status")?;
 
     let mut opts = StatusOptions::new();
-    opts.include_untracked(true);
+    match query.untracked_files.as_deref().unwrap_or("normal") {
+        "normal" => opts.include_untracked(true),
+        "all" => opts.include_untracked(true).recurse_untracked_dirs(true),
+        "no" => opts.include_untracked(false),
+        other => {
+            return Err(format!(
+                "E_STATUS_UNTRACKED_MODE: '{}' (expected normal, all or no)",
+                other
+            ))
+        }
+    };
     opts.include_ignored(false);
+    opts.update_index(query.update_index);
+    for pathspec in &query.pathspecs {
+        opts.pathspec(pathspec);
+    }
+    opts.renames_head_to_index(true);
+    opts.renames_index_to_workdir(true);
 
     let statuses = repo
         .statuses(Some(&mut opts))
         .map_err(|e| format!("Failed to get status: {}", e))?;
 
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let mut flagged = if query.include_hidden {
+        flagged_entries(repo)?
+    } else {
+        HashMap::new()
+    };
+    if !flagged.is_empty() && !query.pathspecs.is_empty() {
+        let pathspec = Pathspec::new(query.pathspecs.iter())
+            .map_err(|e| format!("Invalid pathspec: {}", e))?;
+        flagged.retain(|path, _| pathspec.matches_path(Path::new(path), PathspecFlags::DEFAULT));
+    }
+    let conflicts = conflict_kinds(repo)?;
     let mut result = Vec::new();
 
     for entry in statuses.iter() {
         let file_path = entry.path().ok_or("Invalid UTF-8 in path")?.to_string();
         let status = entry.status();
+        let flags = flagged
+            .remove(&file_path)
+            .unwrap_or_else(|| flags_of(&index, &file_path));
+
+        if status.contains(Status::CONFLICTED) {
+            result.push(FileStatus {
+                conflict: conflicts.get(&file_path).map(|kind| kind.to_string()),
+                path: file_path,
+                status: "conflicted".to_string(),
+                is_staged: false,
+                old_path: None,
+                skip_worktree: flags.skip_worktree,
+                assume_unchanged: flags.assume_unchanged,
+            });
+            continue;
+        }
 
         // Staged changes
         if status.intersects(
@@ -44,10 +125,15 @@ pub fn get_status(path: &str) -> Result<Vec<FileStatus>, String> {
                 "modified"
             };
 
+            let (path, old_path) = delta_paths(entry.head_to_index(), &file_path);
             result.push(FileStatus {
-                path: file_path.clone(),
+                path,
                 status: status_str.to_string(),
                 is_staged: true,
+                old_path,
+                skip_worktree: flags.skip_worktree,
+                assume_unchanged: flags.assume_unchanged,
+                conflict: None,
             });
         }
 
@@ -69,28 +155,139 @@ pub fn get_status(path: &str) -> Result<Vec<FileStatus>, String> {
                 "modified"
             };
 
+            let (path, old_path) = delta_paths(entry.index_to_workdir(), &file_path);
             result.push(FileStatus {
-                path: file_path,
+                path,
                 status: status_str.to_string(),
                 is_staged: false,
+                old_path,
+                skip_worktree: flags.skip_worktree,
+                assume_unchanged: flags.assume_unchanged,
+                conflict: None,
             });
         }
     }
 
+    // Flagged files git no longer compares are listed so they can be
+    // unhidden; their worktree state is unknown by design.
+    let mut hidden: Vec<_> = flagged.into_iter().collect();
+    hidden.sort_by(|a, b| a.0.cmp(&b.0));
+    for (file_path, flags) in hidden {
+        result.push(FileStatus {
+            path: file_path,
+            status: "hidden".to_string(),
+            is_staged: false,
+            old_path: None,
+            skip_worktree: flags.skip_worktree,
+            assume_unchanged: flags.assume_unchanged,
+            conflict: None,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Current path of a status delta, plus the previous one when it moved.
+/// Status entries are keyed by the old path, so renames need the delta.
+fn delta_paths(delta: Option<DiffDelta>, fallback: &str) -> (String, Option<String>) {
+    let path_of = |file: DiffFile| file.path().map(|p| p.to_string_lossy().into_owned());
+    let Some(delta) = delta else {
+        return (fallback.to_string(), None);
+    };
+    let old_path = path_of(delta.old_file());
+    let new_path = path_of(delta.new_file()).unwrap_or_else(|| fallback.to_string());
+    match old_path {
+        Some(old_path) if delta.status() == Delta::Renamed && old_path != new_path => {
+            (new_path, Some(old_path))
+        }
+        _ => (new_path, None),
+    }
+}
+
+/// Conflict kind per path, from which stages the index holds for it.
+fn conflict_kinds(repo: &Repository) -> Result<HashMap<String, &'static str>, String> {
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    if !index.has_conflicts() {
+        return Ok(HashMap::new());
+    }
+    let conflicts = index
+        .conflicts()
+        .map_err(|e| format!("Failed to get conflicts: {}", e))?;
+
+    let mut kinds = HashMap::new();
+    for conflict in conflicts {
+        let conflict = conflict.map_err(|e| format!("Failed to read conflict: {}", e))?;
+        let kind = match (
+            conflict.ancestor.is_some(),
+            conflict.our.is_some(),
+            conflict.their.is_some(),
+        ) {
+            (true, true, true) => "both_modified",
+            (false, true, true) => "both_added",
+            (true, false, true) => "deleted_by_us",
+            (true, true, false) => "deleted_by_them",
+            (false, true, false) => "added_by_us",
+            (false, false, true) => "added_by_them",
+            _ => "both_deleted",
+        };
+        let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+        if let Some(entry) = entry {
+            kinds.insert(String::from_utf8_lossy(&entry.path).into_owned(), kind);
+        }
+    }
+    Ok(kinds)
+}
+
+/// List ignored paths separately from `get_status` so the normal status view
+/// is not flooded. Ignored directories are reported once (with a trailing
+/// `/`) rather than expanded file by file.
+pub fn list_ignored_files(path: &str) -> Result<Vec<String>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Listing ignored files")?;
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false);
+    opts.include_ignored(true);
+    opts.recurse_ignored_dirs(false);
+
+    let statuses = repo
+        .statuses(Some(&mut opts))
+        .map_err(|e| format!("Failed to get status: {}", e))?;
+
+    let mut result = Vec::new();
+    for entry in statuses.iter() {
+        if entry.status().contains(Status::IGNORED) {
+            let file_path = entry.path().ok_or("Invalid UTF-8 in path")?;
+            result.push(file_path.to_string());
+        }
+    }
+    result.sort();
+
     Ok(result)
 }
 
+/// `git add <file>...`: a file missing from the worktree, such as the old
+/// path of a rename, is staged as deleted.
 pub fn stage_files(path: &str, files: &[String]) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Staging")?;
+    let workdir = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?;
 
     let mut index = repo
         .index()
         .map_err(|e| format!("Failed to get index: {}", e))?;
 
     for file in files {
-        index
-            .add_path(Path::new(file))
-            .map_err(|e| format!("Failed to add file '{}': {}", file, e))?;
+        let result = if workdir.join(file).symlink_metadata().is_ok() {
+            index.add_path(Path::new(file))
+        } else {
+            index.remove_path(Path::new(file))
+        };
+        result.map_err(|e| format!("Failed to add file '{}': {}", file, e))?;
     }
 
     index
@@ -102,6 +299,7 @@ pub fn stage_files(path: &str, files: &[String]) -> Result<(), String> {
 
 pub fn unstage_files(path: &str, files: &[String]) -> Result<(), String> {
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Unstaging")?;
 
     let mut index = repo
         .index()
@@ -156,14 +354,115 @@ pub fn unstage_files(path: &str, files: &[String]) -> Result<(), String> {
     Ok(())
 }
 
+/// Stage everything matching git pathspecs (`src/`, `*.rs`, ...) like
+/// `git add <pathspec>`, deletions included. Returns the staged paths.
+pub fn stage_pathspec(path: &str, patterns: &[String]) -> Result<Vec<String>, String> {
+    ensure_patterns(patterns)?;
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Staging")?;
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let mut staged = Vec::new();
+    let mut collect = |file: &Path, _: &[u8]| {
+        staged.push(file.to_string_lossy().into_owned());
+        0
+    };
+    index
+        .add_all(patterns, IndexAddOption::DEFAULT, Some(&mut collect))
+        .and_then(|_| index.update_all(patterns, Some(&mut collect)))
+        .map_err(|e| format!("Failed to stage '{}': {}", patterns.join(" "), e))?;
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    staged.sort();
+    staged.dedup();
+    Ok(staged)
+}
+
+/// Unstage every staged change matching the pathspecs. Returns the
+/// unstaged paths.
+pub fn unstage_pathspec(path: &str, patterns: &[String]) -> Result<Vec<String>, String> {
+    ensure_patterns(patterns)?;
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Unstaging")?;
+
+    let head_tree = match repo.head() {
+        Ok(head) => Some(
+            head.peel_to_tree()
+                .map_err(|e| format!("Failed to get tree: {}", e))?,
+        ),
+        Err(e) if e.code() == ErrorCode::UnbornBranch || e.code() == ErrorCode::NotFound => None,
+        Err(e) => return Err(format!("Failed to get HEAD: {}", e)),
+    };
+    let mut opts = DiffOptions::new();
+    for pattern in patterns {
+        opts.pathspec(pattern);
+    }
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))
+        .map_err(|e| format!("Failed to diff index: {}", e))?;
+
+    let mut staged: Vec<String> = Vec::new();
+    for delta in diff.deltas() {
+        for file in [delta.old_file(), delta.new_file()] {
+            if let Some(file_path) = file.path() {
+                staged.push(file_path.to_string_lossy().into_owned());
+            }
+        }
+    }
+    staged.sort();
+    staged.dedup();
+    if !staged.is_empty() {
+        unstage_files(path, &staged)?;
+    }
+    Ok(staged)
+}
+
+fn ensure_patterns(patterns: &[String]) -> Result<(), String> {
+    if patterns.iter().all(|pattern| pattern.trim().is_empty()) {
+        return Err("E_PATHSPEC_EMPTY: at least one pattern is required".to_string());
+    }
+    Ok(())
+}
+
+/// Options for `commit_changes_with`. The committer is always the
+/// configured identity; the `author_*` fields only change the author.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct CommitOptions {
+    /// Commit even when nothing is staged (CI re-runs, release markers).
+    pub allow_empty: bool,
+    /// Defaults to `user.name`.
+    pub author_name: Option<String>,
+    /// Defaults to `user.email`.
+    pub author_email: Option<String>,
+    /// Unix seconds; defaults to now.
+    pub author_time: Option<i64>,
+    /// UTC offset of `author_time` in minutes; defaults to the committer's.
+    pub author_offset_minutes: Option<i32>,
+}
+
 pub fn commit_changes(path: &str, message: &str, description: &str) -> Result<String, String> {
+    commit_changes_with(path, message, description, &CommitOptions::default())
+}
+
+pub fn commit_changes_with(
+    path: &str,
+    message: &str,
+    description: &str,
+    options: &CommitOptions,
+) -> Result<String, String> {
     if message.trim().is_empty() {
         return Err("E_COMMIT_EMPTY_MESSAGE: commit message is required".to_string());
     }
 
     let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Committing")?;
 
-    if !has_staged_changes(&repo)? {
+    if !options.allow_empty && !has_staged_changes(&repo)? {
         return Err("E_COMMIT_NO_STAGED: no staged changes".to_string());
     }
 
@@ -190,6 +489,7 @@ pub fn commit_changes(path: &str, message: &str, description: &str) -> Result<St
     let sig = repo
         .signature()
         .map_err(|e| format!("Failed to get signature: {}", e))?;
+    let author = author_signature(&sig, options)?;
 
     let full_message = if description.trim().is_empty() {
         message.to_string()
@@ -200,12 +500,59 @@ pub fn commit_changes(path: &str, message: &str, description: &str) -> Result<St
     let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
 
     let oid = repo
-        .commit(Some("HEAD"), &sig, &sig, &full_message, &tree, &parents)
+        .commit(Some("HEAD"), &author, &sig, &full_message, &tree, &parents)
         .map_err(|e| format!("Failed to commit: {}", e))?;
 
     Ok(oid.to_string())
 }
 
+/// `base` with the `author_*` overrides in `options` applied.
+pub(super) fn author_signature(
+    base: &Signature<'_>,
+    options: &CommitOptions,
+) -> Result<Signature<'static>, String> {
+    let field = |value: &Option<String>, fallback: Option<&str>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .or(fallback)
+            .unwrap_or("")
+            .to_string()
+    };
+    let name = field(&options.author_name, base.name());
+    let email = field(&options.author_email, base.email());
+    if name.contains(['<', '>', '\n']) || email.contains(['<', '>', '\n']) {
+        return Err(
+            "E_COMMIT_AUTHOR_INVALID: author name and email cannot contain '<', '>' or newlines"
+                .to_string(),
+        );
+    }
+    // Checked after the fallback, so a blank override means "the default"
+    // and the default itself has to be an address too.
+    if !email.contains('@') {
+        return Err(format!(
+            "E_COMMIT_AUTHOR_INVALID: '{}' is not an email address",
+            email
+        ));
+    }
+
+    let result = match options.author_time {
+        Some(seconds) => Signature::new(
+            &name,
+            &email,
+            &Time::new(
+                seconds,
+                options
+                    .author_offset_minutes
+                    .unwrap_or_else(|| base.when().offset_minutes()),
+            ),
+        ),
+        None => Signature::new(&name, &email, &base.when()),
+    };
+    result.map_err(|e| format!("E_COMMIT_AUTHOR_INVALID: {}", e))
+}
+
 fn has_staged_changes(repo: &Repository) -> Result<bool, String> {
     let mut opts = StatusOptions::new();
     opts.include_untracked(true);
@@ -327,6 +674,210 @@ mod tests {
         fs::remove_dir_all(test_repo).unwrap();
     }
 
+    #[test]
+    fn test_get_status_reports_conflicts() {
+        let test_repo = create_test_repo();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(&test_repo)
+                .output()
+                .unwrap()
+        };
+        fs::write(test_repo.join("gone.txt"), "base").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "Add gone.txt"]);
+        git(&["checkout", "-b", "other"]);
+        fs::write(test_repo.join("initial.txt"), "theirs").unwrap();
+        fs::write(test_repo.join("gone.txt"), "changed by them").unwrap();
+        git(&["commit", "-am", "Theirs"]);
+        git(&["checkout", "-"]);
+        fs::write(test_repo.join("initial.txt"), "ours").unwrap();
+        fs::remove_file(test_repo.join("gone.txt")).unwrap();
+        git(&["commit", "-am", "Ours"]);
+        assert!(!git(&["merge", "other"]).status.success());
+
+        let status = get_status(test_repo.to_str().unwrap()).unwrap();
+        let kind = |path: &str| {
+            let entry = status.iter().find(|s| s.path == path).unwrap();
+            assert_eq!(entry.status, "conflicted");
+            entry.conflict.clone()
+        };
+        assert_eq!(kind("initial.txt").as_deref(), Some("both_modified"));
+        assert_eq!(kind("gone.txt").as_deref(), Some("deleted_by_us"));
+        assert_eq!(status.len(), 2);
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_get_status_reports_renames_with_old_path() {
+        let test_repo = create_test_repo();
+        let content = "a line long enough for rename similarity\n".repeat(4);
+        fs::write(test_repo.join("staged.txt"), &content).unwrap();
+        fs::write(test_repo.join("moved.txt"), content.replace("line", "row")).unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "Add files"])
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+
+        Command::new("git")
+            .args(["mv", "staged.txt", "staged-renamed.txt"])
+            .current_dir(&test_repo)
+            .output()
+            .unwrap();
+        fs::rename(
+            test_repo.join("moved.txt"),
+            test_repo.join("moved-renamed.txt"),
+        )
+        .unwrap();
+
+        let status = get_status(test_repo.to_str().unwrap()).unwrap();
+        let staged = status
+            .iter()
+            .find(|s| s.path == "staged-renamed.txt")
+            .unwrap();
+        assert!(staged.is_staged);
+        assert_eq!(staged.status, "renamed");
+        assert_eq!(staged.old_path.as_deref(), Some("staged.txt"));
+
+        let moved = status
+            .iter()
+            .find(|s| s.path == "moved-renamed.txt")
+            .unwrap();
+        assert!(!moved.is_staged);
+        assert_eq!(moved.status, "renamed");
+        assert_eq!(moved.old_path.as_deref(), Some("moved.txt"));
+        assert_eq!(status.len(), 2);
+
+        let path = test_repo.to_str().unwrap();
+        let rename = ["moved.txt".to_string(), "moved-renamed.txt".to_string()];
+        stage_files(path, &rename).unwrap();
+        let status = get_status(path).unwrap();
+        let moved = status.iter().find(|s| s.path == "moved-renamed.txt");
+        assert!(moved.unwrap().is_staged);
+        assert_eq!(status.len(), 2);
+
+        unstage_files(path, &rename).unwrap();
+        let status = get_status(path).unwrap();
+        let moved = status.iter().find(|s| s.path == "moved-renamed.txt");
+        assert!(!moved.unwrap().is_staged);
+        assert_eq!(status.len(), 2);
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_get_status_query_modes_and_pathspecs() {
+        let test_repo = create_test_repo();
+        fs::create_dir_all(test_repo.join("vendor/lib")).unwrap();
+        fs::write(test_repo.join("vendor/lib/a.rs"), "a").unwrap();
+        fs::write(test_repo.join("vendor/b.rs"), "b").unwrap();
+        fs::write(test_repo.join("initial.txt"), "changed").unwrap();
+        let repo = Repository::open(&test_repo).unwrap();
+        let paths = |query: StatusQuery| {
+            let mut paths: Vec<_> = get_status_in(&repo, &query)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(
+            paths(StatusQuery::default()),
+            vec!["initial.txt".to_string(), "vendor/".to_string()]
+        );
+        assert_eq!(
+            paths(StatusQuery {
+                untracked_files: Some("all".to_string()),
+                pathspecs: vec!["*.rs".to_string()],
+                ..StatusQuery::default()
+            }),
+            vec!["vendor/b.rs".to_string(), "vendor/lib/a.rs".to_string()]
+        );
+        assert_eq!(
+            paths(StatusQuery {
+                untracked_files: Some("no".to_string()),
+                update_index: true,
+                ..StatusQuery::default()
+            }),
+            vec!["initial.txt".to_string()]
+        );
+        assert!(get_status_in(
+            &repo,
+            &StatusQuery {
+                untracked_files: Some("some".to_string()),
+                ..StatusQuery::default()
+            }
+        )
+        .unwrap_err()
+        .starts_with("E_STATUS_UNTRACKED_MODE"));
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_stage_and_unstage_pathspec() {
+        let test_repo = create_test_repo();
+        let path = test_repo.to_str().unwrap();
+        fs::create_dir_all(test_repo.join("src/nested")).unwrap();
+        fs::write(test_repo.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(test_repo.join("src/nested/lib.rs"), "").unwrap();
+        fs::write(test_repo.join("src/notes.md"), "").unwrap();
+        fs::write(test_repo.join("build.rs"), "").unwrap();
+        fs::remove_file(test_repo.join("initial.txt")).unwrap();
+
+        let staged = stage_pathspec(path, &["src/*.rs".to_string()]).unwrap();
+        assert_eq!(staged, vec!["src/main.rs", "src/nested/lib.rs"]);
+        let staged = stage_pathspec(path, &["initial.txt".to_string()]).unwrap();
+        assert_eq!(staged, vec!["initial.txt"], "deletions are staged too");
+
+        let is_staged = |file: &str| {
+            get_status(path)
+                .unwrap()
+                .iter()
+                .any(|s| s.path == file && s.is_staged)
+        };
+        assert!(is_staged("src/main.rs") && is_staged("initial.txt"));
+        assert!(!is_staged("build.rs") && !is_staged("src/notes.md"));
+
+        let unstaged = unstage_pathspec(path, &["src".to_string()]).unwrap();
+        assert_eq!(unstaged, vec!["src/main.rs", "src/nested/lib.rs"]);
+        assert!(!is_staged("src/main.rs") && is_staged("initial.txt"));
+
+        assert!(stage_pathspec(path, &[" ".to_string()])
+            .unwrap_err()
+            .starts_with("E_PATHSPEC_EMPTY"));
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_list_ignored_files_is_separate_from_status() {
+        let test_repo = create_test_repo();
+
+        fs::write(test_repo.join(".gitignore"), "*.log\nbuild/\n").unwrap();
+        fs::write(test_repo.join("debug.log"), "noise").unwrap();
+        fs::create_dir_all(test_repo.join("build/out")).unwrap();
+        fs::write(test_repo.join("build/out/app.bin"), "binary").unwrap();
+
+        let ignored = list_ignored_files(test_repo.to_str().unwrap()).unwrap();
+        assert_eq!(ignored, vec!["build/".to_string(), "debug.log".to_string()]);
+
+        let status = get_status(test_repo.to_str().unwrap()).unwrap();
+        assert!(status.iter().all(|s| s.path == ".gitignore"));
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
     #[test]
     fn test_unstage_files() {
         let test_repo = create_test_repo();
@@ -422,6 +973,66 @@ mod tests {
         fs::remove_dir_all(test_repo).unwrap();
     }
 
+    #[test]
+    fn test_commit_changes_with_author_override() {
+        let test_repo = create_test_repo();
+        let path = test_repo.to_str().unwrap();
+        fs::write(test_repo.join("pair.txt"), "pairing").unwrap();
+        stage_files(path, &[String::from("pair.txt")]).unwrap();
+
+        let invalid = CommitOptions {
+            author_email: Some("not-an-email".to_string()),
+            ..Default::default()
+        };
+        assert!(commit_changes_with(path, "Pair", "", &invalid)
+            .unwrap_err()
+            .starts_with("E_COMMIT_AUTHOR_INVALID"));
+
+        let options = CommitOptions {
+            author_name: Some("Ada Lovelace".to_string()),
+            author_email: Some("ada@example.com".to_string()),
+            author_time: Some(1_700_000_000),
+            author_offset_minutes: Some(120),
+            ..Default::default()
+        };
+        let oid = commit_changes_with(path, "Pair", "", &options).unwrap();
+
+        let repo = Repository::open(path).unwrap();
+        let commit = repo
+            .find_commit(git2::Oid::from_str(&oid).unwrap())
+            .unwrap();
+        assert_eq!(commit.author().name(), Some("Ada Lovelace"));
+        assert_eq!(commit.author().email(), Some("ada@example.com"));
+        assert_eq!(commit.author().when().seconds(), 1_700_000_000);
+        assert_eq!(commit.author().when().offset_minutes(), 120);
+        assert_ne!(commit.committer().name(), Some("Ada Lovelace"));
+        assert_ne!(commit.committer().when().seconds(), 1_700_000_000);
+
+        fs::remove_dir_all(test_repo).unwrap();
+    }
+
+    #[test]
+    fn test_author_signature_checks_the_resolved_email() {
+        let base = Signature::now("Test User", "test@example.com").unwrap();
+        let blank = CommitOptions {
+            author_email: Some("  ".to_string()),
+            ..Default::default()
+        };
+        let author = author_signature(&base, &blank).unwrap();
+        assert_eq!(author.email(), Some("test@example.com"));
+
+        let bad_default = Signature::now("Test User", "nobody").unwrap();
+        assert!(author_signature(&bad_default, &blank)
+            .err()
+            .unwrap()
+            .starts_with("E_COMMIT_AUTHOR_INVALID"));
+        let fixed = CommitOptions {
+            author_email: Some("test@example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(author_signature(&bad_default, &fixed).is_ok());
+    }
+
     #[test]
     fn test_commit_changes_fails_when_nothing_staged() {
         let test_repo = create_test_repo();
@@ -430,6 +1041,18 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("E_COMMIT_NO_STAGED"));
 
+        let options = CommitOptions {
+            allow_empty: true,
+            ..Default::default()
+        };
+        let oid =
+            commit_changes_with(test_repo.to_str().unwrap(), "Trigger CI", "", &options).unwrap();
+        let repo = Repository::open(&test_repo).unwrap();
+        let commit = repo
+            .find_commit(git2::Oid::from_str(&oid).unwrap())
+            .unwrap();
+        assert_eq!(commit.tree_id(), commit.parent(0).unwrap().tree_id());
+
         fs::remove_dir_all(test_repo).unwrap();
     }
 }
@@ -1,3 +1,5 @@
+use super::index_backup::backup_index;
+use super::repository::ensure_worktree;
 use git2::{Oid, Repository, StashApplyOptions, StashFlags};
 use serde::Serialize;
 
@@ -45,6 +47,7 @@ pub fn list_stashes(path: &str) -> Result<Vec<StashEntry>, String> {
 pub fn create_stash(path: &str, message: Option<&str>) -> Result<(), String> {
     let mut repo =
         Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Stashing")?;
 
     let signature = repo
         .signature()
@@ -80,6 +83,7 @@ pub fn create_stash(path: &str, message: Option<&str>) -> Result<(), String> {
 pub fn apply_stash(path: &str, index: usize) -> Result<(), String> {
     let mut repo =
         Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Applying a stash")?;
 
     if !stash_index_exists(&mut repo, index)? {
         return Err(format!(
@@ -88,6 +92,7 @@ pub fn apply_stash(path: &str, index: usize) -> Result<(), String> {
         ));
     }
 
+    backup_index(&repo, "stash apply")?;
     let mut options = StashApplyOptions::new();
     repo.stash_apply(index, Some(&mut options)).map_err(|e| {
         let lower = e.message().to_ascii_lowercase();
@@ -104,6 +109,7 @@ pub fn apply_stash(path: &str, index: usize) -> Result<(), String> {
 pub fn drop_stash(path: &str, index: usize) -> Result<(), String> {
     let mut repo =
         Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    ensure_worktree(&repo, "Dropping a stash")?;
 
     if !stash_index_exists(&mut repo, index)? {
         return Err(format!(
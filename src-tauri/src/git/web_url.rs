@@ -0,0 +1,245 @@
+use super::remote_url::{classify_provider, web_base_url};
+use git2::Repository;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct WebUrlArgs {
+    /// Remote to link to; defaults to `origin`, then the first remote.
+    pub remote: Option<String>,
+    /// Commit, branch or tag. Defaults to the HEAD commit.
+    pub revision: Option<String>,
+    pub file: Option<String>,
+    pub start_line: Option<u32>,
+    pub end_line: Option<u32>,
+}
+
+/// Build the hosting provider's web URL for a `commit`, a `file` at a
+/// revision, or a `lines` range within a file.
+pub fn get_web_url(path: &str, kind: &str, args: &WebUrlArgs) -> Result<String, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let remote_url = remote_url(&repo, args.remote.as_deref())?;
+    let provider = classify_provider(&remote_url);
+    let base = match (provider, web_base_url(&remote_url)) {
+        ("other", _) | (_, None) => {
+            return Err(format!(
+                "E_WEB_URL_UNSUPPORTED: no known web host for remote URL '{}'",
+                remote_url
+            ))
+        }
+        (_, Some(base)) => base,
+    };
+
+    let revision = resolve_revision(&repo, kind, args.revision.as_deref())?;
+
+    match kind {
+        "commit" => Ok(match provider {
+            "gitlab" => format!("{}/-/commit/{}", base, revision),
+            "bitbucket" => format!("{}/commits/{}", base, revision),
+            _ => format!("{}/commit/{}", base, revision),
+        }),
+        "file" | "lines" => {
+            let file = args
+                .file
+                .as_deref()
+                .map(|file| file.trim().trim_start_matches('/'))
+                .filter(|file| !file.is_empty())
+                .ok_or_else(|| "E_WEB_URL_ARGS: file is required".to_string())?;
+            let file = encode_path(file);
+
+            let mut url = match provider {
+                "gitlab" => format!("{}/-/blob/{}/{}", base, revision, file),
+                "bitbucket" => format!("{}/src/{}/{}", base, revision, file),
+                _ => format!("{}/blob/{}/{}", base, revision, file),
+            };
+            if let Some(anchor) = line_anchor(provider, kind, args)? {
+                url.push_str(&anchor);
+            }
+            Ok(url)
+        }
+        other => Err(format!(
+            "E_WEB_URL_ARGS: unknown kind '{}' (expected commit, file or lines)",
+            other
+        )),
+    }
+}
+
+fn remote_url(repo: &Repository, remote: Option<&str>) -> Result<String, String> {
+    let name = match remote.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => name.to_string(),
+        None if repo.find_remote("origin").is_ok() => "origin".to_string(),
+        None => repo
+            .remotes()
+            .ok()
+            .and_then(|names| names.iter().flatten().next().map(str::to_string))
+            .ok_or_else(|| "E_WEB_URL_NO_REMOTE: repository has no remotes".to_string())?,
+    };
+
+    let remote = repo
+        .find_remote(&name)
+        .map_err(|e| format!("E_WEB_URL_NO_REMOTE: remote '{}': {}", name, e))?;
+    remote
+        .url()
+        .map(str::to_string)
+        .ok_or_else(|| format!("E_WEB_URL_NO_REMOTE: remote '{}' has no URL", name))
+}
+
+/// Commits are always linked by full hash; files may keep a branch or tag name.
+fn resolve_revision(
+    repo: &Repository,
+    kind: &str,
+    revision: Option<&str>,
+) -> Result<String, String> {
+    let revision = revision.map(str::trim).filter(|rev| !rev.is_empty());
+    let spec = revision.unwrap_or("HEAD");
+    let object = repo
+        .revparse_single(spec)
+        .map_err(|e| format!("E_WEB_URL_REVISION: '{}': {}", spec, e))?;
+
+    let keep_name = kind != "commit"
+        && revision.is_some_and(|rev| {
+            repo.find_branch(rev, git2::BranchType::Local).is_ok()
+                || repo.find_reference(&format!("refs/tags/{}", rev)).is_ok()
+        });
+    if keep_name {
+        return Ok(encode_path(revision.unwrap_or(spec)));
+    }
+
+    let commit = object
+        .peel_to_commit()
+        .map_err(|e| format!("E_WEB_URL_REVISION: '{}': {}", spec, e))?;
+    Ok(commit.id().to_string())
+}
+
+fn line_anchor(provider: &str, kind: &str, args: &WebUrlArgs) -> Result<Option<String>, String> {
+    let Some(start) = args.start_line.filter(|line| *line > 0) else {
+        if kind == "lines" {
+            return Err("E_WEB_URL_ARGS: start_line is required".to_string());
+        }
+        return Ok(None);
+    };
+    let end = args.end_line.filter(|end| *end > start);
+
+    Ok(Some(match (provider, end) {
+        ("gitlab", Some(end)) => format!("#L{}-{}", start, end),
+        ("bitbucket", Some(end)) => format!("#lines-{}:{}", start, end),
+        ("bitbucket", None) => format!("#lines-{}", start),
+        (_, Some(end)) => format!("#L{}-L{}", start, end),
+        (_, None) => format!("#L{}", start),
+    }))
+}
+
+/// Percent-encode everything except unreserved characters and `/`.
+fn encode_path(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn create_test_repo(remote_url: &str) -> PathBuf {
+        let test_dir =
+            std::env::temp_dir().join(format!("gitlite-web-url-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&test_dir).unwrap();
+        for args in [
+            vec!["init"],
+            vec!["config", "user.name", "Test User"],
+            vec!["config", "user.email", "test@example.com"],
+            vec!["commit", "--allow-empty", "-m", "Initial commit"],
+            vec!["branch", "release"],
+            vec!["remote", "add", "origin", remote_url],
+        ] {
+            Command::new("git")
+                .args(&args)
+                .current_dir(&test_dir)
+                .output()
+                .unwrap();
+        }
+        test_dir
+    }
+
+    fn head(repo: &PathBuf) -> String {
+        Repository::open(repo)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_get_web_url_per_provider() {
+        let github = create_test_repo("git@github.com:octo/app.git");
+        let sha = head(&github);
+        let args = WebUrlArgs {
+            file: Some("src/my file.rs".to_string()),
+            start_line: Some(10),
+            end_line: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(
+            get_web_url(github.to_str().unwrap(), "commit", &WebUrlArgs::default()).unwrap(),
+            format!("https://github.com/octo/app/commit/{}", sha)
+        );
+        assert_eq!(
+            get_web_url(github.to_str().unwrap(), "lines", &args).unwrap(),
+            format!(
+                "https://github.com/octo/app/blob/{}/src/my%20file.rs#L10-L20",
+                sha
+            )
+        );
+        let on_branch = WebUrlArgs {
+            revision: Some("release".to_string()),
+            file: Some("README.md".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            get_web_url(github.to_str().unwrap(), "file", &on_branch).unwrap(),
+            "https://github.com/octo/app/blob/release/README.md"
+        );
+
+        let gitlab = create_test_repo("https://gitlab.com/group/app.git");
+        assert_eq!(
+            get_web_url(gitlab.to_str().unwrap(), "lines", &args).unwrap(),
+            format!(
+                "https://gitlab.com/group/app/-/blob/{}/src/my%20file.rs#L10-20",
+                head(&gitlab)
+            )
+        );
+
+        let bitbucket = create_test_repo("https://bitbucket.org/team/app.git");
+        assert_eq!(
+            get_web_url(bitbucket.to_str().unwrap(), "lines", &args).unwrap(),
+            format!(
+                "https://bitbucket.org/team/app/src/{}/src/my%20file.rs#lines-10:20",
+                head(&bitbucket)
+            )
+        );
+
+        for dir in [github, gitlab, bitbucket] {
+            fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_get_web_url_rejects_unknown_host() {
+        let repo = create_test_repo("/srv/git/app.git");
+        let result = get_web_url(repo.to_str().unwrap(), "commit", &WebUrlArgs::default());
+        assert!(result.unwrap_err().starts_with("E_WEB_URL_UNSUPPORTED"));
+        fs::remove_dir_all(repo).unwrap();
+    }
+}
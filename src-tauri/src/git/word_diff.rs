@@ -0,0 +1,206 @@
+use super::diff::{DiffHunk, DiffLineData};
+use serde::Serialize;
+
+/// Lines with more tokens than this are left unannotated; the LCS table
+/// grows with the product of both lengths.
+const MAX_TOKENS: usize = 400;
+
+/// Changed span inside a line, in UTF-16 code units so the frontend can
+/// `slice` the content directly.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct IntraLineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Pair each run of deleted lines with the added lines that follow it and
+/// record which words changed on both sides. Unpaired lines keep `None`.
+pub(super) fn annotate_hunk(hunk: &mut DiffHunk) {
+    let lines = &mut hunk.lines;
+    let mut i = 0;
+    while i < lines.len() {
+        let deletes = run_length(lines, i, "delete");
+        let adds = run_length(lines, i + deletes, "add");
+        if deletes == 0 || adds == 0 {
+            i += deletes.max(1);
+            continue;
+        }
+        for pair in 0..deletes.min(adds) {
+            let (old, new) =
+                intraline_ranges(&lines[i + pair].content, &lines[i + deletes + pair].content)
+                    .unwrap_or_default();
+            if !old.is_empty() || !new.is_empty() {
+                lines[i + pair].changes = Some(old);
+                lines[i + deletes + pair].changes = Some(new);
+            }
+        }
+        i += deletes + adds;
+    }
+}
+
+fn run_length(lines: &[DiffLineData], start: usize, line_type: &str) -> usize {
+    lines[start.min(lines.len())..]
+        .iter()
+        .take_while(|line| line.line_type == line_type)
+        .count()
+}
+
+/// Word-level diff of two lines. `None` when either side is too long to
+/// compare or the lines share nothing, where highlighting adds no signal.
+fn intraline_ranges(old: &str, new: &str) -> Option<(Vec<IntraLineRange>, Vec<IntraLineRange>)> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    if old_tokens.len() > MAX_TOKENS || new_tokens.len() > MAX_TOKENS {
+        return None;
+    }
+
+    let (old_common, new_common) = common_tokens(&old_tokens, &new_tokens);
+    if !old_common.iter().any(|&common| common) {
+        return None;
+    }
+    Some((
+        changed_ranges(&old_tokens, &old_common),
+        changed_ranges(&new_tokens, &new_common),
+    ))
+}
+
+/// Split into identifier runs, whitespace runs and single other characters.
+fn tokenize(line: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut previous: Option<Class> = None;
+    for (index, c) in line.char_indices() {
+        let current = class(c);
+        let joins = current != Class::Other && previous.as_ref() == Some(&current);
+        if !joins && index > start {
+            tokens.push(&line[start..index]);
+            start = index;
+        }
+        previous = Some(current);
+    }
+    if start < line.len() {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+/// Mark the tokens on each side that belong to a longest common subsequence.
+fn common_tokens(old: &[&str], new: &[&str]) -> (Vec<bool>, Vec<bool>) {
+    let width = new.len() + 1;
+    let mut table = vec![0u16; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut old_common = vec![false; old.len()];
+    let mut new_common = vec![false; new.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            old_common[i] = true;
+            new_common[j] = true;
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (old_common, new_common)
+}
+
+fn changed_ranges(tokens: &[&str], common: &[bool]) -> Vec<IntraLineRange> {
+    let mut ranges: Vec<IntraLineRange> = Vec::new();
+    let mut offset = 0;
+    for (token, &is_common) in tokens.iter().zip(common) {
+        let end = offset + token.encode_utf16().count();
+        if !is_common {
+            match ranges.last_mut() {
+                Some(last) if last.end == offset => last.end = end,
+                _ => ranges.push(IntraLineRange { start: offset, end }),
+            }
+        }
+        offset = end;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_type: &str, content: &str) -> DiffLineData {
+        DiffLineData {
+            line_type: line_type.to_string(),
+            content: content.to_string(),
+            old_lineno: None,
+            new_lineno: None,
+            changes: None,
+        }
+    }
+
+    #[test]
+    fn pairs_removed_and_added_lines_by_word() {
+        let mut hunk = DiffHunk {
+            old_start: 1,
+            old_lines: 2,
+            new_start: 1,
+            new_lines: 2,
+            lines: vec![
+                line("context", "fn main() {"),
+                line("delete", "    let total = price * 2;"),
+                line("add", "    let total = price * quantity;"),
+                line("add", "    println!(\"{}\", total);"),
+                line("context", "}"),
+            ],
+        };
+        annotate_hunk(&mut hunk);
+
+        let range = |start, end| IntraLineRange { start, end };
+        assert_eq!(hunk.lines[0].changes, None);
+        assert_eq!(hunk.lines[1].changes, Some(vec![range(24, 25)]));
+        assert_eq!(hunk.lines[2].changes, Some(vec![range(24, 32)]));
+        assert_eq!(hunk.lines[3].changes, None);
+        assert_eq!(
+            &hunk.lines[2].content[24..32],
+            "quantity",
+            "ranges index the content"
+        );
+    }
+
+    #[test]
+    fn unrelated_lines_and_utf16_offsets() {
+        assert_eq!(intraline_ranges("alpha", "omega"), None);
+
+        let (old, new) = intraline_ranges("naïve café", "naïve cafe").unwrap();
+        assert_eq!(old, vec![IntraLineRange { start: 6, end: 10 }]);
+        assert_eq!(new, vec![IntraLineRange { start: 6, end: 10 }]);
+
+        let (old, new) = intraline_ranges("😀 x", "😀 y").unwrap();
+        assert_eq!(old, vec![IntraLineRange { start: 3, end: 4 }]);
+        assert_eq!(new, vec![IntraLineRange { start: 3, end: 4 }]);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::error::GitLiteError;
+use crate::perf;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::sync::Semaphore;
+
+/// Worker count used when settings could not be read at startup.
+pub const DEFAULT_WORKERS: usize = 4;
+pub const MAX_WORKERS: usize = 32;
+
+static WORKERS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+static NETWORK_WORKERS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
+/// Size the pools from `AppConfig.git_worker_threads`. Only the first call
+/// takes effect, so a changed setting applies after a restart.
+pub fn configure(workers: usize) {
+    let workers = workers.clamp(1, MAX_WORKERS);
+    let _ = WORKERS.set(Arc::new(Semaphore::new(workers)));
+    let _ = NETWORK_WORKERS.set(Arc::new(Semaphore::new(workers)));
+}
+
+fn workers() -> Arc<Semaphore> {
+    WORKERS
+        .get_or_init(|| Arc::new(Semaphore::new(DEFAULT_WORKERS)))
+        .clone()
+}
+
+fn network_workers() -> Arc<Semaphore> {
+    NETWORK_WORKERS
+        .get_or_init(|| Arc::new(Semaphore::new(DEFAULT_WORKERS)))
+        .clone()
+}
+
+/// Run blocking libgit2 work off the async runtime threads so IPC stays
+/// responsive. At most `git_worker_threads` closures run at once; the rest
+/// wait for a free worker without holding a runtime thread.
+///
+/// Every run is timed under `operation`, the name of the calling command.
+pub async fn run<T, E, F>(operation: &'static str, f: F) -> Result<T, GitLiteError>
+where
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+    T: Send + 'static,
+    E: Into<GitLiteError> + Send + 'static,
+{
+    run_on(workers(), operation, f).await
+}
+
+/// Like `run`, but on a separate pool of the same size: for network
+/// operations that can block on a credential or host key prompt for
+/// minutes, which would otherwise keep a worker from every local command.
+pub async fn run_interactive<T, E, F>(operation: &'static str, f: F) -> Result<T, GitLiteError>
+where
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+    T: Send + 'static,
+    E: Into<GitLiteError> + Send + 'static,
+{
+    run_on(network_workers(), operation, f).await
+}
+
+async fn run_on<T, E, F>(
+    pool: Arc<Semaphore>,
+    operation: &'static str,
+    f: F,
+) -> Result<T, GitLiteError>
+where
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+    T: Send + 'static,
+    E: Into<GitLiteError> + Send + 'static,
+{
+    let queued_at = Instant::now();
+    let permit = pool
+        .acquire_owned()
+        .await
+        .map_err(|e| format!("E_GIT_WORKER: {}", e))?;
+    let queued = queued_at.elapsed();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _permit = permit;
+        perf::time(operation, queued, f)
+    })
+    .await
+    .map_err(|e| format!("E_GIT_WORKER: {}", e))?
+    .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+    use crate::error::ErrorCode;
+
+    #[test]
+    fn run_returns_result_and_maps_error_codes() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let value = runtime
+            .block_on(run("test", || Ok::<_, String>(42)))
+            .unwrap();
+        assert_eq!(value, 42);
+
+        let error = runtime
+            .block_on(run("test", || {
+                Err::<(), _>("E_BARE: no working tree".to_string())
+            }))
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Bare);
+
+        let error = runtime
+            .block_on(run("test", || -> Result<(), String> { panic!("boom") }))
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::GitWorker);
+    }
+}
@@ -1,24 +1,58 @@
-use reqwest::header::{ACCEPT, AUTHORIZATION, USER_AGENT};
+use crate::credentials::{KeychainCodes, KeychainItem};
+use crate::github_client;
+use crate::oauth::{self, DeviceCode, Endpoints, FlowCodes, DEVICE_GRANT_TYPE};
+use reqwest::header::{HeaderMap, ACCEPT, AUTHORIZATION, USER_AGENT};
 use serde::{Deserialize, Serialize};
 
-const DEVICE_CODE_URL: &str = "https://github.com/login/device/code";
-const ACCESS_TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
-const USER_PROFILE_URL: &str = "https://api.github.com/user";
+const DEFAULT_HOST: &str = "github.com";
 const GITHUB_API_VERSION: &str = "2022-11-28";
 const OAUTH_SCOPE: &str = "read:user repo";
-const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
 const APP_USER_AGENT: &str = "GitLite/0.1.0";
 const KEYCHAIN_SERVICE: &str = "com.gitlite.app.github";
+const KEYCHAIN_CODES: KeychainCodes = KeychainCodes {
+    init: "E_GITHUB_KEYCHAIN_INIT",
+    read: "E_GITHUB_KEYCHAIN_READ",
+    write: "E_GITHUB_KEYCHAIN_WRITE",
+    delete: "E_GITHUB_KEYCHAIN_DELETE",
+};
+const FLOW_CODES: FlowCodes = FlowCodes {
+    forge: "GitHub",
+    client_id_missing: "E_GITHUB_CLIENT_ID_MISSING: GitHub OAuth Client ID is required",
+    device_code_empty: "E_GITHUB_OAUTH_DEVICE_CODE_EMPTY",
+    network: "E_GITHUB_OAUTH_NETWORK",
+    start_failed: "E_GITHUB_OAUTH_START_FAILED",
+    parse: "E_GITHUB_OAUTH_PARSE",
+    poll_invalid: "E_GITHUB_OAUTH_POLL_INVALID",
+    poll_error: "E_GITHUB_OAUTH_POLL_ERROR",
+};
 const KEYCHAIN_ACCOUNT: &str = "oauth-token";
+const REPOS_PER_PAGE: u32 = 50;
+const REPO_AFFILIATIONS: [&str; 3] = ["owner", "collaborator", "organization_member"];
 
-#[derive(Serialize)]
-pub struct GitHubDeviceCode {
-    pub device_code: String,
-    pub user_code: String,
-    pub verification_uri: String,
-    pub verification_uri_complete: Option<String>,
-    pub expires_in: u64,
-    pub interval: u64,
+/// Endpoints of github.com or a GitHub Enterprise Server instance, which
+/// serves the same API under `/api/v3`.
+fn endpoints_for(host: Option<&str>) -> Result<Endpoints, String> {
+    let host = crate::oauth::resolve_host(host, DEFAULT_HOST, "E_GITHUB_HOST_INVALID")?;
+    let api_base = if host == DEFAULT_HOST {
+        "https://api.github.com".to_string()
+    } else {
+        format!("https://{}/api/v3", host)
+    };
+    Ok(Endpoints {
+        device_code_url: format!("https://{}/login/device/code", host),
+        access_token_url: format!("https://{}/login/oauth/access_token", host),
+        api_base,
+        host,
+    })
+}
+
+/// github.com keeps the original account name so existing tokens load.
+fn keychain_account(endpoints: &Endpoints) -> String {
+    if endpoints.host == DEFAULT_HOST {
+        KEYCHAIN_ACCOUNT.to_string()
+    } else {
+        endpoints.keychain_account()
+    }
 }
 
 #[derive(Serialize, Clone)]
@@ -28,6 +62,50 @@ pub struct GitHubUser {
     pub name: Option<String>,
 }
 
+/// What a token may do, for diagnosing permission failures.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GitHubTokenInfo {
+    pub login: String,
+    /// OAuth scopes of classic and OAuth app tokens; `None` for
+    /// fine-grained and GitHub App tokens, which carry permissions instead.
+    pub scopes: Option<Vec<String>>,
+    /// Whether the `repo` scope (private repository read/write) is granted;
+    /// `None` when the token has no scopes to inspect.
+    pub has_repo: Option<bool>,
+    /// Expiry as GitHub reports it (`2026-01-31 12:00:00 UTC`); `None` for
+    /// tokens that do not expire.
+    pub expires_at: Option<String>,
+}
+
+/// A repository the authenticated user can clone.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GitHubRepo {
+    pub name: String,
+    pub full_name: String,
+    pub clone_url: String,
+    pub ssh_url: String,
+    pub default_branch: String,
+    pub private: bool,
+    /// ISO 8601 time of the last push, as reported by GitHub.
+    pub pushed_at: Option<String>,
+}
+
+/// A fork just requested for the authenticated user, with the repository
+/// it was forked from.
+#[derive(Serialize, Deserialize)]
+pub struct GitHubFork {
+    #[serde(flatten)]
+    pub fork: GitHubRepo,
+    pub parent: Option<GitHubRepo>,
+}
+
+#[derive(Serialize)]
+pub struct GitHubRepoPage {
+    pub repos: Vec<GitHubRepo>,
+    pub page: u32,
+    pub has_next_page: bool,
+}
+
 #[derive(Serialize)]
 pub struct GitHubAuthPollResult {
     pub status: String,
@@ -39,23 +117,13 @@ pub struct GitHubAuthPollResult {
 }
 
 #[derive(Deserialize)]
-struct DeviceCodeResponse {
-    device_code: String,
-    user_code: String,
-    verification_uri: String,
-    verification_uri_complete: Option<String>,
-    expires_in: u64,
-    interval: Option<u64>,
+struct UserSearchResponse {
+    items: Vec<UserSearchItem>,
 }
 
 #[derive(Deserialize)]
-struct AccessTokenResponse {
-    access_token: Option<String>,
-    token_type: Option<String>,
-    scope: Option<String>,
-    error: Option<String>,
-    error_description: Option<String>,
-    interval: Option<u64>,
+struct UserSearchItem {
+    avatar_url: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -65,21 +133,163 @@ struct GitHubUserResponse {
     name: Option<String>,
 }
 
-pub async fn start_device_flow(client_id: &str) -> Result<GitHubDeviceCode, String> {
-    let normalized_client_id = normalize_client_id(client_id)?;
-    let client = reqwest::Client::new();
+pub async fn start_device_flow(client_id: &str, host: Option<&str>) -> Result<DeviceCode, String> {
+    let endpoints = endpoints_for(host)?;
+    let normalized_client_id = oauth::normalize_client_id(client_id, FLOW_CODES.client_id_missing)?;
+    let response = github_client::send(
+        github_client::client()
+            .post(&endpoints.device_code_url)
+            .header(ACCEPT, "application/json")
+            .header(USER_AGENT, APP_USER_AGENT)
+            .form(&[
+                ("client_id", normalized_client_id.as_str()),
+                ("scope", OAUTH_SCOPE),
+            ]),
+    )
+    .await;
+    oauth::device_code(response, &FLOW_CODES).await
+}
+
+pub async fn poll_device_flow(
+    client_id: &str,
+    device_code: &str,
+    host: Option<&str>,
+) -> Result<GitHubAuthPollResult, String> {
+    let endpoints = endpoints_for(host)?;
+    let normalized_client_id = oauth::normalize_client_id(client_id, FLOW_CODES.client_id_missing)?;
+    let normalized_device_code = oauth::normalize_device_code(device_code, &FLOW_CODES)?;
 
-    let response = client
-        .post(DEVICE_CODE_URL)
-        .header(ACCEPT, "application/json")
-        .header(USER_AGENT, APP_USER_AGENT)
-        .form(&[
-            ("client_id", normalized_client_id.as_str()),
-            ("scope", OAUTH_SCOPE),
-        ])
-        .send()
+    let response = github_client::send(
+        github_client::client()
+            .post(&endpoints.access_token_url)
+            .header(ACCEPT, "application/json")
+            .header(USER_AGENT, APP_USER_AGENT)
+            .form(&[
+                ("client_id", normalized_client_id.as_str()),
+                ("device_code", normalized_device_code),
+                ("grant_type", DEVICE_GRANT_TYPE),
+            ]),
+    )
+    .await;
+    let payload =
+        oauth::token_response(response, "E_GITHUB_OAUTH_POLL_FAILED", &FLOW_CODES).await?;
+
+    if let Some(access_token) = payload.access_token {
+        let (user, _) = fetch_authenticated_user(&endpoints, &access_token).await?;
+        return Ok(GitHubAuthPollResult {
+            status: "success".to_string(),
+            access_token: Some(access_token),
+            token_type: payload.token_type,
+            scope: payload.scope,
+            user: Some(user),
+            retry_after: None,
+        });
+    }
+
+    let (status, retry_after) = oauth::waiting_status(&payload, &FLOW_CODES)?;
+    Ok(GitHubAuthPollResult {
+        status: status.to_string(),
+        access_token: None,
+        token_type: None,
+        scope: None,
+        user: None,
+        retry_after,
+    })
+}
+
+pub async fn fetch_user(access_token: &str, host: Option<&str>) -> Result<GitHubUser, String> {
+    let endpoints = endpoints_for(host)?;
+    let token = access_token.trim();
+    if token.is_empty() {
+        return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
+    }
+    Ok(fetch_authenticated_user(&endpoints, token).await?.0)
+}
+
+/// Scopes and expiry GitHub reports for `access_token`, read from the
+/// headers of a `/user` request.
+pub async fn check_token(
+    access_token: &str,
+    host: Option<&str>,
+) -> Result<GitHubTokenInfo, String> {
+    let endpoints = endpoints_for(host)?;
+    let token = access_token.trim();
+    if token.is_empty() {
+        return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
+    }
+    let (user, headers) = fetch_authenticated_user(&endpoints, token).await?;
+    Ok(token_info(user.login, &headers))
+}
+
+/// Avatar of the GitHub user whose public email is `email`, if exactly one
+/// account lists it.
+pub async fn search_avatar_by_email(
+    access_token: &str,
+    email: &str,
+) -> Result<Option<String>, String> {
+    let endpoints = endpoints_for(None)?;
+    let response = github_client::send(
+        github_client::client()
+            .get(endpoints.api_url("/search/users"))
+            .query(&[("q", format!("{} in:email", email))])
+            .header(ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
+            .header(USER_AGENT, APP_USER_AGENT)
+            .header(AUTHORIZATION, format!("Bearer {}", access_token.trim())),
+    )
+    .await
+    .map_err(|error| format!("E_GITHUB_USER_FETCH: {}", error))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!(
+            "E_GITHUB_USER_FETCH: GitHub returned {}",
+            status.as_u16()
+        ));
+    }
+
+    let payload: UserSearchResponse = response
+        .json()
         .await
-        .map_err(|error| format!("E_GITHUB_OAUTH_NETWORK: {}", error))?;
+        .map_err(|error| format!("E_GITHUB_USER_PARSE: {}", error))?;
+    match payload.items.as_slice() {
+        [only] => Ok(only.avatar_url.clone()),
+        _ => Ok(None),
+    }
+}
+
+/// One page of `GET /user/repos`, most recently pushed first. `affiliation`
+/// is a comma-separated subset of `owner,collaborator,organization_member`.
+pub async fn list_repos(
+    access_token: &str,
+    page: u32,
+    affiliation: Option<&str>,
+    host: Option<&str>,
+) -> Result<GitHubRepoPage, String> {
+    let endpoints = endpoints_for(host)?;
+    let token = access_token.trim();
+    if token.is_empty() {
+        return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
+    }
+    let affiliation = normalize_affiliation(affiliation)?;
+    let page = page.max(1);
+
+    let response = github_client::send(
+        github_client::client()
+            .get(endpoints.api_url("/user/repos"))
+            .query(&[
+                ("affiliation", affiliation),
+                ("sort", "pushed".to_string()),
+                ("per_page", REPOS_PER_PAGE.to_string()),
+                ("page", page.to_string()),
+            ])
+            .header(ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
+            .header(USER_AGENT, APP_USER_AGENT)
+            .header(AUTHORIZATION, format!("Bearer {}", token)),
+    )
+    .await
+    .map_err(|error| format!("E_GITHUB_REPOS_FETCH: {}", error))?;
 
     let status = response.status();
     if !status.is_success() {
@@ -88,50 +298,53 @@ pub async fn start_device_flow(client_id: &str) -> Result<GitHubDeviceCode, Stri
             .await
             .unwrap_or_else(|_| "Unable to read response body".to_string());
         return Err(format!(
-            "E_GITHUB_OAUTH_START_FAILED: GitHub returned {} ({})",
+            "E_GITHUB_REPOS_FETCH: GitHub returned {} ({})",
             status.as_u16(),
             body
         ));
     }
 
-    let payload: DeviceCodeResponse = response
+    let has_next_page = response
+        .headers()
+        .get(reqwest::header::LINK)
+        .and_then(|link| link.to_str().ok())
+        .is_some_and(|link| link.contains("rel=\"next\""));
+    let repos: Vec<GitHubRepo> = response
         .json()
         .await
-        .map_err(|error| format!("E_GITHUB_OAUTH_PARSE: {}", error))?;
-
-    Ok(GitHubDeviceCode {
-        device_code: payload.device_code,
-        user_code: payload.user_code,
-        verification_uri: payload.verification_uri,
-        verification_uri_complete: payload.verification_uri_complete,
-        expires_in: payload.expires_in,
-        interval: payload.interval.unwrap_or(5),
+        .map_err(|error| format!("E_GITHUB_REPOS_PARSE: {}", error))?;
+
+    Ok(GitHubRepoPage {
+        repos,
+        page,
+        has_next_page,
     })
 }
 
-pub async fn poll_device_flow(
-    client_id: &str,
-    device_code: &str,
-) -> Result<GitHubAuthPollResult, String> {
-    let normalized_client_id = normalize_client_id(client_id)?;
-    let normalized_device_code = device_code.trim();
-    if normalized_device_code.is_empty() {
-        return Err("E_GITHUB_OAUTH_DEVICE_CODE_EMPTY: Device code is required".to_string());
+/// Fork `owner/repo` into the authenticated user's account. GitHub creates
+/// forks asynchronously, so cloning may need a few seconds' retry. Forking a
+/// repository that was already forked returns the existing fork.
+pub async fn fork_repo(
+    access_token: &str,
+    owner: &str,
+    repo: &str,
+    host: Option<&str>,
+) -> Result<GitHubFork, String> {
+    let token = access_token.trim();
+    if token.is_empty() {
+        return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
     }
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(ACCESS_TOKEN_URL)
-        .header(ACCEPT, "application/json")
-        .header(USER_AGENT, APP_USER_AGENT)
-        .form(&[
-            ("client_id", normalized_client_id.as_str()),
-            ("device_code", normalized_device_code),
-            ("grant_type", DEVICE_GRANT_TYPE),
-        ])
-        .send()
+    let (owner, repo) = (owner.trim(), repo.trim());
+    if owner.is_empty() || repo.is_empty() || owner.contains('/') || repo.contains('/') {
+        return Err(format!(
+            "E_GITHUB_FORK_INVALID: '{}/{}' is not a repository",
+            owner, repo
+        ));
+    }
+    let url = format!("{}/repos/{}/{}/forks", api_base(host)?, owner, repo);
+    let response = github_client::send(api_request(reqwest::Method::POST, &url, token))
         .await
-        .map_err(|error| format!("E_GITHUB_OAUTH_NETWORK: {}", error))?;
+        .map_err(|error| format!("E_GITHUB_FORK_FAILED: {}", error))?;
 
     let status = response.status();
     if !status.is_success() {
@@ -140,145 +353,122 @@ pub async fn poll_device_flow(
             .await
             .unwrap_or_else(|_| "Unable to read response body".to_string());
         return Err(format!(
-            "E_GITHUB_OAUTH_POLL_FAILED: GitHub returned {} ({})",
+            "E_GITHUB_FORK_FAILED: GitHub returned {} ({})",
             status.as_u16(),
             body
         ));
     }
-
-    let payload: AccessTokenResponse = response
+    response
         .json()
         .await
-        .map_err(|error| format!("E_GITHUB_OAUTH_PARSE: {}", error))?;
-
-    if let Some(access_token) = payload.access_token {
-        let user = fetch_authenticated_user(&client, &access_token).await?;
-        return Ok(GitHubAuthPollResult {
-            status: "success".to_string(),
-            access_token: Some(access_token),
-            token_type: payload.token_type,
-            scope: payload.scope,
-            user: Some(user),
-            retry_after: None,
-        });
-    }
-
-    let Some(error_code) = payload.error else {
-        return Err("E_GITHUB_OAUTH_POLL_INVALID: Missing access_token and error".to_string());
-    };
-
-    let retry_after = payload.interval;
-    match error_code.as_str() {
-        "authorization_pending" => Ok(GitHubAuthPollResult {
-            status: "pending".to_string(),
-            access_token: None,
-            token_type: None,
-            scope: None,
-            user: None,
-            retry_after,
-        }),
-        "slow_down" => Ok(GitHubAuthPollResult {
-            status: "slow_down".to_string(),
-            access_token: None,
-            token_type: None,
-            scope: None,
-            user: None,
-            retry_after,
-        }),
-        "expired_token" => Ok(GitHubAuthPollResult {
-            status: "expired".to_string(),
-            access_token: None,
-            token_type: None,
-            scope: None,
-            user: None,
-            retry_after: None,
-        }),
-        "access_denied" => Ok(GitHubAuthPollResult {
-            status: "denied".to_string(),
-            access_token: None,
-            token_type: None,
-            scope: None,
-            user: None,
-            retry_after: None,
-        }),
-        _ => Err(format!(
-            "E_GITHUB_OAUTH_POLL_ERROR: {} ({})",
-            error_code,
-            payload.error_description.unwrap_or_default()
-        )),
-    }
+        .map_err(|error| format!("E_GITHUB_REPOS_PARSE: {}", error))
 }
 
-pub async fn fetch_user(access_token: &str) -> Result<GitHubUser, String> {
+pub fn save_token_to_keychain(access_token: &str, host: Option<&str>) -> Result<(), String> {
     let token = access_token.trim();
     if token.is_empty() {
         return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
     }
-    let client = reqwest::Client::new();
-    fetch_authenticated_user(&client, token).await
+
+    keychain_item(host)?.save(token)
 }
 
-pub fn save_token_to_keychain(access_token: &str) -> Result<(), String> {
-    let token = access_token.trim();
-    if token.is_empty() {
-        return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
-    }
+pub fn load_token_from_keychain(host: Option<&str>) -> Result<Option<String>, String> {
+    Ok(keychain_item(host)?
+        .load()?
+        .filter(|token| !token.trim().is_empty()))
+}
 
-    let entry = keyring_entry()?;
-    entry
-        .set_password(token)
-        .map_err(|error| format!("E_GITHUB_KEYCHAIN_WRITE: {}", error))
+pub fn delete_token_from_keychain(host: Option<&str>) -> Result<(), String> {
+    keychain_item(host)?.delete()
 }
 
-pub fn load_token_from_keychain() -> Result<Option<String>, String> {
-    let entry = keyring_entry()?;
-    match entry.get_password() {
-        Ok(token) => {
-            if token.trim().is_empty() {
-                Ok(None)
-            } else {
-                Ok(Some(token))
-            }
-        }
-        Err(keyring::Error::NoEntry) => Ok(None),
-        Err(error) => Err(format!("E_GITHUB_KEYCHAIN_READ: {}", error)),
-    }
+/// REST API root for `host`: `https://api.github.com` or `https://<host>/api/v3`.
+pub(crate) fn api_base(host: Option<&str>) -> Result<String, String> {
+    Ok(endpoints_for(host)?.api_base)
 }
 
-pub fn delete_token_from_keychain() -> Result<(), String> {
-    let entry = keyring_entry()?;
-    match entry.delete_password() {
-        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
-        Err(error) => Err(format!("E_GITHUB_KEYCHAIN_DELETE: {}", error)),
-    }
+/// Whether `host` serves the GitHub API GitLite talks to: github.com, or a
+/// GitHub Enterprise Server host a token has been saved for.
+pub(crate) fn is_api_host(host: &str) -> bool {
+    host == DEFAULT_HOST || load_token_from_keychain(Some(host)).is_ok_and(|token| token.is_some())
+}
+
+/// A REST request carrying the headers every GitHub API call sends.
+pub(crate) fn api_request(
+    method: reqwest::Method,
+    url: &str,
+    access_token: &str,
+) -> reqwest::RequestBuilder {
+    github_client::client()
+        .request(method, url)
+        .header(ACCEPT, "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
+        .header(USER_AGENT, APP_USER_AGENT)
+        .header(AUTHORIZATION, format!("Bearer {}", access_token.trim()))
 }
 
-fn keyring_entry() -> Result<keyring::Entry, String> {
-    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
-        .map_err(|error| format!("E_GITHUB_KEYCHAIN_INIT: {}", error))
+fn keychain_item(host: Option<&str>) -> Result<KeychainItem, String> {
+    let account = keychain_account(&endpoints_for(host)?);
+    KeychainItem::new(KEYCHAIN_SERVICE, &account, &KEYCHAIN_CODES)
 }
 
-fn normalize_client_id(client_id: &str) -> Result<String, String> {
-    let normalized = client_id.trim();
-    if normalized.is_empty() {
-        return Err("E_GITHUB_CLIENT_ID_MISSING: GitHub OAuth Client ID is required".to_string());
+fn normalize_affiliation(affiliation: Option<&str>) -> Result<String, String> {
+    let Some(affiliation) = affiliation.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(REPO_AFFILIATIONS.join(","));
+    };
+    let parts: Vec<&str> = affiliation.split(',').map(str::trim).collect();
+    if let Some(unknown) = parts.iter().find(|part| !REPO_AFFILIATIONS.contains(part)) {
+        return Err(format!(
+            "E_GITHUB_REPOS_AFFILIATION: '{}' is not one of {}",
+            unknown,
+            REPO_AFFILIATIONS.join(", ")
+        ));
+    }
+    Ok(parts.join(","))
+}
+
+fn token_info(login: String, headers: &HeaderMap) -> GitHubTokenInfo {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    let scopes: Option<Vec<String>> = header("x-oauth-scopes").map(|scopes| {
+        scopes
+            .split(',')
+            .map(str::trim)
+            .filter(|scope| !scope.is_empty())
+            .map(str::to_string)
+            .collect()
+    });
+    GitHubTokenInfo {
+        login,
+        has_repo: scopes
+            .as_ref()
+            .map(|scopes| scopes.iter().any(|scope| scope == "repo")),
+        scopes,
+        expires_at: header("github-authentication-token-expiration")
+            .filter(|expiry| !expiry.is_empty())
+            .map(str::to_string),
     }
-    Ok(normalized.to_string())
 }
 
 async fn fetch_authenticated_user(
-    client: &reqwest::Client,
+    endpoints: &Endpoints,
     access_token: &str,
-) -> Result<GitHubUser, String> {
-    let response = client
-        .get(USER_PROFILE_URL)
-        .header(ACCEPT, "application/vnd.github+json")
-        .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
-        .header(USER_AGENT, APP_USER_AGENT)
-        .header(AUTHORIZATION, format!("Bearer {}", access_token))
-        .send()
-        .await
-        .map_err(|error| format!("E_GITHUB_USER_FETCH: {}", error))?;
+) -> Result<(GitHubUser, HeaderMap), String> {
+    let response = github_client::send(
+        github_client::client()
+            .get(endpoints.user_url())
+            .header(ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
+            .header(USER_AGENT, APP_USER_AGENT)
+            .header(AUTHORIZATION, format!("Bearer {}", access_token)),
+    )
+    .await
+    .map_err(|error| format!("E_GITHUB_USER_FETCH: {}", error))?;
 
     let status = response.status();
     if !status.is_success() {
@@ -293,14 +483,99 @@ async fn fetch_authenticated_user(
         ));
     }
 
+    let headers = response.headers().clone();
     let payload: GitHubUserResponse = response
         .json()
         .await
         .map_err(|error| format!("E_GITHUB_USER_PARSE: {}", error))?;
 
-    Ok(GitHubUser {
+    let user = GitHubUser {
         login: payload.login,
         avatar_url: payload.avatar_url.unwrap_or_default(),
         name: payload.name,
-    })
+    };
+    Ok((user, headers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_affiliation_defaults_to_all_and_rejects_unknown_values() {
+        assert_eq!(
+            normalize_affiliation(None).unwrap(),
+            "owner,collaborator,organization_member"
+        );
+        assert_eq!(
+            normalize_affiliation(Some(" owner, collaborator ")).unwrap(),
+            "owner,collaborator"
+        );
+        assert!(normalize_affiliation(Some("owner,member"))
+            .unwrap_err()
+            .starts_with("E_GITHUB_REPOS_AFFILIATION"));
+    }
+
+    #[test]
+    fn token_scopes_and_expiry_come_from_response_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-oauth-scopes",
+            "read:org, repo, workflow".parse().unwrap(),
+        );
+        headers.insert(
+            "github-authentication-token-expiration",
+            "2026-01-31 12:00:00 UTC".parse().unwrap(),
+        );
+        let classic = token_info("octo".to_string(), &headers);
+        assert_eq!(classic.has_repo, Some(true));
+        assert_eq!(
+            classic.scopes.unwrap(),
+            vec!["read:org", "repo", "workflow"]
+        );
+        assert_eq!(
+            classic.expires_at.as_deref(),
+            Some("2026-01-31 12:00:00 UTC")
+        );
+
+        headers.insert("x-oauth-scopes", "public_repo".parse().unwrap());
+        assert_eq!(
+            token_info("octo".to_string(), &headers).has_repo,
+            Some(false)
+        );
+
+        let fine_grained = token_info("octo".to_string(), &HeaderMap::new());
+        assert_eq!((fine_grained.scopes, fine_grained.has_repo), (None, None));
+    }
+
+    #[test]
+    fn enterprise_hosts_use_api_v3_and_their_own_keychain_account() {
+        let public = endpoints_for(None).unwrap();
+        assert_eq!(public, endpoints_for(Some(" GitHub.com ")).unwrap());
+        assert_eq!(public.user_url(), "https://api.github.com/user");
+        assert_eq!(
+            public.device_code_url,
+            "https://github.com/login/device/code"
+        );
+        assert_eq!(keychain_account(&public), KEYCHAIN_ACCOUNT);
+
+        let enterprise = endpoints_for(Some("https://GHE.corp.example/org/repo")).unwrap();
+        assert_eq!(enterprise.host, "ghe.corp.example");
+        assert_eq!(
+            enterprise.access_token_url,
+            "https://ghe.corp.example/login/oauth/access_token"
+        );
+        assert_eq!(
+            enterprise.api_url("/search/users"),
+            "https://ghe.corp.example/api/v3/search/users"
+        );
+        assert_eq!(
+            keychain_account(&enterprise),
+            "oauth-token@ghe.corp.example"
+        );
+
+        assert!(endpoints_for(Some("https:///"))
+            .unwrap_err()
+            .starts_with("E_GITHUB_HOST_INVALID"));
+    }
 }
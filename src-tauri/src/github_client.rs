@@ -0,0 +1,272 @@
+use crate::json_store::unix_now;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+/// Retries after the first attempt for 5xx responses, dropped connections
+/// and rate limits that lift soon enough. Only GET and HEAD are retried on
+/// 5xx and connection errors; other methods may already have taken effect.
+const MAX_RETRIES: u32 = 2;
+/// Longest rate-limit wait worth holding a request for; beyond that the
+/// error goes back to the caller.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static RATE_LIMITS: Mutex<BTreeMap<(String, String), GitHubRateLimit>> =
+    Mutex::new(BTreeMap::new());
+
+/// Quota from the `X-RateLimit-*` headers of the latest response for one
+/// API host and resource (`core`, `search`, ...).
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GitHubRateLimit {
+    pub host: String,
+    pub resource: String,
+    pub limit: u64,
+    pub remaining: u64,
+    pub used: u64,
+    /// Unix seconds at which `remaining` resets to `limit`.
+    pub reset_at: i64,
+}
+
+/// Connection-pooling client shared by every GitHub call.
+pub fn client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// Send `request`, recording rate-limit headers and retrying transient
+/// failures. Requests with streaming bodies are sent once.
+pub async fn send(request: RequestBuilder) -> reqwest::Result<Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let idempotent = is_idempotent(request.method());
+    let mut attempt = 0;
+    loop {
+        let Some(current) = request.try_clone() else {
+            return client.execute(request).await;
+        };
+        let delay = match client.execute(current).await {
+            Ok(response) => {
+                if let Some(host) = response.url().host_str() {
+                    record(host, response.headers());
+                }
+                match retry_delay(
+                    response.status(),
+                    response.headers(),
+                    idempotent,
+                    attempt,
+                    unix_now(),
+                ) {
+                    Some(delay) => delay,
+                    None => return Ok(response),
+                }
+            }
+            Err(error)
+                if idempotent
+                    && attempt < MAX_RETRIES
+                    && (error.is_connect() || error.is_timeout()) =>
+            {
+                backoff(attempt)
+            }
+            Err(error) => return Err(error),
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Latest known quotas for the API at `api_base`, e.g.
+/// `https://api.github.com`. Empty until a request has been made.
+pub fn rate_limits(api_base: &str) -> Vec<GitHubRateLimit> {
+    let Some(api_host) = reqwest::Url::parse(api_base)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+    else {
+        return Vec::new();
+    };
+    lock()
+        .values()
+        .filter(|limit| limit.host == api_host)
+        .cloned()
+        .collect()
+}
+
+fn record(host: &str, headers: &HeaderMap) {
+    if let Some(limit) = parse_rate_limit(host, headers) {
+        lock().insert((limit.host.clone(), limit.resource.clone()), limit);
+    }
+}
+
+fn parse_rate_limit(host: &str, headers: &HeaderMap) -> Option<GitHubRateLimit> {
+    Some(GitHubRateLimit {
+        host: host.to_string(),
+        resource: header_str(headers, "x-ratelimit-resource")
+            .unwrap_or("core")
+            .to_string(),
+        limit: header_str(headers, "x-ratelimit-limit")?.parse().ok()?,
+        remaining: header_str(headers, "x-ratelimit-remaining")?.parse().ok()?,
+        used: header_str(headers, "x-ratelimit-used")
+            .and_then(|used| used.parse().ok())
+            .unwrap_or(0),
+        reset_at: header_str(headers, "x-ratelimit-reset")?.parse().ok()?,
+    })
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD)
+}
+
+/// How long to wait before retrying, or `None` to hand the response back.
+/// Secondary rate limits send `Retry-After`; an exhausted primary quota is
+/// only waited out when it resets within `MAX_RATE_LIMIT_WAIT`. A rejected
+/// non-idempotent request is only resent on 429 or a 403 with `Retry-After`,
+/// where GitHub guarantees it was not processed.
+fn retry_delay(
+    status: StatusCode,
+    headers: &HeaderMap,
+    idempotent: bool,
+    attempt: u32,
+    now: i64,
+) -> Option<Duration> {
+    if attempt >= MAX_RETRIES {
+        return None;
+    }
+    if !idempotent
+        && status != StatusCode::TOO_MANY_REQUESTS
+        && !(status == StatusCode::FORBIDDEN && headers.contains_key(RETRY_AFTER))
+    {
+        return None;
+    }
+    match status {
+        StatusCode::INTERNAL_SERVER_ERROR
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::GATEWAY_TIMEOUT => Some(backoff(attempt)),
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => {
+            let wait = if let Some(seconds) = header_str(headers, RETRY_AFTER.as_str()) {
+                Duration::from_secs(seconds.parse().ok()?)
+            } else if header_str(headers, "x-ratelimit-remaining") == Some("0") {
+                let reset: i64 = header_str(headers, "x-ratelimit-reset")?.parse().ok()?;
+                Duration::from_secs(reset.saturating_sub(now).max(1) as u64)
+            } else if status == StatusCode::TOO_MANY_REQUESTS {
+                backoff(attempt)
+            } else {
+                // A plain 403 is a permission problem, not a rate limit.
+                return None;
+            };
+            (wait <= MAX_RATE_LIMIT_WAIT).then_some(wait)
+        }
+        _ => None,
+    }
+}
+
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt)
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn lock() -> MutexGuard<'static, BTreeMap<(String, String), GitHubRateLimit>> {
+    RATE_LIMITS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn retries_transient_errors_and_short_rate_limits_only() {
+        let none = HeaderMap::new();
+        assert_eq!(
+            retry_delay(StatusCode::BAD_GATEWAY, &none, true, 0, 0),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            retry_delay(StatusCode::BAD_GATEWAY, &none, true, MAX_RETRIES, 0),
+            None
+        );
+        assert_eq!(retry_delay(StatusCode::NOT_FOUND, &none, true, 0, 0), None);
+        assert_eq!(retry_delay(StatusCode::FORBIDDEN, &none, true, 0, 0), None);
+
+        let secondary = headers(&[("retry-after", "30")]);
+        assert_eq!(
+            retry_delay(StatusCode::FORBIDDEN, &secondary, true, 0, 0),
+            Some(Duration::from_secs(30))
+        );
+        let exhausted = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1000"),
+        ]);
+        assert_eq!(
+            retry_delay(StatusCode::FORBIDDEN, &exhausted, true, 0, 990),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            retry_delay(StatusCode::FORBIDDEN, &exhausted, true, 0, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn non_idempotent_requests_retry_only_explicit_rate_limits() {
+        assert!(is_idempotent(&Method::GET) && !is_idempotent(&Method::POST));
+        let none = HeaderMap::new();
+        assert_eq!(
+            retry_delay(StatusCode::BAD_GATEWAY, &none, false, 0, 0),
+            None
+        );
+        assert_eq!(
+            retry_delay(StatusCode::TOO_MANY_REQUESTS, &none, false, 0, 0),
+            Some(Duration::from_secs(1))
+        );
+        let secondary = headers(&[("retry-after", "5")]);
+        assert_eq!(
+            retry_delay(StatusCode::FORBIDDEN, &secondary, false, 0, 0),
+            Some(Duration::from_secs(5))
+        );
+        let exhausted = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1000"),
+        ]);
+        assert_eq!(
+            retry_delay(StatusCode::FORBIDDEN, &exhausted, false, 0, 990),
+            None
+        );
+    }
+
+    #[test]
+    fn rate_limit_headers_are_recorded_per_resource() {
+        let host = format!("api.{}.example", uuid::Uuid::new_v4());
+        record(&host, &HeaderMap::new());
+        assert!(rate_limits(&format!("https://{}", host)).is_empty());
+
+        record(
+            &host,
+            &headers(&[
+                ("x-ratelimit-limit", "30"),
+                ("x-ratelimit-remaining", "29"),
+                ("x-ratelimit-used", "1"),
+                ("x-ratelimit-reset", "1700000000"),
+                ("x-ratelimit-resource", "search"),
+            ]),
+        );
+        let limits = rate_limits(&format!("https://{}/api/v3", host));
+        assert_eq!(limits.len(), 1);
+        assert_eq!(
+            (limits[0].resource.as_str(), limits[0].remaining),
+            ("search", 29)
+        );
+    }
+}
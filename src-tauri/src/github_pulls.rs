@@ -0,0 +1,584 @@
+use crate::git::{push_branch, rewrite_url, web_base_url};
+use crate::github_auth::{self, api_base, api_request};
+use crate::github_client;
+use git2::{Direction, Repository};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+const PULLS_PER_PAGE: u32 = 50;
+/// Head commits whose CI state is looked up at once; each lookup makes two
+/// requests.
+const MAX_CONCURRENT_CI_LOOKUPS: usize = 4;
+
+/// The GitHub repository behind a local repository's `origin`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GitHubRepoSlug {
+    pub host: String,
+    pub owner: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct GitHubPullRequest {
+    pub number: u64,
+    pub title: String,
+    pub author: String,
+    /// Head branch name; for forks `head_label` carries the `owner:branch` form.
+    pub head: String,
+    pub head_label: String,
+    pub head_sha: String,
+    pub base: String,
+    pub draft: bool,
+    /// `success | failure | pending | none | unknown`, from commit statuses and check runs.
+    pub ci_state: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CreatedPullRequest {
+    pub number: u64,
+    pub url: String,
+}
+
+/// Fields of a new pull request; `head` may be `owner:branch` for a fork.
+#[derive(Deserialize, Clone, Debug)]
+pub struct NewPullRequest {
+    pub base: String,
+    pub head: String,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub draft: bool,
+}
+
+#[derive(Deserialize)]
+struct CreatedPullResponse {
+    number: u64,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct PullResponse {
+    number: u64,
+    title: String,
+    user: Option<UserRef>,
+    head: BranchRef,
+    base: BranchRef,
+    #[serde(default)]
+    draft: bool,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct UserRef {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct BranchRef {
+    #[serde(rename = "ref")]
+    name: String,
+    label: Option<String>,
+    sha: String,
+}
+
+/// CI state of one commit: the worst of its commit statuses and check runs.
+#[derive(Serialize, Clone, Debug)]
+pub struct CommitCiStatus {
+    pub sha: String,
+    /// `success | failure | pending | none`
+    pub state: String,
+    pub checks: Vec<CiCheck>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CiCheck {
+    pub name: String,
+    /// `status` (commit status API) or `check_run` (Checks API).
+    pub kind: String,
+    /// `success | failure | pending`
+    pub state: String,
+    pub description: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CombinedStatusResponse {
+    statuses: Vec<StatusResponse>,
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    context: String,
+    state: String,
+    description: Option<String>,
+    target_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CheckRunsResponse {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Deserialize)]
+struct CheckRun {
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+    html_url: Option<String>,
+    output: Option<CheckRunOutput>,
+}
+
+#[derive(Deserialize)]
+struct CheckRunOutput {
+    title: Option<String>,
+}
+
+/// Owner and name of the GitHub repository `origin` points at. Enterprise
+/// hosts count once a GitHub token is saved for them.
+pub fn origin_repo(path: &str) -> Result<GitHubRepoSlug, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let remote = repo
+        .find_remote("origin")
+        .map_err(|_| "E_REMOTE_NOT_FOUND: remote 'origin' does not exist".to_string())?;
+    let url = remote.url().unwrap_or_default();
+    let url = match repo.config() {
+        Ok(config) => rewrite_url(&config, url, Direction::Fetch),
+        Err(_) => url.to_string(),
+    };
+    repo_slug(&url, github_auth::is_api_host).ok_or_else(|| {
+        format!(
+            "E_GITHUB_REMOTE_UNSUPPORTED: origin '{}' is not on github.com or a GitHub host you signed in to",
+            url
+        )
+    })
+}
+
+/// Open pull requests of `slug`, newest first, with the CI state of each head.
+pub async fn list_pull_requests(
+    access_token: &str,
+    slug: &GitHubRepoSlug,
+) -> Result<Vec<GitHubPullRequest>, String> {
+    if access_token.trim().is_empty() {
+        return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
+    }
+    let repo_url = format!(
+        "{}/repos/{}/{}",
+        api_base(Some(&slug.host))?,
+        slug.owner,
+        slug.name
+    );
+    let response = github_client::send(
+        api_request(Method::GET, &format!("{}/pulls", repo_url), access_token).query(&[
+            ("state", "open".to_string()),
+            ("per_page", PULLS_PER_PAGE.to_string()),
+        ]),
+    )
+    .await
+    .map_err(|error| format!("E_GITHUB_PULLS_FETCH: {}", error))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unable to read response body".to_string());
+        return Err(format!(
+            "E_GITHUB_PULLS_FETCH: GitHub returned {} ({})",
+            status.as_u16(),
+            body
+        ));
+    }
+    let pulls: Vec<PullResponse> = response
+        .json()
+        .await
+        .map_err(|error| format!("E_GITHUB_PULLS_PARSE: {}", error))?;
+
+    // One status lookup per head commit, a few at a time.
+    let mut lookups = tokio::task::JoinSet::new();
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_CI_LOOKUPS));
+    for (index, pull) in pulls.iter().enumerate() {
+        let commit_url = format!("{}/commits/{}", repo_url, pull.head.sha);
+        let (token, permits) = (access_token.to_string(), permits.clone());
+        lookups.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            (index, ci_state(&commit_url, &token).await)
+        });
+    }
+    let mut states = vec!["unknown".to_string(); pulls.len()];
+    while let Some(joined) = lookups.join_next().await {
+        // A panicked lookup leaves its pull request `unknown`.
+        if let Ok((index, state)) = joined {
+            states[index] = state;
+        }
+    }
+
+    Ok(pulls
+        .into_iter()
+        .zip(states)
+        .map(|(pull, ci_state)| GitHubPullRequest {
+            number: pull.number,
+            title: pull.title,
+            author: pull.user.map(|user| user.login).unwrap_or_default(),
+            head_label: pull.head.label.unwrap_or_else(|| pull.head.name.clone()),
+            head: pull.head.name,
+            head_sha: pull.head.sha,
+            base: pull.base.name,
+            draft: pull.draft,
+            ci_state,
+            url: pull.html_url,
+        })
+        .collect())
+}
+
+/// Make sure `origin` has every commit of the local `head` branch, pushing
+/// it when `push` is set. A local branch behind `origin/<head>` counts as
+/// published. Fork heads (`owner:branch`) are not checked.
+pub fn ensure_head_pushed(path: &str, head: &str, push: bool) -> Result<(), String> {
+    if head.contains(':') {
+        return Ok(());
+    }
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let local = repo
+        .find_reference(&format!("refs/heads/{}", head))
+        .ok()
+        .and_then(|reference| reference.target());
+    let remote = repo
+        .find_reference(&format!("refs/remotes/origin/{}", head))
+        .ok()
+        .and_then(|reference| reference.target());
+    let published = match (local, remote) {
+        (Some(local), Some(remote)) => {
+            local == remote
+                || repo
+                    .graph_descendant_of(remote, local)
+                    .map_err(|e| format!("Failed to compare with origin: {}", e))?
+        }
+        _ => false,
+    };
+    match (local, remote) {
+        (None, None) => Err(format!(
+            "E_GITHUB_PR_HEAD_NOT_FOUND: branch '{}' does not exist locally or on origin",
+            head
+        )),
+        (None, Some(_)) => Ok(()),
+        (Some(_), Some(_)) if published => Ok(()),
+        (Some(_), _) if push => push_branch(path, "origin", head, "", ""),
+        (Some(_), None) => Err(format!(
+            "E_GITHUB_PR_HEAD_NOT_PUSHED: branch '{}' has not been pushed to origin",
+            head
+        )),
+        (Some(_), Some(_)) => Err(format!(
+            "E_GITHUB_PR_HEAD_NOT_PUSHED: branch '{}' has commits origin does not have",
+            head
+        )),
+    }
+}
+
+/// Open a pull request on `slug`.
+pub async fn create_pull_request(
+    access_token: &str,
+    slug: &GitHubRepoSlug,
+    request: &NewPullRequest,
+) -> Result<CreatedPullRequest, String> {
+    if access_token.trim().is_empty() {
+        return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
+    }
+    let request = validate_new_pull_request(request)?;
+    let url = format!(
+        "{}/repos/{}/{}/pulls",
+        api_base(Some(&slug.host))?,
+        slug.owner,
+        slug.name
+    );
+    let response = github_client::send(api_request(Method::POST, &url, access_token).json(
+        &serde_json::json!({
+            "base": request.base,
+            "head": request.head,
+            "title": request.title,
+            "body": request.body,
+            "draft": request.draft,
+        }),
+    ))
+    .await
+    .map_err(|error| format!("E_GITHUB_PR_CREATE: {}", error))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unable to read response body".to_string());
+        return Err(format!(
+            "E_GITHUB_PR_CREATE: GitHub returned {} ({})",
+            status.as_u16(),
+            body
+        ));
+    }
+    let created: CreatedPullResponse = response
+        .json()
+        .await
+        .map_err(|error| format!("E_GITHUB_PULLS_PARSE: {}", error))?;
+    Ok(CreatedPullRequest {
+        number: created.number,
+        url: created.html_url,
+    })
+}
+
+fn validate_new_pull_request(request: &NewPullRequest) -> Result<NewPullRequest, String> {
+    let trimmed = NewPullRequest {
+        base: request.base.trim().to_string(),
+        head: request.head.trim().to_string(),
+        title: request.title.trim().to_string(),
+        body: request.body.clone(),
+        draft: request.draft,
+    };
+    for (field, value) in [
+        ("base", &trimmed.base),
+        ("head", &trimmed.head),
+        ("title", &trimmed.title),
+    ] {
+        if value.is_empty() {
+            return Err(format!("E_GITHUB_PR_INVALID: {} is required", field));
+        }
+    }
+    if trimmed.base == trimmed.head {
+        return Err("E_GITHUB_PR_INVALID: head and base are the same branch".to_string());
+    }
+    Ok(trimmed)
+}
+
+/// CI status of `sha` in `slug`.
+pub async fn commit_status(
+    access_token: &str,
+    slug: &GitHubRepoSlug,
+    sha: &str,
+) -> Result<CommitCiStatus, String> {
+    if access_token.trim().is_empty() {
+        return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
+    }
+    let commit_url = format!(
+        "{}/repos/{}/{}/commits/{}",
+        api_base(Some(&slug.host))?,
+        slug.owner,
+        slug.name,
+        sha
+    );
+    let checks = fetch_ci_checks(&commit_url, access_token)
+        .await
+        .map_err(|error| format!("E_GITHUB_STATUS_FETCH: {}", error))?;
+    Ok(CommitCiStatus {
+        sha: sha.to_string(),
+        state: summarize_ci_state(&checks).to_string(),
+        checks,
+    })
+}
+
+/// Full commit SHA of `reference` (branch, tag or revision) in the local repository.
+pub fn resolve_commit(path: &str, reference: &str) -> Result<String, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    repo.revparse_single(reference.trim())
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id().to_string())
+        .map_err(|e| format!("E_GITHUB_STATUS_REF_NOT_FOUND: '{}': {}", reference, e))
+}
+
+/// Summary state for a pull request head; lookups that fail report
+/// `unknown` rather than failing the whole listing.
+async fn ci_state(commit_url: &str, token: &str) -> String {
+    match fetch_ci_checks(commit_url, token).await {
+        Ok(checks) => summarize_ci_state(&checks).to_string(),
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+async fn fetch_ci_checks(commit_url: &str, token: &str) -> Result<Vec<CiCheck>, reqwest::Error> {
+    let status = async {
+        github_client::send(
+            api_request(Method::GET, &format!("{}/status", commit_url), token)
+                .query(&[("per_page", "100")]),
+        )
+        .await?
+        .error_for_status()?
+        .json::<CombinedStatusResponse>()
+        .await
+    };
+    let check_runs = async {
+        github_client::send(
+            api_request(Method::GET, &format!("{}/check-runs", commit_url), token)
+                .query(&[("per_page", "100")]),
+        )
+        .await?
+        .error_for_status()?
+        .json::<CheckRunsResponse>()
+        .await
+    };
+    let (status, check_runs) = tokio::join!(status, check_runs);
+    let mut checks: Vec<CiCheck> = status?.statuses.into_iter().map(status_check).collect();
+    checks.extend(check_runs?.check_runs.into_iter().map(check_run_check));
+    Ok(checks)
+}
+
+fn status_check(status: StatusResponse) -> CiCheck {
+    CiCheck {
+        name: status.context,
+        kind: "status".to_string(),
+        state: match status.state.as_str() {
+            "success" => "success",
+            "pending" => "pending",
+            _ => "failure",
+        }
+        .to_string(),
+        description: status.description,
+        url: status.target_url,
+    }
+}
+
+fn check_run_check(run: CheckRun) -> CiCheck {
+    CiCheck {
+        name: run.name,
+        kind: "check_run".to_string(),
+        state: match (run.status.as_str(), run.conclusion.as_deref()) {
+            ("completed", Some("success" | "neutral" | "skipped")) => "success",
+            ("completed", _) => "failure",
+            _ => "pending",
+        }
+        .to_string(),
+        description: run.output.and_then(|output| output.title),
+        url: run.html_url,
+    }
+}
+
+fn summarize_ci_state(checks: &[CiCheck]) -> &'static str {
+    ["failure", "pending", "success"]
+        .into_iter()
+        .find(|state| checks.iter().any(|check| check.state == *state))
+        .unwrap_or("none")
+}
+
+fn repo_slug(url: &str, is_api_host: impl Fn(&str) -> bool) -> Option<GitHubRepoSlug> {
+    let base = web_base_url(url)?;
+    let (host, path) = base.strip_prefix("https://")?.split_once('/')?;
+    if !is_api_host(host) {
+        return None;
+    }
+    let (owner, name) = path.split_once('/')?;
+    if owner.is_empty() || name.is_empty() || name.contains('/') {
+        return None;
+    }
+    Some(GitHubRepoSlug {
+        host: host.to_string(),
+        owner: owner.to_string(),
+        name: name.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slug_is_parsed_from_github_remotes() {
+        let known = |host: &str| host == "github.com" || host == "code.corp.example";
+        let slug = repo_slug("git@github.com:octo/app.git", known).unwrap();
+        assert_eq!((slug.owner.as_str(), slug.name.as_str()), ("octo", "app"));
+        assert_eq!(
+            repo_slug("https://code.corp.example/team/tool", known).map(|slug| slug.host),
+            Some("code.corp.example".to_string())
+        );
+        // hosts without a GitHub sign-in are not guessed from their name
+        assert_eq!(
+            repo_slug("https://github.example.com/team/tool", known),
+            None
+        );
+        assert_eq!(repo_slug("https://gitlab.com/octo/app.git", known), None);
+        assert_eq!(repo_slug("https://github.com/octo", known), None);
+    }
+
+    #[test]
+    fn head_must_be_pushed_unless_push_is_requested() {
+        use std::process::Command;
+        let git = |args: &[&str], cwd: &std::path::Path| {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+        };
+        let base = std::env::temp_dir().join(format!("gitlite-pr-head-{}", uuid::Uuid::new_v4()));
+        let (local, remote) = (base.join("local"), base.join("remote"));
+        std::fs::create_dir_all(&local).unwrap();
+        std::fs::create_dir_all(&remote).unwrap();
+        git(&["init", "--bare"], &remote);
+        git(&["init", "-b", "master"], &local);
+        git(&["config", "user.name", "Test User"], &local);
+        git(&["config", "user.email", "test@example.com"], &local);
+        git(&["commit", "--allow-empty", "-m", "base"], &local);
+        git(
+            &["remote", "add", "origin", remote.to_str().unwrap()],
+            &local,
+        );
+        git(&["checkout", "-b", "feature"], &local);
+        git(&["commit", "--allow-empty", "-m", "work"], &local);
+        let path = local.to_str().unwrap();
+
+        assert!(ensure_head_pushed(path, "missing", true)
+            .unwrap_err()
+            .starts_with("E_GITHUB_PR_HEAD_NOT_FOUND"));
+        assert!(ensure_head_pushed(path, "feature", false)
+            .unwrap_err()
+            .starts_with("E_GITHUB_PR_HEAD_NOT_PUSHED"));
+        git(&["checkout", "master"], &local);
+        ensure_head_pushed(path, "feature", true).unwrap();
+        ensure_head_pushed(path, "feature", false).unwrap();
+        ensure_head_pushed(path, "someone:feature", false).unwrap();
+
+        // behind origin: everything local is already published
+        git(&["branch", "-f", "feature", "feature~1"], &local);
+        ensure_head_pushed(path, "feature", false).unwrap();
+        git(&["checkout", "feature"], &local);
+        git(&["commit", "--allow-empty", "-m", "diverged"], &local);
+        assert!(ensure_head_pushed(path, "feature", false)
+            .unwrap_err()
+            .contains("has commits origin does not have"));
+
+        std::fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    fn ci_state_prefers_failure_then_pending() {
+        let run = |status: &str, conclusion: Option<&str>| {
+            check_run_check(CheckRun {
+                name: "build".to_string(),
+                status: status.to_string(),
+                conclusion: conclusion.map(str::to_string),
+                html_url: None,
+                output: None,
+            })
+        };
+        assert_eq!(summarize_ci_state(&[]), "none");
+        assert_eq!(
+            summarize_ci_state(&[run("completed", Some("skipped"))]),
+            "success"
+        );
+        assert_eq!(
+            summarize_ci_state(&[run("in_progress", None), run("completed", Some("success"))]),
+            "pending"
+        );
+        let errored = status_check(StatusResponse {
+            context: "ci/legacy".to_string(),
+            state: "error".to_string(),
+            description: Some("crashed".to_string()),
+            target_url: None,
+        });
+        assert_eq!(errored.state, "failure");
+        assert_eq!(
+            summarize_ci_state(&[errored, run("queued", None)]),
+            "failure"
+        );
+    }
+}
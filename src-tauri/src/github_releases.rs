@@ -0,0 +1,204 @@
+use crate::github_auth::{api_base, api_request};
+use crate::github_client;
+use crate::github_pulls::GitHubRepoSlug;
+use git2::Repository;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+/// Fields of a new release. `tag` may be a local tag or a new one, which
+/// GitHub creates at `target_commitish`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct NewRelease {
+    pub tag: String,
+    /// Branch or commit SHA a new tag is created at; the repository's
+    /// default branch when empty. Ignored when `tag` exists locally.
+    #[serde(default)]
+    pub target_commitish: String,
+    /// Release name; the tag when empty.
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    /// Let GitHub write notes from the merged pull requests since the
+    /// previous release; `notes` is placed above them.
+    #[serde(default)]
+    pub generate_notes: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CreatedRelease {
+    pub id: u64,
+    pub tag: String,
+    pub url: String,
+    pub draft: bool,
+}
+
+#[derive(Deserialize)]
+struct ReleaseResponse {
+    id: u64,
+    tag_name: String,
+    html_url: String,
+    draft: bool,
+}
+
+/// What the release targets: the commit the local tag `request.tag` points
+/// at, peeling annotated tags, else `request.target_commitish`. `None`
+/// leaves the choice to GitHub, which uses the default branch.
+pub fn release_target(path: &str, request: &NewRelease) -> Result<Option<String>, String> {
+    let tag = request.tag.trim();
+    if tag.is_empty() {
+        return Err("E_GITHUB_RELEASE_INVALID: tag is required".to_string());
+    }
+    if let Some(commit) = local_tag_commit(path, tag)? {
+        return Ok(Some(commit));
+    }
+    let target = request.target_commitish.trim();
+    Ok((!target.is_empty()).then(|| target.to_string()))
+}
+
+/// Commit behind the local tag `tag`, if it exists.
+pub fn local_tag_commit(path: &str, tag: &str) -> Result<Option<String>, String> {
+    let repo = Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let commit = repo
+        .find_reference(&format!("refs/tags/{}", tag.trim()))
+        .and_then(|reference| reference.peel_to_commit())
+        .ok()
+        .map(|commit| commit.id().to_string());
+    Ok(commit)
+}
+
+/// Publish a release for `request.tag` at `target`. When the tag is not on
+/// GitHub yet, GitHub creates it as a lightweight tag at `target`.
+pub async fn create_release(
+    access_token: &str,
+    slug: &GitHubRepoSlug,
+    request: &NewRelease,
+    target: Option<&str>,
+) -> Result<CreatedRelease, String> {
+    if access_token.trim().is_empty() {
+        return Err("E_GITHUB_TOKEN_EMPTY: Access token is required".to_string());
+    }
+    let url = format!(
+        "{}/repos/{}/{}/releases",
+        api_base(Some(&slug.host))?,
+        slug.owner,
+        slug.name
+    );
+    let response = github_client::send(
+        api_request(Method::POST, &url, access_token).json(&release_body(request, target)),
+    )
+    .await
+    .map_err(|error| format!("E_GITHUB_RELEASE_CREATE: {}", error))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unable to read response body".to_string());
+        return Err(format!(
+            "E_GITHUB_RELEASE_CREATE: GitHub returned {} ({})",
+            status.as_u16(),
+            body
+        ));
+    }
+    let created: ReleaseResponse = response
+        .json()
+        .await
+        .map_err(|error| format!("E_GITHUB_RELEASE_PARSE: {}", error))?;
+    Ok(CreatedRelease {
+        id: created.id,
+        tag: created.tag_name,
+        url: created.html_url,
+        draft: created.draft,
+    })
+}
+
+fn release_body(request: &NewRelease, target: Option<&str>) -> serde_json::Value {
+    let tag = request.tag.trim();
+    let title = request.title.trim();
+    let mut body = serde_json::json!({
+        "tag_name": tag,
+        "name": if title.is_empty() { tag } else { title },
+        "body": request.notes,
+        "draft": request.draft,
+        "prerelease": request.prerelease,
+        "generate_release_notes": request.generate_notes,
+    });
+    if let Some(target) = target {
+        body["target_commitish"] = target.into();
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn releases_target_local_tags_or_the_requested_commitish() {
+        let dir = std::env::temp_dir().join(format!("gitlite-release-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for args in [
+            &["init"][..],
+            &["config", "user.name", "Test User"],
+            &["config", "user.email", "test@example.com"],
+            &["commit", "--allow-empty", "-m", "v1"],
+            &["tag", "-a", "v1.0.0", "-m", "first release"],
+        ] {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(&dir)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+        }
+        let path = dir.to_str().unwrap();
+        let head = Repository::open(&dir)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap()
+            .to_string();
+
+        let mut request = NewRelease {
+            tag: " v1.0.0 ".to_string(),
+            target_commitish: "main".to_string(),
+            title: String::new(),
+            notes: "Highlights".to_string(),
+            draft: true,
+            prerelease: false,
+            generate_notes: true,
+        };
+        let target = release_target(path, &request).unwrap();
+        assert_eq!(target.as_deref(), Some(head.as_str()));
+        let body = release_body(&request, target.as_deref());
+        assert_eq!(body["name"], "v1.0.0");
+        assert_eq!(body["target_commitish"], head.as_str());
+        assert_eq!(body["generate_release_notes"], true);
+
+        request.tag = "v2.0.0".to_string();
+        assert_eq!(local_tag_commit(path, "v2.0.0").unwrap(), None);
+        assert_eq!(
+            release_target(path, &request).unwrap().as_deref(),
+            Some("main")
+        );
+        request.target_commitish = " ".to_string();
+        assert_eq!(release_target(path, &request).unwrap(), None);
+        assert!(release_body(&request, None)
+            .get("target_commitish")
+            .is_none());
+        request.tag = "  ".to_string();
+        assert!(release_target(path, &request)
+            .unwrap_err()
+            .starts_with("E_GITHUB_RELEASE_INVALID"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
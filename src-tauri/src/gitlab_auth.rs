@@ -0,0 +1,407 @@
+use crate::credentials::{KeychainCodes, KeychainItem};
+use crate::json_store::unix_now;
+use crate::oauth::{
+    self, AccessTokenResponse, DeviceCode, Endpoints, FlowCodes, DEVICE_GRANT_TYPE,
+};
+use reqwest::header::{ACCEPT, AUTHORIZATION, USER_AGENT};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_HOST: &str = "gitlab.com";
+const OAUTH_SCOPE: &str = "read_user write_repository";
+const APP_USER_AGENT: &str = "GitLite/0.1.0";
+const KEYCHAIN_SERVICE: &str = "com.gitlite.app.gitlab";
+const KEYCHAIN_CODES: KeychainCodes = KeychainCodes {
+    init: "E_GITLAB_KEYCHAIN_INIT",
+    read: "E_GITLAB_KEYCHAIN_READ",
+    write: "E_GITLAB_KEYCHAIN_WRITE",
+    delete: "E_GITLAB_KEYCHAIN_DELETE",
+};
+const FLOW_CODES: FlowCodes = FlowCodes {
+    forge: "GitLab",
+    client_id_missing: "E_GITLAB_CLIENT_ID_MISSING: GitLab OAuth application ID is required",
+    device_code_empty: "E_GITLAB_OAUTH_DEVICE_CODE_EMPTY",
+    network: "E_GITLAB_OAUTH_NETWORK",
+    start_failed: "E_GITLAB_OAUTH_START_FAILED",
+    parse: "E_GITLAB_OAUTH_PARSE",
+    poll_invalid: "E_GITLAB_OAUTH_POLL_INVALID",
+    poll_error: "E_GITLAB_OAUTH_POLL_ERROR",
+};
+const REFRESH_KEYCHAIN_ACCOUNT: &str = "refresh-token";
+/// Refresh this long before the reported expiry, so a token does not run
+/// out halfway through a push.
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// Endpoints of gitlab.com or a self-managed instance.
+fn endpoints_for(host: Option<&str>) -> Result<Endpoints, String> {
+    let host = crate::oauth::resolve_host(host, DEFAULT_HOST, "E_GITLAB_HOST_INVALID")?;
+    Ok(Endpoints {
+        device_code_url: format!("https://{}/oauth/authorize_device", host),
+        access_token_url: format!("https://{}/oauth/token", host),
+        api_base: format!("https://{}/api/v4", host),
+        host,
+    })
+}
+
+#[derive(Serialize, Clone)]
+pub struct GitLabUser {
+    pub username: String,
+    pub avatar_url: String,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct GitLabAuthPollResult {
+    pub status: String,
+    pub access_token: Option<String>,
+    pub refresh_token: Option<String>,
+    /// Seconds until `access_token` expires.
+    pub expires_in: Option<u64>,
+    pub scope: Option<String>,
+    pub user: Option<GitLabUser>,
+    pub retry_after: Option<u64>,
+}
+
+impl GitLabAuthPollResult {
+    fn waiting(status: &str, retry_after: Option<u64>) -> Self {
+        GitLabAuthPollResult {
+            status: status.to_string(),
+            access_token: None,
+            refresh_token: None,
+            expires_in: None,
+            scope: None,
+            user: None,
+            retry_after,
+        }
+    }
+}
+
+/// How to renew an OAuth access token once it expires.
+#[derive(Deserialize)]
+pub struct GitLabRefresh {
+    pub refresh_token: String,
+    pub client_id: String,
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredRefresh {
+    refresh_token: String,
+    client_id: String,
+    /// Unix seconds at which the access token expires.
+    expires_at: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct GitLabUserResponse {
+    username: String,
+    avatar_url: Option<String>,
+    name: Option<String>,
+}
+
+pub async fn start_device_flow(client_id: &str, host: Option<&str>) -> Result<DeviceCode, String> {
+    let endpoints = endpoints_for(host)?;
+    let normalized_client_id = oauth::normalize_client_id(client_id, FLOW_CODES.client_id_missing)?;
+    let response = reqwest::Client::new()
+        .post(&endpoints.device_code_url)
+        .header(ACCEPT, "application/json")
+        .header(USER_AGENT, APP_USER_AGENT)
+        .form(&[
+            ("client_id", normalized_client_id.as_str()),
+            ("scope", OAUTH_SCOPE),
+        ])
+        .send()
+        .await;
+    oauth::device_code(response, &FLOW_CODES).await
+}
+
+pub async fn poll_device_flow(
+    client_id: &str,
+    device_code: &str,
+    host: Option<&str>,
+) -> Result<GitLabAuthPollResult, String> {
+    let endpoints = endpoints_for(host)?;
+    let normalized_client_id = oauth::normalize_client_id(client_id, FLOW_CODES.client_id_missing)?;
+    let normalized_device_code = oauth::normalize_device_code(device_code, &FLOW_CODES)?;
+
+    let client = reqwest::Client::new();
+    let payload = request_token(
+        &client,
+        &endpoints,
+        &[
+            ("client_id", normalized_client_id.as_str()),
+            ("device_code", normalized_device_code),
+            ("grant_type", DEVICE_GRANT_TYPE),
+        ],
+        "E_GITLAB_OAUTH_POLL_FAILED",
+    )
+    .await?;
+
+    if let Some(access_token) = payload.access_token {
+        let user = parse_user(request_user(&client, &endpoints, &access_token).await?).await?;
+        return Ok(GitLabAuthPollResult {
+            status: "success".to_string(),
+            access_token: Some(access_token),
+            refresh_token: payload.refresh_token,
+            expires_in: payload.expires_in,
+            scope: payload.scope,
+            user: Some(user),
+            retry_after: None,
+        });
+    }
+
+    let (status, retry_after) = oauth::waiting_status(&payload, &FLOW_CODES)?;
+    Ok(GitLabAuthPollResult::waiting(status, retry_after))
+}
+
+/// Profile for an OAuth token or a personal access token. When GitLab
+/// rejects the saved OAuth token it is refreshed and the lookup retried.
+pub async fn fetch_user(access_token: &str, host: Option<&str>) -> Result<GitLabUser, String> {
+    let endpoints = endpoints_for(host)?;
+    let token = access_token.trim();
+    if token.is_empty() {
+        return Err("E_GITLAB_TOKEN_EMPTY: Access token is required".to_string());
+    }
+    let client = reqwest::Client::new();
+    let response = request_user(&client, &endpoints, token).await?;
+    let saved = load_token_from_keychain(host).ok().flatten();
+    if response.status() == StatusCode::UNAUTHORIZED && saved.as_deref() == Some(token) {
+        if let Some(refreshed) = refresh_access_token(host).await? {
+            return parse_user(request_user(&client, &endpoints, &refreshed).await?).await;
+        }
+    }
+    parse_user(response).await
+}
+
+/// Exchange the saved refresh token for a new access token and store both.
+/// `None` when no refresh token is saved, e.g. for personal access tokens.
+pub async fn refresh_access_token(host: Option<&str>) -> Result<Option<String>, String> {
+    let endpoints = endpoints_for(host)?;
+    let Some(stored) = load_refresh(host)? else {
+        return Ok(None);
+    };
+
+    let client = reqwest::Client::new();
+    let payload = request_token(
+        &client,
+        &endpoints,
+        &[
+            ("client_id", stored.client_id.as_str()),
+            ("refresh_token", stored.refresh_token.as_str()),
+            ("grant_type", "refresh_token"),
+        ],
+        "E_GITLAB_OAUTH_REFRESH_FAILED",
+    )
+    .await?;
+    let Some(access_token) = payload.access_token else {
+        return Err(format!(
+            "E_GITLAB_OAUTH_REFRESH_FAILED: {} ({})",
+            payload.error.unwrap_or_default(),
+            payload.error_description.unwrap_or_default()
+        ));
+    };
+
+    // GitLab rotates refresh tokens; keep the old one if none came back.
+    let refresh = GitLabRefresh {
+        refresh_token: payload.refresh_token.unwrap_or(stored.refresh_token),
+        client_id: stored.client_id,
+        expires_in: payload.expires_in,
+    };
+    save_token_to_keychain(&access_token, Some(&refresh), host)?;
+    Ok(Some(access_token))
+}
+
+/// `("oauth2", token)` for an HTTPS remote on a host with a saved GitLab
+/// token, for git credential callbacks. The token is refreshed first when it
+/// has expired or the server rejected it (`rejected`). Lookup failures yield
+/// `None`.
+pub fn find_for_url(url: &str, rejected: bool) -> Option<(String, String)> {
+    let host = crate::credentials::normalize_host(url).ok()?;
+    let token = load_token_from_keychain(Some(&host)).ok().flatten()?;
+    let stored = load_refresh(Some(&host)).ok().flatten();
+    let token = if needs_refresh(stored.as_ref(), rejected, unix_now()) {
+        tauri::async_runtime::block_on(refresh_access_token(Some(&host)))
+            .ok()
+            .flatten()
+            .unwrap_or(token)
+    } else {
+        token
+    };
+    Some(("oauth2".to_string(), token))
+}
+
+/// Save `access_token`, with what is needed to renew it for OAuth tokens.
+/// Without `refresh` (personal access tokens) any stale refresh token is
+/// removed.
+pub fn save_token_to_keychain(
+    access_token: &str,
+    refresh: Option<&GitLabRefresh>,
+    host: Option<&str>,
+) -> Result<(), String> {
+    let token = access_token.trim();
+    if token.is_empty() {
+        return Err("E_GITLAB_TOKEN_EMPTY: Access token is required".to_string());
+    }
+    let refresh_item = refresh_keychain_item(host)?;
+    let Some(refresh) = refresh else {
+        keychain_item(host)?.save(token)?;
+        return refresh_item.delete();
+    };
+
+    let refresh_token = refresh.refresh_token.trim();
+    if refresh_token.is_empty() {
+        return Err("E_GITLAB_TOKEN_EMPTY: Refresh token is required".to_string());
+    }
+    let stored = StoredRefresh {
+        refresh_token: refresh_token.to_string(),
+        client_id: oauth::normalize_client_id(&refresh.client_id, FLOW_CODES.client_id_missing)?,
+        expires_at: refresh
+            .expires_in
+            .map(|seconds| unix_now().saturating_add(seconds as i64)),
+    };
+    let secret = serde_json::to_string(&stored)
+        .map_err(|error| format!("E_GITLAB_KEYCHAIN_WRITE: {}", error))?;
+    keychain_item(host)?.save(token)?;
+    refresh_item.save(&secret)
+}
+
+pub fn load_token_from_keychain(host: Option<&str>) -> Result<Option<String>, String> {
+    Ok(keychain_item(host)?
+        .load()?
+        .filter(|token| !token.trim().is_empty()))
+}
+
+pub fn delete_token_from_keychain(host: Option<&str>) -> Result<(), String> {
+    keychain_item(host)?.delete()?;
+    refresh_keychain_item(host)?.delete()
+}
+
+fn load_refresh(host: Option<&str>) -> Result<Option<StoredRefresh>, String> {
+    refresh_keychain_item(host)?
+        .load()?
+        .map(|secret| serde_json::from_str(&secret))
+        .transpose()
+        .map_err(|error| format!("E_GITLAB_KEYCHAIN_READ: {}", error))
+}
+
+/// Whether to refresh before handing the token to git: it was just
+/// rejected, or expires within `EXPIRY_MARGIN_SECS`. Tokens without a
+/// refresh token cannot be renewed.
+fn needs_refresh(stored: Option<&StoredRefresh>, rejected: bool, now: i64) -> bool {
+    stored.is_some_and(|stored| {
+        rejected
+            || stored
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= now + EXPIRY_MARGIN_SECS)
+    })
+}
+
+fn keychain_item(host: Option<&str>) -> Result<KeychainItem, String> {
+    let account = endpoints_for(host)?.keychain_account();
+    KeychainItem::new(KEYCHAIN_SERVICE, &account, &KEYCHAIN_CODES)
+}
+
+fn refresh_keychain_item(host: Option<&str>) -> Result<KeychainItem, String> {
+    let account = format!("{}@{}", REFRESH_KEYCHAIN_ACCOUNT, endpoints_for(host)?.host);
+    KeychainItem::new(KEYCHAIN_SERVICE, &account, &KEYCHAIN_CODES)
+}
+
+async fn request_token(
+    client: &reqwest::Client,
+    endpoints: &Endpoints,
+    form: &[(&str, &str)],
+    failure_code: &str,
+) -> Result<AccessTokenResponse, String> {
+    let response = client
+        .post(&endpoints.access_token_url)
+        .header(ACCEPT, "application/json")
+        .header(USER_AGENT, APP_USER_AGENT)
+        .form(form)
+        .send()
+        .await;
+    oauth::token_response(response, failure_code, &FLOW_CODES).await
+}
+
+async fn request_user(
+    client: &reqwest::Client,
+    endpoints: &Endpoints,
+    access_token: &str,
+) -> Result<reqwest::Response, String> {
+    client
+        .get(endpoints.user_url())
+        .header(ACCEPT, "application/json")
+        .header(USER_AGENT, APP_USER_AGENT)
+        .header(AUTHORIZATION, format!("Bearer {}", access_token))
+        .send()
+        .await
+        .map_err(|error| format!("E_GITLAB_USER_FETCH: {}", error))
+}
+
+async fn parse_user(response: reqwest::Response) -> Result<GitLabUser, String> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unable to read response body".to_string());
+        return Err(format!(
+            "E_GITLAB_USER_FETCH: GitLab returned {} ({})",
+            status.as_u16(),
+            body
+        ));
+    }
+
+    let payload: GitLabUserResponse = response
+        .json()
+        .await
+        .map_err(|error| format!("E_GITLAB_USER_PARSE: {}", error))?;
+
+    Ok(GitLabUser {
+        username: payload.username,
+        avatar_url: payload.avatar_url.unwrap_or_default(),
+        name: payload.name,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_managed_hosts_get_their_own_endpoints() {
+        let public = endpoints_for(None).unwrap();
+        assert_eq!(public.user_url(), "https://gitlab.com/api/v4/user");
+        assert_eq!(
+            public.device_code_url,
+            "https://gitlab.com/oauth/authorize_device"
+        );
+        assert_eq!(public.keychain_account(), "oauth-token@gitlab.com");
+
+        let internal = endpoints_for(Some("https://GitLab.corp.example/group")).unwrap();
+        assert_eq!(
+            internal.access_token_url,
+            "https://gitlab.corp.example/oauth/token"
+        );
+        assert!(endpoints_for(Some("https:///"))
+            .unwrap_err()
+            .starts_with("E_GITLAB_HOST_INVALID"));
+    }
+
+    #[test]
+    fn oauth_tokens_are_refreshed_when_rejected_or_about_to_expire() {
+        let stored = |expires_at| StoredRefresh {
+            refresh_token: "refresh".to_string(),
+            client_id: "app".to_string(),
+            expires_at,
+        };
+        let now = 1_000_000;
+
+        assert!(!needs_refresh(Some(&stored(Some(now + 3600))), false, now));
+        assert!(needs_refresh(Some(&stored(Some(now + 30))), false, now));
+        assert!(needs_refresh(Some(&stored(Some(now - 1))), false, now));
+        assert!(!needs_refresh(Some(&stored(None)), false, now));
+        assert!(needs_refresh(Some(&stored(None)), true, now));
+        // personal access tokens have nothing to refresh with
+        assert!(!needs_refresh(None, true, now));
+    }
+}
@@ -0,0 +1,439 @@
+use crate::git::{self, CheckoutProgress, SubmoduleProgress, TransferProgress};
+use crate::json_store::unix_now;
+use crate::{perf, shutdown};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+use tauri::Emitter;
+use tokio::sync::Semaphore;
+
+/// Event emitted on every job state or progress change (payload `JobInfo`).
+pub const JOB_PROGRESS_EVENT: &str = "job://progress";
+const MAX_CONCURRENT_JOBS: usize = 2;
+/// Finished jobs kept for `list_jobs`; older ones are dropped.
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// Long-running operation to run in the background.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobRequest {
+    Fetch {
+        path: String,
+        remote_name: String,
+        #[serde(default)]
+        username: String,
+        #[serde(default)]
+        password: String,
+        #[serde(default, flatten)]
+        scope: git::FetchScope,
+    },
+    Push {
+        path: String,
+        remote_name: String,
+        #[serde(default)]
+        username: String,
+        #[serde(default)]
+        password: String,
+    },
+    Clone {
+        url: String,
+        dest: String,
+        branch: Option<String>,
+        /// Initialize and update submodules, nested ones included.
+        #[serde(default)]
+        recurse_submodules: bool,
+        /// Repository `url` was forked from; added as `upstream` after the
+        /// clone (see `configure_fork_remotes`).
+        #[serde(default)]
+        upstream_url: Option<String>,
+    },
+}
+
+impl JobRequest {
+    fn kind(&self) -> &'static str {
+        match self {
+            JobRequest::Fetch { .. } => "fetch",
+            JobRequest::Push { .. } => "push",
+            JobRequest::Clone { .. } => "clone",
+        }
+    }
+
+    fn path(&self) -> &str {
+        match self {
+            JobRequest::Fetch { path, .. } | JobRequest::Push { path, .. } => path,
+            JobRequest::Clone { dest, .. } => dest,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: String,
+    pub state: String, // "queued" | "running" | "succeeded" | "failed" | "cancelled"
+    pub progress: Option<TransferProgress>,
+    /// Working-tree checkout progress (clone jobs only).
+    pub checkout_progress: Option<CheckoutProgress>,
+    /// Submodule being updated (recursive clone jobs only).
+    pub submodule_progress: Option<SubmoduleProgress>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Background job queue kept in Tauri managed state. At most
+/// `MAX_CONCURRENT_JOBS` run at once; the rest wait in `queued`.
+#[derive(Clone)]
+pub struct JobManager {
+    jobs: Arc<Mutex<HashMap<String, JobEntry>>>,
+    slots: Arc<Semaphore>,
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        JobManager {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            slots: Arc::new(Semaphore::new(MAX_CONCURRENT_JOBS)),
+        }
+    }
+}
+
+impl JobManager {
+    /// Queue `request` and return its job id immediately.
+    pub fn enqueue(&self, app: &tauri::AppHandle, request: JobRequest) -> String {
+        let app = app.clone();
+        self.enqueue_with(request, move |info| {
+            let _ = app.emit(JOB_PROGRESS_EVENT, info);
+        })
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self
+            .lock_jobs()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect();
+        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(a.id.cmp(&b.id)));
+        jobs
+    }
+
+    /// Request cancellation. Queued jobs never start; running transfers stop
+    /// at their next progress callback.
+    pub fn cancel(&self, id: &str) -> Result<JobInfo, String> {
+        let jobs = self.lock_jobs();
+        let entry = jobs
+            .get(id)
+            .ok_or_else(|| format!("E_JOB_NOT_FOUND: {}", id))?;
+        if entry.info.finished_at.is_some() {
+            return Err(format!(
+                "E_JOB_FINISHED: job {} already {}",
+                id, entry.info.state
+            ));
+        }
+        entry.cancel.store(true, Ordering::SeqCst);
+        Ok(entry.info.clone())
+    }
+
+    /// Cancel every unfinished job, e.g. on shutdown. Returns how many were
+    /// still queued or running.
+    pub fn cancel_all(&self) -> usize {
+        let jobs = self.lock_jobs();
+        let pending: Vec<_> = jobs
+            .values()
+            .filter(|entry| entry.info.finished_at.is_none())
+            .collect();
+        for entry in &pending {
+            entry.cancel.store(true, Ordering::SeqCst);
+        }
+        pending.len()
+    }
+
+    fn enqueue_with(
+        &self,
+        request: JobRequest,
+        emit: impl Fn(&JobInfo) + Send + Sync + 'static,
+    ) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let info = JobInfo {
+            id: id.clone(),
+            kind: request.kind().to_string(),
+            state: "queued".to_string(),
+            progress: None,
+            checkout_progress: None,
+            submodule_progress: None,
+            error: None,
+            created_at: unix_now(),
+            finished_at: None,
+        };
+        {
+            let mut jobs = self.lock_jobs();
+            prune_finished(&mut jobs);
+            jobs.insert(
+                id.clone(),
+                JobEntry {
+                    info: info.clone(),
+                    cancel: cancel.clone(),
+                },
+            );
+        }
+        emit(&info);
+
+        let manager = self.clone();
+        let job_id = id.clone();
+        let queued_at = Instant::now();
+        tauri::async_runtime::spawn(async move {
+            let _permit = manager.slots.clone().acquire_owned().await;
+            let queued = queued_at.elapsed();
+            let emit = Arc::new(emit);
+
+            if cancel.load(Ordering::SeqCst) {
+                manager.finish(&job_id, Err(cancelled_error()), &*emit);
+                return;
+            }
+            manager.update(&job_id, &*emit, |info| info.state = "running".to_string());
+
+            let worker = manager.clone();
+            let worker_emit = emit.clone();
+            let worker_id = job_id.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                let _operation = shutdown::track(request.kind(), request.path())?;
+                let operation = format!("{}_job", request.kind());
+                perf::time(&operation, queued, || {
+                    run_request(
+                        &request,
+                        &cancel,
+                        |progress| {
+                            worker.update(&worker_id, &*worker_emit, |info| {
+                                info.progress = Some(progress.clone())
+                            });
+                        },
+                        |progress| {
+                            worker.update(&worker_id, &*worker_emit, |info| {
+                                info.checkout_progress = Some(progress.clone())
+                            });
+                        },
+                        |progress| {
+                            worker.update(&worker_id, &*worker_emit, |info| {
+                                info.submodule_progress = Some(progress.clone())
+                            });
+                        },
+                    )
+                })
+            })
+            .await
+            .unwrap_or_else(|error| Err(format!("E_JOB_FAILED: {}", error)));
+
+            manager.finish(&job_id, result, &*emit);
+        });
+
+        id
+    }
+
+    fn update(&self, id: &str, emit: &dyn Fn(&JobInfo), change: impl FnOnce(&mut JobInfo)) {
+        let info = {
+            let mut jobs = self.lock_jobs();
+            let Some(entry) = jobs.get_mut(id) else {
+                return;
+            };
+            change(&mut entry.info);
+            entry.info.clone()
+        };
+        emit(&info);
+    }
+
+    fn finish(&self, id: &str, result: Result<(), String>, emit: &dyn Fn(&JobInfo)) {
+        self.update(id, emit, |info| {
+            info.finished_at = Some(unix_now());
+            match result {
+                Ok(()) => info.state = "succeeded".to_string(),
+                Err(error) => {
+                    info.state = if error.starts_with(git::E_TRANSFER_CANCELLED) {
+                        "cancelled".to_string()
+                    } else {
+                        "failed".to_string()
+                    };
+                    info.error = Some(error);
+                }
+            }
+        });
+    }
+
+    fn lock_jobs(&self) -> MutexGuard<'_, HashMap<String, JobEntry>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Run one request on the current thread. Progress hooks stop the transfer
+/// once `cancel` is set.
+fn run_request(
+    request: &JobRequest,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(&TransferProgress),
+    on_checkout: impl FnMut(&CheckoutProgress),
+    on_submodule: impl FnMut(&SubmoduleProgress),
+) -> Result<(), String> {
+    if cancel.load(Ordering::SeqCst) {
+        return Err(cancelled_error());
+    }
+    let mut hook = |progress: &TransferProgress| {
+        on_progress(progress);
+        !cancel.load(Ordering::SeqCst)
+    };
+
+    match request {
+        JobRequest::Fetch {
+            path,
+            remote_name,
+            username,
+            password,
+            scope,
+        } => git::fetch_remote_with_progress(path, remote_name, username, password, scope, hook),
+        JobRequest::Push {
+            path,
+            remote_name,
+            username,
+            password,
+        } => git::push_with_progress(path, remote_name, username, password, hook),
+        JobRequest::Clone {
+            url,
+            dest,
+            branch,
+            recurse_submodules,
+            upstream_url,
+        } => {
+            git::clone_repository(
+                url,
+                Path::new(dest),
+                branch.as_deref(),
+                *recurse_submodules,
+                &mut hook,
+                on_checkout,
+                on_submodule,
+            )?;
+            match upstream_url.as_deref().filter(|url| !url.trim().is_empty()) {
+                Some(upstream_url) => {
+                    git::configure_fork_remotes(dest, url, upstream_url).map(|_| ())
+                }
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+fn cancelled_error() -> String {
+    format!("{}: Job cancelled", git::E_TRANSFER_CANCELLED)
+}
+
+fn prune_finished(jobs: &mut HashMap<String, JobEntry>) {
+    let mut finished: Vec<(i64, String)> = jobs
+        .iter()
+        .filter_map(|(id, entry)| entry.info.finished_at.map(|at| (at, id.clone())))
+        .collect();
+    if finished.len() < MAX_FINISHED_JOBS {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() + 1 - MAX_FINISHED_JOBS) {
+        jobs.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+    use std::time::Duration;
+
+    fn run_git(args: &[&str], cwd: &Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn queued_jobs_run_and_cancel() {
+        let base_dir =
+            std::env::temp_dir().join(format!("gitlite-jobs-test-{}", uuid::Uuid::new_v4()));
+        let source = base_dir.join("source");
+        fs::create_dir_all(&source).unwrap();
+        run_git(&["init"], &source);
+        run_git(&["config", "user.name", "Test User"], &source);
+        run_git(&["config", "user.email", "test@example.com"], &source);
+        run_git(
+            &["commit", "--allow-empty", "-m", "Initial commit"],
+            &source,
+        );
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let manager = JobManager::default();
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let seen = states.clone();
+
+        let id = runtime.block_on(async {
+            manager.enqueue_with(
+                JobRequest::Clone {
+                    url: source.to_string_lossy().into_owned(),
+                    dest: base_dir.join("dest").to_string_lossy().into_owned(),
+                    branch: None,
+                    recurse_submodules: false,
+                    upstream_url: Some("https://example.com/parent.git".to_string()),
+                },
+                move |info| seen.lock().unwrap().push(info.state.clone()),
+            )
+        });
+        for _ in 0..100 {
+            if manager.list()[0].finished_at.is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let job = manager.list().into_iter().find(|job| job.id == id).unwrap();
+        assert_eq!(job.state, "succeeded", "{:?}", job.error);
+        assert!(base_dir.join("dest/.git").exists());
+        let clone = git2::Repository::open(base_dir.join("dest")).unwrap();
+        assert_eq!(
+            clone.find_remote("upstream").unwrap().url(),
+            Some("https://example.com/parent.git")
+        );
+        assert_eq!(
+            states.lock().unwrap().first().map(String::as_str),
+            Some("queued")
+        );
+        assert!(manager
+            .cancel(&id)
+            .unwrap_err()
+            .starts_with("E_JOB_FINISHED"));
+        assert!(manager
+            .cancel("missing")
+            .unwrap_err()
+            .starts_with("E_JOB_NOT_FOUND"));
+
+        let cancel = AtomicBool::new(true);
+        let request = JobRequest::Fetch {
+            path: source.to_string_lossy().into_owned(),
+            remote_name: "origin".to_string(),
+            username: String::new(),
+            password: String::new(),
+            scope: git::FetchScope::default(),
+        };
+        assert!(run_request(&request, &cancel, |_| {}, |_| {}, |_| {})
+            .unwrap_err()
+            .starts_with(git::E_TRANSFER_CANCELLED));
+
+        drop(runtime);
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+/// A JSON file in the app data dir plus the lock that serializes
+/// read-modify-write cycles on it. Every failure is reported under `code`.
+pub struct JsonStore<T> {
+    filename: &'static str,
+    code: &'static str,
+    lock: Mutex<()>,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> JsonStore<T> {
+    pub const fn new(filename: &'static str, code: &'static str) -> Self {
+        Self {
+            filename,
+            code,
+            lock: Mutex::new(()),
+            value: PhantomData,
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn path(&self, app: &tauri::AppHandle) -> Result<PathBuf, String> {
+        let app_data_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|error| format!("{}: Failed to resolve app data dir: {}", self.code, error))?;
+
+        fs::create_dir_all(&app_data_dir)
+            .map_err(|error| format!("{}: Failed to create app data dir: {}", self.code, error))?;
+
+        Ok(app_data_dir.join(self.filename))
+    }
+
+    /// Load the file; a missing file reads as the empty value.
+    pub fn read(&self, path: &Path) -> Result<T, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                serde_json::from_str(&content).map_err(|error| format!("{}: {}", self.code, error))
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+            Err(error) => Err(format!("{}: {}", self.code, error)),
+        }
+    }
+
+    pub fn write(&self, path: &Path, value: &T) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(value)
+            .map_err(|error| format!("{}: {}", self.code, error))?;
+        fs::write(path, json).map_err(|error| format!("{}: {}", self.code, error))
+    }
+}
+
+/// Seconds since the Unix epoch; 0 if the clock is before it.
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    static STORE: JsonStore<BTreeMap<String, i64>> =
+        JsonStore::new("store.json", "E_REPO_NOTES_STORE");
+
+    #[test]
+    fn missing_file_reads_as_empty_and_writes_round_trip() {
+        let dir = std::env::temp_dir().join(format!("gitlite-json-store-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("store.json");
+
+        let empty = STORE.read(&path).unwrap();
+        assert!(empty.is_empty());
+
+        let value = BTreeMap::from([("a".to_string(), 1)]);
+        STORE.write(&path, &value).unwrap();
+        assert_eq!(STORE.read(&path).unwrap(), value);
+
+        fs::write(&path, "not json").unwrap();
+        let error = STORE.read(&path).unwrap_err();
+        assert!(error.starts_with("E_REPO_NOTES_STORE: "));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,405 @@
+use crate::credential_prompt::Prompter;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use base64::Engine;
+use git2::cert::Cert;
+use git2::{CertificateCheckStatus, ErrorClass, ErrorCode, RemoteCallbacks};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::Emitter;
+
+/// How long an operation waits for the user to trust a host key.
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+const SYSTEM_KNOWN_HOSTS: &str = "/etc/ssh/ssh_known_hosts";
+
+static PROMPTER: Prompter<HostKeyRequest, bool> = Prompter::new();
+
+/// An SSH host key that is in no known_hosts file, sent to the frontend so
+/// the user can compare the fingerprint before trusting it.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct HostKeyRequest {
+    pub id: String,
+    pub host: String,
+    /// known_hosts key type, e.g. `ssh-ed25519`.
+    pub key_type: String,
+    /// `SHA256:...`, as printed by `ssh-keygen -lf`.
+    pub fingerprint: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum HostKeyStatus {
+    Known,
+    Unknown,
+    /// The host has a different key of the same type on record.
+    Mismatch,
+    Revoked,
+}
+
+/// Send prompts to the frontend as `ssh://host-key` events.
+pub fn install(app: tauri::AppHandle) {
+    PROMPTER.install(Box::new(move |request| {
+        let _ = app.emit("ssh://host-key", request);
+    }));
+}
+
+/// Answer a pending `ssh://host-key` request.
+pub fn respond(id: &str, trust: bool) -> Result<(), String> {
+    if PROMPTER.respond(id, Some(trust)) {
+        Ok(())
+    } else {
+        Err(format!(
+            "E_HOST_KEY_PROMPT_NOT_FOUND: prompt '{}' already timed out or was answered",
+            id
+        ))
+    }
+}
+
+/// Verify SSH host keys of the remote at `url` against
+/// `~/.ssh/known_hosts` and the system file. Unknown keys are offered to the
+/// user when `interactive`, and saved to `~/.ssh/known_hosts` once trusted;
+/// otherwise they are rejected. TLS certificates keep libgit2's own
+/// validation and are only noted for error messages.
+pub fn verify_host_keys(callbacks: &mut RemoteCallbacks<'_>, url: &str, interactive: bool) {
+    // libgit2 passes the bare host name; the port only shows in the URL.
+    let port = ssh_port(url);
+    callbacks.certificate_check(move |cert, host| {
+        check(cert, &known_hosts_name(host, port), interactive)
+    });
+}
+
+/// Start or stop prompting; the frontend enables this once its
+/// `ssh://host-key` listener is registered.
+pub fn listen(enabled: bool) {
+    PROMPTER.listen(enabled);
+}
+
+/// Port of an `ssh://` URL; scp-like `host:path` URLs always use 22.
+fn ssh_port(url: &str) -> u16 {
+    let Some((_, rest)) = url.split_once("://") else {
+        return 22;
+    };
+    let authority = rest.split('/').next().unwrap_or("");
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let port = match host_port.rsplit_once(']') {
+        Some((_, after)) => after.strip_prefix(':'),
+        None => host_port.split_once(':').map(|(_, port)| port),
+    };
+    port.and_then(|port| port.parse().ok()).unwrap_or(22)
+}
+
+/// How known_hosts names `host`: bare on port 22, else `[host]:port`.
+fn known_hosts_name(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
+/// The `E_SSH_HOST_KEY_*` message of an operation that failed host-key
+/// verification.
+pub fn rejection(error: &git2::Error) -> Option<String> {
+    error
+        .message()
+        .starts_with("E_SSH_HOST_KEY_")
+        .then(|| error.message().to_string())
+}
+
+fn check(
+    cert: &Cert<'_>,
+    host: &str,
+    interactive: bool,
+) -> Result<CertificateCheckStatus, git2::Error> {
+    let Some(hostkey) = cert.as_hostkey() else {
+        if let Some(x509) = cert.as_x509() {
+            crate::tls_certs::remember_peer(x509.data());
+        }
+        return Ok(CertificateCheckStatus::CertificatePassthrough);
+    };
+    let (Some(key), Some(key_type)) = (hostkey.hostkey(), hostkey.hostkey_type()) else {
+        return Ok(CertificateCheckStatus::CertificatePassthrough);
+    };
+    let key_type = key_type.name();
+    let user_file = user_known_hosts();
+    let known = [user_file.as_deref(), Some(Path::new(SYSTEM_KNOWN_HOSTS))]
+        .into_iter()
+        .flatten()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match lookup(&known, host, key_type, key) {
+        HostKeyStatus::Known => Ok(CertificateCheckStatus::CertificateOk),
+        HostKeyStatus::Revoked => Err(reject(format!(
+            "E_SSH_HOST_KEY_REVOKED: the {} key of {} is marked @revoked",
+            key_type, host
+        ))),
+        HostKeyStatus::Mismatch => Err(reject(format!(
+            "E_SSH_HOST_KEY_MISMATCH: the {} key of {} ({}) differs from known_hosts; \
+             it may have been replaced or the connection intercepted",
+            key_type,
+            host,
+            fingerprint(key)
+        ))),
+        HostKeyStatus::Unknown => {
+            let trusted = interactive
+                && PROMPTER
+                    .request(
+                        |id| HostKeyRequest {
+                            id,
+                            host: host.to_string(),
+                            key_type: key_type.to_string(),
+                            fingerprint: fingerprint(key),
+                        },
+                        PROMPT_TIMEOUT,
+                    )
+                    .unwrap_or(false);
+            if !trusted {
+                return Err(reject(format!(
+                    "E_SSH_HOST_KEY_UNKNOWN: {} key {} of {} is not trusted",
+                    key_type,
+                    fingerprint(key),
+                    host
+                )));
+            }
+            if let Some(path) = user_file {
+                append(&path, host, key_type, key).map_err(|error| {
+                    reject(format!(
+                        "E_SSH_HOST_KEY_SAVE: failed to update {}: {}",
+                        path.display(),
+                        error
+                    ))
+                })?;
+            }
+            Ok(CertificateCheckStatus::CertificateOk)
+        }
+    }
+}
+
+fn reject(message: String) -> git2::Error {
+    git2::Error::new(ErrorCode::Certificate, ErrorClass::Ssh, message)
+}
+
+fn user_known_hosts() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("known_hosts"))
+}
+
+fn lookup(known_hosts: &str, host: &str, key_type: &str, key: &[u8]) -> HostKeyStatus {
+    let mut status = HostKeyStatus::Unknown;
+    for line in known_hosts.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(mut hosts) = fields.next() else {
+            continue;
+        };
+        let marker = hosts.strip_prefix('@');
+        if marker.is_some() {
+            let Some(next) = fields.next() else {
+                continue;
+            };
+            hosts = next;
+        }
+        let (Some(entry_type), Some(entry_key)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if hosts.starts_with('#') || !hosts_match(hosts, host) {
+            continue;
+        }
+        let same_key = entry_type == key_type
+            && STANDARD
+                .decode(entry_key)
+                .is_ok_and(|decoded| decoded == key);
+        match marker {
+            Some("revoked") if same_key => return HostKeyStatus::Revoked,
+            Some(_) => {}
+            None if same_key => status = HostKeyStatus::Known,
+            None if entry_type == key_type && status == HostKeyStatus::Unknown => {
+                status = HostKeyStatus::Mismatch
+            }
+            None => {}
+        }
+    }
+    status
+}
+
+/// Whether a comma-separated known_hosts host field (plain, wildcard,
+/// negated or `|1|` hashed names) covers `host`, which is `[host]:port` off
+/// the default port.
+fn hosts_match(patterns: &str, host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    let mut matched = false;
+    for pattern in patterns.split(',') {
+        if let Some(hashed) = pattern.strip_prefix("|1|") {
+            matched |= hashed_match(hashed, &host);
+        } else if let Some(negated) = pattern.strip_prefix('!') {
+            if glob_match(negated.to_ascii_lowercase().as_bytes(), host.as_bytes()) {
+                return false;
+            }
+        } else {
+            matched |= glob_match(pattern.to_ascii_lowercase().as_bytes(), host.as_bytes());
+        }
+    }
+    matched
+}
+
+/// `salt|hash`: base64 HMAC-SHA1 of the host name keyed by the salt.
+fn hashed_match(hashed: &str, host: &str) -> bool {
+    let Some((salt, hash)) = hashed.split_once('|') else {
+        return false;
+    };
+    let (Ok(salt), Ok(hash)) = (STANDARD.decode(salt), STANDARD.decode(hash)) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha1>::new_from_slice(&salt) else {
+        return false;
+    };
+    mac.update(host.as_bytes());
+    mac.verify_slice(&hash).is_ok()
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob_match(&pattern[1..], text) || (!text.is_empty() && glob_match(pattern, &text[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob_match(&pattern[1..], &text[1..]),
+        (Some(p), Some(t)) if p == t => glob_match(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+pub(crate) fn fingerprint(key: &[u8]) -> String {
+    format!("SHA256:{}", STANDARD_NO_PAD.encode(Sha256::digest(key)))
+}
+
+fn append(path: &Path, host: &str, key_type: &str, key: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+        }
+    }
+    let needs_newline = fs::read(path)
+        .map(|contents| !contents.is_empty() && !contents.ends_with(b"\n"))
+        .unwrap_or(false);
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if needs_newline {
+        writeln!(file)?;
+    }
+    writeln!(file, "{} {} {}", host, key_type, STANDARD.encode(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashed_entry(host: &str) -> String {
+        let salt = b"0123456789abcdefghij";
+        let mut mac = Hmac::<Sha1>::new_from_slice(salt).unwrap();
+        mac.update(host.as_bytes());
+        format!(
+            "|1|{}|{}",
+            STANDARD.encode(salt),
+            STANDARD.encode(mac.finalize().into_bytes())
+        )
+    }
+
+    #[test]
+    fn host_keys_are_matched_by_plain_hashed_and_wildcard_names() {
+        let (key, other) = (b"server key".as_slice(), b"other key".as_slice());
+        let known = format!(
+            "# comment\n\
+             github.com,140.82.112.3 ssh-ed25519 {key}\n\
+             {hashed} ssh-ed25519 {key}\n\
+             *.corp.example,!bad.corp.example ssh-ed25519 {key}\n\
+             @revoked old.example ssh-ed25519 {key}\n\
+             [github.com]:2222 ssh-ed25519 {other}\n",
+            key = STANDARD.encode(key),
+            other = STANDARD.encode(other),
+            hashed = hashed_entry("hidden.example"),
+        );
+        let status = |host: &str, key_type: &str, key: &[u8]| lookup(&known, host, key_type, key);
+
+        assert_eq!(
+            status("GitHub.com", "ssh-ed25519", key),
+            HostKeyStatus::Known
+        );
+        assert_eq!(
+            status("hidden.example", "ssh-ed25519", key),
+            HostKeyStatus::Known
+        );
+        assert_eq!(
+            status("git.corp.example", "ssh-ed25519", key),
+            HostKeyStatus::Known
+        );
+        assert_eq!(
+            status("bad.corp.example", "ssh-ed25519", key),
+            HostKeyStatus::Unknown
+        );
+        assert_eq!(
+            status("github.com", "ssh-ed25519", other),
+            HostKeyStatus::Mismatch
+        );
+        assert_eq!(
+            status("github.com", "ssh-rsa", other),
+            HostKeyStatus::Unknown
+        );
+        assert_eq!(
+            status("old.example", "ssh-ed25519", key),
+            HostKeyStatus::Revoked
+        );
+        assert_eq!(
+            status("[github.com]:2222", "ssh-ed25519", other),
+            HostKeyStatus::Known
+        );
+        assert_eq!(
+            status("[github.com]:2200", "ssh-ed25519", key),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn non_default_ports_use_bracketed_names() {
+        assert_eq!(ssh_port("ssh://git@example.com:2222/team/app.git"), 2222);
+        assert_eq!(ssh_port("ssh://[::1]:2200/app.git"), 2200);
+        assert_eq!(ssh_port("ssh://git@example.com/app.git"), 22);
+        assert_eq!(ssh_port("git@example.com:team/app.git"), 22);
+        assert_eq!(known_hosts_name("example.com", 2222), "[example.com]:2222");
+        assert_eq!(known_hosts_name("example.com", 22), "example.com");
+    }
+
+    #[test]
+    fn trusted_keys_are_appended_with_their_fingerprint_format() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-known-hosts-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(".ssh").join("known_hosts");
+        append(&path, "example.com", "ssh-ed25519", b"key one").unwrap();
+        append(&path, "example.org", "ssh-rsa", b"key two").unwrap();
+        append(&path, "[example.net]:2222", "ssh-rsa", b"key three").unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            lookup(&contents, "example.org", "ssh-rsa", b"key two"),
+            HostKeyStatus::Known
+        );
+        assert_eq!(
+            lookup(&contents, "[example.net]:2222", "ssh-rsa", b"key three"),
+            HostKeyStatus::Known
+        );
+        assert!(contents.contains("\n[example.net]:2222 ssh-rsa "));
+        assert_eq!(contents.lines().count(), 3);
+        assert!(fingerprint(b"key one").starts_with("SHA256:"));
+        assert!(!fingerprint(b"key one").ends_with('='));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
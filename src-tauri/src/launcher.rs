@@ -0,0 +1,316 @@
+use crate::config;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Open `file` of the repository at `repo_path` in the editor chosen in
+/// settings, at `line` (1-based, default 1).
+pub fn open_in_editor(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    file: &str,
+    line: Option<u32>,
+) -> Result<(), String> {
+    let settings = config::read_settings(app)?;
+    let target = repo_file(repo_path, file, "E_EDITOR_PATH")?;
+    #[allow(unused_mut)]
+    let mut args = editor_command(
+        &settings.editor,
+        settings.editor_command.as_deref(),
+        &target,
+        line.unwrap_or(1).max(1),
+    )?;
+    #[cfg(windows)]
+    if settings.editor == "vscode" {
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let exe = vscode_executable(&path).ok_or_else(|| {
+            "E_EDITOR_LAUNCH: cannot find Code.exe next to code.cmd on PATH".to_string()
+        })?;
+        args[0] = exe.to_string_lossy().into_owned();
+    }
+    // Quoted by the standard library, never through cmd.exe: repository file
+    // names may contain `&`, `|` or `%`.
+    let mut command = Command::new(&args[0]);
+    command.args(&args[1..]);
+    spawn_detached(command).map_err(|e| format!("E_EDITOR_LAUNCH: cannot start {}: {}", args[0], e))
+}
+
+/// VS Code's `Code.exe` for the first `bin\code.cmd` on `path`. The batch
+/// file only forwards to it, and would run through cmd.exe.
+#[cfg(any(windows, test))]
+fn vscode_executable(path: &std::ffi::OsStr) -> Option<PathBuf> {
+    std::env::split_paths(path)
+        .find(|dir| dir.join("code.cmd").is_file())
+        .and_then(|bin| Some(bin.parent()?.join("Code.exe")))
+        .filter(|exe| exe.is_file())
+}
+
+/// Program and arguments for `editor`. Templates are split into arguments
+/// before `{file}` and `{line}` are substituted, so paths need no quoting.
+fn editor_command(
+    editor: &str,
+    template: Option<&str>,
+    file: &Path,
+    line: u32,
+) -> Result<Vec<String>, String> {
+    let file = file.to_string_lossy();
+    let file = file.trim_start_matches(r"\\?\");
+    let args = match editor {
+        "vscode" => vec![
+            "code".to_string(),
+            "--goto".to_string(),
+            format!("{}:{}", file, line),
+        ],
+        "sublime" => vec!["subl".to_string(), format!("{}:{}", file, line)],
+        _ => {
+            let template = template.filter(|template| !template.trim().is_empty());
+            let template = template.ok_or_else(|| {
+                "E_EDITOR_NOT_CONFIGURED: set an editor command template in Settings".to_string()
+            })?;
+            split_template(template)
+                .into_iter()
+                .map(|arg| {
+                    arg.replace("{file}", file)
+                        .replace("{line}", &line.to_string())
+                })
+                .collect()
+        }
+    };
+    if args.is_empty() {
+        return Err("E_EDITOR_NOT_CONFIGURED: the editor command is empty".to_string());
+    }
+    Ok(args)
+}
+
+/// Whitespace-separated words; single or double quotes group words.
+fn split_template(template: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+    for ch in template.chars() {
+        match (quote, ch) {
+            (Some(open), ch) if ch == open => quote = None,
+            (Some(_), ch) => current.push(ch),
+            (None, '"' | '\'') => {
+                quote = Some(ch);
+                in_word = true;
+            }
+            (None, ch) if ch.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, ch) => {
+                current.push(ch);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        args.push(current);
+    }
+    args
+}
+
+/// Select `file` of the repository (or the repository folder itself) in
+/// Finder, Explorer or the Linux file manager.
+pub fn reveal_in_file_manager(repo_path: &str, file: Option<&str>) -> Result<(), String> {
+    let target = repo_file(repo_path, file.unwrap_or(""), "E_REVEAL_PATH")?;
+    let mut candidates = reveal_commands(std::env::consts::OS, &target);
+    let last = candidates
+        .pop()
+        .ok_or_else(|| "E_REVEAL_FAILED: no file manager for this platform".to_string())?;
+    // Earlier candidates return quickly and report whether they worked; the
+    // fallback may block for the lifetime of the window, so it is detached.
+    for args in candidates {
+        let shown = command(&args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if shown {
+            return Ok(());
+        }
+    }
+    spawn_detached(command(&last))
+        .map_err(|e| format!("E_REVEAL_FAILED: cannot start {}: {}", last[0], e))
+}
+
+/// Commands to try in order for `os` (`std::env::consts::OS`).
+fn reveal_commands(os: &str, target: &Path) -> Vec<Vec<String>> {
+    let path = target.to_string_lossy().into_owned();
+    match os {
+        "macos" => vec![vec!["open".to_string(), "-R".to_string(), path]],
+        // Passed verbatim (see `command`): explorer wants the quotes around
+        // the path but not around the whole argument.
+        "windows" => vec![vec![
+            "explorer".to_string(),
+            format!("/select,\"{}\"", path.trim_start_matches(r"\\?\")),
+        ]],
+        _ => {
+            let folder = target.parent().unwrap_or(target).to_string_lossy();
+            vec![
+                vec![
+                    "dbus-send".to_string(),
+                    "--session".to_string(),
+                    "--print-reply".to_string(),
+                    "--dest=org.freedesktop.FileManager1".to_string(),
+                    "--type=method_call".to_string(),
+                    "/org/freedesktop/FileManager1".to_string(),
+                    "org.freedesktop.FileManager1.ShowItems".to_string(),
+                    format!("array:string:{}", file_uri(target)),
+                    "string:".to_string(),
+                ],
+                vec!["xdg-open".to_string(), folder.into_owned()],
+            ]
+        }
+    }
+}
+
+/// `file://` URI with every byte outside the unreserved set and `/`
+/// percent-encoded, so spaces, `#` and commas survive dbus-send's parsing.
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+/// Absolute path of `file` inside the repository's working tree; paths that
+/// escape it are rejected with `code`.
+fn repo_file(repo_path: &str, file: &str, code: &str) -> Result<PathBuf, String> {
+    let root = Path::new(repo_path)
+        .canonicalize()
+        .map_err(|e| format!("{}: cannot resolve {}: {}", code, repo_path, e))?;
+    let target = root
+        .join(file)
+        .canonicalize()
+        .map_err(|e| format!("{}: cannot resolve {}: {}", code, file, e))?;
+    if !target.starts_with(&root) {
+        return Err(format!("{}: {} is outside the repository", code, file));
+    }
+    Ok(target)
+}
+
+/// On Windows the arguments are passed without re-quoting, which explorer's
+/// own command-line parsing needs.
+fn command(args: &[String]) -> Command {
+    let mut command = Command::new(&args[0]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        for arg in &args[1..] {
+            command.raw_arg(arg);
+        }
+    }
+    #[cfg(not(windows))]
+    command.args(&args[1..]);
+    command
+}
+
+/// Start `command` without waiting for it; a thread reaps the process.
+fn spawn_detached(mut command: Command) -> std::io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn editor_commands_put_the_file_and_line_in_place() {
+        let file = Path::new("/repo/my dir/main.rs");
+        assert_eq!(
+            editor_command("vscode", None, file, 42).unwrap(),
+            ["code", "--goto", "/repo/my dir/main.rs:42"]
+        );
+        assert_eq!(
+            editor_command("vscode", None, Path::new(r"\\?\C:\a&b %x%\main.rs"), 42).unwrap(),
+            ["code", "--goto", r"C:\a&b %x%\main.rs:42"]
+        );
+        assert_eq!(
+            editor_command(
+                "custom",
+                Some("'/opt/My Editor/idea' --line {line} {file}"),
+                file,
+                7
+            )
+            .unwrap(),
+            ["/opt/My Editor/idea", "--line", "7", "/repo/my dir/main.rs"]
+        );
+        assert!(editor_command("custom", Some("  "), file, 1)
+            .unwrap_err()
+            .starts_with("E_EDITOR_NOT_CONFIGURED"));
+    }
+
+    #[test]
+    fn vscode_executable_sits_above_the_code_cmd_on_path() {
+        let dir = std::env::temp_dir().join(format!("gitlite-vscode-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        fs::create_dir_all(dir.join("VS Code/bin")).unwrap();
+        fs::write(dir.join("VS Code/bin/code.cmd"), "").unwrap();
+        let path = std::env::join_paths([dir.join("empty"), dir.join("VS Code/bin")]).unwrap();
+        assert_eq!(vscode_executable(&path), None);
+
+        fs::write(dir.join("VS Code/Code.exe"), "").unwrap();
+        assert_eq!(vscode_executable(&path), Some(dir.join("VS Code/Code.exe")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn repo_file_rejects_paths_outside_the_repository() {
+        let dir = std::env::temp_dir().join(format!("gitlite-editor-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("repo/src")).unwrap();
+        fs::write(dir.join("repo/src/lib.rs"), "").unwrap();
+        fs::write(dir.join("secret.txt"), "").unwrap();
+        let repo = dir.join("repo");
+        let repo = repo.to_str().unwrap();
+
+        let code = "E_EDITOR_PATH";
+        assert!(repo_file(repo, "src/lib.rs", code)
+            .unwrap()
+            .ends_with("src/lib.rs"));
+        assert!(repo_file(repo, "../secret.txt", code)
+            .unwrap_err()
+            .contains("outside the repository"));
+        assert!(repo_file(repo, "missing.rs", code)
+            .unwrap_err()
+            .starts_with("E_EDITOR_PATH"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reveal_commands_select_the_file_on_each_platform() {
+        let file = Path::new("/repo/notes #1, draft.md");
+        assert_eq!(
+            reveal_commands("macos", file),
+            [["open", "-R", "/repo/notes #1, draft.md"]]
+        );
+        let windows = reveal_commands("windows", Path::new(r"\\?\C:\repo\a b.txt"));
+        assert_eq!(windows[0][1], r#"/select,"C:\repo\a b.txt""#);
+
+        let linux = reveal_commands("linux", file);
+        assert_eq!(
+            linux[0][7],
+            "array:string:file:///repo/notes%20%231%2C%20draft.md"
+        );
+        assert_eq!(linux[1], ["xdg-open", "/repo"]);
+    }
+}
@@ -1,15 +1,72 @@
+mod auto_fetch;
+mod avatars;
+mod bitbucket_auth;
+mod commit_stream;
 mod config;
+mod credential_helper;
+mod credential_prompt;
+mod credentials;
+mod environment;
+mod error;
+mod features;
 pub mod git;
+mod git_pool;
 mod github_auth;
+mod github_client;
+mod github_pulls;
+mod github_releases;
+mod gitlab_auth;
+mod jobs;
+mod json_store;
+mod known_hosts;
+mod launcher;
+mod mirror;
+mod notes;
+mod oauth;
+mod perf;
+mod policy;
+mod recent;
 mod runtime;
+mod self_test;
+mod shutdown;
+mod ssh_passphrase;
+mod tls_certs;
+mod workspace;
 
+use avatars::AuthorAvatar;
+use bitbucket_auth::{BitbucketAccount, BitbucketUser};
+use commit_stream::CommitStreams;
 use config::{
     get_git_config, load_settings, load_theme, save_settings, save_theme, set_git_config,
 };
-use git::{Branch, Commit, DiffFile, FileStatus, RemoteInfo, StashEntry, SyncStatus};
-use github_auth::{GitHubAuthPollResult, GitHubDeviceCode, GitHubUser};
+use credential_helper::{CredentialHelperStatus, HelperStoreResult};
+use credential_prompt::CredentialAnswer;
+use credentials::CredentialEntry;
+use error::{ErrorCode, GitLiteError};
+use features::FeatureFlag;
+use git::{
+    ArchiveExport, Branch, Commit, CommitFilter, CommitPage, CommitQuery, CommitStats,
+    ContributionStats, DiffFile, DiffSummary, DirectoryOwnership, DiscoveredRepository, FetchScope,
+    FileLock, FileMove, FileStatus, GitmojiEntry, HeadState, HealthReport, IndexBackup,
+    MaintenanceResult, MergePreview, PatchFileResult, Permalink, PolicyPreset, PolicyViolation,
+    PushPreview, RemoteInfo, RepoManager, RepoOverview, RepoStats, SshAgentKey, StashEntry,
+    StatusQuery, SyncStatus, WebUrlArgs,
+};
+use github_auth::{GitHubAuthPollResult, GitHubFork, GitHubRepoPage, GitHubTokenInfo, GitHubUser};
+use github_client::GitHubRateLimit;
+use github_pulls::{CommitCiStatus, CreatedPullRequest, GitHubPullRequest, NewPullRequest};
+use github_releases::{CreatedRelease, NewRelease};
+use gitlab_auth::{GitLabAuthPollResult, GitLabRefresh, GitLabUser};
+use jobs::{JobInfo, JobManager, JobRequest};
+use mirror::{MirrorJob, MirrorJobInput};
+use notes::RepoNotes;
+use recent::RecentRepository;
 use runtime::RuntimeInfo;
+use self_test::SelfTestReport;
+use tauri::Emitter;
 use tauri_plugin_dialog::DialogExt;
+use tls_certs::CaBundle;
+use workspace::{WorkspaceImportResult, WorkspaceManifest, WorkspaceRepoInput};
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -18,80 +75,592 @@ fn greet(name: &str) -> String {
 
 #[tauri::command]
 async fn get_commits(
+    app: tauri::AppHandle,
+    repos: tauri::State<'_, RepoManager>,
+    path: String,
+    limit: usize,
+    reference: Option<String>,
+    filter: Option<CommitFilter>,
+) -> Result<Vec<Commit>, GitLiteError> {
+    let page = get_commits_page(app, repos, path, limit, reference, filter, None).await?;
+    Ok(page.commits)
+}
+
+#[tauri::command]
+async fn get_commits_page(
+    app: tauri::AppHandle,
+    repos: tauri::State<'_, RepoManager>,
     path: String,
     limit: usize,
     reference: Option<String>,
-) -> Result<Vec<Commit>, String> {
-    git::get_commits(&path, limit, reference.as_deref())
+    filter: Option<CommitFilter>,
+    continuation: Option<String>,
+) -> Result<CommitPage, GitLiteError> {
+    let settings = config::read_settings(&app)?;
+    let guards = settings.revwalk_guards();
+    let query = CommitQuery {
+        limit,
+        reference,
+        filter: filter.unwrap_or_default(),
+        continuation,
+        use_mailmap: settings.use_mailmap,
+    };
+    let repos = repos.inner().clone();
+    git_pool::run("get_commits_page", move || {
+        repos.with_repo(&path, |repo| git::get_commits_in(repo, &query, &guards))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_author_avatars(
+    app: tauri::AppHandle,
+    emails: Vec<String>,
+) -> Result<Vec<AuthorAvatar>, GitLiteError> {
+    avatars::get_author_avatars(&app, &emails)
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn stream_commits(
+    app: tauri::AppHandle,
+    streams: tauri::State<'_, CommitStreams>,
+    path: String,
+    reference: Option<String>,
+    limit: Option<usize>,
+    batch_size: Option<usize>,
+    filter: Option<CommitFilter>,
+) -> String {
+    let use_mailmap = config::read_settings(&app)
+        .map(|settings| settings.use_mailmap)
+        .unwrap_or(true);
+    let query = CommitQuery {
+        limit: limit.unwrap_or(usize::MAX),
+        reference,
+        filter: filter.unwrap_or_default(),
+        continuation: None,
+        use_mailmap,
+    };
+    streams.start(&app, path, query, batch_size)
+}
+
+#[tauri::command]
+fn cancel_commit_stream(
+    streams: tauri::State<'_, CommitStreams>,
+    stream_id: String,
+) -> Result<(), GitLiteError> {
+    streams.cancel(&stream_id).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn get_branches(
+    repos: tauri::State<'_, RepoManager>,
+    path: String,
+) -> Result<Vec<Branch>, GitLiteError> {
+    let repos = repos.inner().clone();
+    git_pool::run("get_branches", move || {
+        repos.with_repo(&path, |repo| git::get_branches_in(repo))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn branches_containing(
+    path: String,
+    commit_hash: String,
+) -> Result<Vec<Branch>, GitLiteError> {
+    git_pool::run("branches_containing", move || {
+        git::branches_containing(&path, &commit_hash)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn tags_containing(path: String, commit_hash: String) -> Result<Vec<String>, GitLiteError> {
+    git_pool::run("tags_containing", move || {
+        git::tags_containing(&path, &commit_hash)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn create_branch(path: String, name: String) -> Result<(), GitLiteError> {
+    git_pool::run("create_branch", move || {
+        let _operation = shutdown::track("branch", &path)?;
+        git::create_branch(&path, &name)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn delete_branch(path: String, name: String) -> Result<(), GitLiteError> {
+    git_pool::run("delete_branch", move || {
+        let _operation = shutdown::track("branch", &path)?;
+        git::delete_branch(&path, &name)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn checkout_branch(
+    app: tauri::AppHandle,
+    path: String,
+    name: String,
+) -> Result<(), GitLiteError> {
+    git_pool::run("checkout_branch", move || {
+        let _operation = shutdown::track("checkout", &path)?;
+        git::checkout_branch(&path, &name, |progress| {
+            let _ = app.emit("checkout-progress", progress);
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+async fn checkout_pull_request(
+    app: tauri::AppHandle,
+    path: String,
+    pr_number: u64,
+    remote_name: Option<String>,
+) -> Result<String, GitLiteError> {
+    let remote_name = remote_name.unwrap_or_else(|| "origin".to_string());
+    git_pool::run("checkout_pull_request", move || {
+        let _operation = shutdown::track("checkout", &path)?;
+        git::checkout_pull_request(&path, &remote_name, pr_number, |progress| {
+            let _ = app.emit("checkout-progress", progress);
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_commit_diff(
+    app: tauri::AppHandle,
+    path: String,
+    commit_hash: String,
+) -> Result<Vec<DiffFile>, GitLiteError> {
+    let limits = config::read_settings(&app)?.diff_limits();
+    git_pool::run("get_commit_diff", move || {
+        git::get_commit_diff(&path, &commit_hash, &limits)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_commit_stats(
+    path: String,
+    hashes: Vec<String>,
+) -> Result<Vec<CommitStats>, GitLiteError> {
+    git_pool::run("get_commit_stats", move || {
+        git::get_commit_stats(&path, &hashes)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn load_full_file_diff(
+    app: tauri::AppHandle,
+    path: String,
+    commit_hash: String,
+    file: String,
+) -> Result<DiffFile, GitLiteError> {
+    let context_lines = config::read_settings(&app)?.diff_context_lines;
+    git_pool::run("load_full_file_diff", move || {
+        git::load_full_file_diff(&path, &commit_hash, &file, context_lines)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_contribution_stats(
+    app: tauri::AppHandle,
+    path: String,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<ContributionStats, GitLiteError> {
+    let settings = config::read_settings(&app)?;
+    let guards = settings.revwalk_guards();
+    git_pool::run("get_contribution_stats", move || {
+        git::get_contribution_stats(&path, since, until, settings.use_mailmap, &guards)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_directory_ownership(
+    app: tauri::AppHandle,
+    path: String,
+    dir: String,
+    depth: Option<usize>,
+) -> Result<Vec<DirectoryOwnership>, GitLiteError> {
+    let use_mailmap = config::read_settings(&app)?.use_mailmap;
+    git_pool::run("get_directory_ownership", move || {
+        git::get_directory_ownership(&path, &dir, depth.unwrap_or(1), use_mailmap)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn summarize_diff(path: String, spec: String) -> Result<DiffSummary, GitLiteError> {
+    git_pool::run("summarize_diff", move || git::summarize_diff(&path, &spec)).await
+}
+
+#[tauri::command]
+async fn get_repo_overview(
+    repos: tauri::State<'_, RepoManager>,
+    path: String,
+) -> Result<RepoOverview, GitLiteError> {
+    let repos = repos.inner().clone();
+    git_pool::run("get_repo_overview", move || {
+        repos.with_repo(&path, git::get_repo_overview_in)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_head_state(
+    repos: tauri::State<'_, RepoManager>,
+    path: String,
+) -> Result<HeadState, GitLiteError> {
+    let repos = repos.inner().clone();
+    git_pool::run("get_head_state", move || {
+        repos.with_repo(&path, git::get_head_state_in)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_web_url(path: String, kind: String, args: WebUrlArgs) -> Result<String, GitLiteError> {
+    git_pool::run("get_web_url", move || git::get_web_url(&path, &kind, &args)).await
 }
 
 #[tauri::command]
-async fn get_branches(path: String) -> Result<Vec<Branch>, String> {
-    git::get_branches(&path)
+async fn get_permalink(
+    path: String,
+    file: String,
+    line: u32,
+    remote: Option<String>,
+) -> Result<Permalink, GitLiteError> {
+    git_pool::run("get_permalink", move || {
+        git::get_permalink(&path, &file, line, remote.as_deref())
+    })
+    .await
 }
 
 #[tauri::command]
-async fn create_branch(path: String, name: String) -> Result<(), String> {
-    git::create_branch(&path, &name)
+async fn get_status(
+    repos: tauri::State<'_, RepoManager>,
+    path: String,
+    query: Option<StatusQuery>,
+) -> Result<Vec<FileStatus>, GitLiteError> {
+    let repos = repos.inner().clone();
+    let query = query.unwrap_or_default();
+    git_pool::run("get_status", move || {
+        repos.with_repo(&path, |repo| git::get_status_in(repo, &query))
+    })
+    .await
 }
 
 #[tauri::command]
-async fn delete_branch(path: String, name: String) -> Result<(), String> {
-    git::delete_branch(&path, &name)
+async fn list_ignored_files(path: String) -> Result<Vec<String>, GitLiteError> {
+    git_pool::run("list_ignored_files", move || git::list_ignored_files(&path)).await
 }
 
 #[tauri::command]
-async fn checkout_branch(path: String, name: String) -> Result<(), String> {
-    git::checkout_branch(&path, &name)
+async fn stage_files(
+    app: tauri::AppHandle,
+    path: String,
+    files: Vec<String>,
+) -> Result<(), GitLiteError> {
+    git_pool::run("stage_files", move || {
+        let _operation = shutdown::track("stage", &path)?;
+        if let Ok(locks) = git::locked_by_others(&path, &files) {
+            if !locks.is_empty() {
+                let _ = app.emit("file-lock-warning", &locks);
+            }
+        }
+        git::stage_files(&path, &files)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn get_commit_diff(path: String, commit_hash: String) -> Result<Vec<DiffFile>, String> {
-    git::get_commit_diff(&path, &commit_hash)
+async fn stage_pathspec(
+    app: tauri::AppHandle,
+    path: String,
+    patterns: Vec<String>,
+) -> Result<Vec<String>, GitLiteError> {
+    git_pool::run("stage_pathspec", move || {
+        let _operation = shutdown::track("stage", &path)?;
+        let staged = git::stage_pathspec(&path, &patterns)?;
+        if let Ok(locks) = git::locked_by_others(&path, &staged) {
+            if !locks.is_empty() {
+                let _ = app.emit("file-lock-warning", &locks);
+            }
+        }
+        Ok::<_, String>(staged)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn get_status(path: String) -> Result<Vec<FileStatus>, String> {
-    git::get_status(&path)
+async fn unstage_pathspec(
+    path: String,
+    patterns: Vec<String>,
+) -> Result<Vec<String>, GitLiteError> {
+    git_pool::run("unstage_pathspec", move || {
+        let _operation = shutdown::track("unstage", &path)?;
+        git::unstage_pathspec(&path, &patterns)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn stage_files(path: String, files: Vec<String>) -> Result<(), String> {
-    git::stage_files(&path, &files)
+async fn lock_file(
+    path: String,
+    file: String,
+    remote: Option<String>,
+) -> Result<FileLock, GitLiteError> {
+    git_pool::run("lock_file", move || {
+        git::lock_file(&path, &file, remote.as_deref())
+    })
+    .await
 }
 
 #[tauri::command]
-async fn unstage_files(path: String, files: Vec<String>) -> Result<(), String> {
-    git::unstage_files(&path, &files)
+async fn unlock_file(
+    path: String,
+    file: String,
+    remote: Option<String>,
+    force: Option<bool>,
+) -> Result<(), GitLiteError> {
+    git_pool::run("unlock_file", move || {
+        git::unlock_file(&path, &file, remote.as_deref(), force.unwrap_or(false))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn list_file_locks(
+    path: String,
+    remote: Option<String>,
+) -> Result<Vec<FileLock>, GitLiteError> {
+    git_pool::run("list_file_locks", move || {
+        git::list_locks(&path, remote.as_deref())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn git_move(path: String, from: String, to: String) -> Result<FileMove, GitLiteError> {
+    git_pool::run("git_move", move || {
+        let _operation = shutdown::track("move", &path)?;
+        git::git_move(&path, &from, &to)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn git_move_many(path: String, moves: Vec<FileMove>) -> Result<Vec<FileMove>, GitLiteError> {
+    git_pool::run("git_move_many", move || {
+        let _operation = shutdown::track("move", &path)?;
+        git::git_move_many(&path, &moves)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn unstage_files(path: String, files: Vec<String>) -> Result<(), GitLiteError> {
+    git_pool::run("unstage_files", move || {
+        let _operation = shutdown::track("unstage", &path)?;
+        git::unstage_files(&path, &files)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn set_index_flag(
+    path: String,
+    files: Vec<String>,
+    flag: String,
+    enabled: bool,
+) -> Result<(), GitLiteError> {
+    git_pool::run("set_index_flag", move || {
+        let _operation = shutdown::track("index_flag", &path)?;
+        git::set_index_flag(&path, &files, &flag, enabled)
+    })
+    .await
 }
 
 #[tauri::command]
 async fn commit_changes(
+    app: tauri::AppHandle,
+    path: String,
+    message: String,
+    description: String,
+    expand_emoji: Option<bool>,
+    skip_policy: Option<bool>,
+    options: Option<git::CommitOptions>,
+) -> Result<String, GitLiteError> {
+    let options = options.unwrap_or_default();
+    let (message, mut description) = if expand_emoji.unwrap_or(false) {
+        (
+            git::expand_shortcodes(&message),
+            git::expand_shortcodes(&description),
+        )
+    } else {
+        (message, description)
+    };
+
+    git_pool::run("commit_changes", move || {
+        let _operation = shutdown::track("commit", &path)?;
+        let preset = if skip_policy.unwrap_or(false) {
+            policy::RepoPreset::Unset
+        } else {
+            policy::preset_for_repo(&app, &path)?
+        };
+        match preset {
+            policy::RepoPreset::Imported(preset) => {
+                if preset.sign_off {
+                    description = git::with_sign_off(&path, &description)?;
+                }
+                let violations = git::check_commit_policy(&path, &preset, &message, &description)?;
+                if !violations.is_empty() {
+                    let details: Vec<String> = violations.into_iter().map(|v| v.message).collect();
+                    return Err(GitLiteError::new(
+                        ErrorCode::PolicyViolation,
+                        format!("commit violates policy preset '{}'", preset.name),
+                    )
+                    .with_details(details.join("\n")));
+                }
+            }
+            policy::RepoPreset::Missing(name) => runtime::append_runtime_log(&format!(
+                "policy: preset '{}' of {} is not imported; commit not checked",
+                name, path
+            )),
+            policy::RepoPreset::Unset => {}
+        }
+
+        git::commit_changes_with(&path, &message, &description, &options)
+            .map_err(GitLiteError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+fn import_policy_preset(
+    app: tauri::AppHandle,
+    file_path: String,
+) -> Result<PolicyPreset, GitLiteError> {
+    policy::import_preset(&app, &file_path).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn list_policy_presets(app: tauri::AppHandle) -> Result<Vec<PolicyPreset>, GitLiteError> {
+    policy::list_presets(&app).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn delete_policy_preset(app: tauri::AppHandle, name: String) -> Result<(), GitLiteError> {
+    policy::delete_preset(&app, &name).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn set_repo_policy_preset(
+    app: tauri::AppHandle,
+    path: String,
+    name: Option<String>,
+) -> Result<(), GitLiteError> {
+    policy::assign_preset(&app, &path, name.as_deref()).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn get_repo_policy(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<Option<PolicyPreset>, GitLiteError> {
+    match policy::preset_for_repo(&app, &path)? {
+        policy::RepoPreset::Imported(preset) => Ok(Some(preset)),
+        _ => Ok(None),
+    }
+}
+
+#[tauri::command]
+async fn check_commit_policy(
+    app: tauri::AppHandle,
     path: String,
     message: String,
     description: String,
-) -> Result<String, String> {
-    git::commit_changes(&path, &message, &description)
+) -> Result<Vec<PolicyViolation>, GitLiteError> {
+    let preset = match policy::preset_for_repo(&app, &path)? {
+        policy::RepoPreset::Imported(preset) => preset,
+        missing => return Ok(missing.missing_warning().into_iter().collect()),
+    };
+    git_pool::run("check_commit_policy", move || {
+        git::check_commit_policy(&path, &preset, &message, &description)
+    })
+    .await
+}
+
+#[tauri::command]
+fn get_gitmoji_table() -> Vec<GitmojiEntry> {
+    git::gitmoji_table()
+}
+
+#[tauri::command]
+async fn apply_patch(
+    path: String,
+    patch_text: String,
+    to_index: bool,
+    check_only: bool,
+) -> Result<Vec<PatchFileResult>, GitLiteError> {
+    git_pool::run("apply_patch", move || {
+        let _operation = shutdown::track("apply_patch", &path)?;
+        git::apply_patch(&path, &patch_text, to_index, check_only)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn restore_index_backup(path: String) -> Result<IndexBackup, GitLiteError> {
+    git_pool::run("restore_index_backup", move || {
+        let _operation = shutdown::track("restore_index", &path)?;
+        git::restore_index_backup(&path)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn list_stashes(path: String) -> Result<Vec<StashEntry>, String> {
-    git::list_stashes(&path)
+async fn list_stashes(path: String) -> Result<Vec<StashEntry>, GitLiteError> {
+    git_pool::run("list_stashes", move || git::list_stashes(&path)).await
 }
 
 #[tauri::command]
-async fn create_stash(path: String, message: Option<String>) -> Result<(), String> {
-    git::create_stash(&path, message.as_deref())
+async fn create_stash(path: String, message: Option<String>) -> Result<(), GitLiteError> {
+    git_pool::run("create_stash", move || {
+        let _operation = shutdown::track("stash", &path)?;
+        git::create_stash(&path, message.as_deref())
+    })
+    .await
 }
 
 #[tauri::command]
-async fn apply_stash(path: String, index: usize) -> Result<(), String> {
-    git::apply_stash(&path, index)
+async fn apply_stash(path: String, index: usize) -> Result<(), GitLiteError> {
+    git_pool::run("apply_stash", move || {
+        let _operation = shutdown::track("stash", &path)?;
+        git::apply_stash(&path, index)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn drop_stash(path: String, index: usize) -> Result<(), String> {
-    git::drop_stash(&path, index)
+async fn drop_stash(path: String, index: usize) -> Result<(), GitLiteError> {
+    git_pool::run("drop_stash", move || {
+        let _operation = shutdown::track("stash", &path)?;
+        git::drop_stash(&path, index)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -100,8 +669,12 @@ async fn push_remote(
     remote_name: String,
     username: String,
     password: String,
-) -> Result<(), String> {
-    git::push(&path, &remote_name, &username, &password)
+) -> Result<(), GitLiteError> {
+    git_pool::run_interactive("push_remote", move || {
+        let _operation = shutdown::track("push", &path)?;
+        git::push(&path, &remote_name, &username, &password)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -110,8 +683,15 @@ async fn pull_remote(
     remote_name: String,
     username: String,
     password: String,
-) -> Result<(), String> {
-    git::pull(&path, &remote_name, &username, &password)
+    autostash: Option<bool>,
+    pull_strategy: Option<String>,
+) -> Result<(), GitLiteError> {
+    let options = git::PullOptions::from_args(autostash, pull_strategy.as_deref())?;
+    git_pool::run_interactive("pull_remote", move || {
+        let _operation = shutdown::track("pull", &path)?;
+        git::pull(&path, &remote_name, &username, &password, &options)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -120,27 +700,150 @@ async fn fetch_remote(
     remote_name: String,
     username: String,
     password: String,
-) -> Result<(), String> {
-    git::fetch_remote(&path, &remote_name, &username, &password)
+    scope: Option<FetchScope>,
+) -> Result<(), GitLiteError> {
+    git_pool::run_interactive("fetch_remote", move || {
+        let _operation = shutdown::track("fetch", &path)?;
+        git::fetch_remote(
+            &path,
+            &remote_name,
+            &username,
+            &password,
+            &scope.unwrap_or_default(),
+        )
+    })
+    .await
 }
 
 #[tauri::command]
-async fn merge_branch(path: String, source_branch: String) -> Result<(), String> {
-    git::merge_branch(&path, &source_branch)
+async fn preview_push(path: String, remote_name: String) -> Result<PushPreview, GitLiteError> {
+    git_pool::run("preview_push", move || {
+        git::preview_push(&path, &remote_name)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cherry_pick_commit(path: String, commit_hash: String) -> Result<String, String> {
-    git::cherry_pick_commit(&path, &commit_hash)
+async fn merge_branch(path: String, source_branch: String) -> Result<(), GitLiteError> {
+    git_pool::run("merge_branch", move || {
+        let _operation = shutdown::track("merge", &path)?;
+        git::merge_branch(&path, &source_branch)
+    })
+    .await
+}
+
+#[tauri::command]
+fn open_in_editor(
+    app: tauri::AppHandle,
+    path: String,
+    file: String,
+    line: Option<u32>,
+) -> Result<(), GitLiteError> {
+    launcher::open_in_editor(&app, &path, &file, line).map_err(GitLiteError::from)
+}
+
+/// The D-Bus call can block until its timeout, so it runs on a blocking
+/// thread rather than the main thread or a git worker.
+#[tauri::command]
+async fn reveal_in_file_manager(path: String, file: Option<String>) -> Result<(), GitLiteError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        launcher::reveal_in_file_manager(&path, file.as_deref())
+    })
+    .await
+    .map_err(|e| GitLiteError::from(format!("E_REVEAL_FAILED: {}", e)))?
+    .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn get_external_tools(path: String) -> Result<git::ExternalTools, GitLiteError> {
+    git_pool::run("get_external_tools", move || git::get_external_tools(&path)).await
+}
+
+/// Runs the tool on its own blocking thread so a long merge session does not
+/// hold a git worker; the result is staged when the tool exits with 0.
+#[tauri::command]
+async fn launch_merge_tool(
+    path: String,
+    file: String,
+) -> Result<git::MergeToolResult, GitLiteError> {
+    let (tool_path, tool_file) = (path.clone(), file.clone());
+    let exit_code = tauri::async_runtime::spawn_blocking(move || {
+        perf::time("launch_merge_tool", std::time::Duration::ZERO, || {
+            git::run_merge_tool(&tool_path, &tool_file)
+        })
+    })
+    .await
+    .map_err(|e| GitLiteError::from(format!("E_MERGE_TOOL_FAILED: {}", e)))??;
+    if exit_code != Some(0) {
+        return Ok(git::MergeToolResult {
+            exit_code,
+            resolved: false,
+        });
+    }
+    git_pool::run("launch_merge_tool", move || {
+        let _operation = shutdown::track("merge_tool", &path)?;
+        match git::mark_conflict_resolved(&path, &file).map_err(GitLiteError::from) {
+            Ok(()) => Ok(true),
+            Err(error) if error.code == ErrorCode::MergeToolUnresolved => Ok(false),
+            Err(error) => Err(error),
+        }
+    })
+    .await
+    .map(|resolved| git::MergeToolResult {
+        exit_code,
+        resolved,
+    })
+}
+
+#[tauri::command]
+async fn mark_conflict_resolved(path: String, file: String) -> Result<(), GitLiteError> {
+    git_pool::run("mark_conflict_resolved", move || {
+        let _operation = shutdown::track("resolve_conflict", &path)?;
+        git::mark_conflict_resolved(&path, &file)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn abort_operation(path: String) -> Result<String, GitLiteError> {
+    git_pool::run("abort_operation", move || {
+        let _operation = shutdown::track("abort", &path)?;
+        git::abort_operation(&path)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn preview_merge(path: String, source_branch: String) -> Result<MergePreview, GitLiteError> {
+    git_pool::run("preview_merge", move || {
+        git::preview_merge(&path, &source_branch)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cherry_pick_commit(path: String, commit_hash: String) -> Result<String, GitLiteError> {
+    git_pool::run("cherry_pick_commit", move || {
+        let _operation = shutdown::track("cherry_pick", &path)?;
+        git::cherry_pick_commit(&path, &commit_hash)
+    })
+    .await
 }
 
 #[tauri::command]
 async fn reset_current_branch(
+    app: tauri::AppHandle,
     path: String,
     commit_hash: String,
     mode: String,
-) -> Result<(), String> {
-    git::reset_current_branch(&path, &commit_hash, &mode)
+) -> Result<(), GitLiteError> {
+    git_pool::run("reset_current_branch", move || {
+        let _operation = shutdown::track("reset", &path)?;
+        git::reset_current_branch(&path, &commit_hash, &mode, |progress| {
+            let _ = app.emit("checkout-progress", progress);
+        })
+    })
+    .await
 }
 
 #[tauri::command]
@@ -148,34 +851,117 @@ async fn create_branch_from_commit(
     path: String,
     name: String,
     commit_hash: String,
-) -> Result<(), String> {
-    git::create_branch_from_commit(&path, &name, &commit_hash)
+) -> Result<(), GitLiteError> {
+    git_pool::run("create_branch_from_commit", move || {
+        let _operation = shutdown::track("branch", &path)?;
+        git::create_branch_from_commit(&path, &name, &commit_hash)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn checkout_commit(
+    app: tauri::AppHandle,
+    path: String,
+    commit_hash: String,
+) -> Result<(), GitLiteError> {
+    git_pool::run("checkout_commit", move || {
+        let _operation = shutdown::track("checkout", &path)?;
+        git::checkout_commit(&path, &commit_hash, |progress| {
+            let _ = app.emit("checkout-progress", progress);
+        })
+    })
+    .await
 }
 
 #[tauri::command]
-async fn checkout_commit(path: String, commit_hash: String) -> Result<(), String> {
-    git::checkout_commit(&path, &commit_hash)
+async fn revert_commit(path: String, commit_hash: String) -> Result<String, GitLiteError> {
+    git_pool::run("revert_commit", move || {
+        let _operation = shutdown::track("revert", &path)?;
+        git::revert_commit(&path, &commit_hash)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn revert_commit(path: String, commit_hash: String) -> Result<String, String> {
-    git::revert_commit(&path, &commit_hash)
+async fn amend_commit_metadata(
+    path: String,
+    options: Option<git::CommitOptions>,
+) -> Result<String, GitLiteError> {
+    git_pool::run("amend_commit_metadata", move || {
+        let _operation = shutdown::track("amend", &path)?;
+        git::amend_commit_metadata(&path, &options.unwrap_or_default())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn reword_commit(
+    app: tauri::AppHandle,
+    path: String,
+    commit_hash: String,
+    new_message: String,
+) -> Result<String, GitLiteError> {
+    features::require(&config::read_settings(&app)?, "rebase_engine")?;
+    git_pool::run("reword_commit", move || {
+        let _operation = shutdown::track("reword", &path)?;
+        git::reword_commit(&path, &commit_hash, &new_message)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn detect_ssh_keys() -> Result<Vec<String>, String> {
+async fn export_archive(
+    path: String,
+    reference: String,
+    format: String,
+    dest: String,
+    prefix: Option<String>,
+) -> Result<ArchiveExport, GitLiteError> {
+    git_pool::run("export_archive", move || {
+        git::export_archive(&path, &reference, &format, &dest, prefix.as_deref())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn detect_ssh_keys() -> Result<Vec<String>, GitLiteError> {
     let keys = git::detect_ssh_keys();
     Ok(keys.iter().map(|p| p.display().to_string()).collect())
 }
 
+#[tauri::command]
+async fn list_ssh_agent_keys() -> Result<Vec<SshAgentKey>, GitLiteError> {
+    git_pool::run("list_ssh_agent_keys", git::list_ssh_agent_keys).await
+}
+
+#[tauri::command]
+fn save_ssh_passphrase(key_path: String, passphrase: String) -> Result<(), GitLiteError> {
+    ssh_passphrase::save_passphrase(&key_path, &passphrase).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn has_ssh_passphrase(key_path: String) -> Result<bool, GitLiteError> {
+    ssh_passphrase::has_passphrase(&key_path).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn delete_ssh_passphrase(key_path: String) -> Result<(), GitLiteError> {
+    ssh_passphrase::delete_passphrase(&key_path).map_err(GitLiteError::from)
+}
+
 #[tauri::command]
 async fn push_ssh(
     path: String,
     remote_name: String,
     key_path: String,
     passphrase: Option<String>,
-) -> Result<(), String> {
-    git::push_ssh(&path, &remote_name, &key_path, passphrase)
+) -> Result<(), GitLiteError> {
+    git_pool::run_interactive("push_ssh", move || {
+        let _operation = shutdown::track("push", &path)?;
+        git::push_ssh(&path, &remote_name, &key_path, passphrase)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -184,8 +970,15 @@ async fn pull_ssh(
     remote_name: String,
     key_path: String,
     passphrase: Option<String>,
-) -> Result<(), String> {
-    git::pull_ssh(&path, &remote_name, &key_path, passphrase)
+    autostash: Option<bool>,
+    pull_strategy: Option<String>,
+) -> Result<(), GitLiteError> {
+    let options = git::PullOptions::from_args(autostash, pull_strategy.as_deref())?;
+    git_pool::run_interactive("pull_ssh", move || {
+        let _operation = shutdown::track("pull", &path)?;
+        git::pull_ssh(&path, &remote_name, &key_path, passphrase, &options)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -194,42 +987,142 @@ async fn fetch_ssh(
     remote_name: String,
     key_path: String,
     passphrase: Option<String>,
-) -> Result<(), String> {
-    git::fetch_ssh(&path, &remote_name, &key_path, passphrase)
+    scope: Option<FetchScope>,
+) -> Result<(), GitLiteError> {
+    git_pool::run_interactive("fetch_ssh", move || {
+        let _operation = shutdown::track("fetch", &path)?;
+        git::fetch_ssh(
+            &path,
+            &remote_name,
+            &key_path,
+            passphrase,
+            &scope.unwrap_or_default(),
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+async fn list_remotes(
+    repos: tauri::State<'_, RepoManager>,
+    path: String,
+) -> Result<Vec<RemoteInfo>, GitLiteError> {
+    let repos = repos.inner().clone();
+    git_pool::run("list_remotes", move || {
+        repos.with_repo(&path, |repo| git::list_remotes_in(repo))
+    })
+    .await
 }
 
 #[tauri::command]
-async fn list_remotes(path: String) -> Result<Vec<RemoteInfo>, String> {
-    git::list_remotes(&path)
+async fn add_remote(path: String, name: String, url: String) -> Result<(), GitLiteError> {
+    git_pool::run("add_remote", move || git::add_remote(&path, &name, &url)).await
 }
 
 #[tauri::command]
-async fn add_remote(path: String, name: String, url: String) -> Result<(), String> {
-    git::add_remote(&path, &name, &url)
+async fn remove_remote(path: String, name: String) -> Result<(), GitLiteError> {
+    git_pool::run("remove_remote", move || git::remove_remote(&path, &name)).await
 }
 
 #[tauri::command]
-async fn remove_remote(path: String, name: String) -> Result<(), String> {
-    git::remove_remote(&path, &name)
+async fn rename_remote(
+    path: String,
+    old_name: String,
+    new_name: String,
+) -> Result<(), GitLiteError> {
+    git_pool::run("rename_remote", move || {
+        git::rename_remote(&path, &old_name, &new_name)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn rename_remote(path: String, old_name: String, new_name: String) -> Result<(), String> {
-    git::rename_remote(&path, &old_name, &new_name)
+async fn set_remote_url(path: String, name: String, new_url: String) -> Result<(), GitLiteError> {
+    git_pool::run("set_remote_url", move || {
+        git::set_remote_url(&path, &name, &new_url)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn set_remote_push_url(
+    path: String,
+    name: String,
+    push_url: Option<String>,
+) -> Result<(), GitLiteError> {
+    git_pool::run("set_remote_push_url", move || {
+        git::set_remote_push_url(&path, &name, push_url.as_deref())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn configure_fork_remotes(
+    path: String,
+    fork_url: String,
+    upstream_url: String,
+) -> Result<Vec<RemoteInfo>, GitLiteError> {
+    git_pool::run("configure_fork_remotes", move || {
+        git::configure_fork_remotes(&path, &fork_url, &upstream_url)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn set_remote_url(path: String, name: String, new_url: String) -> Result<(), String> {
-    git::set_remote_url(&path, &name, &new_url)
+async fn get_use_github_token(path: String) -> Result<bool, GitLiteError> {
+    git_pool::run("get_use_github_token", move || {
+        git::get_use_github_token(&path)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn sync_status(path: String, remote_name: String) -> Result<SyncStatus, String> {
-    git::sync_status(&path, &remote_name)
+async fn set_use_github_token(path: String, enabled: bool) -> Result<(), GitLiteError> {
+    git_pool::run("set_use_github_token", move || {
+        git::set_use_github_token(&path, enabled)
+    })
+    .await
 }
 
 #[tauri::command]
-fn is_git_repository(path: String) -> Result<bool, String> {
+async fn get_fetch_refspecs(path: String, name: String) -> Result<Vec<String>, GitLiteError> {
+    git_pool::run("get_fetch_refspecs", move || {
+        git::get_fetch_refspecs(&path, &name)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn add_fetch_refspec(
+    path: String,
+    name: String,
+    refspec: String,
+) -> Result<Vec<String>, GitLiteError> {
+    git_pool::run("add_fetch_refspec", move || {
+        git::add_fetch_refspec(&path, &name, &refspec)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn remove_fetch_refspec(
+    path: String,
+    name: String,
+    refspec: String,
+) -> Result<Vec<String>, GitLiteError> {
+    git_pool::run("remove_fetch_refspec", move || {
+        git::remove_fetch_refspec(&path, &name, &refspec)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn sync_status(path: String, remote_name: String) -> Result<SyncStatus, GitLiteError> {
+    git_pool::run("sync_status", move || git::sync_status(&path, &remote_name)).await
+}
+
+#[tauri::command]
+fn is_git_repository(path: String) -> Result<bool, GitLiteError> {
     match git2::Repository::open(&path) {
         Ok(_) => Ok(true),
         Err(_) => Ok(false),
@@ -237,16 +1130,70 @@ fn is_git_repository(path: String) -> Result<bool, String> {
 }
 
 #[tauri::command]
-fn git_init(path: String) -> Result<(), String> {
-    git2::Repository::init(&path).map_err(|e| e.message().to_string())?;
-    Ok(())
+async fn check_repository(path: String) -> Result<HealthReport, GitLiteError> {
+    git_pool::run("check_repository", move || git::check_repository(&path)).await
+}
+
+#[tauri::command]
+async fn repo_maintenance(path: String) -> Result<RepoStats, GitLiteError> {
+    git_pool::run("repo_maintenance", move || git::get_repo_stats(&path)).await
+}
+
+#[tauri::command]
+async fn run_repo_maintenance(
+    app: tauri::AppHandle,
+    path: String,
+    action: String,
+) -> Result<MaintenanceResult, GitLiteError> {
+    git_pool::run("run_repo_maintenance", move || {
+        let _operation = shutdown::track("maintenance", &path)?;
+        git::run_maintenance(&path, &action, |progress| {
+            let _ = app.emit("repo-maintenance-progress", progress);
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+async fn is_bare_repository(path: String) -> Result<bool, GitLiteError> {
+    git_pool::run("is_bare_repository", move || git::is_bare_repository(&path)).await
+}
+
+#[tauri::command]
+async fn get_repo_kind(path: String) -> Result<git::RepoKind, GitLiteError> {
+    git_pool::run("get_repo_kind", move || git::get_repo_kind(&path)).await
+}
+
+#[tauri::command]
+async fn scan_for_repositories(
+    base_dir: String,
+    max_depth: Option<usize>,
+) -> Result<Vec<DiscoveredRepository>, GitLiteError> {
+    git_pool::run("scan_for_repositories", move || {
+        git::scan_for_repositories(&base_dir, max_depth.unwrap_or(3))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn git_init(
+    path: String,
+    options: Option<git::InitOptions>,
+) -> Result<git::InitResult, GitLiteError> {
+    let options = options.unwrap_or_default();
+    git_pool::run("git_init", move || git::init_repository(&path, &options)).await
+}
+
+#[tauri::command]
+fn list_init_templates() -> git::InitTemplates {
+    git::init_templates()
 }
 
 #[tauri::command]
 async fn pick_repository_folder(
     app: tauri::AppHandle,
     start_dir: Option<String>,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, GitLiteError> {
     let mut dialog = app.dialog().file().set_title("Open Git Repository");
 
     if let Some(initial) = start_dir.and_then(|value| {
@@ -271,57 +1218,558 @@ async fn pick_repository_folder(
 }
 
 #[tauri::command]
-async fn github_oauth_start(client_id: String) -> Result<GitHubDeviceCode, String> {
-    github_auth::start_device_flow(&client_id).await
+async fn github_oauth_start(
+    client_id: String,
+    host: Option<String>,
+) -> Result<oauth::DeviceCode, GitLiteError> {
+    github_auth::start_device_flow(&client_id, host.as_deref())
+        .await
+        .map_err(GitLiteError::from)
 }
 
 #[tauri::command]
 async fn github_oauth_poll(
     client_id: String,
     device_code: String,
-) -> Result<GitHubAuthPollResult, String> {
-    github_auth::poll_device_flow(&client_id, &device_code).await
+    host: Option<String>,
+) -> Result<GitHubAuthPollResult, GitLiteError> {
+    github_auth::poll_device_flow(&client_id, &device_code, host.as_deref())
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn github_fetch_user(
+    access_token: String,
+    host: Option<String>,
+) -> Result<GitHubUser, GitLiteError> {
+    github_auth::fetch_user(&access_token, host.as_deref())
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn github_check_token(
+    access_token: String,
+    host: Option<String>,
+) -> Result<GitHubTokenInfo, GitLiteError> {
+    github_auth::check_token(&access_token, host.as_deref())
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn github_list_repos(
+    access_token: String,
+    page: Option<u32>,
+    affiliation: Option<String>,
+    host: Option<String>,
+) -> Result<GitHubRepoPage, GitLiteError> {
+    github_auth::list_repos(
+        &access_token,
+        page.unwrap_or(1),
+        affiliation.as_deref(),
+        host.as_deref(),
+    )
+    .await
+    .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn github_rate_limit(host: Option<String>) -> Result<Vec<GitHubRateLimit>, GitLiteError> {
+    let api_base = github_auth::api_base(host.as_deref())?;
+    Ok(github_client::rate_limits(&api_base))
+}
+
+#[tauri::command]
+async fn github_list_pull_requests(
+    path: String,
+    access_token: String,
+) -> Result<Vec<GitHubPullRequest>, GitLiteError> {
+    let slug = git_pool::run("github_list_pull_requests", move || {
+        github_pulls::origin_repo(&path)
+    })
+    .await?;
+    github_pulls::list_pull_requests(&access_token, &slug)
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn github_create_pull_request(
+    path: String,
+    access_token: String,
+    request: NewPullRequest,
+    push_head: Option<bool>,
+) -> Result<CreatedPullRequest, GitLiteError> {
+    let head = request.head.trim().to_string();
+    let push_head = push_head.unwrap_or(false);
+    let slug = git_pool::run_interactive("github_create_pull_request", move || {
+        let slug = github_pulls::origin_repo(&path)?;
+        let _operation = if push_head {
+            Some(shutdown::track("push", &path)?)
+        } else {
+            None
+        };
+        github_pulls::ensure_head_pushed(&path, &head, push_head)?;
+        Ok::<_, GitLiteError>(slug)
+    })
+    .await?;
+    github_pulls::create_pull_request(&access_token, &slug, &request)
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn github_get_commit_status(
+    path: String,
+    access_token: String,
+    reference: String,
+) -> Result<CommitCiStatus, GitLiteError> {
+    let (slug, sha) = git_pool::run("github_get_commit_status", move || {
+        let slug = github_pulls::origin_repo(&path)?;
+        let sha = github_pulls::resolve_commit(&path, &reference)?;
+        Ok::<_, String>((slug, sha))
+    })
+    .await?;
+    github_pulls::commit_status(&access_token, &slug, &sha)
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn github_fork_repo(
+    access_token: String,
+    owner: String,
+    repo: String,
+    host: Option<String>,
+) -> Result<GitHubFork, GitLiteError> {
+    github_auth::fork_repo(&access_token, &owner, &repo, host.as_deref())
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn github_create_release(
+    path: String,
+    access_token: String,
+    request: NewRelease,
+    push_tag: Option<bool>,
+) -> Result<CreatedRelease, GitLiteError> {
+    let tag = request.tag.trim().to_string();
+    let push_tag = push_tag.unwrap_or(false);
+    let release = request.clone();
+    let (slug, target) = git_pool::run_interactive("github_create_release", move || {
+        let slug = github_pulls::origin_repo(&path)?;
+        let target = github_releases::release_target(&path, &release)?;
+        if push_tag {
+            if github_releases::local_tag_commit(&path, &tag)?.is_none() {
+                return Err(GitLiteError::from(format!(
+                    "E_GITHUB_RELEASE_TAG_NOT_FOUND: no local tag '{}' to push",
+                    tag
+                )));
+            }
+            let _operation = shutdown::track("push", &path)?;
+            git::push_tag(&path, "origin", &tag, "", "")?;
+        }
+        Ok::<_, GitLiteError>((slug, target))
+    })
+    .await?;
+    github_releases::create_release(&access_token, &slug, &request, target.as_deref())
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn save_github_token(access_token: String, host: Option<String>) -> Result<(), GitLiteError> {
+    github_auth::save_token_to_keychain(&access_token, host.as_deref()).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn load_github_token(host: Option<String>) -> Result<Option<String>, GitLiteError> {
+    github_auth::load_token_from_keychain(host.as_deref()).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn delete_github_token(host: Option<String>) -> Result<(), GitLiteError> {
+    github_auth::delete_token_from_keychain(host.as_deref()).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn gitlab_oauth_start(
+    client_id: String,
+    host: Option<String>,
+) -> Result<oauth::DeviceCode, GitLiteError> {
+    gitlab_auth::start_device_flow(&client_id, host.as_deref())
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn gitlab_oauth_poll(
+    client_id: String,
+    device_code: String,
+    host: Option<String>,
+) -> Result<GitLabAuthPollResult, GitLiteError> {
+    gitlab_auth::poll_device_flow(&client_id, &device_code, host.as_deref())
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn gitlab_fetch_user(
+    access_token: String,
+    host: Option<String>,
+) -> Result<GitLabUser, GitLiteError> {
+    gitlab_auth::fetch_user(&access_token, host.as_deref())
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn save_gitlab_token(
+    access_token: String,
+    host: Option<String>,
+    refresh: Option<GitLabRefresh>,
+) -> Result<(), GitLiteError> {
+    gitlab_auth::save_token_to_keychain(&access_token, refresh.as_ref(), host.as_deref())
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn load_gitlab_token(host: Option<String>) -> Result<Option<String>, GitLiteError> {
+    gitlab_auth::load_token_from_keychain(host.as_deref()).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn delete_gitlab_token(host: Option<String>) -> Result<(), GitLiteError> {
+    gitlab_auth::delete_token_from_keychain(host.as_deref()).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn save_bitbucket_app_password(
+    workspace: String,
+    username: String,
+    app_password: String,
+) -> Result<BitbucketAccount, GitLiteError> {
+    bitbucket_auth::save_app_password(&workspace, &username, &app_password)
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn load_bitbucket_account(workspace: String) -> Result<Option<BitbucketAccount>, GitLiteError> {
+    bitbucket_auth::load_account(&workspace).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn delete_bitbucket_app_password(workspace: String) -> Result<(), GitLiteError> {
+    bitbucket_auth::delete_app_password(&workspace).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn bitbucket_fetch_user(
+    username: String,
+    app_password: String,
+) -> Result<BitbucketUser, GitLiteError> {
+    bitbucket_auth::fetch_user(&username, &app_password)
+        .await
+        .map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn list_credentials(app: tauri::AppHandle) -> Result<Vec<CredentialEntry>, GitLiteError> {
+    credentials::list_credentials(&app).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn save_credential(
+    app: tauri::AppHandle,
+    host: String,
+    username: String,
+    secret: String,
+) -> Result<CredentialEntry, GitLiteError> {
+    credentials::save_credential(&app, &host, &username, &secret).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn delete_credential(
+    app: tauri::AppHandle,
+    host: String,
+    username: String,
+) -> Result<(), GitLiteError> {
+    credentials::delete_credential(&app, &host, &username).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn store_credentials(
+    url: String,
+    username: String,
+    password: String,
+) -> Result<Vec<HelperStoreResult>, GitLiteError> {
+    credential_helper::store_credentials(&url, &username, &password).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn listen_credential_prompts(enabled: bool) {
+    credential_prompt::listen(enabled);
+}
+
+#[tauri::command]
+fn respond_credentials(id: String, answer: Option<CredentialAnswer>) -> Result<(), GitLiteError> {
+    credential_prompt::respond(&id, answer).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn listen_host_key_prompts(enabled: bool) {
+    known_hosts::listen(enabled);
+}
+
+#[tauri::command]
+fn respond_host_key(id: String, trust: bool) -> Result<(), GitLiteError> {
+    known_hosts::respond(&id, trust).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn import_ca_certificates(
+    app: tauri::AppHandle,
+    pem_path: String,
+) -> Result<CaBundle, GitLiteError> {
+    tls_certs::import_ca_bundle(&app, &pem_path).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn get_ca_certificates(app: tauri::AppHandle) -> Result<Option<CaBundle>, GitLiteError> {
+    tls_certs::get_ca_bundle(&app).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn clear_ca_certificates(app: tauri::AppHandle) -> Result<(), GitLiteError> {
+    tls_certs::clear_ca_bundle(&app).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn get_credential_helper_status() -> Result<CredentialHelperStatus, GitLiteError> {
+    credential_helper::get_status().map_err(GitLiteError::from)
 }
 
 #[tauri::command]
-async fn github_fetch_user(access_token: String) -> Result<GitHubUser, String> {
-    github_auth::fetch_user(&access_token).await
+fn set_credential_helper_enabled(enabled: bool) -> Result<CredentialHelperStatus, GitLiteError> {
+    credential_helper::set_enabled(enabled).map_err(GitLiteError::from)
 }
 
 #[tauri::command]
-fn save_github_token(access_token: String) -> Result<(), String> {
-    github_auth::save_token_to_keychain(&access_token)
+fn record_recent_repository(
+    app: tauri::AppHandle,
+    path: String,
+    name: Option<String>,
+) -> Result<Vec<RecentRepository>, GitLiteError> {
+    recent::record(&app, &path, name).map_err(GitLiteError::from)
 }
 
 #[tauri::command]
-fn load_github_token() -> Result<Option<String>, String> {
-    github_auth::load_token_from_keychain()
+fn register_open_repository(path: String) -> Result<(), GitLiteError> {
+    auto_fetch::register_repository(&path).map_err(GitLiteError::from)
 }
 
 #[tauri::command]
-fn delete_github_token() -> Result<(), String> {
-    github_auth::delete_token_from_keychain()
+fn unregister_open_repository(path: String) {
+    auto_fetch::unregister_repository(&path)
 }
 
 #[tauri::command]
-fn get_runtime_info(app: tauri::AppHandle) -> Result<RuntimeInfo, String> {
-    runtime::get_runtime_info(&app)
+fn get_feature_flags(app: tauri::AppHandle) -> Result<Vec<FeatureFlag>, GitLiteError> {
+    features::get_feature_flags(&app).map_err(GitLiteError::from)
 }
 
 #[tauri::command]
-fn read_runtime_logs(app: tauri::AppHandle, limit: Option<usize>) -> Result<Vec<String>, String> {
-    runtime::read_runtime_logs(&app, limit.unwrap_or(200))
+fn set_feature_flag(
+    app: tauri::AppHandle,
+    name: String,
+    enabled: bool,
+) -> Result<Vec<FeatureFlag>, GitLiteError> {
+    features::set_feature_flag(&app, &name, enabled).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn list_recent_repositories(app: tauri::AppHandle) -> Result<Vec<RecentRepository>, GitLiteError> {
+    recent::list(&app).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn pin_recent_repository(
+    app: tauri::AppHandle,
+    path: String,
+    pinned: bool,
+) -> Result<Vec<RecentRepository>, GitLiteError> {
+    recent::set_pinned(&app, &path, pinned).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn remove_recent_repository(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<Vec<RecentRepository>, GitLiteError> {
+    recent::remove(&app, &path).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn get_repo_notes(app: tauri::AppHandle, path: String) -> Result<RepoNotes, GitLiteError> {
+    notes::get_notes(&app, &path).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn set_repo_notes(
+    app: tauri::AppHandle,
+    path: String,
+    text: String,
+) -> Result<RepoNotes, GitLiteError> {
+    notes::set_notes(&app, &path, &text).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn start_job(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, JobManager>,
+    request: JobRequest,
+) -> String {
+    jobs.enqueue(&app, request)
+}
+
+#[tauri::command]
+fn list_jobs(jobs: tauri::State<'_, JobManager>) -> Vec<JobInfo> {
+    jobs.list()
+}
+
+#[tauri::command]
+fn cancel_job(jobs: tauri::State<'_, JobManager>, id: String) -> Result<JobInfo, GitLiteError> {
+    jobs.cancel(&id).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn list_mirror_jobs(app: tauri::AppHandle) -> Result<Vec<MirrorJob>, GitLiteError> {
+    mirror::list_jobs(&app).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn save_mirror_job(app: tauri::AppHandle, job: MirrorJobInput) -> Result<MirrorJob, GitLiteError> {
+    mirror::save_job(&app, job).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn delete_mirror_job(app: tauri::AppHandle, id: String) -> Result<(), GitLiteError> {
+    mirror::delete_job(&app, &id).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn run_mirror_job(app: tauri::AppHandle, id: String) -> Result<MirrorJob, GitLiteError> {
+    git_pool::run("run_mirror_job", move || mirror::run_job_now(&app, &id)).await
+}
+
+#[tauri::command]
+async fn export_workspace_manifest(
+    name: Option<String>,
+    repos: Vec<WorkspaceRepoInput>,
+    dest: String,
+) -> Result<WorkspaceManifest, GitLiteError> {
+    git_pool::run("export_workspace_manifest", move || {
+        workspace::export_manifest(name, &repos, &dest)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn read_workspace_manifest(manifest_path: String) -> Result<WorkspaceManifest, GitLiteError> {
+    git_pool::run("read_workspace_manifest", move || {
+        workspace::read_manifest(&manifest_path)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn import_workspace_manifest(
+    app: tauri::AppHandle,
+    manifest_path: String,
+    target_dir: String,
+) -> Result<Vec<WorkspaceImportResult>, GitLiteError> {
+    git_pool::run("import_workspace_manifest", move || {
+        workspace::import_manifest(&app, &manifest_path, &target_dir)
+    })
+    .await
+}
+
+#[tauri::command]
+fn get_runtime_info(app: tauri::AppHandle) -> Result<RuntimeInfo, GitLiteError> {
+    runtime::get_runtime_info(&app).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn read_runtime_logs(
+    app: tauri::AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<String>, GitLiteError> {
+    runtime::read_runtime_logs(&app, limit.unwrap_or(200)).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+fn clear_runtime_logs(app: tauri::AppHandle) -> Result<(), GitLiteError> {
+    runtime::clear_runtime_logs(&app).map_err(GitLiteError::from)
+}
+
+#[tauri::command]
+async fn check_environment() -> Result<environment::EnvironmentReport, GitLiteError> {
+    git_pool::run("check_environment", || {
+        Ok::<_, String>(environment::check_environment())
+    })
+    .await
+}
+
+#[tauri::command]
+fn get_performance_report(limit: Option<usize>) -> perf::PerformanceReport {
+    perf::performance_report(limit.unwrap_or(20))
+}
+
+#[tauri::command]
+async fn run_self_test(app: tauri::AppHandle) -> Result<SelfTestReport, GitLiteError> {
+    git_pool::run("run_self_test", move || self_test::run_self_test(&app)).await
+}
+
+/// Run gitlite as a `git credential` helper instead of starting the app.
+/// Returns `None` when the arguments do not request helper mode.
+pub fn run_credential_helper_mode(args: &[String]) -> Option<i32> {
+    if args.get(1).map(String::as_str) != Some(credential_helper::HELPER_ARG) {
+        return None;
+    }
+    let action = args.get(2).map(String::as_str).unwrap_or("");
+    Some(credential_helper::run(action))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .manage(RepoManager::default())
+        .manage(JobManager::default())
+        .manage(CommitStreams::default())
         .setup(|app| {
             if let Err(error) = runtime::init_runtime(app.handle()) {
                 eprintln!("runtime initialization failed: {}", error);
             } else {
                 runtime::append_runtime_log("runtime initialized");
             }
+            let workers = config::read_settings(app.handle())
+                .map(|settings| settings.git_worker_threads)
+                .unwrap_or(git_pool::DEFAULT_WORKERS);
+            git_pool::configure(workers);
+            if let Err(error) = config::migrate_theme_file(app.handle()) {
+                runtime::append_runtime_log(&format!("theme migration failed: {}", error));
+            }
+            credential_prompt::install(app.handle().clone());
+            known_hosts::install(app.handle().clone());
+            tls_certs::init(app.handle());
+            if let Ok(settings) = config::read_settings(app.handle()) {
+                git::prefer_ssh_identity(settings.ssh_identity);
+            }
+            mirror::start_scheduler(app.handle().clone());
+            auto_fetch::start_scheduler(app.handle().clone());
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
@@ -332,53 +1780,176 @@ pub fn run() {
             save_theme,
             load_settings,
             save_settings,
+            get_feature_flags,
+            set_feature_flag,
             get_git_config,
             set_git_config,
             get_commits,
+            get_commits_page,
+            stream_commits,
+            get_author_avatars,
+            cancel_commit_stream,
             get_branches,
+            branches_containing,
+            tags_containing,
             create_branch,
             delete_branch,
             checkout_branch,
+            checkout_pull_request,
             get_commit_diff,
+            load_full_file_diff,
+            get_commit_stats,
+            get_directory_ownership,
+            get_contribution_stats,
+            summarize_diff,
+            get_repo_overview,
+            get_head_state,
+            get_web_url,
+            get_permalink,
             get_status,
+            list_ignored_files,
             stage_files,
+            lock_file,
+            unlock_file,
+            list_file_locks,
             unstage_files,
+            stage_pathspec,
+            unstage_pathspec,
+            set_index_flag,
+            git_move,
+            git_move_many,
             commit_changes,
+            get_gitmoji_table,
+            import_policy_preset,
+            list_policy_presets,
+            delete_policy_preset,
+            set_repo_policy_preset,
+            get_repo_policy,
+            check_commit_policy,
+            apply_patch,
+            restore_index_backup,
             list_stashes,
             create_stash,
             apply_stash,
             drop_stash,
             push_remote,
+            preview_push,
             pull_remote,
             fetch_remote,
             merge_branch,
+            get_external_tools,
+            open_in_editor,
+            reveal_in_file_manager,
+            launch_merge_tool,
+            mark_conflict_resolved,
+            preview_merge,
+            abort_operation,
             cherry_pick_commit,
             reset_current_branch,
             create_branch_from_commit,
             checkout_commit,
             revert_commit,
+            reword_commit,
+            amend_commit_metadata,
+            export_archive,
             detect_ssh_keys,
             push_ssh,
             pull_ssh,
             fetch_ssh,
+            list_ssh_agent_keys,
+            save_ssh_passphrase,
+            has_ssh_passphrase,
+            delete_ssh_passphrase,
             list_remotes,
             add_remote,
             remove_remote,
             rename_remote,
             set_remote_url,
+            set_remote_push_url,
+            configure_fork_remotes,
+            get_use_github_token,
+            set_use_github_token,
+            get_fetch_refspecs,
+            add_fetch_refspec,
+            remove_fetch_refspec,
             sync_status,
             is_git_repository,
+            is_bare_repository,
+            get_repo_kind,
+            check_repository,
+            repo_maintenance,
+            run_repo_maintenance,
             git_init,
+            list_init_templates,
+            scan_for_repositories,
             pick_repository_folder,
             github_oauth_start,
             github_oauth_poll,
             github_fetch_user,
+            github_check_token,
+            github_list_repos,
+            github_rate_limit,
+            github_list_pull_requests,
+            github_create_pull_request,
+            github_get_commit_status,
+            github_fork_repo,
+            github_create_release,
             save_github_token,
             load_github_token,
             delete_github_token,
+            gitlab_oauth_start,
+            gitlab_oauth_poll,
+            gitlab_fetch_user,
+            save_gitlab_token,
+            load_gitlab_token,
+            delete_gitlab_token,
+            save_bitbucket_app_password,
+            load_bitbucket_account,
+            delete_bitbucket_app_password,
+            bitbucket_fetch_user,
+            list_credentials,
+            save_credential,
+            delete_credential,
+            store_credentials,
+            listen_credential_prompts,
+            respond_credentials,
+            listen_host_key_prompts,
+            respond_host_key,
+            import_ca_certificates,
+            get_ca_certificates,
+            clear_ca_certificates,
+            get_credential_helper_status,
+            set_credential_helper_enabled,
+            record_recent_repository,
+            list_recent_repositories,
+            register_open_repository,
+            unregister_open_repository,
+            pin_recent_repository,
+            remove_recent_repository,
+            get_repo_notes,
+            set_repo_notes,
+            start_job,
+            list_jobs,
+            cancel_job,
+            list_mirror_jobs,
+            save_mirror_job,
+            delete_mirror_job,
+            run_mirror_job,
+            export_workspace_manifest,
+            read_workspace_manifest,
+            import_workspace_manifest,
             get_runtime_info,
-            read_runtime_logs
+            read_runtime_logs,
+            clear_runtime_logs,
+            get_performance_report,
+            check_environment,
+            run_self_test
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { code, api, .. } = event {
+                shutdown::on_exit_requested(app, code, &api);
+            }
+        });
 }
@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(code) = gitlite_lib::run_credential_helper_mode(&args) {
+        std::process::exit(code);
+    }
     gitlite_lib::run()
 }
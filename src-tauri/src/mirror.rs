@@ -0,0 +1,322 @@
+use crate::json_store::{unix_now, JsonStore};
+use crate::{git, perf};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+
+const MIRROR_JOBS_FILENAME: &str = "mirror_jobs.json";
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+const MIN_INTERVAL_MINUTES: u32 = 5;
+
+/// Event emitted after every run with the updated `MirrorJob`.
+pub const MIRROR_JOB_FINISHED_EVENT: &str = "mirror-job-finished";
+/// Event emitted (in addition to the finished event) when a run fails.
+pub const MIRROR_JOB_FAILED_EVENT: &str = "mirror-job-failed";
+
+// Serializes read-modify-write cycles on the job file across the scheduler
+// and IPC commands.
+static JOBS_STORE: JsonStore<Vec<MirrorJob>> =
+    JsonStore::new(MIRROR_JOBS_FILENAME, "E_MIRROR_JOB_STORE");
+static RUNNING_JOBS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MirrorJob {
+    pub id: String,
+    pub repo_path: String,
+    pub source_remote: String,
+    pub destination_remote: String,
+    pub interval_minutes: u32,
+    pub enabled: bool,
+    pub last_run_at: Option<i64>,
+    pub last_status: Option<String>, // "success" | "failed"
+    pub last_error: Option<String>,
+    pub last_summary: Option<git::MirrorSummary>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MirrorJobInput {
+    pub id: Option<String>,
+    pub repo_path: String,
+    pub source_remote: String,
+    pub destination_remote: String,
+    pub interval_minutes: u32,
+    pub enabled: bool,
+}
+
+pub fn list_jobs(app: &tauri::AppHandle) -> Result<Vec<MirrorJob>, String> {
+    let _guard = JOBS_STORE.lock();
+    JOBS_STORE.read(&JOBS_STORE.path(app)?)
+}
+
+/// Create a job (no `id`) or update the configuration of an existing one.
+/// Run history is preserved on update.
+pub fn save_job(app: &tauri::AppHandle, input: MirrorJobInput) -> Result<MirrorJob, String> {
+    let _guard = JOBS_STORE.lock();
+    save_job_at(&JOBS_STORE.path(app)?, input)
+}
+
+pub fn delete_job(app: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    let _guard = JOBS_STORE.lock();
+    let jobs_path = JOBS_STORE.path(app)?;
+    let mut jobs = JOBS_STORE.read(&jobs_path)?;
+    let before = jobs.len();
+    jobs.retain(|job| job.id != id);
+    if jobs.len() == before {
+        return Err(format!("E_MIRROR_JOB_NOT_FOUND: {}", id));
+    }
+    JOBS_STORE.write(&jobs_path, &jobs)
+}
+
+/// Run a job immediately, regardless of its schedule or enabled flag.
+pub fn run_job_now(app: &tauri::AppHandle, id: &str) -> Result<MirrorJob, String> {
+    let job = {
+        let _guard = JOBS_STORE.lock();
+        JOBS_STORE
+            .read(&JOBS_STORE.path(app)?)?
+            .into_iter()
+            .find(|job| job.id == id)
+            .ok_or_else(|| format!("E_MIRROR_JOB_NOT_FOUND: {}", id))?
+    };
+    execute_job(app, job)
+}
+
+/// Spawn the background loop that runs due jobs. Called once from `setup`.
+pub fn start_scheduler(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SCHEDULER_TICK).await;
+
+            let due = match list_jobs(&app) {
+                Ok(jobs) => due_jobs(&jobs, unix_now()),
+                Err(error) => {
+                    crate::runtime::append_runtime_log(&format!(
+                        "mirror scheduler failed to load jobs: {}",
+                        error
+                    ));
+                    continue;
+                }
+            };
+
+            for job in due {
+                let app = app.clone();
+                let _ = tauri::async_runtime::spawn_blocking(move || {
+                    perf::time("mirror_job", Duration::ZERO, || execute_job(&app, job))
+                })
+                .await;
+            }
+        }
+    });
+}
+
+fn execute_job(app: &tauri::AppHandle, job: MirrorJob) -> Result<MirrorJob, String> {
+    let _operation = crate::shutdown::track("mirror", &job.repo_path)?;
+    if !mark_running(&job.id) {
+        return Err(format!("E_MIRROR_JOB_RUNNING: {}", job.id));
+    }
+
+    crate::runtime::append_runtime_log(&format!(
+        "mirror job {} started repo={} {} -> {}",
+        job.id, job.repo_path, job.source_remote, job.destination_remote
+    ));
+    let result = git::mirror_remote(&job.repo_path, &job.source_remote, &job.destination_remote);
+    clear_running(&job.id);
+
+    match &result {
+        Ok(summary) => crate::runtime::append_runtime_log(&format!(
+            "mirror job {} succeeded updated={} deleted={}",
+            job.id, summary.updated_refs, summary.deleted_refs
+        )),
+        Err(error) => {
+            crate::runtime::append_runtime_log(&format!("mirror job {} failed: {}", job.id, error))
+        }
+    }
+
+    let updated = {
+        let _guard = JOBS_STORE.lock();
+        let jobs_path = JOBS_STORE.path(app)?;
+        let mut jobs = JOBS_STORE.read(&jobs_path)?;
+        let Some(stored) = jobs.iter_mut().find(|stored| stored.id == job.id) else {
+            // Deleted while running; nothing to record.
+            return result.map(|_| job);
+        };
+        record_run(stored, &result, unix_now());
+        let updated = stored.clone();
+        JOBS_STORE.write(&jobs_path, &jobs)?;
+        updated
+    };
+
+    let _ = app.emit(MIRROR_JOB_FINISHED_EVENT, &updated);
+    if result.is_err() {
+        let _ = app.emit(MIRROR_JOB_FAILED_EVENT, &updated);
+    }
+
+    result.map(|_| updated)
+}
+
+fn save_job_at(jobs_path: &Path, input: MirrorJobInput) -> Result<MirrorJob, String> {
+    let source_remote = input.source_remote.trim().to_string();
+    let destination_remote = input.destination_remote.trim().to_string();
+    if input.repo_path.trim().is_empty()
+        || source_remote.is_empty()
+        || destination_remote.is_empty()
+    {
+        return Err(
+            "E_MIRROR_JOB_INVALID: repository, source and destination are required".to_string(),
+        );
+    }
+    if source_remote == destination_remote {
+        return Err(
+            "E_MIRROR_JOB_INVALID: source and destination must be different remotes".to_string(),
+        );
+    }
+    if input.interval_minutes < MIN_INTERVAL_MINUTES {
+        return Err(format!(
+            "E_MIRROR_JOB_INVALID: interval must be at least {} minutes",
+            MIN_INTERVAL_MINUTES
+        ));
+    }
+
+    let mut jobs = JOBS_STORE.read(jobs_path)?;
+    let job = match input.id.as_deref() {
+        Some(id) => {
+            let existing = jobs
+                .iter_mut()
+                .find(|job| job.id == id)
+                .ok_or_else(|| format!("E_MIRROR_JOB_NOT_FOUND: {}", id))?;
+            existing.repo_path = input.repo_path;
+            existing.source_remote = source_remote;
+            existing.destination_remote = destination_remote;
+            existing.interval_minutes = input.interval_minutes;
+            existing.enabled = input.enabled;
+            existing.clone()
+        }
+        None => {
+            let job = MirrorJob {
+                id: uuid::Uuid::new_v4().to_string(),
+                repo_path: input.repo_path,
+                source_remote,
+                destination_remote,
+                interval_minutes: input.interval_minutes,
+                enabled: input.enabled,
+                last_run_at: None,
+                last_status: None,
+                last_error: None,
+                last_summary: None,
+            };
+            jobs.push(job.clone());
+            job
+        }
+    };
+
+    JOBS_STORE.write(jobs_path, &jobs)?;
+    Ok(job)
+}
+
+fn due_jobs(jobs: &[MirrorJob], now: i64) -> Vec<MirrorJob> {
+    jobs.iter()
+        .filter(|job| job.enabled)
+        .filter(|job| {
+            job.last_run_at
+                .is_none_or(|last| now - last >= i64::from(job.interval_minutes) * 60)
+        })
+        .cloned()
+        .collect()
+}
+
+fn record_run(job: &mut MirrorJob, result: &Result<git::MirrorSummary, String>, now: i64) {
+    job.last_run_at = Some(now);
+    match result {
+        Ok(summary) => {
+            job.last_status = Some("success".to_string());
+            job.last_error = None;
+            job.last_summary = Some(summary.clone());
+        }
+        Err(error) => {
+            job.last_status = Some("failed".to_string());
+            job.last_error = Some(error.clone());
+        }
+    }
+}
+
+fn mark_running(id: &str) -> bool {
+    let mut running = RUNNING_JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    running
+        .get_or_insert_with(HashSet::new)
+        .insert(id.to_string())
+}
+
+fn clear_running(id: &str) {
+    let mut running = RUNNING_JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(running) = running.as_mut() {
+        running.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn input(id: Option<String>, interval_minutes: u32) -> MirrorJobInput {
+        MirrorJobInput {
+            id,
+            repo_path: "/srv/repo".to_string(),
+            source_remote: "origin".to_string(),
+            destination_remote: "backup".to_string(),
+            interval_minutes,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn save_job_creates_then_updates_preserving_history() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-mirror-jobs-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let jobs_path = dir.join(MIRROR_JOBS_FILENAME);
+
+        let created = save_job_at(&jobs_path, input(None, 30)).unwrap();
+        let mut jobs = JOBS_STORE.read(&jobs_path).unwrap();
+        record_run(&mut jobs[0], &Err("E_MIRROR_PUSH: boom".to_string()), 1_000);
+        JOBS_STORE.write(&jobs_path, &jobs).unwrap();
+
+        let updated = save_job_at(&jobs_path, input(Some(created.id.clone()), 60)).unwrap();
+        assert_eq!(updated.id, created.id);
+        assert_eq!(updated.interval_minutes, 60);
+        assert_eq!(updated.last_status.as_deref(), Some("failed"));
+        assert_eq!(JOBS_STORE.read(&jobs_path).unwrap().len(), 1);
+
+        assert!(save_job_at(&jobs_path, input(None, 1))
+            .unwrap_err()
+            .starts_with("E_MIRROR_JOB_INVALID"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn due_jobs_respects_interval_and_enabled_flag() {
+        let mut job = MirrorJob {
+            id: "a".to_string(),
+            repo_path: "/srv/repo".to_string(),
+            source_remote: "origin".to_string(),
+            destination_remote: "backup".to_string(),
+            interval_minutes: 10,
+            enabled: true,
+            last_run_at: None,
+            last_status: None,
+            last_error: None,
+            last_summary: None,
+        };
+        assert_eq!(due_jobs(std::slice::from_ref(&job), 0).len(), 1);
+
+        job.last_run_at = Some(1_000);
+        assert!(due_jobs(std::slice::from_ref(&job), 1_000 + 599).is_empty());
+        assert_eq!(due_jobs(std::slice::from_ref(&job), 1_000 + 600).len(), 1);
+
+        job.enabled = false;
+        assert!(due_jobs(std::slice::from_ref(&job), 10_000).is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::json_store::{unix_now, JsonStore};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+const REPO_NOTES_FILENAME: &str = "repo_notes.json";
+const MAX_NOTES_BYTES: usize = 1024 * 1024;
+
+// Serializes read-modify-write cycles on the notes file.
+static NOTES_STORE: JsonStore<BTreeMap<String, RepoNotes>> =
+    JsonStore::new(REPO_NOTES_FILENAME, "E_REPO_NOTES_STORE");
+
+/// Markdown scratchpad for one repository. Kept in the app data dir so it
+/// never shows up in the worktree.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct RepoNotes {
+    pub text: String,
+    pub updated_at: Option<i64>,
+}
+
+pub fn get_notes(app: &tauri::AppHandle, path: &str) -> Result<RepoNotes, String> {
+    let _guard = NOTES_STORE.lock();
+    let notes = NOTES_STORE.read(&NOTES_STORE.path(app)?)?;
+    Ok(notes.get(&notes_key(path)).cloned().unwrap_or_default())
+}
+
+/// Replace the notes for `path`. Saving blank text removes the entry.
+pub fn set_notes(app: &tauri::AppHandle, path: &str, text: &str) -> Result<RepoNotes, String> {
+    let _guard = NOTES_STORE.lock();
+    set_notes_at(&NOTES_STORE.path(app)?, path, text, unix_now())
+}
+
+fn set_notes_at(notes_path: &Path, path: &str, text: &str, now: i64) -> Result<RepoNotes, String> {
+    if path.trim().is_empty() {
+        return Err("E_REPO_NOTES_INVALID: repository path is required".to_string());
+    }
+    if text.len() > MAX_NOTES_BYTES {
+        return Err(format!(
+            "E_REPO_NOTES_TOO_LARGE: notes are limited to {} bytes",
+            MAX_NOTES_BYTES
+        ));
+    }
+
+    let mut notes = NOTES_STORE.read(notes_path)?;
+    let key = notes_key(path);
+    let entry = if text.trim().is_empty() {
+        notes.remove(&key);
+        RepoNotes::default()
+    } else {
+        let entry = RepoNotes {
+            text: text.to_string(),
+            updated_at: Some(now),
+        };
+        notes.insert(key, entry.clone());
+        entry
+    };
+    NOTES_STORE.write(notes_path, &notes)?;
+    Ok(entry)
+}
+
+fn notes_key(path: &str) -> String {
+    fs::canonicalize(path)
+        .map(|canonical| canonical.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| path.trim_end_matches(['/', '\\']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_notes_persists_per_repository_and_clears_on_blank() {
+        let dir = std::env::temp_dir().join(format!("gitlite-repo-notes-{}", uuid::Uuid::new_v4()));
+        let repo_dir = dir.join("repo");
+        fs::create_dir_all(&repo_dir).unwrap();
+        let notes_path = dir.join(REPO_NOTES_FILENAME);
+        let path = repo_dir.to_str().unwrap();
+
+        let saved = set_notes_at(&notes_path, path, "- [ ] ship it", 100).unwrap();
+        assert_eq!(saved.updated_at, Some(100));
+        set_notes_at(&notes_path, "/other/repo", "other", 200).unwrap();
+
+        let stored = NOTES_STORE.read(&notes_path).unwrap();
+        assert_eq!(stored.get(&notes_key(&format!("{}/", path))), Some(&saved));
+        assert_eq!(stored.len(), 2);
+        assert!(fs::read_dir(&repo_dir).unwrap().next().is_none());
+
+        assert_eq!(
+            set_notes_at(&notes_path, path, "  \n", 300).unwrap(),
+            RepoNotes::default()
+        );
+        assert_eq!(NOTES_STORE.read(&notes_path).unwrap().len(), 1);
+
+        let too_large = "x".repeat(MAX_NOTES_BYTES + 1);
+        assert!(set_notes_at(&notes_path, path, &too_large, 400)
+            .unwrap_err()
+            .starts_with("E_REPO_NOTES_TOO_LARGE"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,258 @@
+//! What the GitHub and GitLab device flows (RFC 8628) have in common.
+
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+
+pub const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const KEYCHAIN_ACCOUNT: &str = "oauth-token";
+
+/// OAuth and REST endpoints of one forge host.
+#[derive(Debug, PartialEq)]
+pub struct Endpoints {
+    pub host: String,
+    pub device_code_url: String,
+    pub access_token_url: String,
+    pub api_base: String,
+}
+
+impl Endpoints {
+    pub fn user_url(&self) -> String {
+        self.api_url("/user")
+    }
+
+    pub fn api_url(&self, path: &str) -> String {
+        format!("{}{}", self.api_base, path)
+    }
+
+    /// Keychain account of this host's token.
+    pub fn keychain_account(&self) -> String {
+        format!("{}@{}", KEYCHAIN_ACCOUNT, self.host)
+    }
+}
+
+/// `host` reduced to a bare host name, or `default_host` when it is blank.
+/// An invalid host is reported as `code`.
+pub fn resolve_host(host: Option<&str>, default_host: &str, code: &str) -> Result<String, String> {
+    match host.map(str::trim).filter(|host| !host.is_empty()) {
+        None => Ok(default_host.to_string()),
+        Some(host) => crate::credentials::normalize_host(host)
+            .map_err(|_| format!("{}: '{}' is not a host", code, host)),
+    }
+}
+
+/// Error codes and display name one forge reports its device flow under.
+pub struct FlowCodes {
+    pub forge: &'static str,
+    /// Full message for a blank client ID, code included.
+    pub client_id_missing: &'static str,
+    pub device_code_empty: &'static str,
+    pub network: &'static str,
+    pub start_failed: &'static str,
+    pub parse: &'static str,
+    pub poll_invalid: &'static str,
+    pub poll_error: &'static str,
+}
+
+pub fn normalize_client_id(client_id: &str, missing: &str) -> Result<String, String> {
+    let normalized = client_id.trim();
+    if normalized.is_empty() {
+        return Err(missing.to_string());
+    }
+    Ok(normalized.to_string())
+}
+
+pub fn normalize_device_code<'a>(
+    device_code: &'a str,
+    codes: &FlowCodes,
+) -> Result<&'a str, String> {
+    let normalized = device_code.trim();
+    if normalized.is_empty() {
+        return Err(format!(
+            "{}: Device code is required",
+            codes.device_code_empty
+        ));
+    }
+    Ok(normalized)
+}
+
+#[derive(Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    interval: Option<u64>,
+}
+
+/// Codes to show the user while the app polls for the token.
+#[derive(Serialize)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    pub interval: u64,
+}
+
+/// Read the answer to a device authorization request.
+pub async fn device_code(
+    response: reqwest::Result<Response>,
+    codes: &FlowCodes,
+) -> Result<DeviceCode, String> {
+    let response = response.map_err(|error| format!("{}: {}", codes.network, error))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unable to read response body".to_string());
+        return Err(format!(
+            "{}: {} returned {} ({})",
+            codes.start_failed,
+            codes.forge,
+            status.as_u16(),
+            body
+        ));
+    }
+
+    let payload: DeviceCodeResponse = response
+        .json()
+        .await
+        .map_err(|error| format!("{}: {}", codes.parse, error))?;
+
+    Ok(DeviceCode {
+        device_code: payload.device_code,
+        user_code: payload.user_code,
+        verification_uri: payload.verification_uri,
+        verification_uri_complete: payload.verification_uri_complete,
+        expires_in: payload.expires_in,
+        interval: payload.interval.unwrap_or(5),
+    })
+}
+
+/// Token endpoint answer for both the device and the refresh grant.
+#[derive(Deserialize)]
+pub struct AccessTokenResponse {
+    pub access_token: Option<String>,
+    pub token_type: Option<String>,
+    pub refresh_token: Option<String>,
+    pub expires_in: Option<u64>,
+    pub scope: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+    pub interval: Option<u64>,
+}
+
+/// Read a token endpoint answer. Pending, denied and invalid-grant states
+/// may come back as 400 responses with the standard RFC 8628 error body, so
+/// only an unparsable body counts as `failure_code`.
+pub async fn token_response(
+    response: reqwest::Result<Response>,
+    failure_code: &str,
+    codes: &FlowCodes,
+) -> Result<AccessTokenResponse, String> {
+    let response = response.map_err(|error| format!("{}: {}", codes.network, error))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|error| format!("{}: {}", codes.network, error))?;
+    match serde_json::from_str(&body) {
+        Ok(payload) => Ok(payload),
+        Err(_) if !status.is_success() => Err(format!(
+            "{}: {} returned {} ({})",
+            failure_code,
+            codes.forge,
+            status.as_u16(),
+            body
+        )),
+        Err(error) => Err(format!("{}: {}", codes.parse, error)),
+    }
+}
+
+/// Poll status and retry interval for a token answer that carries no
+/// access token.
+pub fn waiting_status(
+    payload: &AccessTokenResponse,
+    codes: &FlowCodes,
+) -> Result<(&'static str, Option<u64>), String> {
+    let Some(error_code) = payload.error.as_deref() else {
+        return Err(format!(
+            "{}: Missing access_token and error",
+            codes.poll_invalid
+        ));
+    };
+
+    match error_code {
+        "authorization_pending" => Ok(("pending", payload.interval)),
+        "slow_down" => Ok(("slow_down", payload.interval)),
+        "expired_token" => Ok(("expired", None)),
+        "access_denied" => Ok(("denied", None)),
+        _ => Err(format!(
+            "{}: {} ({})",
+            codes.poll_error,
+            error_code,
+            payload.error_description.as_deref().unwrap_or_default()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODES: FlowCodes = FlowCodes {
+        forge: "GitHub",
+        client_id_missing: "E_GITHUB_CLIENT_ID_MISSING: GitHub OAuth Client ID is required",
+        device_code_empty: "E_GITHUB_OAUTH_DEVICE_CODE_EMPTY",
+        network: "E_GITHUB_OAUTH_NETWORK",
+        start_failed: "E_GITHUB_OAUTH_START_FAILED",
+        parse: "E_GITHUB_OAUTH_PARSE",
+        poll_invalid: "E_GITHUB_OAUTH_POLL_INVALID",
+        poll_error: "E_GITHUB_OAUTH_POLL_ERROR",
+    };
+
+    #[test]
+    fn poll_errors_map_to_waiting_states() {
+        let payload = |error: Option<&str>| AccessTokenResponse {
+            access_token: None,
+            token_type: None,
+            refresh_token: None,
+            expires_in: None,
+            scope: None,
+            error: error.map(str::to_string),
+            error_description: Some("nope".to_string()),
+            interval: Some(10),
+        };
+
+        assert_eq!(
+            waiting_status(&payload(Some("authorization_pending")), &CODES),
+            Ok(("pending", Some(10)))
+        );
+        assert_eq!(
+            waiting_status(&payload(Some("slow_down")), &CODES),
+            Ok(("slow_down", Some(10)))
+        );
+        assert_eq!(
+            waiting_status(&payload(Some("expired_token")), &CODES),
+            Ok(("expired", None))
+        );
+        assert_eq!(
+            waiting_status(&payload(Some("access_denied")), &CODES),
+            Ok(("denied", None))
+        );
+        assert_eq!(
+            waiting_status(&payload(Some("unsupported_grant_type")), &CODES),
+            Err("E_GITHUB_OAUTH_POLL_ERROR: unsupported_grant_type (nope)".to_string())
+        );
+        assert!(waiting_status(&payload(None), &CODES)
+            .unwrap_err()
+            .starts_with("E_GITHUB_OAUTH_POLL_INVALID"));
+        assert_eq!(
+            normalize_client_id("  ", CODES.client_id_missing),
+            Err(CODES.client_id_missing.to_string())
+        );
+    }
+}
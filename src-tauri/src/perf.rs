@@ -0,0 +1,165 @@
+use crate::runtime::append_runtime_log;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Timings kept in memory; older ones are dropped.
+const RING_CAPACITY: usize = 500;
+/// Operations at least this slow are also written to the runtime log.
+const LOG_THRESHOLD: Duration = Duration::from_millis(250);
+
+static RECENT: Mutex<VecDeque<OperationTiming>> = Mutex::new(VecDeque::new());
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct OperationTiming {
+    /// Command that ran the work, e.g. `get_commits_page`.
+    pub operation: String,
+    pub duration_ms: u64,
+    /// Time spent waiting for a free git worker before running.
+    pub queued_ms: u64,
+    pub ok: bool,
+    /// Unix seconds.
+    pub finished_at: i64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct OperationStats {
+    pub operation: String,
+    pub count: usize,
+    pub mean_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PerformanceReport {
+    /// Timings currently held (at most 500).
+    pub recorded: usize,
+    /// Slowest recent runs, slowest first.
+    pub slowest: Vec<OperationTiming>,
+    /// Per-operation totals over the recorded runs, by mean duration.
+    pub operations: Vec<OperationStats>,
+}
+
+/// Record one finished operation.
+pub fn record(operation: &str, duration: Duration, queued: Duration, ok: bool) {
+    let timing = OperationTiming {
+        operation: operation.to_string(),
+        duration_ms: duration.as_millis() as u64,
+        queued_ms: queued.as_millis() as u64,
+        ok,
+        finished_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or(0),
+    };
+    if duration >= LOG_THRESHOLD {
+        append_runtime_log(&format!(
+            "timing operation={} ms={} queued_ms={} ok={}",
+            timing.operation, timing.duration_ms, timing.queued_ms, ok
+        ));
+    }
+    let mut recent = RECENT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if recent.len() == RING_CAPACITY {
+        recent.pop_front();
+    }
+    recent.push_back(timing);
+}
+
+pub fn performance_report(limit: usize) -> PerformanceReport {
+    let recent = RECENT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    build_report(recent.iter(), limit)
+}
+
+fn build_report<'a>(
+    timings: impl Iterator<Item = &'a OperationTiming>,
+    limit: usize,
+) -> PerformanceReport {
+    let mut slowest: Vec<OperationTiming> = timings.cloned().collect();
+    let recorded = slowest.len();
+
+    let mut totals: BTreeMap<&str, (usize, u64, u64)> = BTreeMap::new();
+    for timing in &slowest {
+        let entry = totals.entry(&timing.operation).or_default();
+        entry.0 += 1;
+        entry.1 += timing.duration_ms;
+        entry.2 = entry.2.max(timing.duration_ms);
+    }
+    let mut operations: Vec<OperationStats> = totals
+        .into_iter()
+        .map(|(operation, (count, total, max_ms))| OperationStats {
+            operation: operation.to_string(),
+            count,
+            mean_ms: total / count as u64,
+            max_ms,
+        })
+        .collect();
+    operations.sort_by_key(|stats| Reverse(stats.mean_ms));
+
+    slowest.sort_by_key(|timing| Reverse(timing.duration_ms));
+    slowest.truncate(limit);
+    PerformanceReport {
+        recorded,
+        slowest,
+        operations,
+    }
+}
+
+/// Run `f` and record it under `operation`, for blocking work that runs
+/// outside `git_pool` (jobs, streams, schedulers). `queued` is how long it
+/// waited to start.
+pub fn time<T, E>(
+    operation: &str,
+    queued: Duration,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let started_at = Instant::now();
+    let result = f();
+    record(operation, started_at.elapsed(), queued, result.is_ok());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(operation: &str, duration_ms: u64) -> OperationTiming {
+        OperationTiming {
+            operation: operation.to_string(),
+            duration_ms,
+            queued_ms: 0,
+            ok: true,
+            finished_at: 0,
+        }
+    }
+
+    #[test]
+    fn report_lists_slowest_runs_and_per_operation_means() {
+        let timings = [
+            timing("get_status", 10),
+            timing("get_commits", 900),
+            timing("get_status", 30),
+            timing("fetch", 400),
+        ];
+        let report = build_report(timings.iter(), 2);
+        assert_eq!(report.recorded, 4);
+        let slowest: Vec<&str> = report
+            .slowest
+            .iter()
+            .map(|t| t.operation.as_str())
+            .collect();
+        assert_eq!(slowest, ["get_commits", "fetch"]);
+        let status = report
+            .operations
+            .iter()
+            .find(|stats| stats.operation == "get_status")
+            .unwrap();
+        assert_eq!((status.count, status.mean_ms, status.max_ms), (2, 20, 30));
+        assert_eq!(report.operations[0].operation, "get_commits");
+    }
+}
@@ -0,0 +1,158 @@
+use crate::git::{self, PolicyPreset, PolicyViolation};
+use crate::json_store::JsonStore;
+use std::fs;
+use std::path::Path;
+
+const POLICY_PRESETS_FILENAME: &str = "policy_presets.json";
+
+// Serializes read-modify-write cycles on the preset file.
+static PRESETS_STORE: JsonStore<Vec<PolicyPreset>> =
+    JsonStore::new(POLICY_PRESETS_FILENAME, "E_POLICY_PRESET_STORE");
+
+/// Import a preset from a JSON file, replacing any preset with the same name.
+pub fn import_preset(app: &tauri::AppHandle, file_path: &str) -> Result<PolicyPreset, String> {
+    let content =
+        fs::read_to_string(file_path).map_err(|e| format!("E_POLICY_PRESET_READ: {}", e))?;
+    let preset: PolicyPreset =
+        serde_json::from_str(&content).map_err(|e| format!("E_POLICY_INVALID: {}", e))?;
+    let _guard = PRESETS_STORE.lock();
+    save_preset_at(&PRESETS_STORE.path(app)?, preset)
+}
+
+pub fn list_presets(app: &tauri::AppHandle) -> Result<Vec<PolicyPreset>, String> {
+    let _guard = PRESETS_STORE.lock();
+    PRESETS_STORE.read(&PRESETS_STORE.path(app)?)
+}
+
+pub fn delete_preset(app: &tauri::AppHandle, name: &str) -> Result<(), String> {
+    let _guard = PRESETS_STORE.lock();
+    let presets_path = PRESETS_STORE.path(app)?;
+    let mut presets = PRESETS_STORE.read(&presets_path)?;
+    let before = presets.len();
+    presets.retain(|preset| preset.name != name);
+    if presets.len() == before {
+        return Err(format!("E_POLICY_PRESET_NOT_FOUND: {}", name));
+    }
+    PRESETS_STORE.write(&presets_path, &presets)
+}
+
+/// Point `path` at an imported preset (or clear it with `None`).
+pub fn assign_preset(app: &tauri::AppHandle, path: &str, name: Option<&str>) -> Result<(), String> {
+    if let Some(name) = name.map(str::trim).filter(|name| !name.is_empty()) {
+        let known = list_presets(app)?.iter().any(|preset| preset.name == name);
+        if !known {
+            return Err(format!("E_POLICY_PRESET_NOT_FOUND: {}", name));
+        }
+    }
+    git::set_repo_policy_preset(path, name)
+}
+
+/// What the repository's `gitlite.policyPreset` points at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepoPreset {
+    Unset,
+    Imported(PolicyPreset),
+    /// Not imported here (deleted, or assigned on another machine). Callers
+    /// warn instead of blocking.
+    Missing(String),
+}
+
+impl RepoPreset {
+    /// Advisory entry for `check_commit_policy` when the preset is missing.
+    pub fn missing_warning(&self) -> Option<PolicyViolation> {
+        let RepoPreset::Missing(name) = self else {
+            return None;
+        };
+        Some(PolicyViolation {
+            rule: "preset_missing".to_string(),
+            message: format!(
+                "policy preset '{}' is not imported; commits are not checked",
+                name
+            ),
+            path: None,
+        })
+    }
+}
+
+pub fn preset_for_repo(app: &tauri::AppHandle, path: &str) -> Result<RepoPreset, String> {
+    let Some(name) = git::get_repo_policy_preset(path)? else {
+        return Ok(RepoPreset::Unset);
+    };
+    Ok(resolve_preset(list_presets(app)?, name))
+}
+
+fn resolve_preset(presets: Vec<PolicyPreset>, name: String) -> RepoPreset {
+    presets
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .map_or(RepoPreset::Missing(name), RepoPreset::Imported)
+}
+
+fn save_preset_at(presets_path: &Path, preset: PolicyPreset) -> Result<PolicyPreset, String> {
+    let mut preset = preset;
+    preset.name = preset.name.trim().to_string();
+    git::validate_preset(&preset)?;
+
+    let mut presets = PRESETS_STORE.read(presets_path)?;
+    match presets
+        .iter_mut()
+        .find(|existing| existing.name == preset.name)
+    {
+        Some(existing) => *existing = preset.clone(),
+        None => presets.push(preset.clone()),
+    }
+    presets.sort_by(|a, b| a.name.cmp(&b.name));
+    PRESETS_STORE.write(presets_path, &presets)?;
+    Ok(preset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_preset_replaces_by_name_and_rejects_bad_patterns() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-policy-presets-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let presets_path = dir.join(POLICY_PRESETS_FILENAME);
+
+        let preset: PolicyPreset = serde_json::from_str(
+            r#"{"name": " acme ", "protected_branches": ["main"], "sign_off": true}"#,
+        )
+        .unwrap();
+        save_preset_at(&presets_path, preset.clone()).unwrap();
+        let mut updated = preset;
+        updated.max_file_size_bytes = Some(1_000_000);
+        save_preset_at(&presets_path, updated).unwrap();
+
+        let stored = PRESETS_STORE.read(&presets_path).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].name, "acme");
+        assert_eq!(stored[0].max_file_size_bytes, Some(1_000_000));
+        assert!(stored[0].commit_message.subject_pattern.is_none());
+
+        let mut invalid = stored[0].clone();
+        invalid.commit_message.subject_pattern = Some("(".to_string());
+        assert!(save_preset_at(&presets_path, invalid)
+            .unwrap_err()
+            .starts_with("E_POLICY_INVALID"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_preset_is_a_warning() {
+        let preset: PolicyPreset = serde_json::from_str(r#"{"name": "acme"}"#).unwrap();
+        assert_eq!(
+            resolve_preset(vec![preset.clone()], "acme".to_string()),
+            RepoPreset::Imported(preset.clone())
+        );
+        let missing = resolve_preset(vec![preset], "gone".to_string());
+        assert_eq!(missing, RepoPreset::Missing("gone".to_string()));
+        let warning = missing.missing_warning().unwrap();
+        assert_eq!(warning.rule, "preset_missing");
+        assert!(warning.message.contains("'gone'"));
+        assert!(RepoPreset::Unset.missing_warning().is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::json_store::{unix_now, JsonStore};
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const RECENT_REPOS_FILENAME: &str = "recent_repos.json";
+
+// Serializes read-modify-write cycles on the registry file.
+static RECENT_STORE: JsonStore<Vec<RecentRepository>> =
+    JsonStore::new(RECENT_REPOS_FILENAME, "E_RECENT_REPO_STORE");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecentRepository {
+    pub path: String,
+    pub name: String,
+    pub last_opened: i64,
+    pub pinned: bool,
+    /// Recomputed on every read; not meaningful in the stored file.
+    #[serde(default)]
+    pub exists: bool,
+}
+
+/// Record that `path` was opened, moving it to the top of the list. The list
+/// is then pruned to `max_recent_repos` from the settings (pinned entries are
+/// always kept).
+pub fn record(
+    app: &tauri::AppHandle,
+    path: &str,
+    name: Option<String>,
+) -> Result<Vec<RecentRepository>, String> {
+    let max = crate::config::read_settings(app)
+        .map(|config| config.max_recent_repos as usize)
+        .unwrap_or(10);
+    let _guard = RECENT_STORE.lock();
+    record_at(&RECENT_STORE.path(app)?, path, name, max, unix_now())
+}
+
+pub fn list(app: &tauri::AppHandle) -> Result<Vec<RecentRepository>, String> {
+    let _guard = RECENT_STORE.lock();
+    read_recent(&RECENT_STORE.path(app)?)
+}
+
+pub fn set_pinned(
+    app: &tauri::AppHandle,
+    path: &str,
+    pinned: bool,
+) -> Result<Vec<RecentRepository>, String> {
+    let _guard = RECENT_STORE.lock();
+    update_at(&RECENT_STORE.path(app)?, path, |repos, index| {
+        repos[index].pinned = pinned;
+    })
+}
+
+pub fn remove(app: &tauri::AppHandle, path: &str) -> Result<Vec<RecentRepository>, String> {
+    let _guard = RECENT_STORE.lock();
+    update_at(&RECENT_STORE.path(app)?, path, |repos, index| {
+        repos.remove(index);
+    })
+}
+
+fn record_at(
+    recent_path: &Path,
+    path: &str,
+    name: Option<String>,
+    max: usize,
+    now: i64,
+) -> Result<Vec<RecentRepository>, String> {
+    let repo = Repository::open(path).map_err(|e| {
+        format!(
+            "E_RECENT_REPO_INVALID: '{}' is not a repository: {}",
+            path, e
+        )
+    })?;
+    let path = normalize_path(path);
+
+    let mut repos = read_recent(recent_path)?;
+    let previous = repos
+        .iter()
+        .position(|entry| entry.path == path)
+        .map(|index| repos.remove(index));
+
+    let name = name
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .or_else(|| previous.as_ref().map(|entry| entry.name.clone()))
+        .unwrap_or_else(|| default_name(&repo, &path));
+    repos.insert(
+        0,
+        RecentRepository {
+            path,
+            name,
+            last_opened: now,
+            pinned: previous.map(|entry| entry.pinned).unwrap_or(false),
+            exists: true,
+        },
+    );
+
+    prune(&mut repos, max);
+    RECENT_STORE.write(recent_path, &repos)?;
+    Ok(sorted(repos))
+}
+
+fn update_at(
+    recent_path: &Path,
+    path: &str,
+    change: impl FnOnce(&mut Vec<RecentRepository>, usize),
+) -> Result<Vec<RecentRepository>, String> {
+    let path = normalize_path(path);
+    let mut repos = read_recent(recent_path)?;
+    let index = repos
+        .iter()
+        .position(|entry| entry.path == path)
+        .ok_or_else(|| format!("E_RECENT_REPO_NOT_FOUND: {}", path))?;
+    change(&mut repos, index);
+    RECENT_STORE.write(recent_path, &repos)?;
+    Ok(sorted(repos))
+}
+
+/// Keep every pinned entry plus the most recently opened unpinned ones, up to
+/// `max` entries in total.
+fn prune(repos: &mut Vec<RecentRepository>, max: usize) {
+    repos.sort_by_key(|entry| std::cmp::Reverse(entry.last_opened));
+    let pinned = repos.iter().filter(|entry| entry.pinned).count();
+    let mut unpinned_budget = max.saturating_sub(pinned);
+    repos.retain(|entry| {
+        if entry.pinned {
+            return true;
+        }
+        if unpinned_budget == 0 {
+            return false;
+        }
+        unpinned_budget -= 1;
+        true
+    });
+}
+
+/// Pinned entries first, then most recently opened.
+fn sorted(mut repos: Vec<RecentRepository>) -> Vec<RecentRepository> {
+    for entry in repos.iter_mut() {
+        entry.exists = Repository::open(&entry.path).is_ok();
+    }
+    repos.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then_with(|| b.last_opened.cmp(&a.last_opened))
+    });
+    repos
+}
+
+fn default_name(repo: &Repository, path: &str) -> String {
+    let root = repo.workdir().unwrap_or_else(|| repo.path());
+    root.file_name()
+        .or_else(|| Path::new(path).file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .map(|name| name.trim_end_matches(".git").to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn normalize_path(path: &str) -> String {
+    fs::canonicalize(path)
+        .map(|canonical| canonical.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| path.trim_end_matches(['/', '\\']).to_string())
+}
+
+fn read_recent(recent_path: &Path) -> Result<Vec<RecentRepository>, String> {
+    RECENT_STORE.read(recent_path).map(sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_prunes_unpinned_and_tracks_missing_repositories() {
+        let dir =
+            std::env::temp_dir().join(format!("gitlite-recent-repos-{}", uuid::Uuid::new_v4()));
+        let recent_path = dir.join(RECENT_REPOS_FILENAME);
+        let repo_paths: Vec<String> = (0..3)
+            .map(|i| {
+                let repo_dir = dir.join(format!("repo{}", i));
+                Repository::init(&repo_dir).unwrap();
+                repo_dir.to_string_lossy().into_owned()
+            })
+            .collect();
+
+        record_at(&recent_path, &repo_paths[0], None, 2, 100).unwrap();
+        update_at(&recent_path, &repo_paths[0], |repos, index| {
+            repos[index].pinned = true
+        })
+        .unwrap();
+        record_at(&recent_path, &repo_paths[1], Some("Second".into()), 2, 200).unwrap();
+        let repos = record_at(&recent_path, &repo_paths[2], None, 2, 300).unwrap();
+
+        let names: Vec<&str> = repos.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["repo0", "repo2"]);
+        assert!(repos[0].pinned);
+
+        fs::remove_dir_all(&repo_paths[2]).unwrap();
+        let listed = read_recent(&recent_path).unwrap();
+        assert!(listed[0].exists);
+        assert!(!listed[1].exists);
+
+        assert!(record_at(&recent_path, &repo_paths[2], None, 2, 400)
+            .unwrap_err()
+            .starts_with("E_RECENT_REPO_INVALID"));
+        assert!(update_at(&recent_path, "/nowhere", |_, _| {})
+            .unwrap_err()
+            .starts_with("E_RECENT_REPO_NOT_FOUND"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -2,13 +2,19 @@ use serde::Serialize;
 use std::backtrace::Backtrace;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::Manager;
 
 const RUNTIME_LOG_FILE: &str = "runtime.log";
+/// The log rotates to `runtime.log.1`, `runtime.log.2`, ... past this size.
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+/// Files kept including the active one.
+const MAX_LOG_FILES: usize = 3;
 static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+// Serializes rotation with appends from other threads.
+static LOG_LOCK: Mutex<()> = Mutex::new(());
 
 #[derive(Serialize)]
 pub struct RuntimeInfo {
@@ -57,15 +63,40 @@ pub fn get_runtime_info(app: &tauri::AppHandle) -> Result<RuntimeInfo, String> {
     })
 }
 
+/// The last `limit` lines across the rotated files, oldest first.
 pub fn read_runtime_logs(app: &tauri::AppHandle, limit: usize) -> Result<Vec<String>, String> {
+    read_log_lines(&ensure_log_path(app)?, limit)
+}
+
+/// Delete the rotated files and empty the active log.
+pub fn clear_runtime_logs(app: &tauri::AppHandle) -> Result<(), String> {
     let log_path = ensure_log_path(app)?;
-    if !log_path.exists() {
-        return Ok(Vec::new());
+    let _guard = LOG_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    for index in 1..MAX_LOG_FILES {
+        remove_if_exists(&rotated_path(&log_path, index))?;
     }
+    fs::write(&log_path, "").map_err(|error| {
+        format!(
+            "E_RUNTIME_LOG_WRITE: Failed to clear runtime log: {}",
+            error
+        )
+    })
+}
 
-    let raw = fs::read_to_string(&log_path)
-        .map_err(|error| format!("E_RUNTIME_LOG_READ: Failed to read runtime log: {}", error))?;
-    let mut lines: Vec<String> = raw.lines().map(|line| line.to_string()).collect();
+fn read_log_lines(log_path: &Path, limit: usize) -> Result<Vec<String>, String> {
+    let mut lines: Vec<String> = Vec::new();
+    for index in (0..MAX_LOG_FILES).rev() {
+        let path = rotated_path(log_path, index);
+        if !path.exists() {
+            continue;
+        }
+        let raw = fs::read_to_string(&path).map_err(|error| {
+            format!("E_RUNTIME_LOG_READ: Failed to read runtime log: {}", error)
+        })?;
+        lines.extend(raw.lines().map(|line| line.to_string()));
+    }
     if lines.len() > limit {
         lines = lines.split_off(lines.len().saturating_sub(limit));
     }
@@ -118,7 +149,13 @@ fn ensure_log_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     Ok(app_data_dir.join(RUNTIME_LOG_FILE))
 }
 
-fn append_log_line(log_path: &PathBuf, message: &str) -> Result<(), String> {
+fn append_log_line(log_path: &Path, message: &str) -> Result<(), String> {
+    let _guard = LOG_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if fs::metadata(log_path).is_ok_and(|metadata| metadata.len() >= MAX_LOG_BYTES) {
+        rotate(log_path)?;
+    }
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
@@ -137,3 +174,73 @@ fn append_log_line(log_path: &PathBuf, message: &str) -> Result<(), String> {
         )
     })
 }
+
+/// `runtime.log.<index>`; index 0 is the active log.
+fn rotated_path(log_path: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        return log_path.to_path_buf();
+    }
+    let mut name = log_path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Shift every file one slot up, dropping the oldest.
+fn rotate(log_path: &Path) -> Result<(), String> {
+    remove_if_exists(&rotated_path(log_path, MAX_LOG_FILES - 1))?;
+    for index in (0..MAX_LOG_FILES - 1).rev() {
+        let from = rotated_path(log_path, index);
+        if from.exists() {
+            fs::rename(&from, rotated_path(log_path, index + 1)).map_err(|error| {
+                format!(
+                    "E_RUNTIME_LOG_WRITE: Failed to rotate runtime log: {}",
+                    error
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(format!(
+            "E_RUNTIME_LOG_WRITE: Failed to remove {}: {}",
+            path.display(),
+            error
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_rotates_and_keeps_a_bounded_number_of_files() {
+        let dir = std::env::temp_dir().join(format!("gitlite-runtime-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let log_path = dir.join(RUNTIME_LOG_FILE);
+
+        for round in 0..4 {
+            fs::write(&log_path, vec![b'x'; MAX_LOG_BYTES as usize]).unwrap();
+            append_log_line(&log_path, &format!("round {}", round)).unwrap();
+        }
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["runtime.log", "runtime.log.1", "runtime.log.2"]);
+
+        let lines = read_log_lines(&log_path, 1).unwrap();
+        assert!(lines[0].ends_with("round 3"), "{:?}", lines);
+        let all = read_log_lines(&log_path, usize::MAX).unwrap();
+        assert!(all.last().unwrap().ends_with("round 3"));
+        assert_eq!(all.len(), 3, "two padded rotated files plus the new line");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,207 @@
+use crate::git;
+use git2::Repository;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+const FEATURE_BRANCH: &str = "self-test-feature";
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SelfTestCheck {
+    pub name: String,
+    pub status: String, // "passed" | "failed" | "skipped"
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub app_version: String,
+    pub os: String,
+    pub libgit2_version: String,
+    pub duration_ms: u64,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+/// Exercise the core git paths against a throwaway repository in the temp
+/// directory. After the first failure the remaining checks are skipped.
+pub fn run_self_test(app: &tauri::AppHandle) -> Result<SelfTestReport, String> {
+    let dir = std::env::temp_dir().join(format!("gitlite-self-test-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).map_err(|e| {
+        format!(
+            "E_SELF_TEST_SETUP: failed to create {}: {}",
+            dir.display(),
+            e
+        )
+    })?;
+
+    let started = Instant::now();
+    let checks = run_checks(&dir);
+    let _ = fs::remove_dir_all(&dir);
+
+    let (major, minor, rev) = git2::Version::get().libgit2_version();
+    let report = SelfTestReport {
+        passed: checks.iter().all(|check| check.status == "passed"),
+        app_version: app.package_info().version.to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        libgit2_version: format!("{}.{}.{}", major, minor, rev),
+        duration_ms: started.elapsed().as_millis() as u64,
+        checks,
+    };
+    crate::runtime::append_runtime_log(&format!(
+        "self-test passed={} {}",
+        report.passed,
+        report
+            .checks
+            .iter()
+            .map(|check| format!("{}={}", check.name, check.status))
+            .collect::<Vec<_>>()
+            .join(" ")
+    ));
+    Ok(report)
+}
+
+fn run_checks(dir: &Path) -> Vec<SelfTestCheck> {
+    let path = dir.to_string_lossy().into_owned();
+    let path = path.as_str();
+    let mut base_branch = String::new();
+    let mut feature_commit = String::new();
+
+    let mut runner = CheckRunner::default();
+    runner.check("init", || {
+        let repo = Repository::init(dir).map_err(|e| e.message().to_string())?;
+        let mut config = repo.config().map_err(|e| e.message().to_string())?;
+        config
+            .set_str("user.name", "GitLite Self-Test")
+            .and_then(|_| config.set_str("user.email", "self-test@gitlite.invalid"))
+            .map_err(|e| e.message().to_string())
+    });
+    runner.check("commit", || {
+        write_file(dir, "readme.txt", "self-test\n")?;
+        git::stage_files(path, &["readme.txt".to_string()])?;
+        git::commit_changes(path, "Initial commit", "")?;
+        base_branch = current_branch(dir)?;
+        Ok(())
+    });
+    runner.check("branch", || {
+        git::create_branch(path, FEATURE_BRANCH)?;
+        git::checkout_branch(path, FEATURE_BRANCH, |_| {})?;
+        write_file(dir, "feature.txt", "feature\n")?;
+        git::stage_files(path, &["feature.txt".to_string()])?;
+        feature_commit = git::commit_changes(path, "Add feature", "")?;
+        Ok(())
+    });
+    runner.check("diff", || {
+        let files = git::get_commit_diff(path, &feature_commit, &git::DiffLimits::unlimited())?;
+        if files.iter().any(|file| file.path == "feature.txt") {
+            Ok(())
+        } else {
+            Err("feature.txt missing from commit diff".to_string())
+        }
+    });
+    runner.check("stash", || {
+        write_file(dir, "feature.txt", "feature\nstashed\n")?;
+        git::create_stash(path, Some("self-test"))?;
+        if git::list_stashes(path)?.len() != 1 {
+            return Err("stash was not recorded".to_string());
+        }
+        git::apply_stash(path, 0)?;
+        git::drop_stash(path, 0)?;
+        git::stage_files(path, &["feature.txt".to_string()])?;
+        git::commit_changes(path, "Apply stash", "")?;
+        Ok(())
+    });
+    runner.check("merge", || {
+        git::checkout_branch(path, &base_branch, |_| {})?;
+        write_file(dir, "base.txt", "base\n")?;
+        git::stage_files(path, &["base.txt".to_string()])?;
+        git::commit_changes(path, "Base change", "")?;
+        git::merge_branch(path, FEATURE_BRANCH)?;
+        match fs::read_to_string(dir.join("feature.txt")) {
+            Ok(content) if content == "feature\nstashed\n" => Ok(()),
+            _ => Err("merged content missing from the working tree".to_string()),
+        }
+    });
+    runner.check("history", || {
+        let commits = git::get_commits(path, 10, None)?;
+        match commits.first() {
+            Some(head) if head.parents.len() == 2 => Ok(()),
+            _ => Err("HEAD is not the merge commit".to_string()),
+        }
+    });
+    runner.checks
+}
+
+#[derive(Default)]
+struct CheckRunner {
+    checks: Vec<SelfTestCheck>,
+    failed: bool,
+}
+
+impl CheckRunner {
+    fn check(&mut self, name: &str, run: impl FnOnce() -> Result<(), String>) {
+        if self.failed {
+            self.checks.push(SelfTestCheck {
+                name: name.to_string(),
+                status: "skipped".to_string(),
+                duration_ms: 0,
+                error: None,
+            });
+            return;
+        }
+        let started = Instant::now();
+        let result = run();
+        self.failed = result.is_err();
+        self.checks.push(SelfTestCheck {
+            name: name.to_string(),
+            status: if result.is_ok() { "passed" } else { "failed" }.to_string(),
+            duration_ms: started.elapsed().as_millis() as u64,
+            error: result.err(),
+        });
+    }
+}
+
+fn write_file(dir: &Path, name: &str, content: &str) -> Result<(), String> {
+    fs::write(dir.join(name), content).map_err(|e| format!("failed to write {}: {}", name, e))
+}
+
+fn current_branch(dir: &Path) -> Result<String, String> {
+    let repo = Repository::open(dir).map_err(|e| e.message().to_string())?;
+    let head = repo.head().map_err(|e| e.message().to_string())?;
+    head.shorthand()
+        .map(str::to_string)
+        .ok_or_else(|| "HEAD has no branch name".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_checks_pass_in_a_temp_repository() {
+        let dir = std::env::temp_dir().join(format!("gitlite-self-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let checks = run_checks(&dir);
+        let failures: Vec<_> = checks
+            .iter()
+            .filter(|check| check.status != "passed")
+            .collect();
+        assert!(failures.is_empty(), "{:?}", failures);
+        assert_eq!(checks.len(), 7);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn checks_after_a_failure_are_skipped() {
+        let mut runner = CheckRunner::default();
+        runner.check("first", || Err("boom".to_string()));
+        runner.check("second", || Ok(()));
+        assert_eq!(runner.checks[0].status, "failed");
+        assert_eq!(runner.checks[0].error.as_deref(), Some("boom"));
+        assert_eq!(runner.checks[1].status, "skipped");
+    }
+}
@@ -0,0 +1,261 @@
+use crate::jobs::JobManager;
+use crate::runtime::append_runtime_log;
+use git2::{Oid, Repository};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+/// How long exit is held back for in-flight mutating operations.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Where HEAD as it was before an operation cut short by exit is kept, so
+/// `git reset --keep refs/gitlite/undo/exit` undoes what it got done.
+pub const EXIT_UNDO_REF: &str = "refs/gitlite/undo/exit";
+
+static COORDINATOR: Coordinator = Coordinator::new();
+
+/// Mutating operations currently touching a repository, so exit can wait
+/// for them instead of leaving refs, the index or a pack half-written.
+struct Coordinator {
+    operations: Mutex<BTreeMap<u64, InFlight>>,
+    idle: Condvar,
+    next_id: AtomicU64,
+    shutting_down: AtomicBool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct InFlight {
+    label: String,
+    path: String,
+    /// HEAD when the operation started: its undo snapshot, persisted only
+    /// if exit cuts the operation short.
+    head: Option<Oid>,
+}
+
+/// Keeps an operation registered until dropped.
+pub struct OperationGuard<'a> {
+    coordinator: &'a Coordinator,
+    id: u64,
+}
+
+impl Drop for OperationGuard<'_> {
+    fn drop(&mut self) {
+        self.coordinator.lock_operations().remove(&self.id);
+        self.coordinator.idle.notify_all();
+    }
+}
+
+impl Coordinator {
+    const fn new() -> Self {
+        Coordinator {
+            operations: Mutex::new(BTreeMap::new()),
+            idle: Condvar::new(),
+            next_id: AtomicU64::new(0),
+            shutting_down: AtomicBool::new(false),
+        }
+    }
+
+    fn track(&self, kind: &str, path: &str) -> Result<OperationGuard<'_>, String> {
+        let head = Repository::open(path)
+            .ok()
+            .and_then(|repo| repo.head().ok()?.target());
+        let mut operations = self.lock_operations();
+        // Checked under the lock so nothing registers after the final wait.
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(format!("E_SHUTTING_DOWN: {} not started", kind));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        operations.insert(
+            id,
+            InFlight {
+                label: format!("{} ({})", kind, path),
+                path: path.to_string(),
+                head,
+            },
+        );
+        Ok(OperationGuard {
+            coordinator: self,
+            id,
+        })
+    }
+
+    /// Refuse new operations. Returns `false` if shutdown had already begun.
+    fn begin_shutdown(&self) -> bool {
+        let _operations = self.lock_operations();
+        !self.shutting_down.swap(true, Ordering::SeqCst)
+    }
+
+    fn in_flight(&self) -> Vec<String> {
+        self.lock_operations()
+            .values()
+            .map(|operation| operation.label.clone())
+            .collect()
+    }
+
+    /// Block until every operation finished or `timeout` passed; returns
+    /// the ones still running.
+    fn wait_for_idle(&self, timeout: Duration) -> Vec<InFlight> {
+        let deadline = Instant::now() + timeout;
+        let mut operations = self.lock_operations();
+        while !operations.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            operations = self
+                .idle
+                .wait_timeout(operations, deadline - now)
+                .map(|(guard, _)| guard)
+                .unwrap_or_else(|e| e.into_inner().0);
+        }
+        operations.values().cloned().collect()
+    }
+
+    fn lock_operations(&self) -> MutexGuard<'_, BTreeMap<u64, InFlight>> {
+        self.operations.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Register a mutating operation on `path` for the duration of the guard.
+/// Fails with `E_SHUTTING_DOWN` once the app is exiting.
+pub fn track(kind: &str, path: &str) -> Result<OperationGuard<'static>, String> {
+    COORDINATOR.track(kind, path)
+}
+
+/// Save the undo snapshot of an operation exit is about to cut short.
+fn persist_undo_snapshot(operation: &InFlight) -> Result<(), String> {
+    let Some(head) = operation.head else {
+        return Ok(());
+    };
+    let repo = Repository::open(&operation.path).map_err(|e| e.to_string())?;
+    repo.reference(
+        EXIT_UNDO_REF,
+        head,
+        true,
+        &format!(
+            "gitlite: HEAD before {} was cut short by exit",
+            operation.label
+        ),
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+/// Handle `RunEvent::ExitRequested`: cancel background jobs, then hold exit
+/// until in-flight operations finish or `SHUTDOWN_GRACE` runs out. Those
+/// still running then get their undo snapshot persisted.
+pub fn on_exit_requested(app: &tauri::AppHandle, code: Option<i32>, api: &tauri::ExitRequestApi) {
+    if !COORDINATOR.begin_shutdown() {
+        // Our own `app.exit` after the wait below.
+        return;
+    }
+    let reason = match code {
+        Some(code) => format!("exit requested (code {})", code),
+        None => "last window closed".to_string(),
+    };
+    let cancelled = app.state::<JobManager>().cancel_all();
+    let pending = COORDINATOR.in_flight();
+    append_runtime_log(&format!(
+        "shutdown: {}; cancelled {} job(s); {} operation(s) in flight",
+        reason,
+        cancelled,
+        pending.len()
+    ));
+    if pending.is_empty() {
+        return;
+    }
+
+    api.prevent_exit();
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let remaining = COORDINATOR.wait_for_idle(SHUTDOWN_GRACE);
+        if remaining.is_empty() {
+            append_runtime_log("shutdown: in-flight operations finished");
+        } else {
+            let labels: Vec<&str> = remaining.iter().map(|op| op.label.as_str()).collect();
+            append_runtime_log(&format!(
+                "shutdown: exiting with unfinished operations: {}",
+                labels.join(", ")
+            ));
+            for operation in &remaining {
+                match persist_undo_snapshot(operation) {
+                    Ok(()) if operation.head.is_some() => append_runtime_log(&format!(
+                        "shutdown: HEAD before {} saved to {}",
+                        operation.label, EXIT_UNDO_REF
+                    )),
+                    Ok(()) => {}
+                    Err(error) => append_runtime_log(&format!(
+                        "shutdown: cannot save the undo snapshot of {}: {}",
+                        operation.label, error
+                    )),
+                }
+            }
+        }
+        app.exit(code.unwrap_or(0));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn shutdown_waits_for_operations_and_refuses_new_ones() {
+        let coordinator: &'static Coordinator = Box::leak(Box::new(Coordinator::new()));
+        let guard = coordinator.track("push", "/repo").unwrap();
+        assert_eq!(coordinator.in_flight(), vec!["push (/repo)".to_string()]);
+
+        assert!(coordinator.begin_shutdown());
+        assert!(!coordinator.begin_shutdown());
+        assert!(coordinator
+            .track("commit", "/repo")
+            .err()
+            .unwrap()
+            .starts_with("E_SHUTTING_DOWN"));
+
+        let remaining = coordinator.wait_for_idle(Duration::from_millis(20));
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].label, "push (/repo)");
+        assert_eq!(remaining[0].head, None);
+        persist_undo_snapshot(&remaining[0]).unwrap();
+
+        let released = Arc::new(AtomicBool::new(false));
+        let flag = released.clone();
+        let worker = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            flag.store(true, Ordering::SeqCst);
+            drop(guard);
+        });
+        assert!(coordinator.wait_for_idle(Duration::from_secs(5)).is_empty());
+        assert!(released.load(Ordering::SeqCst));
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn unfinished_operations_persist_head_as_their_undo_snapshot() {
+        let dir = std::env::temp_dir().join(format!("gitlite-shutdown-{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let head = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        let path = dir.to_str().unwrap();
+
+        let coordinator = Coordinator::new();
+        let _guard = coordinator.track("rebase", path).unwrap();
+        let remaining = coordinator.wait_for_idle(Duration::from_millis(1));
+        assert_eq!(remaining[0].head, Some(head));
+
+        persist_undo_snapshot(&remaining[0]).unwrap();
+        let saved = repo.find_reference(EXIT_UNDO_REF).unwrap();
+        assert_eq!(saved.target(), Some(head));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
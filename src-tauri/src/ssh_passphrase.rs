@@ -0,0 +1,86 @@
+use crate::credentials::{KeychainCodes, KeychainItem};
+use std::fs;
+use std::path::Path;
+
+const KEYCHAIN_SERVICE: &str = "com.gitlite.app.ssh";
+const KEYCHAIN_CODES: KeychainCodes = KeychainCodes {
+    init: "E_SSH_PASSPHRASE_KEYCHAIN_INIT",
+    read: "E_SSH_PASSPHRASE_KEYCHAIN_READ",
+    write: "E_SSH_PASSPHRASE_KEYCHAIN_WRITE",
+    delete: "E_SSH_PASSPHRASE_KEYCHAIN_DELETE",
+};
+
+/// Store the passphrase of the private key at `key_path`, so SSH operations
+/// can unlock it without the frontend passing it in.
+pub fn save_passphrase(key_path: &str, passphrase: &str) -> Result<(), String> {
+    let account = key_account(key_path)?;
+    if passphrase.is_empty() {
+        return Err("E_SSH_PASSPHRASE_EMPTY: passphrase is required".to_string());
+    }
+    keychain_item(&account)?.save(passphrase)
+}
+
+/// Whether a passphrase is stored for `key_path`. The passphrase itself is
+/// never returned to the UI.
+pub fn has_passphrase(key_path: &str) -> Result<bool, String> {
+    Ok(load_passphrase(&key_account(key_path)?)?.is_some())
+}
+
+pub fn delete_passphrase(key_path: &str) -> Result<(), String> {
+    keychain_item(&key_account(key_path)?)?.delete()
+}
+
+/// Stored passphrase for `key_path`, for SSH credential callbacks. Lookup
+/// failures yield `None`.
+pub fn find(key_path: &str) -> Option<String> {
+    load_passphrase(&key_account(key_path).ok()?).ok().flatten()
+}
+
+fn load_passphrase(account: &str) -> Result<Option<String>, String> {
+    keychain_item(account)?.load()
+}
+
+/// Keychain account for a key: its canonical path, so `~/.ssh/id_ed25519`
+/// reached through different spellings shares one entry.
+fn key_account(key_path: &str) -> Result<String, String> {
+    let key_path = key_path.trim();
+    if key_path.is_empty() {
+        return Err("E_SSH_KEY_NOT_FOUND: key path is required".to_string());
+    }
+    fs::canonicalize(Path::new(key_path))
+        .ok()
+        .filter(|path| path.is_file())
+        .map(|path| path.display().to_string())
+        .ok_or_else(|| format!("E_SSH_KEY_NOT_FOUND: no key file at '{}'", key_path))
+}
+
+fn keychain_item(account: &str) -> Result<KeychainItem, String> {
+    KeychainItem::new(KEYCHAIN_SERVICE, account, &KEYCHAIN_CODES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_stored_under_their_canonical_path() {
+        let dir = std::env::temp_dir().join(format!("gitlite-ssh-key-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("id_ed25519"), "key").unwrap();
+
+        let direct = key_account(dir.join("id_ed25519").to_str().unwrap()).unwrap();
+        let indirect = format!(" {}/sub/../id_ed25519 ", dir.display());
+        assert_eq!(key_account(&indirect).unwrap(), direct);
+        for missing in [
+            "",
+            dir.join("id_rsa").to_str().unwrap(),
+            dir.to_str().unwrap(),
+        ] {
+            assert!(key_account(missing)
+                .unwrap_err()
+                .starts_with("E_SSH_KEY_NOT_FOUND"));
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
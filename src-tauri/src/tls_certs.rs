@@ -0,0 +1,336 @@
+use crate::config;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use git2::{ErrorClass, ErrorCode};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+const CA_BUNDLE_FILENAME: &str = "ca-certificates.pem";
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+/// CA files already handed to libgit2, which only ever adds locations.
+static LOADED: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+thread_local! {
+    /// DER certificate of the last TLS server seen on this thread, so a
+    /// failed handshake can name it.
+    static PEER: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// The CA bundle imported in settings.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CaBundle {
+    pub path: String,
+    /// Subject of each certificate, e.g. `CN=Corp Root CA, O=Corp`.
+    pub subjects: Vec<String>,
+}
+
+/// Trust the imported bundle and the global `http.sslCAInfo` at startup.
+pub fn init(app: &tauri::AppHandle) {
+    let imported = config::read_settings(app)
+        .ok()
+        .and_then(|settings| settings.ca_bundle_path);
+    let configured = git2::Config::open_default()
+        .ok()
+        .and_then(|config| config.get_path("http.sslCAInfo").ok());
+    for path in imported.map(PathBuf::from).into_iter().chain(configured) {
+        if let Err(error) = load(&path) {
+            crate::runtime::append_runtime_log(&error);
+        }
+    }
+}
+
+/// Copy the PEM certificates at `pem_path` into the app data directory,
+/// trust them for HTTPS remotes and record the bundle in settings.
+pub fn import_ca_bundle(app: &tauri::AppHandle, pem_path: &str) -> Result<CaBundle, String> {
+    let pem = fs::read_to_string(pem_path.trim())
+        .map_err(|error| format!("E_TLS_CA_INVALID: cannot read '{}': {}", pem_path, error))?;
+    let subjects = bundle_subjects(&pem)?;
+
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|error| format!("E_TLS_CA_WRITE: Failed to resolve app data dir: {}", error))?;
+    fs::create_dir_all(&dir).map_err(|error| format!("E_TLS_CA_WRITE: {}", error))?;
+    let path = dir.join(CA_BUNDLE_FILENAME);
+    fs::write(&path, &pem).map_err(|error| format!("E_TLS_CA_WRITE: {}", error))?;
+    // A replaced bundle keeps its path, so libgit2 must reread it.
+    lock().remove(&path);
+    load(&path)?;
+
+    config::update_settings(app, |settings| {
+        settings.ca_bundle_path = Some(path.display().to_string());
+        Ok(())
+    })?;
+    Ok(CaBundle {
+        path: path.display().to_string(),
+        subjects,
+    })
+}
+
+pub fn get_ca_bundle(app: &tauri::AppHandle) -> Result<Option<CaBundle>, String> {
+    let Some(path) = config::read_settings(app)?.ca_bundle_path else {
+        return Ok(None);
+    };
+    let pem = fs::read_to_string(&path)
+        .map_err(|error| format!("E_TLS_CA_INVALID: cannot read '{}': {}", path, error))?;
+    Ok(Some(CaBundle {
+        subjects: bundle_subjects(&pem)?,
+        path,
+    }))
+}
+
+/// Forget the imported bundle. libgit2 cannot drop trusted locations, so
+/// its certificates stay trusted until restart.
+pub fn clear_ca_bundle(app: &tauri::AppHandle) -> Result<(), String> {
+    config::update_settings(app, |settings| {
+        if let Some(path) = settings.ca_bundle_path.take() {
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(error) => return Err(format!("E_TLS_CA_WRITE: {}", error)),
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Trust the `http.sslCAInfo` file visible to a repository. Load failures
+/// are logged; the operation then reports the certificate error itself.
+pub fn load_configured_ca(config: &git2::Config) {
+    if let Ok(path) = config.get_path("http.sslCAInfo") {
+        if let Err(error) = load(&path) {
+            crate::runtime::append_runtime_log(&error);
+        }
+    }
+}
+
+/// Note the certificate a TLS server presented, for `certificate_error`.
+pub fn remember_peer(der: &[u8]) {
+    PEER.with(|peer| *peer.borrow_mut() = Some(der.to_vec()));
+}
+
+/// `E_TLS_CERTIFICATE` with the server certificate's subject when an
+/// operation failed TLS verification.
+pub fn certificate_error(error: &git2::Error) -> Option<String> {
+    if error.code() != ErrorCode::Certificate || error.class() == ErrorClass::Ssh {
+        return None;
+    }
+    let subject = PEER
+        .with(|peer| peer.borrow_mut().take())
+        .and_then(|der| subject(&der));
+    Some(match subject {
+        Some(subject) => format!(
+            "E_TLS_CERTIFICATE: {} (certificate subject: {})",
+            error.message(),
+            subject
+        ),
+        None => format!("E_TLS_CERTIFICATE: {}", error.message()),
+    })
+}
+
+fn load(path: &Path) -> Result<(), String> {
+    let mut loaded = lock();
+    if loaded.contains(path) {
+        return Ok(());
+    }
+    if !path.is_file() {
+        return Err(format!(
+            "E_TLS_CA_INVALID: no CA file at '{}'",
+            path.display()
+        ));
+    }
+    // SAFETY: libgit2 copies the location into its global TLS context;
+    // `LOADED` serializes calls.
+    unsafe { git2::opts::set_ssl_cert_file(path) }
+        .map_err(|error| format!("E_TLS_CA_UNSUPPORTED: {}", error.message()))?;
+    loaded.insert(path.to_path_buf());
+    Ok(())
+}
+
+fn lock() -> std::sync::MutexGuard<'static, BTreeSet<PathBuf>> {
+    LOADED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn bundle_subjects(pem: &str) -> Result<Vec<String>, String> {
+    let certificates = pem_certificates(pem)
+        .ok_or_else(|| "E_TLS_CA_INVALID: malformed PEM certificate".to_string())?;
+    if certificates.is_empty() {
+        return Err("E_TLS_CA_INVALID: no PEM certificates found".to_string());
+    }
+    Ok(certificates
+        .iter()
+        .map(|der| subject(der).unwrap_or_else(|| "(unreadable subject)".to_string()))
+        .collect())
+}
+
+/// DER bodies of the `CERTIFICATE` blocks in `pem`.
+fn pem_certificates(pem: &str) -> Option<Vec<Vec<u8>>> {
+    let mut certificates = Vec::new();
+    let mut rest = pem;
+    while let Some(start) = rest.find(PEM_BEGIN) {
+        let body = &rest[start + PEM_BEGIN.len()..];
+        let end = body.find(PEM_END)?;
+        let encoded: String = body[..end].split_whitespace().collect();
+        certificates.push(STANDARD.decode(encoded).ok()?);
+        rest = &body[end + PEM_END.len()..];
+    }
+    Some(certificates)
+}
+
+/// Subject of a DER X.509 certificate as `CN=..., O=...`, listing the
+/// common attribute types only.
+fn subject(der: &[u8]) -> Option<String> {
+    let (_, certificate, _) = der_element(der)?;
+    let (_, mut tbs, _) = der_element(certificate)?;
+    if tbs.first() == Some(&0xa0) {
+        tbs = der_element(tbs)?.2;
+    }
+    // serialNumber, signature, issuer, validity
+    for _ in 0..4 {
+        tbs = der_element(tbs)?.2;
+    }
+    let (_, mut name, _) = der_element(tbs)?;
+
+    let mut parts = Vec::new();
+    while !name.is_empty() {
+        let (_, mut set, rest) = der_element(name)?;
+        name = rest;
+        while !set.is_empty() {
+            let (_, attribute, rest) = der_element(set)?;
+            set = rest;
+            let (_, oid, value) = der_element(attribute)?;
+            let (_, value, _) = der_element(value)?;
+            let label = match oid {
+                [0x55, 0x04, 0x03] => "CN",
+                [0x55, 0x04, 0x06] => "C",
+                [0x55, 0x04, 0x07] => "L",
+                [0x55, 0x04, 0x08] => "ST",
+                [0x55, 0x04, 0x0a] => "O",
+                [0x55, 0x04, 0x0b] => "OU",
+                _ => continue,
+            };
+            parts.push(format!("{}={}", label, String::from_utf8_lossy(value)));
+        }
+    }
+    Some(parts.join(", "))
+}
+
+/// Split one DER element off `input`: `(tag, content, rest)`.
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&first, mut input) = input.split_first()?;
+    let length = if first < 0x80 {
+        first as usize
+    } else {
+        let octets = (first & 0x7f) as usize;
+        if octets == 0 || octets > 4 || input.len() < octets {
+            return None;
+        }
+        let (bytes, rest) = input.split_at(octets);
+        input = rest;
+        bytes
+            .iter()
+            .fold(0, |length, &b| (length << 8) | b as usize)
+    };
+    if input.len() < length {
+        return None;
+    }
+    let (content, rest) = input.split_at(length);
+    Some((tag, content, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![tag];
+        if content.len() < 0x80 {
+            encoded.push(content.len() as u8);
+        } else {
+            encoded.extend([0x82, (content.len() >> 8) as u8, content.len() as u8]);
+        }
+        encoded.extend_from_slice(content);
+        encoded
+    }
+
+    fn name(attributes: &[(&[u8], &str)]) -> Vec<u8> {
+        let sets: Vec<u8> = attributes
+            .iter()
+            .flat_map(|(oid, value)| {
+                let attribute = [der(0x06, oid), der(0x0c, value.as_bytes())].concat();
+                der(0x31, &der(0x30, &attribute))
+            })
+            .collect();
+        der(0x30, &sets)
+    }
+
+    fn certificate(subject: &[u8]) -> Vec<u8> {
+        let tbs = [
+            der(0xa0, &der(0x02, &[2])),
+            der(0x02, &[1]),
+            der(0x30, &der(0x06, &[0x2a, 0x86, 0x48])),
+            name(&[(&[0x55, 0x04, 0x03], "Issuer CA")]),
+            der(0x30, &[0; 200]),
+            subject.to_vec(),
+        ]
+        .concat();
+        der(0x30, &der(0x30, &tbs))
+    }
+
+    #[test]
+    fn certificate_subjects_are_read_from_pem_bundles() {
+        let corp = certificate(&name(&[
+            (&[0x55, 0x04, 0x03], "Corp Root CA"),
+            (&[0x2a, 0x03], "ignored"),
+            (&[0x55, 0x04, 0x0a], "Corp"),
+        ]));
+        let pem = format!(
+            "intro\n{}\n{}\n{}\n",
+            PEM_BEGIN,
+            STANDARD.encode(&corp),
+            PEM_END
+        );
+        assert_eq!(
+            bundle_subjects(&pem).unwrap(),
+            vec!["CN=Corp Root CA, O=Corp"]
+        );
+
+        assert!(bundle_subjects("no certificates")
+            .unwrap_err()
+            .starts_with("E_TLS_CA_INVALID"));
+        assert!(bundle_subjects(&format!("{}\n!!!\n{}", PEM_BEGIN, PEM_END))
+            .unwrap_err()
+            .starts_with("E_TLS_CA_INVALID"));
+    }
+
+    #[test]
+    fn certificate_failures_name_the_server_certificate() {
+        let server = certificate(&name(&[(&[0x55, 0x04, 0x03], "git.corp.example")]));
+        remember_peer(&server);
+        let error = git2::Error::new(
+            ErrorCode::Certificate,
+            ErrorClass::Ssl,
+            "the SSL certificate is invalid",
+        );
+        assert_eq!(
+            certificate_error(&error).unwrap(),
+            "E_TLS_CERTIFICATE: the SSL certificate is invalid \
+             (certificate subject: CN=git.corp.example)"
+        );
+        assert_eq!(
+            certificate_error(&error).unwrap(),
+            "E_TLS_CERTIFICATE: the SSL certificate is invalid"
+        );
+
+        let network = git2::Error::new(ErrorCode::GenericError, ErrorClass::Net, "timed out");
+        assert_eq!(certificate_error(&network), None);
+    }
+}
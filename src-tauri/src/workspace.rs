@@ -0,0 +1,351 @@
+use crate::git;
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+
+const MANIFEST_VERSION: u32 = 1;
+
+/// Event emitted while `import_workspace_manifest` clones repositories.
+pub const WORKSPACE_IMPORT_PROGRESS_EVENT: &str = "workspace-import-progress";
+
+/// Shareable list of repositories a team works on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WorkspaceManifest {
+    pub version: u32,
+    pub name: Option<String>,
+    pub repositories: Vec<ManifestRepository>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ManifestRepository {
+    pub name: String,
+    pub url: String,
+    pub default_branch: Option<String>,
+    pub group: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct WorkspaceRepoInput {
+    pub path: String,
+    pub group: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WorkspaceImportProgress {
+    pub index: usize,
+    pub total: usize,
+    pub name: String,
+    pub stage: String, // "cloning" | "done" | "skipped" | "failed"
+    pub progress: Option<git::TransferProgress>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WorkspaceImportResult {
+    pub name: String,
+    pub path: String,
+    pub status: String, // "cloned" | "existing" | "failed"
+    pub error: Option<String>,
+}
+
+/// Build a manifest from local repositories and write it to `dest` as JSON.
+pub fn export_manifest(
+    name: Option<String>,
+    repos: &[WorkspaceRepoInput],
+    dest: &str,
+) -> Result<WorkspaceManifest, String> {
+    if dest.trim().is_empty() {
+        return Err("E_WORKSPACE_DEST_EMPTY: destination path is required".to_string());
+    }
+
+    let repositories = repos
+        .iter()
+        .map(describe_repository)
+        .collect::<Result<Vec<_>, _>>()?;
+    let manifest = WorkspaceManifest {
+        version: MANIFEST_VERSION,
+        name: name.filter(|value| !value.trim().is_empty()),
+        repositories,
+    };
+
+    let json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("E_WORKSPACE_MANIFEST_WRITE: {}", e))?;
+    fs::write(dest, json).map_err(|e| format!("E_WORKSPACE_MANIFEST_WRITE: {}", e))?;
+    Ok(manifest)
+}
+
+pub fn read_manifest(manifest_path: &str) -> Result<WorkspaceManifest, String> {
+    let content = fs::read_to_string(manifest_path)
+        .map_err(|e| format!("E_WORKSPACE_MANIFEST_READ: {}", e))?;
+    let manifest: WorkspaceManifest = serde_json::from_str(&content)
+        .map_err(|e| format!("E_WORKSPACE_MANIFEST_INVALID: {}", e))?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!(
+            "E_WORKSPACE_MANIFEST_INVALID: unsupported manifest version {}",
+            manifest.version
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Clone every manifest repository missing under `target_dir` (grouped into
+/// `<target_dir>/<group>/<name>`), emitting progress events along the way.
+/// Existing repositories are left untouched.
+pub fn import_manifest(
+    app: &tauri::AppHandle,
+    manifest_path: &str,
+    target_dir: &str,
+) -> Result<Vec<WorkspaceImportResult>, String> {
+    let manifest = read_manifest(manifest_path)?;
+    import_manifest_with(&manifest, Path::new(target_dir), |progress| {
+        let _ = app.emit(WORKSPACE_IMPORT_PROGRESS_EVENT, progress);
+    })
+}
+
+fn import_manifest_with(
+    manifest: &WorkspaceManifest,
+    target_dir: &Path,
+    mut on_progress: impl FnMut(&WorkspaceImportProgress),
+) -> Result<Vec<WorkspaceImportResult>, String> {
+    fs::create_dir_all(target_dir).map_err(|e| format!("E_WORKSPACE_TARGET: {}", e))?;
+
+    let total = manifest.repositories.len();
+    let mut results = Vec::with_capacity(total);
+
+    for (index, entry) in manifest.repositories.iter().enumerate() {
+        let dest = repository_dir(target_dir, entry)?;
+        let mut report = |stage: &str, progress: Option<git::TransferProgress>| {
+            on_progress(&WorkspaceImportProgress {
+                index,
+                total,
+                name: entry.name.clone(),
+                stage: stage.to_string(),
+                progress,
+            })
+        };
+
+        if Repository::open(&dest).is_ok() {
+            report("skipped", None);
+            results.push(import_result(entry, &dest, "existing", None));
+            continue;
+        }
+
+        report("cloning", None);
+        let outcome = git::clone_repository(
+            &entry.url,
+            &dest,
+            entry.default_branch.as_deref(),
+            false,
+            |progress| {
+                report("cloning", Some(progress.clone()));
+                true
+            },
+            |_| {},
+            |_| {},
+        );
+
+        match outcome {
+            Ok(()) => {
+                report("done", None);
+                results.push(import_result(entry, &dest, "cloned", None));
+            }
+            Err(error) => {
+                report("failed", None);
+                results.push(import_result(entry, &dest, "failed", Some(error)));
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+fn describe_repository(input: &WorkspaceRepoInput) -> Result<ManifestRepository, String> {
+    let repo = Repository::open(&input.path)
+        .map_err(|e| format!("Failed to open repository '{}': {}", input.path, e))?;
+
+    let remote = repo
+        .find_remote("origin")
+        .ok()
+        .or_else(|| {
+            let names = repo.remotes().ok()?;
+            let first = names.iter().flatten().next()?.to_string();
+            repo.find_remote(&first).ok()
+        })
+        .ok_or_else(|| {
+            format!(
+                "E_WORKSPACE_NO_REMOTE: '{}' has no remote to share",
+                input.path
+            )
+        })?;
+    let url = remote
+        .url()
+        .ok_or_else(|| format!("E_WORKSPACE_NO_REMOTE: '{}' has no remote URL", input.path))?
+        .to_string();
+
+    let remote_name = remote.name().unwrap_or("origin");
+    let default_branch = repo
+        .find_reference(&format!("refs/remotes/{}/HEAD", remote_name))
+        .ok()
+        .and_then(|reference| {
+            reference
+                .symbolic_target()
+                .and_then(|target| target.strip_prefix(&format!("refs/remotes/{}/", remote_name)))
+                .map(str::to_string)
+        })
+        .or_else(|| {
+            repo.head()
+                .ok()
+                .filter(|head| head.is_branch())
+                .and_then(|head| head.shorthand().map(str::to_string))
+        });
+
+    Ok(ManifestRepository {
+        name: repository_name(&url, &input.path),
+        url,
+        default_branch,
+        group: input
+            .group
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string),
+    })
+}
+
+fn repository_name(url: &str, path: &str) -> String {
+    let from_url = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or("")
+        .trim_end_matches(".git");
+    if !from_url.is_empty() {
+        return from_url.to_string();
+    }
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "repository".to_string())
+}
+
+fn repository_dir(target_dir: &Path, entry: &ManifestRepository) -> Result<PathBuf, String> {
+    let mut dest = target_dir.to_path_buf();
+    for segment in entry.group.iter().chain(std::iter::once(&entry.name)) {
+        for part in segment.split('/') {
+            if part.is_empty() || part == "." || part == ".." || part.contains('\\') {
+                return Err(format!(
+                    "E_WORKSPACE_MANIFEST_INVALID: unsafe path segment in '{}'",
+                    segment
+                ));
+            }
+            dest.push(part);
+        }
+    }
+    Ok(dest)
+}
+
+fn import_result(
+    entry: &ManifestRepository,
+    dest: &Path,
+    status: &str,
+    error: Option<String>,
+) -> WorkspaceImportResult {
+    WorkspaceImportResult {
+        name: entry.name.clone(),
+        path: dest.to_string_lossy().into_owned(),
+        status: status.to_string(),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn run_git(args: &[&str], cwd: &Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn export_then_import_clones_missing_repositories() {
+        let base_dir =
+            std::env::temp_dir().join(format!("gitlite-workspace-test-{}", uuid::Uuid::new_v4()));
+        let upstream = base_dir.join("upstream").join("service.git");
+        let local = base_dir.join("local");
+        fs::create_dir_all(&upstream).unwrap();
+        fs::create_dir_all(&local).unwrap();
+
+        run_git(&["init", "--bare"], &upstream);
+        run_git(&["init"], &local);
+        run_git(&["config", "user.name", "Test User"], &local);
+        run_git(&["config", "user.email", "test@example.com"], &local);
+        run_git(&["commit", "--allow-empty", "-m", "Initial commit"], &local);
+        run_git(&["checkout", "-b", "release/2.x"], &local);
+        run_git(
+            &["remote", "add", "origin", upstream.to_str().unwrap()],
+            &local,
+        );
+        run_git(&["push", "origin", "release/2.x"], &local);
+        run_git(&["remote", "set-head", "origin", "release/2.x"], &local);
+        run_git(&["checkout", "-b", "scratch"], &local);
+
+        let manifest_path = base_dir.join("workspace.json");
+        let manifest = export_manifest(
+            Some("Team".to_string()),
+            &[WorkspaceRepoInput {
+                path: local.to_string_lossy().into_owned(),
+                group: Some("backend".to_string()),
+            }],
+            manifest_path.to_str().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest.repositories[0].name, "service");
+        assert_eq!(
+            manifest.repositories[0].default_branch.as_deref(),
+            Some("release/2.x")
+        );
+        assert_eq!(
+            read_manifest(manifest_path.to_str().unwrap()).unwrap(),
+            manifest
+        );
+
+        let target = base_dir.join("checkout");
+        let mut stages = Vec::new();
+        let results =
+            import_manifest_with(&manifest, &target, |p| stages.push(p.stage.clone())).unwrap();
+        assert_eq!(results[0].status, "cloned");
+        assert!(stages.iter().any(|stage| stage == "done"));
+        let cloned = Repository::open(target.join("backend").join("service")).unwrap();
+        assert_eq!(cloned.head().unwrap().shorthand(), Some("release/2.x"));
+
+        let again = import_manifest_with(&manifest, &target, |_| {}).unwrap();
+        assert_eq!(again[0].status, "existing");
+
+        fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn repository_dir_rejects_path_traversal() {
+        let entry = ManifestRepository {
+            name: "repo".to_string(),
+            url: "https://example.com/repo.git".to_string(),
+            default_branch: None,
+            group: Some("../outside".to_string()),
+        };
+        assert!(repository_dir(Path::new("/tmp/ws"), &entry)
+            .unwrap_err()
+            .starts_with("E_WORKSPACE_MANIFEST_INVALID"));
+    }
+}
@@ -26,6 +26,7 @@ export interface GitDataProvider {
   getChanges(): Promise<ChangeItem[]>;
   stageFiles(files: string[]): Promise<void>;
   unstageFiles(files: string[]): Promise<void>;
+  markConflictResolved(file: string): Promise<void>;
   commitChanges(message: string, description?: string): Promise<string>;
   getStashes(): Promise<StashEntry[]>;
   createStash(message?: string): Promise<void>;
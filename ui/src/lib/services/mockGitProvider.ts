@@ -296,13 +296,22 @@ export const mockGitProvider: GitDataProvider = {
   async stageFiles(files: string[]) {
     if (!repoPath || files.length === 0) return;
     const fileSet = new Set(files);
-    changes = changes.map((change) => (fileSet.has(change.path) ? { ...change, staged: true } : change));
+    changes = changes.map((change) => (fileSet.has(change.path) || (change.oldPath && fileSet.has(change.oldPath)) ? { ...change, staged: true } : change));
   },
 
   async unstageFiles(files: string[]) {
     if (!repoPath || files.length === 0) return;
     const fileSet = new Set(files);
-    changes = changes.map((change) => (fileSet.has(change.path) ? { ...change, staged: false } : change));
+    changes = changes.map((change) => (fileSet.has(change.path) || (change.oldPath && fileSet.has(change.oldPath)) ? { ...change, staged: false } : change));
+  },
+
+  async markConflictResolved(file: string) {
+    if (!repoPath) return;
+    changes = changes.map((change) =>
+      change.path === file && change.status === 'conflicted'
+        ? { path: change.path, status: 'modified', staged: true }
+        : change
+    );
   },
 
   async commitChanges(message: string, _description?: string) {
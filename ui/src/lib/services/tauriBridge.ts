@@ -17,5 +17,52 @@ export async function tauriInvoke<T>(cmd: string, args?: Record<string, unknown>
   if (!internals) {
     throw new Error('E_TAURI_UNAVAILABLE: Tauri runtime bridge is not available.');
   }
-  return internals.invoke<T>(cmd, args);
+  try {
+    return await internals.invoke<T>(cmd, args);
+  } catch (error) {
+    throw isGitLiteErrorPayload(error) ? new GitLiteCommandError(error) : error;
+  }
+}
+
+/** Error shape returned by every backend command (`GitLiteError` in Rust). */
+export interface GitLiteErrorPayload {
+  code: string;
+  message: string;
+  details: string | null;
+  hint: string | null;
+  fields?: FieldError[];
+}
+
+/** One rejected input field, e.g. of `save_settings`. */
+export interface FieldError {
+  field: string;
+  message: string;
+}
+
+export class GitLiteCommandError extends Error {
+  readonly code: string;
+  readonly details: string | null;
+  readonly hint: string | null;
+  readonly fields: FieldError[];
+
+  constructor(payload: GitLiteErrorPayload) {
+    // Keep the `E_*: message` form so existing message handling still works;
+    // uncategorized backend failures surface their message only.
+    super(
+      payload.code === 'E_INTERNAL' || !payload.message
+        ? payload.message || payload.code
+        : `${payload.code}: ${payload.message}`
+    );
+    this.name = 'GitLiteCommandError';
+    this.code = payload.code;
+    this.details = payload.details;
+    this.hint = payload.hint;
+    this.fields = payload.fields ?? [];
+  }
+}
+
+function isGitLiteErrorPayload(value: unknown): value is GitLiteErrorPayload {
+  if (typeof value !== 'object' || value === null) return false;
+  const candidate = value as Record<string, unknown>;
+  return typeof candidate.code === 'string' && typeof candidate.message === 'string';
 }
@@ -26,6 +26,10 @@ interface RawStatus {
   path: string;
   status: string;
   is_staged: boolean;
+  old_path: string | null;
+  skip_worktree: boolean;
+  assume_unchanged: boolean;
+  conflict: string | null;
 }
 
 interface RawCommit {
@@ -36,6 +40,12 @@ interface RawCommit {
   parents: string[];
 }
 
+interface RawCommitPage {
+  commits: RawCommit[];
+  truncated: boolean;
+  continuation: string | null;
+}
+
 interface RawDiffLine {
   line_type: string;
   content: string;
@@ -49,7 +59,13 @@ interface RawDiffHunk {
 
 interface RawDiffFile {
   path: string;
+  status: string;
+  old_mode: string | null;
+  new_mode: string | null;
   hunks: RawDiffHunk[];
+  insertions: number;
+  deletions: number;
+  truncated: string | null;
 }
 
 interface RawStashEntry {
@@ -102,7 +118,9 @@ function toStatus(raw: RawStatus): ChangeItem {
   return {
     path: raw.path,
     status: normalized,
-    staged: raw.is_staged
+    staged: raw.is_staged,
+    oldPath: raw.old_path ?? undefined,
+    conflict: raw.conflict ?? undefined
   };
 }
 
@@ -222,7 +240,8 @@ export class TauriGitProvider implements GitDataProvider {
   async getChanges(): Promise<ChangeItem[]> {
     if (!this.repoPath || !this.isGitRepository) return [];
     const raw = await tauriInvoke<RawStatus[]>('get_status', { path: this.repoPath });
-    return raw.map(toStatus);
+    // `hidden` entries are flagged files without visible changes.
+    return raw.filter((entry) => entry.status !== 'hidden').map(toStatus);
   }
 
   async stageFiles(files: string[]): Promise<void> {
@@ -237,6 +256,11 @@ export class TauriGitProvider implements GitDataProvider {
     await tauriInvoke<void>('unstage_files', { path: this.repoPath!, files });
   }
 
+  async markConflictResolved(file: string): Promise<void> {
+    this.ensureGitRepository();
+    await tauriInvoke<void>('mark_conflict_resolved', { path: this.repoPath!, file });
+  }
+
   async commitChanges(message: string, description = ''): Promise<string> {
     this.ensureGitRepository();
     return tauriInvoke<string>('commit_changes', {
@@ -271,12 +295,13 @@ export class TauriGitProvider implements GitDataProvider {
 
   async getCommits(reference?: string): Promise<CommitSummary[]> {
     if (!this.repoPath || !this.isGitRepository) return [];
-    const raw = await tauriInvoke<RawCommit[]>('get_commits', {
+    // A page stopped by the revwalk guards still lists the commits it found.
+    const page = await tauriInvoke<RawCommitPage>('get_commits_page', {
       path: this.repoPath!,
       limit: 200,
       reference: reference ?? null
     });
-    return raw;
+    return page.commits;
   }
 
   async getDiffForCommit(hash: string): Promise<DiffFile[]> {
@@ -1,4 +1,5 @@
 import { beforeEach, describe, expect, it } from 'vitest';
+import { GitLiteCommandError } from '../../services/tauriBridge';
 import { uiState } from '../ui.svelte';
 
 describe('UIStore error mapping', () => {
@@ -23,6 +24,27 @@ describe('UIStore error mapping', () => {
     );
   });
 
+  it('maps structured backend command errors by code', () => {
+    const error = new GitLiteCommandError({
+      code: 'E_PUSH_NON_FF',
+      message: 'rejected',
+      details: null,
+      hint: 'Pull and integrate the remote changes, then try again.'
+    });
+    expect(error.message).toBe('E_PUSH_NON_FF: rejected');
+    expect((uiState as unknown as { messageOf: (value: unknown) => string }).messageOf(error)).toBe(
+      'Push blocked because remote is ahead. Pull/rebase first.'
+    );
+
+    const internal = new GitLiteCommandError({
+      code: 'E_INTERNAL',
+      message: "Failed to add remote 'origin': invalid url",
+      details: null,
+      hint: null
+    });
+    expect(mapMessage(internal.message)).toBe('Failed to add remote. Check name/URL and try again.');
+  });
+
   it('maps non-prefixed backend branch/remote errors', () => {
     expect(mapMessage("Failed to add remote 'origin': invalid url")).toBe(
       'Failed to add remote. Check name/URL and try again.'
@@ -1,5 +1,5 @@
 import { createGitProvider } from '../services/providerFactory';
-import { isTauriRuntime, tauriInvoke } from '../services/tauriBridge';
+import { GitLiteCommandError, isTauriRuntime, tauriInvoke } from '../services/tauriBridge';
 import type {
   BranchInfo,
   ChangeItem,
@@ -60,6 +60,14 @@ interface AppSettingsPayload {
   language: string | null;
   update_channel: string;
   auto_update_check: boolean;
+  revwalk_max_millis: number;
+  revwalk_max_commits: number;
+  use_mailmap: boolean;
+  git_worker_threads: number;
+  feature_flags: Record<string, boolean>;
+  diff_max_files: number;
+  diff_max_lines_per_file: number;
+  diff_max_blob_bytes: number;
 }
 
 interface GitHubOAuthStartResponse {
@@ -540,9 +548,10 @@ class UIStore {
     }
   }
 
-  async stagePath(path: string) {
+  // A rename is staged and unstaged as a whole: its old path and its new one.
+  async stagePath(path: string, oldPath?: string) {
     try {
-      await this.provider.stageFiles([path]);
+      await this.provider.stageFiles(oldPath ? [oldPath, path] : [path]);
       await this.refreshChanges();
       this.notice = '';
     } catch (error) {
@@ -550,9 +559,21 @@ class UIStore {
     }
   }
 
-  async unstagePath(path: string) {
+  async unstagePath(path: string, oldPath?: string) {
     try {
-      await this.provider.unstageFiles([path]);
+      await this.provider.unstageFiles(oldPath ? [oldPath, path] : [path]);
+      await this.refreshChanges();
+      this.notice = '';
+    } catch (error) {
+      this.notice = this.messageOf(error);
+    }
+  }
+
+  // Staging a conflicted file is how git clears the conflict, so it gets its
+  // own action that refuses files still holding conflict markers.
+  async resolveConflict(path: string) {
+    try {
+      await this.provider.markConflictResolved(path);
       await this.refreshChanges();
       this.notice = '';
     } catch (error) {
@@ -1173,9 +1194,13 @@ class UIStore {
     const raw = this.sanitizeSensitive(rawInput);
 
     // If it's a specific internal error code, return a friendly message
-    if (raw.startsWith('E_')) {
-      const [prefix] = raw.split(':');
-      const code = prefix?.trim();
+    const code =
+      error instanceof GitLiteCommandError
+        ? error.code
+        : raw.startsWith('E_')
+          ? raw.split(':')[0]?.trim()
+          : undefined;
+    if (code) {
       switch (code) {
         case 'E_REPO_EMPTY':
           return 'Repository path is empty. Pick a folder first.';
@@ -1213,6 +1238,8 @@ class UIStore {
           return 'No local changes to stash.';
         case 'E_STASH_APPLY_CONFLICT':
           return 'Stash apply produced conflicts. Resolve them and continue.';
+        case 'E_MERGE_TOOL_UNRESOLVED':
+          return 'File still contains conflict markers. Finish resolving it first.';
         case 'E_STASH_INVALID_INDEX':
           return 'Selected stash entry no longer exists.';
         case 'E_REMOTE_INVALID':
@@ -1,4 +1,4 @@
-export type ChangeStatus = 'added' | 'modified' | 'deleted' | 'renamed';
+export type ChangeStatus = 'added' | 'modified' | 'deleted' | 'renamed' | 'conflicted';
 
 export interface RepositoryMeta {
   name: string;
@@ -18,6 +18,8 @@ export interface ChangeItem {
   path: string;
   status: ChangeStatus;
   staged: boolean;
+  oldPath?: string;
+  conflict?: string;
 }
 
 export interface StashEntry {